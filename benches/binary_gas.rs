@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use velvet::prelude::*;
+use velvet_test_utils as test_utils;
+
+// benchmark a neighbor list update shared by every pair potential against one list per pair
+pub fn benchmark_neighbors(c: &mut Criterion) {
+    let system = test_utils::binary_gas_system();
+    let mut potentials = test_utils::binary_gas_potentials();
+    potentials.setup(&system).unwrap();
+
+    let argon = Species::from_element(Element::Ar);
+    let xenon = Species::from_element(Element::Xe);
+    let radius = 13.5;
+    let mut selections: Vec<_> = [(argon, argon), (xenon, xenon), (argon, xenon)]
+        .iter()
+        .map(|&species| {
+            let mut selection =
+                Selection::new(setup_pairs_by_species, update_pairs_by_cutoff_radius);
+            selection.setup(&system, species);
+            selection
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("binary-gas-neighbors");
+
+    group.bench_function("shared", |b| b.iter(|| potentials.update(&system, 0)));
+
+    group.bench_function("per-pair", |b| {
+        b.iter(|| {
+            selections
+                .iter_mut()
+                .for_each(|selection| selection.update(&system, radius))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(binary_gas, benchmark_neighbors);
+criterion_main!(binary_gas);
@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nalgebra::Vector3;
+
+use velvet::prelude::*;
+
+// benchmark minimum image distances in an orthogonal cell against a slightly tilted one
+pub fn benchmark_distance(c: &mut Criterion) {
+    let orthogonal = Cell::triclinic(20.0, 21.0, 22.0, 90.0, 90.0, 90.0);
+    let triclinic = Cell::triclinic(20.0, 21.0, 22.0, 90.0, 90.0, 90.5);
+    let positions: Vec<Vector3<_>> = (0..1000)
+        .map(|i| {
+            Vector3::new(i as _, i as _, i as _).component_mul(&Vector3::new(0.37, 1.13, 0.71))
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("cell-distance");
+
+    group.bench_function("orthogonal", |b| {
+        b.iter(|| {
+            positions
+                .windows(2)
+                .map(|pair| orthogonal.distance(black_box(&pair[0]), black_box(&pair[1])))
+                .fold(0.0, |acc, d| acc + d)
+        })
+    });
+
+    group.bench_function("triclinic", |b| {
+        b.iter(|| {
+            positions
+                .windows(2)
+                .map(|pair| triclinic.distance(black_box(&pair[0]), black_box(&pair[1])))
+                .fold(0.0, |acc, d| acc + d)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(cell, benchmark_distance);
+criterion_main!(cell);
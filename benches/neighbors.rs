@@ -0,0 +1,62 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nalgebra::Vector3;
+
+use velvet::prelude::*;
+use velvet_test_utils as test_utils;
+
+// face centered cubic argon crystal with 2048 atoms
+fn argon_crystal() -> System {
+    test_utils::fcc_lattice(Species::from_element(Element::Ar), 5.26, 8)
+}
+
+// benchmark pair force evaluation over a compressed sparse row neighbor list against a vector of pairs
+pub fn benchmark_force_loop(c: &mut Criterion) {
+    let system = argon_crystal();
+    let potential = LennardJones::new(0.238, 3.4);
+    let cutoff = 8.5;
+    let pairs = update_pairs_by_cutoff_radius(&system, &setup_all_pairs(&system, ()), cutoff);
+    let list = NeighborList::from_pairs(system.size, &pairs);
+
+    let mut group = c.benchmark_group("neighbors-force-loop");
+
+    group.bench_function("pair-vector", |b| {
+        b.iter(|| {
+            let mut forces = vec![Vector3::zeros(); system.size];
+            for &[i, j] in black_box(&pairs) {
+                let pos_i = system.positions[i];
+                let pos_j = system.positions[j];
+                let r = system.cell.distance(&pos_i, &pos_j);
+                if r < cutoff {
+                    let force = potential.force(r) * system.cell.direction(&pos_i, &pos_j);
+                    forces[i] += force;
+                    forces[j] -= force;
+                }
+            }
+            forces
+        })
+    });
+
+    group.bench_function("csr", |b| {
+        b.iter(|| {
+            let mut forces = vec![Vector3::zeros(); system.size];
+            for (i, row) in black_box(&list).rows() {
+                let pos_i = system.positions[i];
+                for &j in row {
+                    let pos_j = system.positions[j];
+                    let r = system.cell.distance(&pos_i, &pos_j);
+                    if r < cutoff {
+                        let force = potential.force(r) * system.cell.direction(&pos_i, &pos_j);
+                        forces[i] += force;
+                        forces[j] -= force;
+                    }
+                }
+            }
+            forces
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(neighbors, benchmark_force_loop);
+criterion_main!(neighbors);
@@ -0,0 +1,63 @@
+use std::convert::TryInto;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use velvet::prelude::*;
+use velvet_test_utils as test_utils;
+
+// face centered cubic argon crystal with 2048 atoms
+fn argon_crystal() -> System {
+    test_utils::fcc_lattice(Species::from_element(Element::Ar), 5.26, 8)
+}
+
+// benchmark the vectorized Lennard-Jones force kernel against the scalar kernel
+pub fn benchmark_lennard_jones(c: &mut Criterion) {
+    let system = argon_crystal();
+    let argon = Species::from_element(Element::Ar);
+    let potential = LennardJones::new(0.238, 3.4);
+    let cutoff = 8.5;
+    let pairs = update_pairs_by_cutoff_radius(&system, &setup_all_pairs(&system, ()), cutoff);
+    let distances: Vec<_> = pairs
+        .iter()
+        .map(|&[i, j]| {
+            system
+                .cell
+                .distance(&system.positions[i], &system.positions[j])
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("simd-lennard-jones");
+
+    group.bench_function("scalar-kernel", |b| {
+        b.iter(|| {
+            black_box(&distances)
+                .iter()
+                .map(|&r| potential.force(r))
+                .fold(0.0, |acc, f| acc + f)
+        })
+    });
+
+    group.bench_function("simd-kernel", |b| {
+        b.iter(|| {
+            black_box(&distances)
+                .chunks_exact(LANES)
+                .map(|chunk| potential.force_lanes(chunk.try_into().unwrap()))
+                .fold(0.0, |acc, f| acc + f.iter().fold(0.0, |sum, f| sum + f))
+        })
+    });
+
+    // the pair forces of the whole system use the vectorized kernel when the feature is enabled
+    let mut potentials = PotentialsBuilder::new()
+        .pair(potential, (argon, argon), cutoff, 0.0)
+        .build();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+    group.bench_function("pair-forces", |b| {
+        b.iter(|| PairForces.calculate(black_box(&system), &potentials))
+    });
+
+    group.finish();
+}
+
+criterion_group!(simd, benchmark_lennard_jones);
+criterion_main!(simd);
@@ -65,4 +65,274 @@
 //     file.write_all(res.as_bytes()).unwrap();
 // }
 
-fn main() {}
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+use std::str::FromStr;
+
+use clap::{value_t, App, Arg, ArgMatches, SubCommand};
+use serde::Deserialize;
+
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+fn main() {
+    let matches = App::new("Velvet CLI")
+        .version("0.1.2")
+        .author("Seaton Ullberg <seatonullberg@gmail.com>")
+        .about("Command line tool built on top of the Velvet API")
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("check a POSCAR structure for overlapping atoms")
+                .arg(
+                    Arg::with_name("src")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("source filepath"),
+                )
+                .arg(
+                    Arg::with_name("min-distance")
+                        .short("d")
+                        .long("min-distance")
+                        .takes_value(true)
+                        .default_value("1.0")
+                        .help("minimum allowed distance between atoms (angstrom)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("run a molecular dynamics simulation described by a RON file")
+                .arg(
+                    Arg::with_name("config")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .help("configuration filepath"),
+                ),
+        )
+        .get_matches();
+
+    if let Some(matches) = matches.subcommand_matches("validate") {
+        handle_validate(matches)
+    }
+    if let Some(matches) = matches.subcommand_matches("run") {
+        handle_run(matches)
+    }
+}
+
+fn handle_validate(matches: &ArgMatches) {
+    let system = Poscar.parse_system_from_file(matches.value_of("src").unwrap());
+    let min_distance = value_t!(matches, "min-distance", f64).unwrap_or_else(|e| e.exit());
+    let overlaps = system.check_overlaps(min_distance as _);
+    if overlaps.is_empty() {
+        println!("no overlaps closer than {} angstrom", min_distance);
+        return;
+    }
+    for (i, j) in &overlaps {
+        let r = system
+            .cell
+            .distance(&system.positions[*i], &system.positions[*j]);
+        println!("atoms {} and {} overlap at {} angstrom", i, j, r);
+    }
+    process::exit(1);
+}
+
+/// NVE molecular dynamics simulation described by the RON file of the `run` subcommand.
+///
+/// ```ron
+/// (
+///     structure: "resources/test/Ar.poscar",
+///     temperature: Some(300.0),
+///     timestep: 0.1,
+///     steps: 1000,
+///     pairs: [(species: ("Ar", "Ar"), epsilon: 4.184, sigma: 3.4, cutoff: 8.5)],
+///     outputs: [(sink: "argon.txt", interval: 100, properties: ["temperature"])],
+/// )
+/// ```
+#[derive(Deserialize)]
+struct RunConfig {
+    /// POSCAR file with the initial structure.
+    structure: String,
+    /// Temperature of the initial Boltzmann velocity distribution.
+    #[serde(default)]
+    temperature: Option<f64>,
+    /// Timestep of the Velocity Verlet integrator.
+    timestep: f64,
+    /// Number of steps to run.
+    steps: usize,
+    /// Lennard-Jones interactions between pairs of elements.
+    pairs: Vec<PairConfig>,
+    /// Raw output groups.
+    #[serde(default)]
+    outputs: Vec<OutputConfig>,
+}
+
+/// Lennard-Jones interaction between a pair of elements.
+#[derive(Deserialize)]
+struct PairConfig {
+    species: (String, String),
+    epsilon: f64,
+    sigma: f64,
+    cutoff: f64,
+    #[serde(default = "default_thickness")]
+    thickness: f64,
+}
+
+fn default_thickness() -> f64 {
+    1.0
+}
+
+/// Raw output group written to a sink named by a string.
+///
+/// A `"buffer"` sink keeps its outputs in memory and prints them once the run is over.
+#[derive(Deserialize)]
+struct OutputConfig {
+    sink: OutputSink,
+    #[serde(default = "default_interval")]
+    interval: usize,
+    properties: Vec<String>,
+}
+
+fn default_interval() -> usize {
+    1
+}
+
+fn handle_run(matches: &ArgMatches) {
+    let src = matches.value_of("config").unwrap();
+    let config = fs::read_to_string(src)
+        .map_err(|error| format!("failed to read {}: {}", src, error))
+        .and_then(|contents| {
+            ron::de::from_str::<RunConfig>(&contents)
+                .map_err(|error| format!("failed to parse {}: {}", src, error))
+        })
+        .unwrap_or_else(|error| exit_with(&error));
+    let system = Poscar.parse_system_from_file(&config.structure);
+    let mut simulation = simulation(&config, system).unwrap_or_else(|error| exit_with(&error));
+    simulation
+        .run(config.steps)
+        .unwrap_or_else(|error| exit_with(&error));
+
+    // buffer sinks are held until the run is over
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for output in &config.outputs {
+        if let OutputSink::Buffer(buffer) = &output.sink {
+            stdout
+                .write_all(&buffer.contents())
+                .unwrap_or_else(|error| {
+                    exit_with(&format!("failed to write raw output: {}", error))
+                });
+        }
+    }
+}
+
+fn exit_with(error: &str) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1)
+}
+
+fn simulation(config: &RunConfig, mut system: System) -> Result<Simulation, String> {
+    if let Some(temperature) = config.temperature {
+        Boltzmann::new(temperature as _).apply(&mut system);
+    }
+
+    let mut potentials = PotentialsBuilder::new();
+    for pair in &config.pairs {
+        let species = (species(&pair.species.0)?, species(&pair.species.1)?);
+        let lj = LennardJones::new(pair.epsilon as _, pair.sigma as _);
+        potentials = potentials.pair(lj, species, pair.cutoff as _, pair.thickness as _);
+    }
+
+    let mut configuration = ConfigurationBuilder::new().timestep(config.timestep as _);
+    for output in &config.outputs {
+        let mut group = RawOutputGroupBuilder::new()
+            .sink(&output.sink)
+            .interval(output.interval);
+        for property in &output.properties {
+            group = match property.as_str() {
+                "potential_energy" => group.output(PotentialEnergy),
+                "kinetic_energy" => group.output(KineticEnergy),
+                "total_energy" => group.output(TotalEnergy),
+                "temperature" => group.output(Temperature),
+                "pressure" => group.output(Pressure),
+                name => return Err(format!("unknown output property '{}'", name)),
+            };
+        }
+        configuration = configuration.raw_output_group(group.build());
+    }
+
+    let md = MolecularDynamics::new(VelocityVerlet::new(config.timestep as _), NullThermostat);
+    Ok(Simulation::new(
+        system,
+        potentials.build(),
+        md,
+        configuration.build(),
+    ))
+}
+
+fn species(symbol: &str) -> Result<Species, String> {
+    Element::from_str(symbol)
+        .map(Species::from_element)
+        .map_err(|_| format!("unknown element '{}'", symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{simulation, RunConfig};
+    use velvet_core::prelude::*;
+    use velvet_external_data::prelude::*;
+
+    const POSCAR: &str = "Ar
+1.0
+10.0 0.0 0.0
+0.0 10.0 0.0
+0.0 0.0 10.0
+Ar
+4
+Direct
+0.0 0.0 0.0
+0.4 0.0 0.0
+0.0 0.4 0.0
+0.0 0.0 0.4
+";
+
+    #[test]
+    fn run_to_buffer_sink() {
+        let config: RunConfig = ron::de::from_str(
+            r#"(
+                structure: "Ar.poscar",
+                timestep: 0.1,
+                steps: 5,
+                pairs: [(species: ("Ar", "Ar"), epsilon: 4.184, sigma: 3.4, cutoff: 4.5)],
+                outputs: [(sink: "buffer", interval: 1, properties: ["temperature"])],
+            )"#,
+        )
+        .unwrap();
+        let system = Poscar.parse_system_from_reader(POSCAR.as_bytes());
+        let mut simulation = simulation(&config, system).unwrap();
+        simulation.run(config.steps).unwrap();
+        let buffer = match &config.outputs[0].sink {
+            OutputSink::Buffer(buffer) => buffer.contents(),
+            _ => panic!("expected a buffer sink"),
+        };
+        let contents = String::from_utf8(buffer).unwrap();
+        assert_eq!(contents.matches("temperature").count(), 5);
+    }
+
+    #[test]
+    fn unknown_property() {
+        let config: RunConfig = ron::de::from_str(
+            r#"(
+                structure: "Ar.poscar",
+                timestep: 0.1,
+                steps: 1,
+                pairs: [],
+                outputs: [(sink: "buffer", properties: ["viscosity"])],
+            )"#,
+        )
+        .unwrap();
+        let system = Poscar.parse_system_from_reader(POSCAR.as_bytes());
+        assert!(simulation(&config, system).is_err());
+    }
+}
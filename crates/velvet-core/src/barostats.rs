@@ -0,0 +1,190 @@
+//! Algorithms which control the pressure of a system.
+
+use rand_distr::{Distribution, Normal};
+
+use crate::internal::consts::boltzmann;
+use crate::internal::Float;
+use crate::potentials::Potentials;
+use crate::properties::pressure::Pressure;
+use crate::properties::Property;
+use crate::rng::{self, VelvetRng};
+use crate::system::System;
+
+/// Shared behavior for algorithms which control the pressure of a system.
+pub trait Barostat: Send + Sync {
+    /// Prepares the barostat to run.
+    fn setup(&mut self, _: &System) {}
+    /// Fires after the integration step and the thermostat.
+    fn post_integrate(&mut self, _: &mut System, _: &Potentials) {}
+    /// Returns the internal state which must be stored to resume the barostat.
+    fn state(&self) -> Vec<Float> {
+        Vec::new()
+    }
+    /// Restores the internal state returned by [`state`](Barostat::state) after setup.
+    fn restore(&mut self, _: &[Float]) {}
+}
+
+/// Mock barostat algorithm which applies no pressure controls.
+#[derive(Clone, Debug)]
+pub struct NullBarostat;
+
+impl Barostat for NullBarostat {}
+
+/// Langevin piston barostat for isotropic NPT sampling.
+///
+/// The cell volume is a dynamical variable with its own mass which is driven by the difference
+/// between the instantaneous and target pressures and coupled to a heat bath through a friction and
+/// a random force. Unlike a Berendsen barostat the volume fluctuates as it should in the
+/// isothermal-isobaric ensemble.
+/// The cell and the positions are scaled isotropically each step while velocities are left to the thermostat.
+///
+/// Pressures are expressed in the internal units of energy per cubic angstrom.
+///
+/// # References
+///
+/// [1] Feller, Scott E., et al. "Constant pressure molecular dynamics simulation: The Langevin piston method." The Journal of chemical physics 103.11 (1995): 4613-4621.
+#[derive(Clone, Debug)]
+pub struct LangevinPiston {
+    pressure: Float,
+    temperature: Float,
+    mass: Float,
+    friction: Float,
+    timestep: Float,
+    velocity: Float,
+    distr: Normal<Float>,
+    rng: VelvetRng,
+}
+
+impl LangevinPiston {
+    /// Returns a new [`LangevinPiston`] barostat.
+    ///
+    /// # Arguments
+    ///
+    /// * `pressure` - Target pressure (Kcal/mole-angstrom^3).
+    /// * `temperature` - Temperature of the heat bath coupled to the piston.
+    /// * `mass` - Mass of the piston which sets how quickly the volume responds.
+    /// * `friction` - Collision frequency of the piston (1 / time).
+    /// * `timestep` - Timestep of the integrator.
+    pub fn new(
+        pressure: Float,
+        temperature: Float,
+        mass: Float,
+        friction: Float,
+        timestep: Float,
+    ) -> LangevinPiston {
+        LangevinPiston {
+            pressure,
+            temperature,
+            mass,
+            friction,
+            timestep,
+            velocity: 0.0,
+            distr: Normal::new(0.0, 1.0).unwrap(),
+            rng: rng::from_entropy(),
+        }
+    }
+
+    /// Seeds the random number generator to produce a reproducible trajectory.
+    pub fn seed(mut self, seed: u64) -> LangevinPiston {
+        self.rng = rng::from_seed(seed);
+        self
+    }
+
+    /// Returns the rate of change of the cell volume.
+    pub fn velocity(&self) -> Float {
+        self.velocity
+    }
+}
+
+impl Barostat for LangevinPiston {
+    fn setup(&mut self, _: &System) {
+        self.velocity = 0.0;
+    }
+
+    fn post_integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let dt = self.timestep;
+        // pressure kick followed by half drifts around the exact friction and noise which
+        // samples the volume accurately even for large timesteps
+        let pressure = Pressure.calculate(system, potentials);
+        self.velocity += dt * (pressure - self.pressure) / self.mass;
+        let mut displacement = 0.5 * dt * self.velocity;
+        let damping = Float::exp(-self.friction * dt);
+        let sigma =
+            Float::sqrt((1.0 - damping * damping) * boltzmann() * self.temperature / self.mass);
+        self.velocity = damping * self.velocity + sigma * self.distr.sample(&mut self.rng);
+        displacement += 0.5 * dt * self.velocity;
+
+        let volume = system.cell.volume();
+        let new_volume = volume + displacement;
+        assert!(new_volume > 0.0, "Langevin piston collapsed the cell");
+        let factor = Float::cbrt(new_volume / volume);
+        system.cell.scale(factor);
+        system.positions.iter_mut().for_each(|pos| *pos *= factor);
+    }
+
+    fn state(&self) -> Vec<Float> {
+        vec![self.velocity]
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.velocity = state[0];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Barostat, LangevinPiston};
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::potentials::PotentialsBuilder;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use crate::velocity_distributions::{Boltzmann, VelocityDistribution};
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn ideal_gas_volume_distribution() {
+        // the volume of an ideal gas at constant pressure follows a gamma distribution
+        // with shape N + 1 and scale kT / P
+        let size = 20;
+        let temperature = 300.0;
+        let pressure = 1.5e-3;
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; size],
+            positions: (0..size)
+                .map(|i| Vector3::new(i as Float, 0.5 * i as Float, 0.25 * i as Float))
+                .collect(),
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        Boltzmann::new(temperature).apply(&mut system);
+        let potentials = PotentialsBuilder::new().build();
+        let mut piston = LangevinPiston::new(pressure, temperature, 2e-6, 0.3, 1.0).seed(1234);
+        piston.setup(&system);
+
+        // the gas does not interact so only the piston needs to be advanced
+        let mut volumes = Vec::new();
+        for step in 0..60_000 {
+            piston.post_integrate(&mut system, &potentials);
+            if step >= 5_000 {
+                volumes.push(system.cell.volume());
+            }
+        }
+        let count = volumes.len() as Float;
+        let mean = volumes.iter().sum::<Float>() / count;
+        let variance = volumes.iter().map(|v| (v - mean).powi(2)).sum::<Float>() / count;
+
+        let scale = boltzmann() * temperature / pressure;
+        let shape = (size + 1) as Float;
+        assert_relative_eq!(mean, shape * scale, max_relative = 0.03);
+        assert_relative_eq!(variance.sqrt(), shape.sqrt() * scale, max_relative = 0.08);
+    }
+}
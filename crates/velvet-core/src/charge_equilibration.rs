@@ -0,0 +1,144 @@
+//! Methods which assign dynamic atomic charges.
+
+use std::collections::HashMap;
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::internal::consts::coulomb;
+use crate::internal::Float;
+use crate::system::species::Species;
+use crate::system::System;
+
+/// Charge equilibration (QEq) method.
+///
+/// Charges are found by minimizing the electronegativity and self-Coulomb energy of each atom
+/// together with the Coulombic interactions between atoms subject to conservation of the total charge.
+/// Interactions are evaluated between the minimum images of every pair of atoms without shielding.
+///
+/// # References
+///
+/// [1] Rappe, Anthony K., and William A. Goddard III. "Charge equilibration for molecular dynamics simulations." The Journal of Physical Chemistry 95.8 (1991): 3358-3363.
+#[derive(Clone, Debug)]
+pub struct QEq {
+    total_charge: Float,
+    parameters: HashMap<u128, (Float, Float)>,
+}
+
+impl QEq {
+    /// Returns a new [`QEq`] method which conserves `total_charge`.
+    pub fn new(total_charge: Float) -> QEq {
+        QEq {
+            total_charge,
+            parameters: HashMap::new(),
+        }
+    }
+
+    /// Sets the electronegativity (Kcal/mole-e) and hardness (Kcal/mole-e^2) of a species.
+    pub fn parameters(
+        mut self,
+        species: Species,
+        electronegativity: Float,
+        hardness: Float,
+    ) -> QEq {
+        self.parameters
+            .insert(species.id(), (electronegativity, hardness));
+        self
+    }
+
+    /// Returns the equilibrated charge of each atom in the system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a species in the system has no parameters.
+    pub fn charges(&self, system: &System) -> Vec<Float> {
+        let n = system.size;
+        let mut a = DMatrix::<Float>::zeros(n + 1, n + 1);
+        let mut b = DVector::<Float>::zeros(n + 1);
+        for i in 0..n {
+            let (electronegativity, hardness) = match self.parameters.get(&system.species[i].id()) {
+                Some(params) => *params,
+                None => panic!(
+                    "missing QEq parameters for species {}",
+                    system.species[i].id()
+                ),
+            };
+            a[(i, i)] = hardness;
+            for j in (i + 1)..n {
+                let r = system
+                    .cell
+                    .distance(&system.positions[i], &system.positions[j]);
+                a[(i, j)] = coulomb() / r;
+                a[(j, i)] = coulomb() / r;
+            }
+            // the electronegativity of every atom equals the chemical potential
+            a[(i, n)] = -1.0;
+            a[(n, i)] = 1.0;
+            b[i] = -electronegativity;
+        }
+        b[n] = self.total_charge;
+        let solution = a.lu().solve(&b).expect("QEq equations are singular");
+        solution.iter().take(n).copied().collect()
+    }
+
+    /// Writes the equilibrated charges into the species of each atom in the system.
+    ///
+    /// Coulomb potentials select their pairs from the charges at setup so they must be set up again after the first equilibration.
+    pub fn equilibrate(&self, system: &mut System) {
+        let charges = self.charges(system);
+        for (species, charge) in system.species.iter_mut().zip(charges) {
+            *species = species.with_charge(charge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QEq;
+    use crate::internal::consts::coulomb;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn diatomic() {
+        let sodium = Species::from_element(Element::Na).with_charge(0.0);
+        let chlorine = Species::from_element(Element::Cl).with_charge(0.0);
+        let r = 5.0;
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(30.0),
+            species: vec![sodium, chlorine],
+            positions: vec![
+                Vector3::new(10.0, 10.0, 10.0),
+                Vector3::new(10.0 + r, 10.0, 10.0),
+            ],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // Rappe and Goddard parameters converted from eV
+        let (chi_na, j_na) = (2.843 * 23.06, 4.592 * 23.06);
+        let (chi_cl, j_cl) = (8.564 * 23.06, 9.892 * 23.06);
+        let qeq = QEq::new(0.0)
+            .parameters(sodium, chi_na, j_na)
+            .parameters(chlorine, chi_cl, j_cl);
+        qeq.equilibrate(&mut system);
+
+        // charge flows toward the more electronegative chlorine
+        let q_na = system.species[0].charge();
+        let q_cl = system.species[1].charge();
+        assert!(q_na > 0.0);
+        assert!(q_cl < 0.0);
+        assert_relative_eq!(q_na + q_cl, 0.0, epsilon = 1e-5);
+        let expected = (chi_cl - chi_na) / (j_na + j_cl - 2.0 * coulomb() / r);
+        assert_relative_eq!(q_na, expected, epsilon = 1e-4);
+
+        // species identity is preserved
+        assert_eq!(system.species[0], sodium);
+        assert_eq!(system.species[1], chlorine);
+    }
+}
@@ -1,5 +1,9 @@
 //! User defined configuration options.
 
+use std::path::{Path, PathBuf};
+
+use crate::internal::Float;
+use crate::outputs::csv::CsvOutputGroup;
 #[cfg(feature = "hdf5-output")]
 use crate::outputs::hdf5::Hdf5OutputGroup;
 use crate::outputs::raw::RawOutputGroup;
@@ -7,8 +11,11 @@ use crate::outputs::raw::RawOutputGroup;
 /// High-level configuration options.
 pub struct Configuration {
     raw_output_groups: Vec<RawOutputGroup>,
+    csv_output_groups: Vec<CsvOutputGroup>,
     #[cfg(feature = "hdf5-output")]
     hdf5_output_groups: Vec<Hdf5OutputGroup>,
+    checkpoint: Option<(PathBuf, usize)>,
+    timestep: Option<Float>,
 }
 
 impl Configuration {
@@ -17,18 +24,38 @@ impl Configuration {
         self.raw_output_groups.iter_mut()
     }
 
+    /// Returns an iterator over the CSV output groups.
+    pub fn csv_output_groups(&mut self) -> impl Iterator<Item = &mut CsvOutputGroup> {
+        self.csv_output_groups.iter_mut()
+    }
+
     /// Returns an iterator over the HDF5 output groups.
     #[cfg(feature = "hdf5-output")]
     pub fn hdf5_output_groups(&mut self) -> impl Iterator<Item = &mut Hdf5OutputGroup> {
         self.hdf5_output_groups.iter_mut()
     }
+
+    /// Returns the path and the number of steps between checkpoints if checkpoints are enabled.
+    pub fn checkpoint(&self) -> Option<(&Path, usize)> {
+        self.checkpoint
+            .as_ref()
+            .map(|(path, interval)| (path.as_path(), *interval))
+    }
+
+    /// Returns the timestep duration reported to outputs if it was set.
+    pub fn timestep(&self) -> Option<Float> {
+        self.timestep
+    }
 }
 
 /// Constructor for the [`Configuration`](velvet_core::config::Configuration) type.
 pub struct ConfigurationBuilder {
     raw_output_groups: Vec<RawOutputGroup>,
+    csv_output_groups: Vec<CsvOutputGroup>,
     #[cfg(feature = "hdf5-output")]
     hdf5_output_groups: Vec<Hdf5OutputGroup>,
+    checkpoint: Option<(PathBuf, usize)>,
+    timestep: Option<Float>,
 }
 
 impl ConfigurationBuilder {
@@ -36,8 +63,11 @@ impl ConfigurationBuilder {
     pub fn new() -> ConfigurationBuilder {
         ConfigurationBuilder {
             raw_output_groups: Vec::new(),
+            csv_output_groups: Vec::new(),
             #[cfg(feature = "hdf5-output")]
             hdf5_output_groups: Vec::new(),
+            checkpoint: None,
+            timestep: None,
         }
     }
 
@@ -47,6 +77,12 @@ impl ConfigurationBuilder {
         self
     }
 
+    /// Adds a CSV output group to the configuration.
+    pub fn csv_output_group(mut self, group: CsvOutputGroup) -> ConfigurationBuilder {
+        self.csv_output_groups.push(group);
+        self
+    }
+
     #[cfg(feature = "hdf5-output")]
     /// Adds an HDF5 output group to the configuration.
     pub fn hdf5_output_group(mut self, group: Hdf5OutputGroup) -> ConfigurationBuilder {
@@ -54,12 +90,37 @@ impl ConfigurationBuilder {
         self
     }
 
+    /// Writes a [`Restart`](crate::restart::Restart) to `path` every `interval` steps.
+    ///
+    /// The previous checkpoint is kept at `<path>.prev`.
+    pub fn checkpoint<P: AsRef<Path>>(mut self, path: P, interval: usize) -> ConfigurationBuilder {
+        self.checkpoint = Some((path.as_ref().to_path_buf(), interval));
+        self
+    }
+
+    /// Sets the timestep duration used to report the simulation time to outputs.
+    ///
+    /// The propagator does not expose its timestep, so it should match the integrator's.
+    pub fn timestep(mut self, timestep: Float) -> ConfigurationBuilder {
+        self.timestep = Some(timestep);
+        self
+    }
+
     /// Returns an initialized [`Configuration`].
     pub fn build(self) -> Configuration {
         Configuration {
             raw_output_groups: self.raw_output_groups,
+            csv_output_groups: self.csv_output_groups,
             #[cfg(feature = "hdf5-output")]
             hdf5_output_groups: self.hdf5_output_groups,
+            checkpoint: self.checkpoint,
+            timestep: self.timestep,
         }
     }
 }
+
+impl Default for ConfigurationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
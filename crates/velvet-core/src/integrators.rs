@@ -1,11 +1,15 @@
 //! Algorithms which integrate the classical equations of motion.
 
 use nalgebra::Vector3;
+use rand_distr::{Distribution, Normal};
 
+use crate::internal::consts::boltzmann;
 use crate::internal::Float;
 use crate::potentials::Potentials;
+use crate::properties::energy::KineticEnergy;
 use crate::properties::forces::Forces;
-use crate::properties::Property;
+use crate::properties::{IntrinsicProperty, Property};
+use crate::rng::{self, VelvetRng};
 use crate::system::System;
 
 /// Shared behavior for algorithms which integrate the classical equations of motion.
@@ -14,6 +18,24 @@ pub trait Integrator: Send + Sync {
     fn setup(&mut self, _: &System, _: &Potentials) {}
     /// Integrates one time step.
     fn integrate(&mut self, system: &mut System, potentials: &Potentials);
+    /// Returns the internal state which must be stored to resume the integration.
+    fn state(&self) -> Vec<Float> {
+        Vec::new()
+    }
+    /// Restores the internal state returned by [`state`](Integrator::state) after setup.
+    fn restore(&mut self, _: &[Float]) {}
+}
+
+// Flattens vectors into their components for storage in a restart.
+fn flatten(vectors: &[Vector3<Float>]) -> Vec<Float> {
+    vectors.iter().flat_map(|v| v.iter().copied()).collect()
+}
+
+fn unflatten(values: &[Float]) -> Vec<Vector3<Float>> {
+    values
+        .chunks(3)
+        .map(|v| Vector3::new(v[0], v[1], v[2]))
+        .collect()
 }
 
 /// Velocity Verlet integration algorithm.
@@ -42,11 +64,121 @@ impl VelocityVerlet {
 }
 
 impl Integrator for VelocityVerlet {
-    fn setup(&mut self, system: &System, _: &Potentials) {
-        self.accelerations = vec![Vector3::zeros(); system.size];
+    fn setup(&mut self, system: &System, potentials: &Potentials) {
+        self.accelerations = Forces
+            .calculate(system, potentials)
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+    }
+
+    fn integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let dt = self.timestep;
+
+        system
+            .positions
+            .iter_mut()
+            .zip(system.velocities.iter())
+            .zip(self.accelerations.iter())
+            .for_each(|((pos, vel), acc)| {
+                *pos += (vel * dt) + (0.5 * acc * dt.powi(2));
+            });
+
+        let forces = Forces.calculate(system, potentials);
+        let new_accelerations: Vec<Vector3<Float>> = forces
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+
+        system
+            .velocities
+            .iter_mut()
+            .zip(self.accelerations.iter())
+            .zip(new_accelerations.iter())
+            .for_each(|((vel, acc), new_acc)| {
+                *vel += 0.5 * dt * (acc + new_acc);
+            });
+
+        self.accelerations = new_accelerations;
+    }
+
+    fn state(&self) -> Vec<Float> {
+        flatten(&self.accelerations)
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.accelerations = unflatten(state);
+    }
+}
+
+/// Factor by which the [`AdaptiveVerlet`] timestep grows when forces are gentle.
+const ADAPTIVE_GROWTH_FACTOR: Float = 1.1;
+
+/// Velocity Verlet integration algorithm with an adaptive timestep.
+///
+/// Each step is limited to `dt_max * min(1, max_force / force)` where `force` is the largest force
+/// magnitude acting on any atom, so the step shrinks at once during close approaches.
+/// Once the forces relax the step grows back by at most 10% per step until it reaches `dt_max`.
+///
+/// Adaptive stepping breaks strict energy conservation and the uniform time spacing required by
+/// time-correlation analysis, so it is best reserved for relaxing unstable starting structures.
+#[derive(Clone, Debug)]
+pub struct AdaptiveVerlet {
+    dt_max: Float,
+    max_force: Float,
+    timestep: Float,
+    accelerations: Vec<Vector3<Float>>,
+}
+
+impl AdaptiveVerlet {
+    /// Returns a new [`AdaptiveVerlet`] algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestep` - Initial timestep duration.
+    /// * `dt_max` - Upper bound on the timestep duration.
+    /// * `max_force` - Force magnitude above which the timestep is reduced.
+    pub fn new(timestep: Float, dt_max: Float, max_force: Float) -> AdaptiveVerlet {
+        AdaptiveVerlet {
+            dt_max,
+            max_force,
+            timestep: Float::min(timestep, dt_max),
+            accelerations: Vec::new(),
+        }
+    }
+
+    /// Returns the timestep duration of the last step.
+    pub fn timestep(&self) -> Float {
+        self.timestep
+    }
+}
+
+impl Integrator for AdaptiveVerlet {
+    fn setup(&mut self, system: &System, potentials: &Potentials) {
+        self.accelerations = Forces
+            .calculate(system, potentials)
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
     }
 
     fn integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let force = self
+            .accelerations
+            .iter()
+            .zip(system.species.iter())
+            .map(|(acc, species)| (acc * species.mass()).norm())
+            .fold(0 as Float, Float::max);
+        // the force limit is scaled from dt_max so repeated close approaches do not compound
+        let limit = if force > self.max_force {
+            self.dt_max * self.max_force / force
+        } else {
+            self.dt_max
+        };
+        self.timestep = Float::min(limit, self.timestep * ADAPTIVE_GROWTH_FACTOR);
         let dt = self.timestep;
 
         system
@@ -76,4 +208,710 @@ impl Integrator for VelocityVerlet {
 
         self.accelerations = new_accelerations;
     }
+
+    fn state(&self) -> Vec<Float> {
+        let mut state = vec![self.timestep];
+        state.extend(flatten(&self.accelerations));
+        state
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        if let Some((&timestep, accelerations)) = state.split_first() {
+            self.timestep = timestep;
+            self.accelerations = unflatten(accelerations);
+        }
+    }
+}
+
+/// Brownian (overdamped Langevin) integration algorithm.
+///
+/// Positions are updated directly from the forces and a random displacement.
+/// Velocities are not used or modified by this integrator.
+/// The random number generator is not part of the restart state so a resumed trajectory draws new noise.
+///
+/// # References
+///
+/// [1] Ermak, Donald L., and J. A. McCammon. "Brownian dynamics with hydrodynamic interactions." The Journal of chemical physics 69.4 (1978): 1352-1360.
+#[derive(Clone, Debug)]
+pub struct Brownian {
+    timestep: Float,
+    friction: Float,
+    temperature: Float,
+    distr: Normal<Float>,
+    rng: VelvetRng,
+}
+
+impl Brownian {
+    /// Returns a new [`Brownian`] algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestep` - Timestep duration.
+    /// * `friction` - Friction coefficient (mass / time).
+    /// * `temperature` - Temperature of the implicit solvent.
+    pub fn new(timestep: Float, friction: Float, temperature: Float) -> Brownian {
+        Brownian {
+            timestep,
+            friction,
+            temperature,
+            distr: Normal::new(0.0, 1.0).unwrap(),
+            rng: rng::from_entropy(),
+        }
+    }
+
+    /// Seeds the random number generator to produce a reproducible trajectory.
+    pub fn seed(mut self, seed: u64) -> Brownian {
+        self.rng = rng::from_seed(seed);
+        self
+    }
+
+    /// Returns the diffusion coefficient `D = kB * T / gamma`.
+    pub fn diffusion_coefficient(&self) -> Float {
+        boltzmann() * self.temperature / self.friction
+    }
+}
+
+impl Integrator for Brownian {
+    fn integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let dt = self.timestep;
+        let width = Float::sqrt(2.0 * self.diffusion_coefficient() * dt);
+        let forces = Forces.calculate(system, potentials);
+        let friction = self.friction;
+        let distr = self.distr;
+        let rng = &mut self.rng;
+
+        system
+            .positions
+            .iter_mut()
+            .zip(forces.iter())
+            .for_each(|(pos, force)| {
+                let noise = Vector3::new(distr.sample(rng), distr.sample(rng), distr.sample(rng));
+                *pos += (force / friction) * dt + noise * width;
+            });
+    }
+}
+
+/// SLLOD equations of motion for homogeneous planar shear flow.
+///
+/// Atoms stream along x with velocity `shear_rate * y` while the cell deforms with
+/// Lees-Edwards boundary conditions (see [`Cell::shear`](crate::system::cell::Cell::shear)).
+/// `System.velocities` hold laboratory frame velocities and the optional Berendsen style
+/// thermostat acts only on the peculiar velocities relative to the streaming profile.
+///
+/// # References
+///
+/// [1] Evans, Denis J., and G. P. Morriss. "Nonlinear-response theory for steady planar Couette flow." Physical Review A 30.3 (1984): 1528.
+#[derive(Clone, Debug)]
+pub struct Sllod {
+    timestep: Float,
+    shear_rate: Float,
+    thermostat: Option<(Float, Float)>,
+    accelerations: Vec<Vector3<Float>>,
+}
+
+impl Sllod {
+    /// Returns a new [`Sllod`] algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestep` - Timestep duration.
+    /// * `shear_rate` - Gradient of the streaming x velocity along y (1 / time).
+    pub fn new(timestep: Float, shear_rate: Float) -> Sllod {
+        Sllod {
+            timestep,
+            shear_rate,
+            thermostat: None,
+            accelerations: Vec::new(),
+        }
+    }
+
+    /// Couples the peculiar velocities to a thermostat.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Target temperature.
+    /// * `tau` - Timestep of the thermostat expressed as a multiple of the integrator's timestep.
+    pub fn thermostat(mut self, target: Float, tau: Float) -> Sllod {
+        self.thermostat = Some((target, tau));
+        self
+    }
+
+    /// Returns the streaming velocity at a position.
+    pub fn streaming_velocity(&self, position: &Vector3<Float>) -> Vector3<Float> {
+        Vector3::new(self.shear_rate * position[1], 0.0, 0.0)
+    }
+
+    // temperature of the peculiar velocities
+    fn peculiar_temperature(&self, system: &System, peculiar: &[Vector3<Float>]) -> Float {
+        let kinetic: Float = system
+            .species
+            .iter()
+            .zip(peculiar.iter())
+            .map(|(species, vel)| 0.5 * species.mass() * vel.norm_squared())
+            .sum();
+        2.0 * kinetic / system.degrees_of_freedom() as Float / boltzmann()
+    }
+}
+
+impl Integrator for Sllod {
+    fn setup(&mut self, system: &System, potentials: &Potentials) {
+        self.accelerations = Forces
+            .calculate(system, potentials)
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+    }
+
+    fn integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let dt = self.timestep;
+        let shear_rate = self.shear_rate;
+
+        // peculiar velocities relative to the streaming profile
+        let mut peculiar: Vec<Vector3<Float>> = system
+            .positions
+            .iter()
+            .zip(system.velocities.iter())
+            .map(|(pos, vel)| vel - self.streaming_velocity(pos))
+            .collect();
+
+        peculiar
+            .iter_mut()
+            .zip(self.accelerations.iter())
+            .for_each(|(vel, acc)| {
+                let shear = Vector3::new(shear_rate * vel[1], 0.0, 0.0);
+                *vel += 0.5 * dt * (acc - shear);
+            });
+
+        let streaming: Vec<Vector3<Float>> = system
+            .positions
+            .iter()
+            .map(|pos| self.streaming_velocity(pos))
+            .collect();
+        let cell = &mut system.cell;
+        cell.shear(shear_rate * cell.b_vector()[1] * dt);
+        system
+            .positions
+            .iter_mut()
+            .zip(peculiar.iter().zip(streaming.iter()))
+            .for_each(|(pos, (vel, stream))| {
+                *pos += (vel + stream) * dt;
+                cell.wrap_vector(pos);
+            });
+
+        let forces = Forces.calculate(system, potentials);
+        self.accelerations = forces
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+
+        peculiar
+            .iter_mut()
+            .zip(self.accelerations.iter())
+            .for_each(|(vel, acc)| {
+                let shear = Vector3::new(shear_rate * vel[1], 0.0, 0.0);
+                *vel += 0.5 * dt * (acc - shear);
+            });
+
+        if let Some((target, tau)) = self.thermostat {
+            let temperature = self.peculiar_temperature(system, &peculiar);
+            if temperature > 0.0 {
+                let factor = Float::sqrt(1.0 + (target / temperature - 1.0) / tau);
+                peculiar.iter_mut().for_each(|vel| *vel *= factor);
+            }
+        }
+
+        system.velocities = system
+            .positions
+            .iter()
+            .zip(peculiar.iter())
+            .map(|(pos, vel)| vel + self.streaming_velocity(pos))
+            .collect();
+    }
+
+    fn state(&self) -> Vec<Float> {
+        flatten(&self.accelerations)
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.accelerations = unflatten(state);
+    }
+}
+
+/// Velocity Verlet integration of the Gaussian isokinetic equations of motion.
+///
+/// The constraint friction `xi = sum(F·v) / sum(m v²)` enters the equations of motion as
+/// `m dv/dt = F - xi m v` and each half-kick applies their analytic solution for constant forces [2],
+/// which holds the kinetic energy constant to floating point precision.
+/// The integrator acts as the thermostat, so combine it with a [`NullThermostat`](crate::thermostats::NullThermostat).
+/// A system at rest has no kinetic energy to hold and stays at rest.
+///
+/// # References
+///
+/// [1] Evans, Denis J., et al. "Nonequilibrium molecular dynamics via Gauss's principle of least constraint." Physical Review A 28.2 (1983): 1016.
+///
+/// [2] Zhang, Fei. "Operator-splitting integrators for constant-temperature molecular dynamics." The Journal of chemical physics 106.14 (1997): 6102-6106.
+#[derive(Clone, Debug)]
+pub struct Gaussian {
+    timestep: Float,
+    multiplier: Float,
+    accelerations: Vec<Vector3<Float>>,
+}
+
+impl Gaussian {
+    /// Returns a new [`Gaussian`] isokinetic algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestep` - Timestep duration.
+    pub fn new(timestep: Float) -> Gaussian {
+        Gaussian {
+            timestep,
+            multiplier: 0 as Float,
+            accelerations: Vec::new(),
+        }
+    }
+
+    /// Returns the constraint friction `xi` at the end of the last step.
+    pub fn multiplier(&self) -> Float {
+        self.multiplier
+    }
+
+    // sums of F·v and F²/m over the atoms
+    fn power(&self, system: &System) -> (Float, Float) {
+        system
+            .species
+            .iter()
+            .zip(system.velocities.iter())
+            .zip(self.accelerations.iter())
+            .fold((0.0, 0.0), |(fv, ff), ((species, vel), acc)| {
+                let mass = species.mass();
+                (fv + mass * acc.dot(vel), ff + mass * acc.norm_squared())
+            })
+    }
+
+    // advances the velocities by `h` under the current forces and the constraint friction
+    fn kick(&self, system: &mut System, h: Float) {
+        let kinetic_energy = KineticEnergy.calculate_intrinsic(system);
+        let (fv, ff) = self.power(system);
+        if kinetic_energy <= 0.0 || ff <= 0.0 {
+            // velocities at rest stay at rest and velocities without forces are unchanged
+            return;
+        }
+        let a = fv / (2.0 * kinetic_energy);
+        let b = ff / (2.0 * kinetic_energy);
+        let root = Float::sqrt(b);
+        let (sinh, cosh) = (Float::sinh(root * h), Float::cosh(root * h));
+        let s = a / b * (cosh - 1.0) + sinh / root;
+        let s_dot = a / root * sinh + cosh;
+        system
+            .velocities
+            .iter_mut()
+            .zip(self.accelerations.iter())
+            .for_each(|(vel, acc)| *vel = (*vel + acc * s) / s_dot);
+    }
+}
+
+impl Integrator for Gaussian {
+    fn setup(&mut self, system: &System, potentials: &Potentials) {
+        self.accelerations = Forces
+            .calculate(system, potentials)
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+    }
+
+    fn integrate(&mut self, system: &mut System, potentials: &Potentials) {
+        let dt = self.timestep;
+
+        self.kick(system, 0.5 * dt);
+
+        system
+            .positions
+            .iter_mut()
+            .zip(system.velocities.iter())
+            .for_each(|(pos, vel)| *pos += vel * dt);
+
+        let forces = Forces.calculate(system, potentials);
+        self.accelerations = forces
+            .iter()
+            .zip(system.species.iter())
+            .map(|(f, species)| f / species.mass())
+            .collect();
+
+        self.kick(system, 0.5 * dt);
+
+        let kinetic_energy = KineticEnergy.calculate_intrinsic(system);
+        self.multiplier = if kinetic_energy > 0.0 {
+            self.power(system).0 / (2.0 * kinetic_energy)
+        } else {
+            0 as Float
+        };
+    }
+
+    fn state(&self) -> Vec<Float> {
+        flatten(&self.accelerations)
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.accelerations = unflatten(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdaptiveVerlet, Brownian, Gaussian, Integrator, Sllod, VelocityVerlet};
+    use crate::internal::Float;
+    use crate::potentials::pair::PairPotential;
+    use crate::potentials::types::LennardJones;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::{KineticEnergy, TotalEnergy};
+    use crate::properties::forces::Forces;
+    use crate::properties::temperature::Temperature;
+    use crate::properties::{IntrinsicProperty, Property};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn brownian_free_particle_msd() {
+        let size = 500;
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size,
+            cell: Cell::cubic(100.0),
+            species: vec![argon; size],
+            positions: vec![Vector3::new(50.0, 50.0, 50.0); size],
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let initial = system.positions.clone();
+        let mut potentials = PotentialsBuilder::new().build();
+        potentials.setup(&system).unwrap();
+
+        let timestep = 1.0;
+        let steps = 100;
+        let mut brownian = Brownian::new(timestep, 1.0, 300.0).seed(1234);
+        brownian.setup(&system, &potentials);
+        for _ in 0..steps {
+            brownian.integrate(&mut system, &potentials);
+        }
+
+        // mean squared displacement per dimension
+        let msd = system
+            .positions
+            .iter()
+            .zip(initial.iter())
+            .map(|(pos, init)| (pos - init).norm_squared())
+            .sum::<Float>()
+            / (3 * size) as Float;
+        let target = 2.0 * brownian.diffusion_coefficient() * timestep * steps as Float;
+        assert_relative_eq!(msd, target, max_relative = 0.1);
+    }
+
+    #[test]
+    fn adaptive_verlet_collision() {
+        // two argon atoms fired at each other along x
+        let argon = Species::from_element(Element::Ar);
+        let speed = 0.05;
+        let collision = || System {
+            size: 2,
+            cell: Cell::cubic(80.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(35.0, 40.0, 40.0),
+                Vector3::new(45.0, 40.0, 40.0),
+            ],
+            velocities: vec![
+                Vector3::new(speed, 0.0, 0.0),
+                Vector3::new(-speed, 0.0, 0.0),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let lj = LennardJones::new(0.238, 3.4);
+        let cutoff = 12.0;
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), cutoff, 1.0)
+            .build();
+        let mut system = collision();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let initial_energy = TotalEnergy.calculate(&system, &potentials);
+
+        // the atoms turn around where the pair energy equals the total energy
+        let (mut lower, mut upper) = (1.0, lj.sigma);
+        for _ in 0..60 {
+            let r = 0.5 * (lower + upper);
+            if lj.energy(r) > initial_energy {
+                lower = r;
+            } else {
+                upper = r;
+            }
+        }
+        let turning_point = lower;
+
+        let energy_error =
+            |system: &System| (TotalEnergy.calculate(system, &potentials) - initial_energy).abs();
+        let separation = |system: &System| {
+            system
+                .cell
+                .distance(&system.positions[0], &system.positions[1])
+        };
+
+        // integrate the collision for 300 fs
+        let timestep = 4.0;
+        let mut adaptive = AdaptiveVerlet::new(timestep, timestep, 0.02);
+        adaptive.setup(&system, &potentials);
+        let (mut time, mut closest, mut drift): (Float, Float, Float) = (0.0, Float::INFINITY, 0.0);
+        let mut timesteps = Vec::new();
+        while time < 300.0 {
+            adaptive.integrate(&mut system, &potentials);
+            time += adaptive.timestep();
+            timesteps.push(adaptive.timestep());
+            closest = closest.min(separation(&system));
+            drift = drift.max(energy_error(&system));
+        }
+        assert!(closest > 0.98 * turning_point);
+        assert!(closest < lj.sigma);
+        // the atoms have separated beyond the cutoff so the timestep has grown back to dt_max
+        assert!(separation(&system) > cutoff);
+        assert_relative_eq!(adaptive.timestep(), timestep);
+        // the timestep grows back gradually rather than jumping to dt_max
+        let shortest = timesteps.iter().copied().fold(Float::INFINITY, Float::min);
+        assert!(shortest < 0.5 * timestep);
+        let growth = timesteps
+            .windows(2)
+            .map(|pair| pair[1] / pair[0])
+            .fold(0.0, Float::max);
+        assert!(growth > 1.0);
+        assert!(growth < 1.1 + 1e-4);
+
+        // an empty restart state leaves the integrator unchanged
+        adaptive.restore(&[]);
+        assert_relative_eq!(adaptive.timestep(), timestep);
+
+        // a fixed timestep overshoots into the repulsive core over the same 300 fs
+        let mut system = collision();
+        let mut fixed = VelocityVerlet::new(timestep);
+        fixed.setup(&system, &potentials);
+        let mut fixed_drift: Float = 0.0;
+        for _ in 0..75 {
+            fixed.integrate(&mut system, &potentials);
+            fixed_drift = fixed_drift.max(energy_error(&system));
+        }
+        assert!(drift < 0.1 * fixed_drift);
+    }
+
+    #[test]
+    fn sllod_velocity_profile() {
+        let argon = Species::from_element(Element::Ar);
+        let length = 16.0;
+        let mut positions = Vec::new();
+        for i in 0..4 {
+            for j in 0..4 {
+                for k in 0..4 {
+                    let ijk = Vector3::new(i as Float, j as Float, k as Float);
+                    positions.push((ijk + Vector3::new(0.5, 0.5, 0.5)) * 4.0);
+                }
+            }
+        }
+        let size = positions.len();
+        let mut system = System {
+            size,
+            cell: Cell::cubic(length),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new().build();
+        potentials.setup(&system).unwrap();
+
+        // the atoms start at rest in the laboratory frame and the thermostat
+        // drains the peculiar velocities until only the streaming profile remains
+        let shear_rate = 0.01;
+        let steps = 500;
+        let mut sllod = Sllod::new(1.0, shear_rate).thermostat(0.01, 10.0);
+        sllod.setup(&system, &potentials);
+        for _ in 0..steps {
+            sllod.integrate(&mut system, &potentials);
+        }
+
+        // the cell tilt follows the accumulated strain
+        let tilt = shear_rate * length * steps as Float;
+        let expected = tilt - length * Float::round(tilt / length);
+        assert_relative_eq!(system.cell.b_vector()[0], expected, epsilon = 1e-2);
+
+        // least squares fit of the x velocity against y
+        let n = size as Float;
+        let mean_y = system.positions.iter().map(|pos| pos[1]).sum::<Float>() / n;
+        let mean_vx = system.velocities.iter().map(|vel| vel[0]).sum::<Float>() / n;
+        let (cov, var) = system.positions.iter().zip(system.velocities.iter()).fold(
+            (0.0, 0.0),
+            |(cov, var), (pos, vel)| {
+                let dy = pos[1] - mean_y;
+                (cov + dy * (vel[0] - mean_vx), var + dy * dy)
+            },
+        );
+        assert_relative_eq!(cov / var, shear_rate, max_relative = 0.05);
+    }
+
+    #[test]
+    fn sllod_without_shear_matches_velocity_verlet() {
+        let argon = Species::from_element(Element::Ar);
+        let dimer = || System {
+            size: 2,
+            cell: Cell::cubic(30.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(13.0, 15.0, 15.0),
+                Vector3::new(16.8, 15.5, 15.0),
+            ],
+            velocities: vec![
+                Vector3::new(0.002, -0.001, 0.0),
+                Vector3::new(-0.002, 0.001, 0.0),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 10.0, 1.0)
+            .build();
+        let mut sllod_system = dimer();
+        potentials.setup(&sllod_system).unwrap();
+        potentials.update(&sllod_system, 0);
+
+        let mut sllod = Sllod::new(1.0, 0.0);
+        sllod.setup(&sllod_system, &potentials);
+        sllod.integrate(&mut sllod_system, &potentials);
+
+        let mut verlet_system = dimer();
+        let mut verlet = VelocityVerlet::new(1.0);
+        verlet.setup(&verlet_system, &potentials);
+        verlet.integrate(&mut verlet_system, &potentials);
+
+        // the first half-kick must already include the initial forces
+        for i in 0..2 {
+            assert_ne!(sllod_system.velocities[i], dimer().velocities[i]);
+            assert_relative_eq!(
+                sllod_system.positions[i],
+                verlet_system.positions[i],
+                epsilon = 1e-5
+            );
+            assert_relative_eq!(
+                sllod_system.velocities[i],
+                verlet_system.velocities[i],
+                epsilon = 1e-7
+            );
+        }
+    }
+
+    #[test]
+    fn sllod_planar_temperature() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 2,
+            cell: Cell::triclinic(16.0, 16.0, 4.0, 90.0, 90.0, 90.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(4.0, 4.0, 0.0), Vector3::new(8.0, 4.0, 0.0)],
+            velocities: vec![
+                Vector3::new(0.01, -0.02, 0.0),
+                Vector3::new(-0.01, 0.02, 0.0),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        system.make_planar();
+        // without shear the peculiar velocities are the velocities and share two degrees of freedom per atom
+        let sllod = Sllod::new(1.0, 0.0);
+        assert_relative_eq!(
+            sllod.peculiar_temperature(&system, &system.velocities),
+            Temperature.calculate_intrinsic(&system),
+            max_relative = 1e-5
+        );
+    }
+
+    #[test]
+    fn gaussian_isokinetic() {
+        let argon = Species::from_element(Element::Ar);
+        let n = 3;
+        let spacing = 3.6;
+        let mut positions = Vec::new();
+        let mut velocities = Vec::new();
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    let i = (x * n + y) * n + z;
+                    positions.push(Vector3::new(x as Float, y as Float, z as Float) * spacing);
+                    let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                    let scale = sign * (1 + i % 3) as Float;
+                    velocities.push(Vector3::new(0.004, -0.002, 0.003) * scale);
+                }
+            }
+        }
+        let size = positions.len();
+        let mut system = System {
+            size,
+            cell: Cell::cubic(spacing * n as Float),
+            species: vec![argon; size],
+            positions,
+            velocities,
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 5.0, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let initial = KineticEnergy.calculate_intrinsic(&system);
+        let mut gaussian = Gaussian::new(1.0);
+        gaussian.setup(&system, &potentials);
+        for step in 1..=300 {
+            gaussian.integrate(&mut system, &potentials);
+            potentials.update(&system, step);
+            let kinetic_energy = KineticEnergy.calculate_intrinsic(&system);
+            assert_relative_eq!(kinetic_energy, initial, max_relative = 1e-5);
+        }
+
+        // the multiplier is the constraint friction of the current forces and velocities
+        let (power, twice_kinetic) = Forces
+            .calculate(&system, &potentials)
+            .iter()
+            .zip(system.velocities.iter())
+            .zip(system.species.iter())
+            .fold((0.0, 0.0), |(fv, mv), ((f, v), species)| {
+                (fv + f.dot(v), mv + species.mass() * v.norm_squared())
+            });
+        assert!(gaussian.multiplier() != 0.0);
+        assert_relative_eq!(
+            gaussian.multiplier(),
+            power / twice_kinetic,
+            max_relative = 1e-3
+        );
+
+        // a system at rest is left at rest
+        system.velocities = vec![Vector3::zeros(); size];
+        let positions = system.positions.clone();
+        gaussian.setup(&system, &potentials);
+        gaussian.integrate(&mut system, &potentials);
+        assert!(system.velocities.iter().all(|v| *v == Vector3::zeros()));
+        assert_eq!(system.positions, positions);
+        assert_eq!(gaussian.multiplier(), 0.0);
+    }
 }
@@ -4,12 +4,38 @@ pub type Float = f64;
 #[cfg(not(feature = "f64"))]
 pub type Float = f32;
 
+// SIMD register of `Float` lanes.
+#[cfg(all(feature = "simd", feature = "f64"))]
+pub type FloatLanes = wide::f64x4;
+
+#[cfg(all(feature = "simd", not(feature = "f64")))]
+pub type FloatLanes = wide::f32x8;
+
 pub mod consts {
     #[cfg(not(feature = "f64"))]
+    #[allow(unused_imports)]
     pub use std::f32::consts::{FRAC_2_SQRT_PI, PI};
     #[cfg(feature = "f64")]
+    #[allow(unused_imports)]
     pub use std::f64::consts::{FRAC_2_SQRT_PI, PI};
 
-    pub const BOLTZMANN: super::Float = 0.001985875;
-    pub const COULOMB: super::Float = 332.0636;
+    use crate::units::{unit_system, UnitSystem};
+
+    pub const AVOGADRO: super::Float = 6.02214076e23;
+
+    // Returns the Boltzmann constant in the active unit system.
+    pub fn boltzmann() -> super::Float {
+        match unit_system() {
+            UnitSystem::Real => 0.001985875,
+            UnitSystem::Reduced => 1.0,
+        }
+    }
+
+    // Returns the Coulomb constant in the active unit system.
+    pub fn coulomb() -> super::Float {
+        match unit_system() {
+            UnitSystem::Real => 332.0636,
+            UnitSystem::Reduced => 1.0,
+        }
+    }
 }
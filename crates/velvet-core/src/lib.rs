@@ -8,43 +8,72 @@
 //! * `energy` - Kcal/mole
 //! * `force` - Kcal/mole-angstrom
 //! * `temperature` - Kelvin
+//!
+//! Lennard-Jones reduced units can be selected at runtime with [`units::set_unit_system`].
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
+// Literals are written at `f64` precision regardless of the `Float` type.
+#![allow(clippy::excessive_precision)]
 
 #[macro_use]
 extern crate strum_macros;
 
+pub mod barostats;
+pub mod charge_equilibration;
 pub mod config;
 pub mod integrators;
 mod internal;
+pub mod minimizers;
+pub mod neighbors;
 pub mod outputs;
 pub mod potentials;
 pub mod propagators;
 pub mod properties;
+pub mod restart;
+pub mod rng;
 pub mod selection;
 pub mod simulation;
 pub mod system;
 pub mod thermostats;
+pub mod units;
 pub mod velocity_distributions;
 
 /// User facing exports.
 pub mod prelude {
+    pub use super::barostats::*;
+    pub use super::charge_equilibration::*;
     pub use super::config::*;
     pub use super::integrators::*;
+    pub use super::minimizers::*;
+    pub use super::neighbors::*;
+    pub use super::outputs::csv::*;
+    pub use super::outputs::extxyz::*;
     #[cfg(feature = "hdf5-output")]
     pub use super::outputs::hdf5::*;
+    pub use super::outputs::json::*;
     pub use super::outputs::raw::*;
     pub use super::outputs::*;
     pub use super::potentials::coulomb::*;
+    pub use super::potentials::external::*;
+    pub use super::potentials::many_body::*;
     pub use super::potentials::pair::*;
     pub use super::potentials::types::*;
     pub use super::potentials::*;
     pub use super::propagators::*;
+    pub use super::properties::correlation::*;
+    pub use super::properties::elastic::*;
     pub use super::properties::energy::*;
     pub use super::properties::forces::*;
+    pub use super::properties::geometry::*;
+    pub use super::properties::order::*;
+    pub use super::properties::pressure::*;
+    pub use super::properties::statistics::*;
     pub use super::properties::temperature::*;
+    pub use super::properties::transport::*;
     pub use super::properties::*;
+    pub use super::restart::*;
+    pub use super::rng::*;
     pub use super::selection::*;
     pub use super::simulation::*;
     pub use super::system::cell::*;
@@ -52,5 +81,6 @@ pub mod prelude {
     pub use super::system::species::*;
     pub use super::system::*;
     pub use super::thermostats::*;
+    pub use super::units::*;
     pub use super::velocity_distributions::*;
 }
@@ -0,0 +1,344 @@
+//! Algorithms which relax a system toward a minimum of its potential energy.
+
+use nalgebra::{DVector, Matrix3, Vector3};
+
+use crate::internal::Float;
+use crate::potentials::Potentials;
+use crate::properties::energy::PotentialEnergy;
+use crate::properties::forces::Forces;
+use crate::properties::Property;
+use crate::system::System;
+
+// Multiple of the machine epsilon of the energy below which a rise in energy is attributed to roundoff.
+const ROUNDOFF: Float = 1e3;
+
+/// Polak-Ribière nonlinear conjugate gradient minimization of the potential energy.
+///
+/// Each iteration searches along a direction built from the current forces and the previous direction.
+/// A trial step moves any coordinate by at most `max_step` and is shortened to where the force along
+/// the direction vanishes if it overshoots, so the search relies on forces rather than small energy differences.
+/// The search restarts along the forces whenever the direction stops pointing downhill or the energy rises.
+///
+/// With cell relaxation the deformation of the cell from its initial shape joins the atomic coordinates
+/// as degrees of freedom driven by the virial, so the minimum is a state of zero stress.
+/// Atoms are then described by their coordinates in the undeformed cell and the deformation is scaled
+/// by the number of atoms so both kinds of coordinates respond to forces on a comparable scale.
+/// Velocities are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use velvet_core::prelude::*;
+/// use nalgebra::Vector3;
+///
+/// let argon = Species::from_element(Element::Ar);
+/// let mut system = System {
+///     size: 2,
+///     cell: Cell::cubic(20.0),
+///     species: vec![argon; 2],
+///     positions: vec![Vector3::new(5.0, 5.0, 5.0), Vector3::new(9.0, 5.0, 5.0)],
+///     velocities: vec![Vector3::zeros(); 2],
+///     bonds: Vec::new(),
+///     angles: Vec::new(),
+///     dihedrals: Vec::new(),
+/// };
+/// let mut potentials = PotentialsBuilder::new()
+///     .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+///     .build();
+///
+/// // the dimer relaxes to the minimum of the Lennard-Jones potential at 2^(1/6) sigma
+/// ConjugateGradient::new(1e-3, 100)
+///     .minimize(&mut system, &mut potentials)
+///     .unwrap();
+/// let r = system.cell.distance(&system.positions[0], &system.positions[1]);
+/// assert!((r - 3.816).abs() < 1e-2);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ConjugateGradient {
+    force_tolerance: Float,
+    max_iterations: usize,
+    max_step: Float,
+    relax_cell: bool,
+}
+
+impl ConjugateGradient {
+    /// Returns a new [`ConjugateGradient`] minimizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `force_tolerance` - Largest generalized force component remaining at convergence.
+    /// * `max_iterations` - Number of iterations before the minimization fails.
+    pub fn new(force_tolerance: Float, max_iterations: usize) -> ConjugateGradient {
+        ConjugateGradient {
+            force_tolerance,
+            max_iterations,
+            max_step: 0.1,
+            relax_cell: false,
+        }
+    }
+
+    /// Sets the largest change of any coordinate in the first trial step of each line search.
+    ///
+    /// Defaults to 0.1 angstrom.
+    pub fn max_step(mut self, max_step: Float) -> ConjugateGradient {
+        self.max_step = max_step;
+        self
+    }
+
+    /// Relaxes the shape and size of the cell along with the atoms.
+    pub fn relax_cell(mut self, relax_cell: bool) -> ConjugateGradient {
+        self.relax_cell = relax_cell;
+        self
+    }
+
+    /// Minimizes the potential energy of the system in place and returns the number of iterations taken.
+    ///
+    /// Returns an error if the forces are still above the tolerance after the maximum number of iterations
+    /// or if the energy can no longer be decreased along the forces
+    /// or if the potentials can not be set up on the system.
+    pub fn minimize(
+        &self,
+        system: &mut System,
+        potentials: &mut Potentials,
+    ) -> Result<usize, String> {
+        potentials.setup(system)?;
+        let mut relaxation = Relaxation::new(system, self.relax_cell);
+        let (mut energy, mut forces) = relaxation.evaluate(system, potentials);
+        let mut direction = forces.clone();
+        for iteration in 0..self.max_iterations {
+            if forces.amax() < self.force_tolerance {
+                return Ok(iteration);
+            }
+            // restart along the forces if the direction no longer points downhill
+            let mut slope = direction.dot(&forces);
+            if slope <= 0.0 {
+                direction = forces.clone();
+                slope = direction.dot(&forces);
+            }
+
+            // the trial step is accepted unless the slope along the direction changes sign
+            // in which case the root of the linearly interpolated slope is taken instead
+            let start = relaxation.coordinates.clone();
+            let trial = self.max_step / direction.amax();
+            relaxation.set(system, &start + &direction * trial);
+            let (mut new_energy, mut new_forces) = relaxation.evaluate(system, potentials);
+            let trial_slope = direction.dot(&new_forces);
+            if trial_slope < 0.0 {
+                let alpha = trial * slope / (slope - trial_slope);
+                relaxation.set(system, &start + &direction * alpha);
+                let (energy, forces) = relaxation.evaluate(system, potentials);
+                new_energy = energy;
+                new_forces = forces;
+            }
+
+            // energies are only compared beyond their roundoff error
+            let roundoff = ROUNDOFF * Float::EPSILON * Float::max(energy.abs(), 1.0);
+            if new_energy > energy + roundoff {
+                relaxation.set(system, start);
+                relaxation.evaluate(system, potentials);
+                if direction == forces {
+                    return Err(format!(
+                        "energy stopped decreasing after {} iterations with a largest force of {}",
+                        iteration,
+                        forces.amax()
+                    ));
+                }
+                direction = forces.clone();
+                continue;
+            }
+
+            let beta = Float::max(
+                0.0,
+                new_forces.dot(&(&new_forces - &forces)) / forces.dot(&forces),
+            );
+            direction = &new_forces + &direction * beta;
+            energy = new_energy;
+            forces = new_forces;
+        }
+        if forces.amax() < self.force_tolerance {
+            return Ok(self.max_iterations);
+        }
+        Err(format!(
+            "minimization did not converge in {} iterations with a largest force of {}",
+            self.max_iterations,
+            forces.amax()
+        ))
+    }
+}
+
+// Generalized coordinates of the atoms and optionally the cell deformation.
+struct Relaxation {
+    relax_cell: bool,
+    size: usize,
+    // deformation gradient of the cell relative to its initial shape
+    deformation: Matrix3<Float>,
+    cell_factor: Float,
+    coordinates: DVector<Float>,
+}
+
+impl Relaxation {
+    fn new(system: &System, relax_cell: bool) -> Relaxation {
+        let size = system.size;
+        let cell_factor = size.max(1) as Float;
+        let deformation = Matrix3::identity();
+        let mut coordinates = DVector::zeros(3 * size + if relax_cell { 9 } else { 0 });
+        for (i, position) in system.positions.iter().enumerate() {
+            coordinates.fixed_rows_mut::<3>(3 * i).copy_from(position);
+        }
+        if relax_cell {
+            coordinates
+                .rows_mut(3 * size, 9)
+                .copy_from_slice((deformation * cell_factor).as_slice());
+        }
+        Relaxation {
+            relax_cell,
+            size,
+            deformation,
+            cell_factor,
+            coordinates,
+        }
+    }
+
+    // Moves the system to a new set of generalized coordinates.
+    fn set(&mut self, system: &mut System, coordinates: DVector<Float>) {
+        if self.relax_cell {
+            let deformation =
+                Matrix3::from_column_slice(coordinates.rows(3 * self.size, 9).as_slice())
+                    / self.cell_factor;
+            let inverse = self.deformation.try_inverse().unwrap();
+            system
+                .cell
+                .apply_strain(deformation * inverse - Matrix3::identity());
+            self.deformation = deformation;
+        }
+        for (i, position) in system.positions.iter_mut().enumerate() {
+            let coordinate: Vector3<Float> = coordinates.fixed_rows::<3>(3 * i).into();
+            *position = self.deformation * coordinate;
+        }
+        self.coordinates = coordinates;
+    }
+
+    // Returns the potential energy and the generalized forces of the current coordinates.
+    fn evaluate(&self, system: &System, potentials: &mut Potentials) -> (Float, DVector<Float>) {
+        potentials.update(system, 0);
+        let energy = PotentialEnergy.calculate(system, potentials);
+        let transpose = self.deformation.transpose();
+        let mut forces = DVector::zeros(self.coordinates.len());
+        for (i, force) in Forces.calculate(system, potentials).iter().enumerate() {
+            forces
+                .fixed_rows_mut::<3>(3 * i)
+                .copy_from(&(transpose * force));
+        }
+        if self.relax_cell {
+            // the virial is the negative derivative of the energy with respect to strain
+            let inverse = transpose.try_inverse().unwrap();
+            let cell_forces = potentials.virial(system) * inverse / self.cell_factor;
+            forces
+                .rows_mut(3 * self.size, 9)
+                .copy_from_slice(cell_forces.as_slice());
+        }
+        (energy, forces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConjugateGradient;
+    use crate::internal::Float;
+    use crate::potentials::types::LennardJones;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::forces::Forces;
+    use crate::properties::pressure::Pressure;
+    use crate::properties::Property;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn relax_cell() {
+        let argon = Species::from_element(Element::Ar);
+        let lj = LennardJones::new(0.238, 3.4);
+        // no shell of neighbors crosses the cutoff between the trial lattice constants
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 7.0, 0.5)
+            .build();
+        let mut pressure = |a: Float| {
+            let system = System::fcc_lattice(argon, a, 3);
+            potentials.setup(&system).unwrap();
+            potentials.update(&system, 0);
+            Pressure.calculate(&system, &potentials)
+        };
+        // the equilibrium lattice constant of the perfect crystal is where the pressure vanishes
+        let (mut lower, mut upper) = (5.1, 5.5);
+        assert!(pressure(lower) > 0.0 && pressure(upper) < 0.0);
+        for _ in 0..40 {
+            let middle = (lower + upper) / 2.0;
+            if pressure(middle) > 0.0 {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+        let a0 = (lower + upper) / 2.0;
+
+        // start from an expanded lattice with displaced atoms
+        let mut system = System::fcc_lattice(argon, 1.03 * a0, 3);
+        let mut rng = StdRng::seed_from_u64(1234);
+        for position in system.positions.iter_mut() {
+            *position += Vector3::new(
+                rng.gen_range(-0.05, 0.05),
+                rng.gen_range(-0.05, 0.05),
+                rng.gen_range(-0.05, 0.05),
+            );
+        }
+        ConjugateGradient::new(1e-3, 500)
+            .relax_cell(true)
+            .minimize(&mut system, &mut potentials)
+            .unwrap();
+
+        let (a, b, c, alpha, beta, gamma) = system.cell.lattice_parameters();
+        for length in [a, b, c].iter() {
+            assert_relative_eq!(*length / 3.0, a0, max_relative = 1e-3);
+        }
+        for angle in [alpha, beta, gamma].iter() {
+            assert_relative_eq!(*angle, 90.0, epsilon = 0.1);
+        }
+        assert_relative_eq!(
+            Pressure.calculate(&system, &potentials),
+            0.0,
+            epsilon = 1e-4
+        );
+        let largest = Forces
+            .calculate(&system, &potentials)
+            .iter()
+            .map(|force| force.amax())
+            .fold(0.0, Float::max);
+        assert!(largest < 1e-2);
+    }
+
+    #[test]
+    fn fixed_cell() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System::fcc_lattice(argon, 5.4, 3);
+        system.positions[0] += Vector3::new(0.3, -0.2, 0.1);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 0.5)
+            .build();
+        ConjugateGradient::new(1e-3, 500)
+            .minimize(&mut system, &mut potentials)
+            .unwrap();
+
+        // the atoms return to the lattice without changing the cell
+        assert_eq!(system.cell.a(), 3.0 * 5.4);
+        let largest = Forces
+            .calculate(&system, &potentials)
+            .iter()
+            .map(|force| force.amax())
+            .fold(0.0, Float::max);
+        assert!(largest < 1e-3);
+    }
+}
@@ -0,0 +1,304 @@
+//! Compact storage of the neighbors of each atom.
+
+#[cfg(feature = "timing")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::internal::Float;
+
+/// Statistics of the builds of a [`NeighborList`] to help choose the skin thickness and update frequency.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NeighborStats {
+    /// Number of times the list has been built.
+    pub rebuilds: usize,
+    /// Average number of neighbors of each atom counting both atoms of every pair.
+    pub average_neighbors: Float,
+    /// Time taken by the most recent build including the search for its pairs.
+    #[cfg(feature = "timing")]
+    pub last_build_time: Duration,
+    /// Total time spent building the list.
+    #[cfg(feature = "timing")]
+    pub total_build_time: Duration,
+}
+
+/// Half neighbor list in compressed sparse row layout.
+///
+/// The neighbors of every atom are stored contiguously in a single flat array and
+/// `offsets[i]..offsets[i + 1]` is the range of atom `i`'s neighbors within it.
+/// Each pair `[i, j]` is stored once in the row of its first atom, so a force loop
+/// which walks the rows in order reads the position of atom `i` once per row and the
+/// neighbor indices sequentially.
+///
+/// The transposed list is stored alongside so every partner of a single atom can be
+/// queried without scanning the other rows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NeighborList {
+    offsets: Vec<usize>,
+    neighbors: Vec<usize>,
+    // rows of the transposed pairs `[j, i]`
+    reverse_offsets: Vec<usize>,
+    reverse_neighbors: Vec<usize>,
+    stats: NeighborStats,
+}
+
+impl NeighborList {
+    /// Returns a new [`NeighborList`] of `size` atoms from a list of pairs.
+    ///
+    /// Pairs keep their relative order within each row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair refers to an atom which is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// let list = NeighborList::from_pairs(4, &[[2, 3], [0, 1], [0, 3]]);
+    /// assert_eq!(list.neighbors(0), &[1, 3]);
+    /// assert!(list.neighbors(1).is_empty());
+    /// assert_eq!(list.pairs().collect::<Vec<_>>(), vec![[0, 1], [0, 3], [2, 3]]);
+    /// assert_eq!(list.partners(3).collect::<Vec<_>>(), vec![2, 0]);
+    /// ```
+    pub fn from_pairs(size: usize, pairs: &[[usize; 2]]) -> NeighborList {
+        let mut list = NeighborList::default();
+        list.rebuild(size, pairs);
+        list
+    }
+
+    /// Replaces the pairs of the list and counts the build in its [`stats`](NeighborList::stats).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair refers to an atom which is out of bounds.
+    pub fn rebuild(&mut self, size: usize, pairs: &[[usize; 2]]) {
+        #[cfg(feature = "timing")]
+        let start = Instant::now();
+        self.store(size, pairs);
+        #[cfg(feature = "timing")]
+        self.record_build_time(start.elapsed());
+    }
+
+    /// Replaces the pairs of the list with those returned by `search` and counts the build in its
+    /// [`stats`](NeighborList::stats).
+    ///
+    /// The recorded build time includes the search.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair refers to an atom which is out of bounds.
+    pub fn rebuild_with<F>(&mut self, size: usize, search: F)
+    where
+        F: FnOnce() -> Vec<[usize; 2]>,
+    {
+        #[cfg(feature = "timing")]
+        let start = Instant::now();
+        let pairs = search();
+        self.store(size, &pairs);
+        #[cfg(feature = "timing")]
+        self.record_build_time(start.elapsed());
+    }
+
+    // Compresses the pairs into rows and counts the build.
+    fn store(&mut self, size: usize, pairs: &[[usize; 2]]) {
+        for &[i, j] in pairs {
+            assert!(
+                i < size && j < size,
+                "pair [{}, {}] is out of bounds for {} atoms",
+                i,
+                j,
+                size
+            );
+        }
+        let (offsets, neighbors) = compress(size, pairs.iter().map(|&[i, j]| (i, j)));
+        let (reverse_offsets, reverse_neighbors) =
+            compress(size, pairs.iter().map(|&[i, j]| (j, i)));
+        self.offsets = offsets;
+        self.neighbors = neighbors;
+        self.reverse_offsets = reverse_offsets;
+        self.reverse_neighbors = reverse_neighbors;
+
+        self.stats.rebuilds += 1;
+        self.stats.average_neighbors = 2.0 * pairs.len() as Float / size.max(1) as Float;
+    }
+
+    #[cfg(feature = "timing")]
+    fn record_build_time(&mut self, elapsed: Duration) {
+        self.stats.last_build_time = elapsed;
+        self.stats.total_build_time += elapsed;
+    }
+
+    /// Returns the statistics of every build of the list.
+    pub fn stats(&self) -> NeighborStats {
+        self.stats
+    }
+
+    /// Returns the number of atoms in the list.
+    pub fn size(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// Returns the total number of pairs.
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    /// Returns `true` if the list has no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    /// Returns the neighbors stored in the row of atom `i`.
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.neighbors[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// Returns an iterator over every atom paired with atom `i` in either position.
+    ///
+    /// The neighbors in the row of atom `i` come first followed by the atoms whose rows hold `i`.
+    pub fn partners(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let reverse = &self.reverse_neighbors[self.reverse_offsets[i]..self.reverse_offsets[i + 1]];
+        self.neighbors(i).iter().chain(reverse.iter()).copied()
+    }
+
+    /// Returns an iterator over each atom and its row of neighbors.
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &[usize])> {
+        (0..self.size()).map(move |i| (i, self.neighbors(i)))
+    }
+
+    /// Returns a parallel iterator over each atom and its row of neighbors.
+    #[cfg(feature = "rayon")]
+    pub fn par_rows(&self) -> impl ParallelIterator<Item = (usize, &[usize])> {
+        (0..self.size())
+            .into_par_iter()
+            .map(move |i| (i, self.neighbors(i)))
+    }
+
+    /// Returns an iterator over every pair ordered by its first atom.
+    pub fn pairs(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.rows()
+            .flat_map(|(i, row)| row.iter().map(move |&j| [i, j]))
+    }
+}
+
+// Counting sort of `(row, value)` entries into row offsets and the flat array of values.
+fn compress<I>(size: usize, entries: I) -> (Vec<usize>, Vec<usize>)
+where
+    I: Iterator<Item = (usize, usize)> + Clone,
+{
+    let mut offsets = vec![0; size + 1];
+    for (row, _) in entries.clone() {
+        offsets[row + 1] += 1;
+    }
+    for i in 0..size {
+        offsets[i + 1] += offsets[i];
+    }
+    let mut cursors = offsets[..size].to_vec();
+    let mut values = vec![0; offsets[size]];
+    for (row, value) in entries {
+        values[cursors[row]] = value;
+        cursors[row] += 1;
+    }
+    (offsets, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NeighborList;
+    use crate::internal::Float;
+    use crate::selection::{setup_all_pairs, update_pairs_by_cutoff_radius};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn same_pairs() {
+        let argon = Species::from_element(Element::Ar);
+        let size = 50;
+        // a scrambled spiral of atoms has neighbors in an irregular order
+        let positions = (0..size)
+            .map(|i| {
+                let t = ((i * 17) % size) as Float;
+                Vector3::new(
+                    10.0 + 6.0 * Float::cos(0.5 * t),
+                    10.0 + 6.0 * Float::sin(0.5 * t),
+                    0.3 * t,
+                )
+            })
+            .collect();
+        let system = System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let possible = setup_all_pairs(&system, ());
+        let mut pairs = update_pairs_by_cutoff_radius(&system, &possible, 5.0);
+        // reversed pairs are kept in the row of their first atom
+        pairs.iter_mut().step_by(3).for_each(|pair| pair.reverse());
+        assert!(!pairs.is_empty() && pairs.len() < possible.len());
+
+        let list = NeighborList::from_pairs(size, &pairs);
+        assert_eq!(list.size(), size);
+        assert_eq!(list.len(), pairs.len());
+        let mut visited: Vec<[usize; 2]> = list.pairs().collect();
+        // rows are visited in order of their atom
+        assert!(visited.windows(2).all(|w| w[0][0] <= w[1][0]));
+        visited.sort_unstable();
+        pairs.sort_unstable();
+        assert_eq!(visited, pairs);
+        for (i, row) in list.rows() {
+            assert!(row.iter().all(|&j| pairs.contains(&[i, j])));
+        }
+        // the partners of an atom are every pair it takes part in
+        for i in 0..size {
+            let mut partners: Vec<usize> = list.partners(i).collect();
+            let mut expected: Vec<usize> = pairs
+                .iter()
+                .filter_map(|&[a, b]| match (a == i, b == i) {
+                    (true, _) => Some(b),
+                    (_, true) => Some(a),
+                    _ => None,
+                })
+                .collect();
+            partners.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(partners, expected);
+        }
+    }
+
+    #[cfg(feature = "timing")]
+    #[test]
+    fn search_time() {
+        let mut list = NeighborList::default();
+        list.rebuild_with(2, || {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            vec![[0, 1]]
+        });
+        assert_eq!(list.len(), 1);
+        let stats = list.stats();
+        // the time spent searching for the pairs is part of the build
+        assert!(stats.last_build_time >= std::time::Duration::from_millis(5));
+        list.rebuild(2, &[]);
+        let total = stats.last_build_time + list.stats().last_build_time;
+        assert_eq!(list.stats().total_build_time, total);
+    }
+
+    #[test]
+    fn empty() {
+        let list = NeighborList::from_pairs(3, &[]);
+        assert!(list.is_empty());
+        assert_eq!(list.size(), 3);
+        assert_eq!(list.pairs().count(), 0);
+        assert_eq!(NeighborList::default().size(), 0);
+    }
+}
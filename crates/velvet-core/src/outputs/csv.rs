@@ -0,0 +1,184 @@
+//! Comma separated thermodynamic logs.
+
+use std::io::Write;
+
+use crate::internal::Float;
+use crate::outputs::{OutputMeta, OutputSink};
+use crate::potentials::Potentials;
+use crate::properties::Property;
+use crate::system::System;
+
+/// Collection of scalar properties written as the columns of a CSV file.
+///
+/// Each row starts with the step and, if enabled, the simulation time in picoseconds.
+pub struct CsvOutputGroup {
+    /// Writer which receives the rows.
+    pub destination: Box<dyn Write>,
+    /// Number of iterations between rows.
+    pub interval: usize,
+    /// Whether to write the simulation time column.
+    pub time: bool,
+    /// Properties to write as columns.
+    pub columns: Vec<Box<dyn Property<Res = Float>>>,
+    header_written: bool,
+}
+
+impl CsvOutputGroup {
+    /// Returns the header row.
+    pub fn header(&self) -> String {
+        let mut names = vec!["step".to_string()];
+        if self.time {
+            names.push("time_ps".to_string());
+        }
+        names.extend(self.columns.iter().map(|column| column.name()));
+        names.join(",")
+    }
+
+    /// Returns the row of values describing the system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time column is enabled and `meta` has no timestep.
+    pub fn row(&self, meta: &OutputMeta, system: &System, potentials: &Potentials) -> String {
+        let mut values = vec![meta.step.to_string()];
+        if self.time {
+            let time = meta
+                .time()
+                .expect("the CSV time column requires a timestep in the configuration");
+            values.push(time.to_string());
+        }
+        values.extend(
+            self.columns
+                .iter()
+                .map(|column| column.calculate(system, potentials).to_string()),
+        );
+        values.join(",")
+    }
+
+    /// Writes a row, preceded by the header if it is the first.
+    pub fn write(&mut self, meta: &OutputMeta, system: &System, potentials: &Potentials) {
+        if !self.header_written {
+            let header = self.header();
+            writeln!(self.destination, "{}", header).unwrap();
+            self.header_written = true;
+        }
+        let row = self.row(meta, system, potentials);
+        writeln!(self.destination, "{}", row).unwrap();
+    }
+}
+
+/// Constructor for the [`CsvOutputGroup`] type.
+pub struct CsvOutputGroupBuilder {
+    destination: Box<dyn Write>,
+    interval: usize,
+    time: bool,
+    columns: Vec<Box<dyn Property<Res = Float>>>,
+}
+
+impl CsvOutputGroupBuilder {
+    /// Returns a new `CsvOutputGroupBuilder`.
+    pub fn new() -> CsvOutputGroupBuilder {
+        CsvOutputGroupBuilder {
+            destination: Box::new(std::io::stderr()),
+            interval: 1,
+            time: false,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Sets the destination of the rows.
+    pub fn destination<T: Write + 'static>(mut self, destination: T) -> CsvOutputGroupBuilder {
+        self.destination = Box::new(destination);
+        self
+    }
+
+    /// Sets the destination of the rows from an [`OutputSink`].
+    pub fn sink(mut self, sink: &OutputSink) -> CsvOutputGroupBuilder {
+        self.destination = sink.writer();
+        self
+    }
+
+    /// Sets the number of iterations between rows.
+    pub fn interval(mut self, interval: usize) -> CsvOutputGroupBuilder {
+        self.interval = interval;
+        self
+    }
+
+    /// Adds a `time_ps` column with the simulation time in picoseconds.
+    ///
+    /// The timestep must be set on the [`Configuration`](crate::config::Configuration).
+    pub fn time(mut self) -> CsvOutputGroupBuilder {
+        self.time = true;
+        self
+    }
+
+    /// Adds a column to the group.
+    pub fn column<T: Property<Res = Float> + 'static>(
+        mut self,
+        column: T,
+    ) -> CsvOutputGroupBuilder {
+        self.columns.push(Box::new(column));
+        self
+    }
+
+    /// Returns an initialized [`CsvOutputGroup`].
+    pub fn build(self) -> CsvOutputGroup {
+        CsvOutputGroup {
+            destination: self.destination,
+            interval: self.interval,
+            time: self.time,
+            columns: self.columns,
+            header_written: false,
+        }
+    }
+}
+
+impl Default for CsvOutputGroupBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CsvOutputGroupBuilder;
+    use crate::outputs::{OutputMeta, SharedBuffer};
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::temperature::Temperature;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn time_in_picoseconds() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::zeros(), Vector3::new(3.0, 0.0, 0.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let buffer = SharedBuffer::new();
+        let mut group = CsvOutputGroupBuilder::new()
+            .destination(buffer.clone())
+            .time()
+            .column(Temperature)
+            .build();
+        group.write(&OutputMeta::new(500, Some(2.0)), &system, &potentials);
+
+        let contents = String::from_utf8(buffer.contents()).unwrap();
+        let mut lines = contents.lines();
+        assert_eq!(lines.next(), Some("step,time_ps,temperature"));
+        let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(row[0], "500");
+        assert_eq!(row[1].parse::<f64>().unwrap(), 1.0);
+        assert_eq!(row[2].parse::<f64>().unwrap(), 0.0);
+    }
+}
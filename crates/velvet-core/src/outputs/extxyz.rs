@@ -0,0 +1,330 @@
+//! Extended XYZ formatted frames labeled with energies and forces.
+
+use std::io::Write;
+
+use crate::outputs::raw::RawOutput;
+use crate::outputs::FrameFilter;
+use crate::potentials::Potentials;
+use crate::properties::energy::{KineticEnergy, PotentialEnergy};
+use crate::properties::forces::Forces;
+use crate::properties::temperature::Temperature;
+use crate::properties::{IntrinsicProperty, Property};
+use crate::system::System;
+
+/// Writes each output as an extended XYZ frame labeled with the potential energy and the force on each atom.
+///
+/// The comment line holds the lattice, the column layout, the potential energy as `energy=...`,
+/// the instantaneous temperature as `T=...` and the total energy as `E=...` followed by one
+/// `species:S:1:pos:R:3:forces:R:3` line per atom, which is the layout expected by most machine learning
+/// potential training codes. Both energies are omitted if the potentials are empty since they are unavailable.
+/// Species which were not constructed from an [`Element`](crate::system::elements::Element) are written as `X`.
+/// The energies and temperature are always those of the whole system even if only a subset of the atoms is written.
+#[derive(Clone, Debug)]
+pub struct XyzFrame {
+    wrap: bool,
+    filter: FrameFilter,
+}
+
+impl XyzFrame {
+    /// Returns a new [`XyzFrame`] output which writes every atom at every output.
+    pub fn new() -> XyzFrame {
+        XyzFrame {
+            wrap: false,
+            filter: FrameFilter::new(),
+        }
+    }
+
+    /// Sets whether positions are wrapped into the primary cell when written.
+    ///
+    /// Only the written copy is wrapped so the simulated positions are left unchanged.
+    pub fn wrap(mut self, wrap: bool) -> XyzFrame {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets the number of outputs between written frames to downsample the trajectory in time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero.
+    pub fn stride(mut self, stride: usize) -> XyzFrame {
+        self.filter.stride(stride);
+        self
+    }
+
+    /// Restricts the written atoms to a subset of the system's indices.
+    ///
+    /// Atoms are written in the order of the subset, so atom `k` of a frame is system atom `atom_subset[k]`.
+    pub fn atom_subset(mut self, atom_subset: Vec<usize>) -> XyzFrame {
+        self.filter.atom_subset(atom_subset);
+        self
+    }
+}
+
+impl Default for XyzFrame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RawOutput for XyzFrame {
+    fn output_raw(&self, system: &System, potentials: &Potentials, writer: &mut dyn Write) {
+        if self.filter.next().is_none() {
+            return;
+        }
+        let atoms = self.filter.atoms(system.size);
+        let forces = Forces.calculate(system, potentials);
+        // the lattice lists the vectors in order
+        let lattice: Vec<String> = [
+            system.cell.a_vector(),
+            system.cell.b_vector(),
+            system.cell.c_vector(),
+        ]
+        .iter()
+        .flat_map(|vector| vector.iter().map(|value| value.to_string()))
+        .collect();
+        let temperature = format!("T={}", Temperature.calculate_intrinsic(system));
+        // energies are unavailable without any potentials
+        let labels = if potentials.is_empty() {
+            temperature
+        } else {
+            let energy = PotentialEnergy.calculate(system, potentials);
+            let total = energy + KineticEnergy.calculate_intrinsic(system);
+            format!("energy={} {} E={}", energy, temperature, total)
+        };
+
+        writeln!(writer, "{}", atoms.len()).unwrap();
+        writeln!(
+            writer,
+            "Lattice=\"{}\" Properties=species:S:1:pos:R:3:forces:R:3 {} pbc=\"T T T\"",
+            lattice.join(" "),
+            labels
+        )
+        .unwrap();
+        for i in atoms {
+            let (mut position, force) = (system.positions[i], forces[i]);
+            if self.wrap {
+                system.cell.wrap_vector(&mut position);
+            }
+            let symbol = system.species[i]
+                .element()
+                .map_or(String::from("X"), |element| element.to_string());
+            writeln!(
+                writer,
+                "{} {} {} {} {} {} {}",
+                symbol, position[0], position[1], position[2], force[0], force[1], force[2]
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XyzFrame;
+    use crate::internal::Float;
+    use crate::outputs::raw::RawOutput;
+    use crate::potentials::types::LennardJones;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::{PotentialEnergy, TotalEnergy};
+    use crate::properties::forces::Forces;
+    use crate::properties::temperature::Temperature;
+    use crate::properties::{IntrinsicProperty, Property};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn energy_and_forces() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(12.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(1.0, 2.0, 3.0),
+                Vector3::new(4.7, 2.0, 3.0),
+                Vector3::new(2.5, 5.1, 3.4),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 5.5, 0.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let mut buffer = Vec::new();
+        XyzFrame::new().output_raw(&system, &potentials, &mut buffer);
+
+        let text = String::from_utf8(buffer).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("3"));
+        let comment = lines.next().unwrap();
+        let (_, lattice) = comment.split_once("Lattice=\"").unwrap();
+        let (lattice, _) = lattice.split_once('"').unwrap();
+        let lattice: Vec<Float> = lattice
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        assert_eq!(lattice.len(), 9);
+        assert_eq!(lattice[..3], system.cell.a_vector().as_slice()[..]);
+        let (_, energy) = comment.split_once("energy=").unwrap();
+        let energy: Float = energy.split_whitespace().next().unwrap().parse().unwrap();
+        assert!(energy.is_finite());
+        assert_eq!(energy, PotentialEnergy.calculate(&system, &potentials));
+
+        // each atom line round trips its position and force
+        let forces = Forces.calculate(&system, &potentials);
+        let atoms: Vec<&str> = lines.collect();
+        assert_eq!(atoms.len(), system.size);
+        for (i, line) in atoms.iter().enumerate() {
+            let mut tokens = line.split_whitespace();
+            assert_eq!(tokens.next(), Some("Ar"));
+            let values: Vec<Float> = tokens.map(|value| value.parse().unwrap()).collect();
+            assert_eq!(values.len(), 6);
+            assert_eq!(
+                Vector3::new(values[0], values[1], values[2]),
+                system.positions[i]
+            );
+            assert_eq!(Vector3::new(values[3], values[4], values[5]), forces[i]);
+        }
+        assert!(forces.iter().any(|force| force.norm() > 0.0));
+    }
+
+    #[test]
+    fn wrapped_positions() {
+        let argon = Species::from_element(Element::Ar);
+        let positions = vec![Vector3::new(-1.0, 2.0, 3.0), Vector3::new(12.5, 2.0, 23.0)];
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 2],
+            positions: positions.clone(),
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let mut buffer = Vec::new();
+        XyzFrame::new()
+            .wrap(true)
+            .output_raw(&system, &potentials, &mut buffer);
+
+        let text = String::from_utf8(buffer).unwrap();
+        let atoms: Vec<Vec<&str>> = text
+            .lines()
+            .skip(2)
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(atoms[0][1..4], ["9", "2", "3"]);
+        assert_eq!(atoms[1][1..4], ["2.5", "2", "3"]);
+        // the simulated positions are not wrapped
+        assert_eq!(system.positions, positions);
+    }
+
+    #[test]
+    fn stride_and_subset() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let size = 10;
+        let mut system = System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: (0..size)
+                .map(|i| if i == 5 { xenon } else { argon })
+                .collect(),
+            positions: (0..size)
+                .map(|i| Vector3::new(i as Float, 1.0, 1.0))
+                .collect(),
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let output = XyzFrame::new().stride(3).atom_subset(vec![7, 2, 5]);
+        let mut buffer = Vec::new();
+        for step in 0..7 {
+            // the y coordinate records the output which wrote the frame
+            system
+                .positions
+                .iter_mut()
+                .for_each(|p| p[1] = step as Float);
+            output.output_raw(&system, &potentials, &mut buffer);
+        }
+
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        // frames of a count line, a comment line and three atoms
+        assert_eq!(lines.len(), 3 * 5);
+        for (frame, step) in lines.chunks(5).zip([0, 3, 6].iter()) {
+            assert_eq!(frame[0], "3");
+            let atoms: Vec<Vec<&str>> = frame[2..]
+                .iter()
+                .map(|line| line.split_whitespace().collect())
+                .collect();
+            let symbols: Vec<&str> = atoms.iter().map(|atom| atom[0]).collect();
+            assert_eq!(symbols, vec!["Ar", "Ar", "Xe"]);
+            let x: Vec<&str> = atoms.iter().map(|atom| atom[1]).collect();
+            assert_eq!(x, vec!["7", "2", "5"]);
+            assert!(atoms.iter().all(|atom| atom[2] == step.to_string()));
+        }
+    }
+
+    #[test]
+    fn temperature_and_total_energy() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(12.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.7, 2.0, 3.0)],
+            velocities: vec![
+                Vector3::new(0.002, 0.0, -0.001),
+                Vector3::new(-0.002, 0.001, 0.0),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // returns the value of a `key=value` field of the comment line if it is present
+        let field = |comment: &str, key: &str| -> Option<Float> {
+            comment
+                .split_whitespace()
+                .find_map(|token| token.strip_prefix(key))
+                .map(|value| value.parse().unwrap())
+        };
+
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 5.5, 0.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let mut buffer = Vec::new();
+        XyzFrame::new().output_raw(&system, &potentials, &mut buffer);
+        let text = String::from_utf8(buffer).unwrap();
+        let comment = text.lines().nth(1).unwrap();
+        let temperature = field(comment, "T=").unwrap();
+        assert!(temperature > 0.0);
+        assert_eq!(temperature, Temperature.calculate_intrinsic(&system));
+        assert_eq!(
+            field(comment, "E="),
+            Some(TotalEnergy.calculate(&system, &potentials))
+        );
+
+        // without potentials the energies are omitted
+        let mut buffer = Vec::new();
+        XyzFrame::new().output_raw(&system, &PotentialsBuilder::new().build(), &mut buffer);
+        let text = String::from_utf8(buffer).unwrap();
+        let comment = text.lines().nth(1).unwrap();
+        assert_eq!(field(comment, "T="), Some(temperature));
+        assert_eq!(field(comment, "E="), None);
+        assert_eq!(field(comment, "energy="), None);
+    }
+}
@@ -77,6 +77,48 @@ impl Hdf5Output for Forces {
     }
 }
 
+/// Writes the position of each atom, optionally wrapped into the primary cell.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Positions {
+    wrap: bool,
+}
+
+impl Positions {
+    /// Returns a new [`Positions`] output which writes the positions as simulated.
+    pub fn new() -> Positions {
+        Positions { wrap: false }
+    }
+
+    /// Sets whether positions are wrapped into the primary cell when written.
+    ///
+    /// Only the written copy is wrapped so the simulated positions are left unchanged.
+    pub fn wrap(mut self, wrap: bool) -> Positions {
+        self.wrap = wrap;
+        self
+    }
+}
+
+impl Hdf5Output for Positions {
+    fn output_hdf5(&self, system: &System, _: &Potentials, group: &hdf5::Group) {
+        let dataset = group
+            .new_dataset::<[Float; 3]>()
+            .create("positions", system.size)
+            .unwrap();
+        let arr: Vec<[Float; 3]> = system
+            .positions
+            .iter()
+            .map(|position| {
+                let mut position = *position;
+                if self.wrap {
+                    system.cell.wrap_vector(&mut position);
+                }
+                [position[0], position[1], position[2]]
+            })
+            .collect();
+        dataset.write(arr.as_slice()).unwrap()
+    }
+}
+
 impl Hdf5Output for KineticEnergy {
     fn output_hdf5(&self, system: &System, potentials: &Potentials, group: &hdf5::Group) {
         let energy = self.calculate(system, potentials);
@@ -0,0 +1,319 @@
+//! JSON formatted frames for web based visualization.
+
+use std::io::Write;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::internal::Float;
+use crate::outputs::raw::RawOutput;
+use crate::outputs::{FrameFilter, OutputMeta};
+use crate::potentials::Potentials;
+use crate::system::species::Species;
+use crate::system::System;
+
+/// Atom entry of a [`JsonSnapshot`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonAtom {
+    /// Symbol of the atom's element, or the unique ID of its species as a decimal string
+    /// if the species is not a plain element.
+    pub species: String,
+    /// Cartesian position.
+    pub position: [Float; 3],
+    /// Electronic charge.
+    pub charge: Float,
+    /// CPK color of the atom's element if it has one.
+    pub color: Option<[u8; 3]>,
+    /// Van der Waals radius of the atom's element if it has one.
+    pub radius: Option<Float>,
+}
+
+/// Serializable snapshot of a system.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonSnapshot {
+    /// Number of completed steps at the snapshot.
+    pub timestep: usize,
+    /// Rows are the `a`, `b`, and `c` lattice vectors.
+    pub cell: [[Float; 3]; 3],
+    /// Every atom in the system.
+    pub atoms: Vec<JsonAtom>,
+}
+
+impl JsonSnapshot {
+    /// Returns a new [`JsonSnapshot`] of the system at a given timestep.
+    pub fn new(system: &System, timestep: usize) -> JsonSnapshot {
+        let lattice = [
+            system.cell.a_vector(),
+            system.cell.b_vector(),
+            system.cell.c_vector(),
+        ];
+        let atoms = (0..system.size)
+            .map(|i| {
+                let pos = system.positions[i];
+                let species = system.species[i];
+                let element = species.element();
+                // labeled species share an element so only a plain element is written by symbol
+                let name = match element {
+                    Some(element) if species == Species::from_element(element) => {
+                        element.to_string()
+                    }
+                    _ => species.id().to_string(),
+                };
+                JsonAtom {
+                    species: name,
+                    position: [pos[0], pos[1], pos[2]],
+                    charge: system.species[i].charge(),
+                    color: element.map(|element| element.cpk_color()),
+                    radius: element.map(|element| element.vdw_radius()),
+                }
+            })
+            .collect();
+        JsonSnapshot {
+            timestep,
+            cell: [
+                [lattice[0][0], lattice[0][1], lattice[0][2]],
+                [lattice[1][0], lattice[1][1], lattice[1][2]],
+                [lattice[2][0], lattice[2][1], lattice[2][2]],
+            ],
+            atoms,
+        }
+    }
+}
+
+/// Writes each output as a [`JsonSnapshot`] on its own line of JSON.
+///
+/// The timestep of each frame is the number of completed steps reported by the simulation.
+/// Frames written directly through [`output_raw`](RawOutput::output_raw) have no step and report zero.
+#[derive(Clone, Debug)]
+pub struct JsonFrame {
+    wrap: bool,
+    filter: FrameFilter,
+}
+
+impl JsonFrame {
+    /// Returns a new [`JsonFrame`] output.
+    pub fn new() -> JsonFrame {
+        JsonFrame {
+            wrap: false,
+            filter: FrameFilter::new(),
+        }
+    }
+
+    /// Sets whether positions are wrapped into the primary cell when written.
+    ///
+    /// Only the written copy is wrapped so the simulated positions are left unchanged.
+    pub fn wrap(mut self, wrap: bool) -> JsonFrame {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets the number of outputs between written frames to downsample the trajectory in time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero.
+    pub fn stride(mut self, stride: usize) -> JsonFrame {
+        self.filter.stride(stride);
+        self
+    }
+
+    /// Restricts the written atoms to a subset of the system's indices.
+    ///
+    /// Atoms are written in the order of the subset, so atom `k` of a frame is system atom `atom_subset[k]`.
+    pub fn atom_subset(mut self, atom_subset: Vec<usize>) -> JsonFrame {
+        self.filter.atom_subset(atom_subset);
+        self
+    }
+}
+
+impl Default for JsonFrame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RawOutput for JsonFrame {
+    fn output_raw(&self, system: &System, potentials: &Potentials, writer: &mut dyn Write) {
+        self.output_raw_at(&OutputMeta::new(0, None), system, potentials, writer)
+    }
+
+    fn output_raw_at(
+        &self,
+        meta: &OutputMeta,
+        system: &System,
+        _: &Potentials,
+        writer: &mut dyn Write,
+    ) {
+        if self.filter.next().is_none() {
+            return;
+        }
+        let mut frame = JsonSnapshot::new(system, meta.step);
+        let atoms = self.filter.atoms(system.size);
+        frame.atoms = atoms.iter().map(|&i| frame.atoms[i].clone()).collect();
+        if self.wrap {
+            for atom in frame.atoms.iter_mut() {
+                let mut position = Vector3::from(atom.position);
+                system.cell.wrap_vector(&mut position);
+                atom.position = position.into();
+            }
+        }
+        serde_json::to_writer(&mut *writer, &frame).unwrap();
+        writer.write_all(b"\n").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonFrame, JsonSnapshot};
+    use crate::internal::Float;
+    use crate::outputs::raw::RawOutput;
+    use crate::outputs::OutputMeta;
+    use crate::potentials::PotentialsBuilder;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn round_trip() {
+        let sodium = Species::from_element(Element::Na).with_charge(1.0);
+        let chlorine = Species::from_element(Element::Cl).with_charge(-1.0);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![sodium, chlorine],
+            positions: vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(3.5, 2.0, 3.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let output = JsonFrame::new();
+        let mut buffer = Vec::new();
+        // the steps of a run resumed at step 40 whose last output falls between intervals
+        output.output_raw_at(
+            &OutputMeta::new(45, None),
+            &system,
+            &potentials,
+            &mut buffer,
+        );
+        output.output_raw_at(
+            &OutputMeta::new(47, None),
+            &system,
+            &potentials,
+            &mut buffer,
+        );
+
+        let text = String::from_utf8(buffer).unwrap();
+        let frames: Vec<JsonSnapshot> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].timestep, 45);
+        assert_eq!(frames[1].timestep, 47);
+        assert_eq!(frames[1].atoms.len(), system.size);
+        assert_eq!(frames[1].atoms[0].species, "Na");
+        assert_eq!(frames[1].atoms[1].charge, -1.0);
+        assert_eq!(frames[1].atoms[1].position, [3.5, 2.0, 3.0]);
+        assert_eq!(frames[1].cell[0], [10.0, 0.0, 0.0]);
+        assert_eq!(frames[1].atoms[1].color, Some(Element::Cl.cpk_color()));
+        assert_eq!(frames[1].atoms[0].radius, Some(Element::Na.vdw_radius()));
+    }
+
+    #[test]
+    fn wrapped_positions() {
+        let argon = Species::from_element(Element::Ar);
+        let positions = vec![Vector3::new(-1.0, 2.0, 3.0), Vector3::new(12.5, 2.0, 23.0)];
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 2],
+            positions: positions.clone(),
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let mut buffer = Vec::new();
+        JsonFrame::new()
+            .wrap(true)
+            .output_raw(&system, &potentials, &mut buffer);
+
+        let frame: JsonSnapshot = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(frame.atoms[0].position, [9.0, 2.0, 3.0]);
+        assert_eq!(frame.atoms[1].position, [2.5, 2.0, 3.0]);
+        // the simulated positions are not wrapped
+        assert_eq!(system.positions, positions);
+    }
+
+    #[test]
+    fn stride_and_subset() {
+        let argon = Species::from_element(Element::Ar);
+        let size = 10;
+        let system = System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; size],
+            positions: (0..size)
+                .map(|i| Vector3::new(i as Float, 1.0, 1.0))
+                .collect(),
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let output = JsonFrame::new().stride(2).atom_subset(vec![9, 0, 4]);
+        let mut buffer = Vec::new();
+        for step in (10..=50).step_by(10) {
+            let meta = OutputMeta::new(step, None);
+            output.output_raw_at(&meta, &system, &potentials, &mut buffer);
+        }
+
+        let text = String::from_utf8(buffer).unwrap();
+        let frames: Vec<JsonSnapshot> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let timesteps: Vec<usize> = frames.iter().map(|frame| frame.timestep).collect();
+        assert_eq!(timesteps, vec![10, 30, 50]);
+        for frame in frames {
+            let x: Vec<Float> = frame.atoms.iter().map(|atom| atom.position[0]).collect();
+            assert_eq!(x, vec![9.0, 0.0, 4.0]);
+        }
+    }
+
+    #[test]
+    fn species_names() {
+        let argon = Species::from_element(Element::Ar);
+        let labeled = Species::labeled(Element::Ar, "Ar2");
+        let bead = Species::named("BeadA", 72.0, 0.0);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(20.0),
+            species: vec![argon, labeled, bead],
+            positions: vec![Vector3::zeros(); 3],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let frame = JsonSnapshot::new(&system, 0);
+        let text = serde_json::to_string(&frame).unwrap();
+        // IDs are written as strings since they exceed the integers a JSON number holds exactly
+        assert!(text.contains(&format!("\"species\":\"{}\"", bead.id())));
+        let names: Vec<String> = frame.atoms.into_iter().map(|atom| atom.species).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Ar".to_string(),
+                labeled.id().to_string(),
+                bead.id().to_string()
+            ]
+        );
+    }
+}
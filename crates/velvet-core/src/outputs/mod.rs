@@ -1,5 +1,288 @@
 //! Properties which can be output as a result from the simulation.
 
+pub mod csv;
+pub mod extxyz;
 #[cfg(feature = "hdf5-output")]
 pub mod hdf5;
+pub mod json;
 pub mod raw;
+
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::internal::Float;
+
+/// Position of the simulation in time when an output is written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputMeta {
+    /// Number of completed steps.
+    pub step: usize,
+    /// Timestep duration if it is known.
+    pub timestep: Option<Float>,
+}
+
+impl OutputMeta {
+    /// Returns a new [`OutputMeta`].
+    pub fn new(step: usize, timestep: Option<Float>) -> OutputMeta {
+        OutputMeta { step, timestep }
+    }
+
+    /// Returns the elapsed simulation time in picoseconds if the timestep is known.
+    pub fn time(&self) -> Option<Float> {
+        // the internal time unit is femtoseconds
+        self.timestep
+            .map(|timestep| self.step as Float * timestep / 1000.0)
+    }
+}
+
+// Selection of the frames and atoms which a trajectory output writes.
+#[derive(Clone, Debug)]
+pub(crate) struct FrameFilter {
+    stride: usize,
+    atom_subset: Option<Vec<usize>>,
+    count: Cell<usize>,
+}
+
+impl FrameFilter {
+    pub(crate) fn new() -> FrameFilter {
+        FrameFilter {
+            stride: 1,
+            atom_subset: None,
+            count: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn stride(&mut self, stride: usize) {
+        assert!(stride > 0, "the stride must be positive");
+        self.stride = stride;
+    }
+
+    pub(crate) fn atom_subset(&mut self, atom_subset: Vec<usize>) {
+        self.atom_subset = Some(atom_subset);
+    }
+
+    // Counts an output and returns its index if its frame is written.
+    pub(crate) fn next(&self) -> Option<usize> {
+        let count = self.count.get();
+        self.count.set(count + 1);
+        if count.is_multiple_of(self.stride) {
+            Some(count)
+        } else {
+            None
+        }
+    }
+
+    // Returns the index of each written atom in the system so written atom `k` is system atom `atoms[k]`.
+    pub(crate) fn atoms(&self, size: usize) -> Vec<usize> {
+        match &self.atom_subset {
+            Some(subset) => {
+                if let Some(&index) = subset.iter().find(|&&index| index >= size) {
+                    panic!(
+                        "atom {} of the subset is out of bounds for {} atoms",
+                        index, size
+                    )
+                }
+                subset.clone()
+            }
+            None => (0..size).collect(),
+        }
+    }
+}
+
+/// In-memory buffer which can be inspected after it is handed to an output group.
+#[derive(Clone, Debug, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Returns a new empty [`SharedBuffer`].
+    pub fn new() -> SharedBuffer {
+        SharedBuffer::default()
+    }
+
+    /// Returns a copy of the bytes written so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer which keeps frames in memory and passes them to an inner writer in batches.
+///
+/// Frames are delimited by calls to [`end_frame`](FrameBuffer::end_frame) and reach the inner
+/// writer every `flush_interval` frames. Pending frames are also written when the buffer is
+/// dropped, including while unwinding from a panic, so none are silently lost.
+pub struct FrameBuffer<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+    flush_interval: usize,
+    frames: usize,
+}
+
+impl<W: Write> FrameBuffer<W> {
+    /// Returns a new [`FrameBuffer`] which writes to `inner` every `flush_interval` frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `flush_interval` is zero.
+    pub fn new(inner: W, flush_interval: usize) -> FrameBuffer<W> {
+        assert!(flush_interval > 0, "the flush interval must be positive");
+        FrameBuffer {
+            inner,
+            pending: Vec::new(),
+            flush_interval,
+            frames: 0,
+        }
+    }
+
+    /// Marks the end of a frame and writes the pending frames if the interval is complete.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.frames += 1;
+        if self.frames.is_multiple_of(self.flush_interval) {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for FrameBuffer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.pending)?;
+        self.pending.clear();
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for FrameBuffer<W> {
+    fn drop(&mut self) {
+        // errors cannot be reported from a destructor
+        let _ = self.flush();
+    }
+}
+
+/// Destination of written outputs.
+///
+/// Sinks parse and deserialize from strings so configuration files can name them: `"stdout"`,
+/// `"buffer"`, or any other string which is treated as a file path.
+#[derive(Clone, Debug)]
+pub enum OutputSink {
+    /// Standard output stream.
+    Stdout,
+    /// File which is created or truncated.
+    File(PathBuf),
+    /// In-memory buffer.
+    Buffer(SharedBuffer),
+}
+
+impl OutputSink {
+    /// Returns a writer which sends bytes to the sink.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a file sink cannot be created.
+    pub fn writer(&self) -> Box<dyn Write> {
+        match self {
+            OutputSink::Stdout => Box::new(io::stdout()),
+            OutputSink::File(path) => Box::new(File::create(path).unwrap()),
+            OutputSink::Buffer(buffer) => Box::new(buffer.clone()),
+        }
+    }
+}
+
+impl FromStr for OutputSink {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "stdout" => OutputSink::Stdout,
+            "buffer" => OutputSink::Buffer(SharedBuffer::new()),
+            path => OutputSink::File(PathBuf::from(path)),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputSink {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.parse() {
+            Ok(sink) => Ok(sink),
+            Err(never) => match never {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameBuffer, OutputSink, SharedBuffer};
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[test]
+    fn buffer_sink() {
+        let sink: OutputSink = "buffer".parse().unwrap();
+        let mut writer = sink.writer();
+        writer.write_all(b"temperature: 300.0\n").unwrap();
+        drop(writer);
+        match sink {
+            OutputSink::Buffer(buffer) => assert_eq!(buffer.contents(), b"temperature: 300.0\n"),
+            _ => panic!("expected a buffer sink"),
+        }
+    }
+
+    #[test]
+    fn parse_sink() {
+        assert!(matches!("stdout".parse().unwrap(), OutputSink::Stdout));
+        match "results/out.txt".parse().unwrap() {
+            OutputSink::File(path) => assert_eq!(path, PathBuf::from("results/out.txt")),
+            _ => panic!("expected a file sink"),
+        }
+    }
+
+    #[test]
+    fn deserialize_sink() {
+        let sinks: Vec<OutputSink> = serde_json::from_str(r#"["stdout", "buffer"]"#).unwrap();
+        assert!(matches!(sinks[0], OutputSink::Stdout));
+        assert!(matches!(sinks[1], OutputSink::Buffer(_)));
+    }
+
+    #[test]
+    fn frame_buffer() {
+        let buffer = SharedBuffer::new();
+        let mut frames = FrameBuffer::new(buffer.clone(), 4);
+        let count = |buffer: &SharedBuffer| {
+            String::from_utf8(buffer.contents())
+                .unwrap()
+                .lines()
+                .count()
+        };
+        for frame in 0..10 {
+            writeln!(frames, "frame {}", frame).unwrap();
+            frames.end_frame().unwrap();
+            // frames arrive in batches of four
+            assert_eq!(count(&buffer), (frame + 1) / 4 * 4);
+        }
+        drop(frames);
+        assert_eq!(count(&buffer), 10);
+        let contents = String::from_utf8(buffer.contents()).unwrap();
+        assert_eq!(contents.lines().last(), Some("frame 9"));
+    }
+}
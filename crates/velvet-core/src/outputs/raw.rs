@@ -1,7 +1,9 @@
 //! Raw text formatted outputs.
 
+use std::fmt::Display;
 use std::io::Write;
 
+use crate::outputs::{FrameBuffer, OutputMeta, OutputSink};
 use crate::potentials::Potentials;
 use crate::properties::Property;
 use crate::system::System;
@@ -10,57 +12,106 @@ use crate::system::System;
 pub trait RawOutput {
     /// Writes the raw text formatted output.
     fn output_raw(&self, system: &System, potentials: &Potentials, writer: &mut dyn Write);
+
+    /// Writes the raw text formatted output at the position in time described by `meta`.
+    ///
+    /// The simulation writes every output through this method. Outputs which do not report the step ignore `meta`
+    /// and defer to [`output_raw`](RawOutput::output_raw).
+    fn output_raw_at(
+        &self,
+        _: &OutputMeta,
+        system: &System,
+        potentials: &Potentials,
+        writer: &mut dyn Write,
+    ) {
+        self.output_raw(system, potentials, writer)
+    }
 }
 
+/// Collection of raw outputs which share a destination and output interval.
 pub struct RawOutputGroup {
-    pub destination: Box<dyn Write>,
+    /// Writer which receives the formatted outputs.
+    ///
+    /// Each output step is one frame of the buffer.
+    pub destination: FrameBuffer<Box<dyn Write>>,
+    /// Number of iterations between outputs.
     pub interval: usize,
+    /// Outputs to write.
     pub outputs: Vec<Box<dyn RawOutput>>,
 }
 
+/// Constructor for the [`RawOutputGroup`] type.
 pub struct RawOutputGroupBuilder {
     destination: Box<dyn Write>,
     interval: usize,
+    flush_interval: usize,
     outputs: Vec<Box<dyn RawOutput>>,
 }
 
 impl RawOutputGroupBuilder {
+    /// Returns a new `RawOutputGroupBuilder`.
     pub fn new() -> RawOutputGroupBuilder {
         RawOutputGroupBuilder {
             destination: Box::new(std::io::stderr()),
             interval: 1,
+            flush_interval: 1,
             outputs: Vec::new(),
         }
     }
 
+    /// Sets the destination of the outputs.
     pub fn destination<T: Write + 'static>(mut self, destination: T) -> RawOutputGroupBuilder {
         self.destination = Box::new(destination);
         self
     }
 
+    /// Sets the destination of the outputs from an [`OutputSink`].
+    pub fn sink(mut self, sink: &OutputSink) -> RawOutputGroupBuilder {
+        self.destination = sink.writer();
+        self
+    }
+
+    /// Sets the number of iterations between outputs.
     pub fn interval(mut self, interval: usize) -> RawOutputGroupBuilder {
         self.interval = interval;
         self
     }
 
+    /// Sets the number of output steps which are kept in memory before they are written to the destination.
+    ///
+    /// Larger values reduce the I/O overhead of frequent outputs.
+    pub fn flush_interval(mut self, flush_interval: usize) -> RawOutputGroupBuilder {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Adds an output to the group.
     pub fn output<T: RawOutput + 'static>(mut self, output: T) -> RawOutputGroupBuilder {
         self.outputs.push(Box::new(output));
         self
     }
 
+    /// Returns an initialized [`RawOutputGroup`].
     pub fn build(self) -> RawOutputGroup {
         RawOutputGroup {
-            destination: self.destination,
+            destination: FrameBuffer::new(self.destination, self.flush_interval),
             interval: self.interval,
             outputs: self.outputs,
         }
     }
 }
 
+impl Default for RawOutputGroupBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // This issue: https://github.com/rust-lang/rust/issues/20400
 // prevents me from specializing the impl block by the trait's associated type.
 // Ideally I will have separate impl blocks for Property<Res=Float> and Property<Res=Vector3<Float>>
 // in order to make the formatting more appropriate.
+// Until then `LabeledOutput` opts into `Display` formatting for any property whose result supports it.
 
 impl<T: Property> RawOutput for T {
     fn output_raw(&self, system: &System, potentials: &Potentials, writer: &mut dyn Write) {
@@ -70,3 +121,82 @@ impl<T: Property> RawOutput for T {
             .unwrap()
     }
 }
+
+/// Adapter which writes a property as `label: value` using the `Display` format of its result.
+///
+/// # Examples
+///
+/// ```
+/// use velvet_core::prelude::*;
+///
+/// let output = LabeledOutput::new("T (K)", Temperature);
+/// let group = RawOutputGroupBuilder::new().output(output).build();
+/// assert_eq!(group.outputs.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LabeledOutput<P> {
+    label: String,
+    property: P,
+}
+
+impl<P> LabeledOutput<P>
+where
+    P: Property,
+    P::Res: Display,
+{
+    /// Returns a new [`LabeledOutput`] which writes `property` under `label`.
+    pub fn new<T: Into<String>>(label: T, property: P) -> LabeledOutput<P> {
+        LabeledOutput {
+            label: label.into(),
+            property,
+        }
+    }
+}
+
+impl<P> RawOutput for LabeledOutput<P>
+where
+    P: Property,
+    P::Res: Display,
+{
+    fn output_raw(&self, system: &System, potentials: &Potentials, writer: &mut dyn Write) {
+        let res = self.property.calculate(system, potentials);
+        writeln!(writer, "{}: {}", self.label, res).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LabeledOutput, RawOutput};
+    use crate::internal::Float;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::temperature::Temperature;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn labeled_output() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::zeros(), Vector3::new(3.0, 0.0, 0.0)],
+            velocities: vec![Vector3::new(0.01, 0.0, 0.0), Vector3::new(-0.01, 0.0, 0.0)],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let output = LabeledOutput::new("T (K)", Temperature);
+        let mut buffer = Vec::new();
+        output.output_raw(&system, &potentials, &mut buffer);
+
+        let temperature: Float = Temperature.calculate(&system, &potentials);
+        let text = String::from_utf8(buffer).unwrap();
+        assert_eq!(text, format!("T (K): {}\n", temperature));
+    }
+}
@@ -1,15 +1,16 @@
 //! Potentials which describe Coulombic electrostatic interactions.
 
 #[cfg(feature = "f64")]
-use libm::erfc as erfc;
+use libm::erfc;
 
 #[cfg(not(feature = "f64"))]
 use libm::erfcf as erfc;
 
-use crate::internal::consts::COULOMB;
+use nalgebra::{Matrix3, Vector3};
+
+use crate::internal::consts::{boltzmann, coulomb, FRAC_2_SQRT_PI};
 use crate::internal::Float;
-use crate::internal::consts::FRAC_2_SQRT_PI;
-use crate::potentials::types::{DampedShiftedForce, StandardCoulombic};
+use crate::potentials::types::{DampedShiftedForce, DebyeHuckel, DirectCoulomb, StandardCoulombic};
 use crate::potentials::Potential;
 use crate::selection::{setup_pairs_with_charge, update_pairs_by_cutoff_radius, Selection};
 use crate::system::System;
@@ -20,6 +21,17 @@ pub trait CoulombPotential: Potential {
     fn energy(&self, qi: Float, qj: Float, r: Float) -> Float;
     /// Returns the magnitude of the force acting on an atom separated from another by a distance `r` with charges `qi` and `qj`.
     fn force(&self, qi: Float, qj: Float, r: Float) -> Float;
+    /// Returns the self energy of a single atom with charge `qi`.
+    fn self_energy(&self, _qi: Float) -> Float {
+        0.0
+    }
+    /// Returns `false` if the potential treats the system as isolated rather than periodic.
+    ///
+    /// Aperiodic potentials interact through the raw displacement between every pair of charged atoms
+    /// and ignore both the minimum image convention and the cutoff.
+    fn is_periodic(&self) -> bool {
+        true
+    }
 }
 
 impl CoulombPotential for DampedShiftedForce {
@@ -34,7 +46,7 @@ impl CoulombPotential for DampedShiftedForce {
         let term_d = factor * (Float::exp(-alpha2 * cutoff2) / self.cutoff);
         let term_e = r - self.cutoff;
 
-        qi * qj * (term_a - term_b + (term_c + term_d) * term_e)
+        coulomb() * qi * qj * (term_a - term_b + (term_c + term_d) * term_e)
     }
 
     fn force(&self, qi: Float, qj: Float, r: Float) -> Float {
@@ -48,17 +60,48 @@ impl CoulombPotential for DampedShiftedForce {
         let term_c = erfc(self.alpha * self.cutoff) / cutoff2;
         let term_d = factor * Float::exp(-alpha2 * cutoff2) / self.cutoff;
 
-        qi * qj * ((term_a + term_b) - (term_c + term_d))
+        coulomb() * qi * qj * ((term_c + term_d) - (term_a + term_b))
+    }
+
+    fn self_energy(&self, qi: Float) -> Float {
+        let term_a = erfc(self.alpha * self.cutoff) / (2.0 * self.cutoff);
+        let term_b = 0.5 * FRAC_2_SQRT_PI * self.alpha;
+        -coulomb() * qi.powi(2) * (term_a + term_b)
+    }
+}
+
+impl CoulombPotential for DebyeHuckel {
+    fn energy(&self, qi: Float, qj: Float, r: Float) -> Float {
+        let prefactor = qi * qj * self.bjerrum_length * boltzmann() * self.temperature;
+        prefactor * Float::exp(-r / self.debye_length) / r
+    }
+
+    fn force(&self, qi: Float, qj: Float, r: Float) -> Float {
+        -self.energy(qi, qj, r) * (1.0 / r + 1.0 / self.debye_length)
+    }
+}
+
+impl CoulombPotential for DirectCoulomb {
+    fn energy(&self, qi: Float, qj: Float, r: Float) -> Float {
+        (coulomb() * qi * qj) / (self.dielectric * r)
+    }
+
+    fn force(&self, qi: Float, qj: Float, r: Float) -> Float {
+        -(coulomb() * qi * qj) / (self.dielectric * r.powi(2))
+    }
+
+    fn is_periodic(&self) -> bool {
+        false
     }
 }
 
 impl CoulombPotential for StandardCoulombic {
     fn energy(&self, qi: Float, qj: Float, r: Float) -> Float {
-        (COULOMB * qi * qj) / (self.dielectric * r)
+        (coulomb() * qi * qj) / (self.dielectric * r)
     }
 
     fn force(&self, qi: Float, qj: Float, r: Float) -> Float {
-        -(COULOMB * qi * qj) / (self.dielectric * r.powi(2))
+        -(coulomb() * qi * qj) / (self.dielectric * r.powi(2))
     }
 }
 
@@ -97,14 +140,85 @@ impl CoulombPotentialMeta {
     }
 
     pub fn update(&mut self, system: &System) {
-        self.selection.update(system, self.cutoff + self.thickness)
+        // an isolated system keeps every charged pair
+        let radius = if self.potential.is_periodic() {
+            self.cutoff + self.thickness
+        } else {
+            Float::INFINITY
+        };
+        self.selection.update(system, radius)
+    }
+
+    /// Returns the vector `r_i - r_j` between atoms `i` and `j`, as a minimum image if the potential is periodic.
+    pub fn separation(&self, system: &System, i: usize, j: usize) -> Vector3<Float> {
+        let mut r_ij = system.positions[i] - system.positions[j];
+        if self.potential.is_periodic() {
+            system.cell.vector_image(&mut r_ij);
+        }
+        r_ij
+    }
+
+    /// Returns `true` if a pair separated by a distance `r` interacts.
+    pub fn interacts(&self, r: Float) -> bool {
+        !self.potential.is_periodic() || r < self.cutoff
+    }
+
+    /// Returns the energy of every selected pair within the cutoff which involves atom `index`.
+    pub fn atom_energy(&self, system: &System, index: usize) -> Float {
+        let qi = system.species[index].charge();
+        self.selection
+            .indices()
+            .filter_map(|&[i, j]| match (i == index, j == index) {
+                (true, _) => Some(j),
+                (_, true) => Some(i),
+                _ => None,
+            })
+            .map(|j| {
+                let r = self.separation(system, index, j).norm();
+                if self.interacts(r) {
+                    self.potential.energy(qi, system.species[j].charge(), r)
+                } else {
+                    0.0
+                }
+            })
+            .sum()
+    }
+
+    /// Returns the virial tensor `sum(r_ij ⊗ f_ij)` of every selected pair within the cutoff.
+    pub fn virial(&self, system: &System) -> Matrix3<Float> {
+        self.selection
+            .indices()
+            .fold(Matrix3::zeros(), |accumulator, &[i, j]| {
+                let qi = system.species[i].charge();
+                let qj = system.species[j].charge();
+                let r_ij = self.separation(system, i, j);
+                let r = r_ij.norm();
+                if self.interacts(r) {
+                    let f_ij = -self.potential.force(qi, qj, r) * r_ij / r;
+                    accumulator + r_ij * f_ij.transpose()
+                } else {
+                    accumulator
+                }
+            })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{CoulombPotential, StandardCoulombic};
+    use super::{
+        CoulombPotential, DampedShiftedForce, DebyeHuckel, DirectCoulomb, StandardCoulombic,
+    };
+    use crate::internal::consts::coulomb;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::CoulombicEnergy;
+    use crate::properties::forces::CoulombicForces;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
     use approx::*;
+    use nalgebra::Vector3;
 
     #[test]
     fn standard_coulombic() {
@@ -135,4 +249,94 @@ mod tests {
         assert_relative_eq!(r2_energy, coulombic.energy(qi, qj, r2), epsilon = 1e-3);
         assert_relative_eq!(r2_force, coulombic.force(qi, qj, r2), epsilon = 1e-3);
     }
+
+    #[test]
+    fn damped_shifted_force() {
+        let alpha = 0.2;
+        let cutoff = 10.0;
+        let dsf = DampedShiftedForce::new(alpha, cutoff);
+        let qi = 1.0;
+        let qj = -1.0;
+
+        // energy and force vanish at the cutoff
+        assert_relative_eq!(dsf.energy(qi, qj, cutoff), 0.0, epsilon = 1e-3);
+        assert_relative_eq!(dsf.force(qi, qj, cutoff), 0.0, epsilon = 1e-3);
+
+        // force is the derivative of the energy
+        for &r in &[2.0, 4.0, 7.5] {
+            let h = 1e-2;
+            let derivative = (dsf.energy(qi, qj, r + h) - dsf.energy(qi, qj, r - h)) / (2.0 * h);
+            assert_relative_eq!(dsf.force(qi, qj, r), derivative, max_relative = 1e-2);
+        }
+    }
+
+    #[test]
+    fn debye_huckel() {
+        // 0.1 M monovalent salt in water at room temperature
+        let dielectric = 78.5;
+        let debye_huckel = DebyeHuckel::from_ionic_strength(0.1, dielectric, 298.0);
+        assert_relative_eq!(debye_huckel.bjerrum_length, 7.14, epsilon = 1e-2);
+        assert_relative_eq!(debye_huckel.debye_length, 9.6, epsilon = 0.1);
+
+        // screening makes the energy decay faster than bare Coulomb in the same solvent
+        let coulombic = StandardCoulombic::new(dielectric);
+        let qi = 1.0;
+        let qj = -1.0;
+        let mut previous = 1.0;
+        for &r in &[2.0, 5.0, 10.0, 20.0] {
+            let ratio = debye_huckel.energy(qi, qj, r) / coulombic.energy(qi, qj, r);
+            assert!(ratio < previous);
+            assert_relative_eq!(
+                ratio,
+                (-r / debye_huckel.debye_length).exp(),
+                epsilon = 1e-4
+            );
+            previous = ratio;
+        }
+
+        // force is the derivative of the energy
+        let r = 5.0;
+        let h = 1e-2;
+        let derivative =
+            (debye_huckel.energy(qi, qj, r + h) - debye_huckel.energy(qi, qj, r - h)) / (2.0 * h);
+        assert_relative_eq!(
+            debye_huckel.force(qi, qj, r),
+            derivative,
+            max_relative = 1e-2
+        );
+    }
+
+    #[test]
+    fn direct_coulomb() {
+        // a charged dimer further apart than half the cell
+        let (qi, qj) = (1.0, -2.0);
+        let r = 7.0;
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![
+                Species::from_element(Element::Na).with_charge(qi),
+                Species::from_element(Element::Cl).with_charge(qj),
+            ],
+            positions: vec![Vector3::new(1.0, 5.0, 5.0), Vector3::new(1.0 + r, 5.0, 5.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // neither the minimum image of 3 angstroms nor the cutoff apply
+        let mut potentials = PotentialsBuilder::new()
+            .coulomb(DirectCoulomb::new(1.0), 2.0, 0.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let energy = CoulombicEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(energy, coulomb() * qi * qj / r, max_relative = 1e-5);
+        // opposite charges attract along the bond
+        let forces = CoulombicForces.calculate(&system, &potentials);
+        let expected = coulomb() * qi * qj / r.powi(2);
+        assert_relative_eq!(forces[0][0], -expected, max_relative = 1e-5);
+        assert_relative_eq!((forces[0] + forces[1]).norm(), 0.0, epsilon = 1e-5);
+    }
 }
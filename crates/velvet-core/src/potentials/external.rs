@@ -0,0 +1,383 @@
+//! Potentials which act on each atom independently of its neighbors.
+
+use nalgebra::{Matrix3, Vector3};
+
+use crate::internal::Float;
+use crate::potentials::types::{
+    AngleRestraint, DistanceRestraint, HarmonicBond, PositionRestraint,
+};
+use crate::potentials::Potential;
+use crate::system::System;
+
+/// Shared behavior for potentials due to an external field or restraint.
+///
+/// Tethers to fixed positions are not balanced by an opposite force on another atom so they do not
+/// conserve momentum or contribute to the virial, while restraints on collective variables such as
+/// distances and angles do both.
+pub trait ExternalPotential: Potential {
+    /// Returns the potential energy of each atom.
+    fn energies(&self, system: &System) -> Vec<Float>;
+    /// Returns the force acting on each atom.
+    fn forces(&self, system: &System) -> Vec<Vector3<Float>>;
+    /// Returns the virial tensor `sum(r_ij ⊗ f_ij)` of the interactions between atoms.
+    ///
+    /// Potentials which only act on atoms independently of each other contribute nothing.
+    fn virial(&self, _system: &System) -> Matrix3<Float> {
+        Matrix3::zeros()
+    }
+    /// Returns the bonded interaction the potential is equivalent to, if any.
+    fn bonded_term(&self) -> Option<BondedTerm> {
+        None
+    }
+    /// Returns the topology of the system the potential acts on, if any.
+    ///
+    /// A potential which acts on `System.bonds`, `System.angles` or `System.dihedrals` silently does nothing
+    /// when they are empty, so [`Potentials::setup`](crate::potentials::Potentials::setup) reports an error.
+    fn requires_topology(&self) -> Option<BondedTerm> {
+        None
+    }
+}
+
+/// Bonded interactions which an [`ExternalPotential`] may stand in for when its energy is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BondedTerm {
+    /// Stretching of the distance between two atoms.
+    Bond,
+    /// Bending of the angle between three atoms.
+    Angle,
+    /// Torsion about the bond between the middle two of four atoms.
+    Dihedral,
+}
+
+impl ExternalPotential for AngleRestraint {
+    fn energies(&self, system: &System) -> Vec<Float> {
+        let theta = system.cell.angle(
+            &system.positions[self.i],
+            &system.positions[self.j],
+            &system.positions[self.k_atom],
+        );
+        // the energy is shared equally between the three atoms
+        let energy = self.k * (theta - self.theta0).powi(2) / 3.0;
+        let mut energies = vec![0 as Float; system.size];
+        energies[self.i] += energy;
+        energies[self.j] += energy;
+        energies[self.k_atom] += energy;
+        energies
+    }
+
+    fn forces(&self, system: &System) -> Vec<Vector3<Float>> {
+        let mut a = system.positions[self.i] - system.positions[self.j];
+        system.cell.vector_image(&mut a);
+        let mut b = system.positions[self.k_atom] - system.positions[self.j];
+        system.cell.vector_image(&mut b);
+        let (a_norm, b_norm) = (a.norm(), b.norm());
+        let (a_hat, b_hat) = (a / a_norm, b / b_norm);
+        let cos = a_hat.dot(&b_hat).clamp(-1.0, 1.0);
+        let theta = Float::acos(cos);
+        // the gradient of the angle diverges for collinear atoms
+        let sin = Float::sqrt(1.0 - cos * cos).max(1e-6);
+        let derivative = 2.0 * self.k * (theta - self.theta0);
+
+        // -dE/dr = dE/dtheta / sin(theta) * dcos(theta)/dr
+        let f_i = derivative / sin * (b_hat - cos * a_hat) / a_norm;
+        let f_k = derivative / sin * (a_hat - cos * b_hat) / b_norm;
+        let mut forces = vec![Vector3::zeros(); system.size];
+        forces[self.i] += f_i;
+        forces[self.k_atom] += f_k;
+        forces[self.j] -= f_i + f_k;
+        forces
+    }
+
+    fn virial(&self, system: &System) -> Matrix3<Float> {
+        // positions relative to the vertex atom, which feels the balancing force
+        let mut a = system.positions[self.i] - system.positions[self.j];
+        system.cell.vector_image(&mut a);
+        let mut b = system.positions[self.k_atom] - system.positions[self.j];
+        system.cell.vector_image(&mut b);
+        let forces = self.forces(system);
+        a * forces[self.i].transpose() + b * forces[self.k_atom].transpose()
+    }
+
+    fn bonded_term(&self) -> Option<BondedTerm> {
+        Some(BondedTerm::Angle)
+    }
+}
+
+impl ExternalPotential for DistanceRestraint {
+    fn energies(&self, system: &System) -> Vec<Float> {
+        let r = system
+            .cell
+            .distance(&system.positions[self.i], &system.positions[self.j]);
+        // the energy is shared equally between both atoms
+        let energy = 0.5 * self.k * (r - self.r0).powi(2);
+        let mut energies = vec![0 as Float; system.size];
+        energies[self.i] += energy;
+        energies[self.j] += energy;
+        energies
+    }
+
+    fn forces(&self, system: &System) -> Vec<Vector3<Float>> {
+        let mut r_ij = system.positions[self.i] - system.positions[self.j];
+        system.cell.vector_image(&mut r_ij);
+        let r = r_ij.norm();
+        let f_ij = -2.0 * self.k * (r - self.r0) * r_ij / r;
+        let mut forces = vec![Vector3::zeros(); system.size];
+        forces[self.i] += f_ij;
+        forces[self.j] -= f_ij;
+        forces
+    }
+
+    fn virial(&self, system: &System) -> Matrix3<Float> {
+        let mut r_ij = system.positions[self.i] - system.positions[self.j];
+        system.cell.vector_image(&mut r_ij);
+        let r = r_ij.norm();
+        let f_ij = -2.0 * self.k * (r - self.r0) * r_ij / r;
+        r_ij * f_ij.transpose()
+    }
+
+    fn bonded_term(&self) -> Option<BondedTerm> {
+        Some(BondedTerm::Bond)
+    }
+}
+
+impl ExternalPotential for HarmonicBond {
+    fn energies(&self, system: &System) -> Vec<Float> {
+        let mut energies = vec![0 as Float; system.size];
+        for (index, &[i, j]) in system.bonds.iter().enumerate() {
+            // the energy is shared equally between both atoms
+            let energy = 0.5 * self.k * (system.bond_length(index) - self.r0).powi(2);
+            energies[i] += energy;
+            energies[j] += energy;
+        }
+        energies
+    }
+
+    fn forces(&self, system: &System) -> Vec<Vector3<Float>> {
+        let mut forces = vec![Vector3::zeros(); system.size];
+        for &[i, j] in system.bonds.iter() {
+            let mut r_ij = system.positions[i] - system.positions[j];
+            system.cell.vector_image(&mut r_ij);
+            let r = r_ij.norm();
+            let f_ij = -2.0 * self.k * (r - self.r0) * r_ij / r;
+            forces[i] += f_ij;
+            forces[j] -= f_ij;
+        }
+        forces
+    }
+
+    fn virial(&self, system: &System) -> Matrix3<Float> {
+        system
+            .bonds
+            .iter()
+            .fold(Matrix3::zeros(), |accumulator, &[i, j]| {
+                let mut r_ij = system.positions[i] - system.positions[j];
+                system.cell.vector_image(&mut r_ij);
+                let r = r_ij.norm();
+                let f_ij = -2.0 * self.k * (r - self.r0) * r_ij / r;
+                accumulator + r_ij * f_ij.transpose()
+            })
+    }
+
+    fn bonded_term(&self) -> Option<BondedTerm> {
+        Some(BondedTerm::Bond)
+    }
+
+    fn requires_topology(&self) -> Option<BondedTerm> {
+        Some(BondedTerm::Bond)
+    }
+}
+
+impl PositionRestraint {
+    // Returns the minimum image vector from the reference to the current position of each restrained atom.
+    fn displacements<'a>(
+        &'a self,
+        system: &'a System,
+    ) -> impl Iterator<Item = (usize, Vector3<Float>)> + 'a {
+        self.references.iter().map(move |&(i, reference)| {
+            let mut displacement = system.positions[i] - reference;
+            system.cell.vector_image(&mut displacement);
+            (i, displacement)
+        })
+    }
+}
+
+impl ExternalPotential for PositionRestraint {
+    fn energies(&self, system: &System) -> Vec<Float> {
+        let mut energies = vec![0 as Float; system.size];
+        for (i, displacement) in self.displacements(system) {
+            energies[i] += self.k * displacement.norm_squared();
+        }
+        energies
+    }
+
+    fn forces(&self, system: &System) -> Vec<Vector3<Float>> {
+        let mut forces = vec![Vector3::zeros(); system.size];
+        for (i, displacement) in self.displacements(system) {
+            forces[i] -= 2.0 * self.k * displacement;
+        }
+        forces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExternalPotential;
+    use crate::internal::consts::PI;
+    use crate::internal::Float;
+    use crate::potentials::types::{
+        AngleRestraint, DistanceRestraint, HarmonicBond, PositionRestraint,
+    };
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::PotentialEnergy;
+    use crate::properties::forces::Forces;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn position_restraint() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(1.5, 2.0, 3.0),
+                Vector3::new(9.8, 5.0, 5.0),
+                Vector3::new(7.0, 7.0, 7.0),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // the second atom is tethered across the periodic boundary
+        let restraint = PositionRestraint::new(
+            5.0,
+            vec![
+                (0, Vector3::new(1.0, 2.0, 3.0)),
+                (1, Vector3::new(0.2, 5.0, 5.0)),
+            ],
+        );
+        let energies = restraint.energies(&system);
+        assert_relative_eq!(energies[0], 5.0 * 0.25, epsilon = 1e-5);
+        assert_relative_eq!(energies[1], 5.0 * 0.16, epsilon = 1e-4);
+        assert_eq!(energies[2], 0.0);
+
+        let mut potentials = PotentialsBuilder::new().external(restraint).build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let forces = Forces.calculate(&system, &potentials);
+        // each displaced atom is pulled back toward its reference
+        assert_relative_eq!(forces[0][0], -5.0, epsilon = 1e-5);
+        assert_relative_eq!(forces[1][0], 4.0, epsilon = 1e-4);
+        assert_relative_eq!(forces[0][1], 0.0);
+        // the unrestrained atom feels no force
+        assert_eq!(forces[2], Vector3::zeros());
+        let energy = PotentialEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(energy, 5.0 * (0.25 + 0.16), epsilon = 1e-4);
+    }
+
+    fn triatomic() -> System {
+        let argon = Species::from_element(Element::Ar);
+        System {
+            size: 4,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 4],
+            positions: vec![
+                Vector3::new(9.0, 5.0, 5.0),
+                Vector3::new(0.5, 5.2, 5.0),
+                Vector3::new(1.2, 6.9, 5.3),
+                Vector3::new(5.0, 5.0, 5.0),
+            ],
+            velocities: vec![Vector3::zeros(); 4],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+
+    // Checks that the forces sum to zero, leave the fourth atom untouched and match the energy gradient.
+    fn check_restraint<T: ExternalPotential>(restraint: &T, system: &System) {
+        let forces = restraint.forces(system);
+        let net: Vector3<Float> = forces.iter().sum();
+        assert_relative_eq!(net.norm(), 0.0, epsilon = 1e-4);
+        assert_eq!(forces[3], Vector3::zeros());
+
+        let energy = |system: &System| -> Float { restraint.energies(system).iter().sum() };
+        let mut displaced = system.clone();
+        let h = 1e-3;
+        for (i, force) in forces.iter().enumerate().take(3) {
+            for k in 0..3 {
+                displaced.positions[i][k] += h;
+                let forward = energy(&displaced);
+                displaced.positions[i][k] -= 2.0 * h;
+                let backward = energy(&displaced);
+                displaced.positions[i][k] += h;
+                let gradient = (forward - backward) / (2.0 * h);
+                assert_relative_eq!(force[k], -gradient, epsilon = 2e-2);
+            }
+        }
+    }
+
+    // Moves each atom a small step along its force.
+    fn relax<T: ExternalPotential>(restraint: &T, system: &System) -> System {
+        let mut relaxed = system.clone();
+        for (position, force) in relaxed.positions.iter_mut().zip(restraint.forces(system)) {
+            *position += 1e-3 * force;
+        }
+        relaxed
+    }
+
+    #[test]
+    fn distance_restraint() {
+        let system = triatomic();
+        // the first two atoms are bonded across the periodic boundary
+        let restraint = DistanceRestraint::new(0, 1, 10.0, 2.0);
+        let distance = |system: &System| {
+            system
+                .cell
+                .distance(&system.positions[0], &system.positions[1])
+        };
+        assert!(distance(&system) < 2.0);
+        check_restraint(&restraint, &system);
+        let relaxed = relax(&restraint, &system);
+        assert!(distance(&relaxed) > distance(&system));
+        assert!(distance(&relaxed) < 2.0);
+    }
+
+    #[test]
+    fn angle_restraint() {
+        let system = triatomic();
+        let restraint = AngleRestraint::new(0, 1, 2, 10.0, PI / 2.0);
+        let angle = |system: &System| {
+            system.cell.angle(
+                &system.positions[0],
+                &system.positions[1],
+                &system.positions[2],
+            )
+        };
+        assert!(angle(&system) > PI / 2.0);
+        check_restraint(&restraint, &system);
+        let relaxed = relax(&restraint, &system);
+        assert!(angle(&relaxed) < angle(&system));
+        assert!(angle(&relaxed) > PI / 2.0);
+    }
+
+    #[test]
+    fn harmonic_bond() {
+        let mut system = triatomic();
+        system.bonds = vec![[0, 1], [1, 2]];
+        let bond = HarmonicBond::new(10.0, 1.5);
+        check_restraint(&bond, &system);
+        // a single bond matches the equivalent distance restraint
+        let restraint = DistanceRestraint::new(0, 1, 10.0, 1.5);
+        system.bonds = vec![[0, 1]];
+        assert_eq!(bond.energies(&system), restraint.energies(&system));
+        assert_eq!(bond.forces(&system), restraint.forces(&system));
+    }
+}
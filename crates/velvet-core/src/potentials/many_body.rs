@@ -0,0 +1,261 @@
+//! Potentials whose energy depends on the environment of each atom.
+
+use nalgebra::{Matrix3, Vector3};
+
+use crate::internal::Float;
+use crate::potentials::pair::PairPotential;
+use crate::potentials::types::Eam;
+use crate::potentials::Potential;
+use crate::selection::{setup_all_pairs, update_pairs_by_cutoff_radius, Selection};
+use crate::system::System;
+
+/// Pair of neighboring atoms within the cutoff of a many-body potential.
+#[derive(Clone, Copy, Debug)]
+pub struct NeighborPair {
+    /// Indices of atoms `i` and `j`.
+    pub indices: [usize; 2],
+    /// Minimum image vector pointing from atom `j` to atom `i`.
+    pub r_ij: Vector3<Float>,
+    /// Length of `r_ij`.
+    pub r: Float,
+}
+
+/// Shared behavior for many-body potentials.
+///
+/// Forces are decomposed into contributions between pairs of neighbors
+/// so the virial is defined in the same way as for pair potentials.
+pub trait ManyBodyPotential: Potential {
+    /// Returns the potential energy of each atom given every pair of neighbors within the cutoff.
+    fn energies(&self, system: &System, pairs: &[NeighborPair]) -> Vec<Float>;
+    /// Returns the force `f_ij` acting on atom `i` due to atom `j` for each pair of neighbors.
+    ///
+    /// Atom `j` feels the opposite force.
+    fn pair_forces(&self, system: &System, pairs: &[NeighborPair]) -> Vec<Vector3<Float>>;
+}
+
+impl Eam {
+    // Returns the index of each atom's species in the potential.
+    fn species_indices(&self, system: &System) -> Vec<Option<usize>> {
+        system
+            .species
+            .iter()
+            .map(|species| self.species.iter().position(|s| s == species))
+            .collect()
+    }
+
+    // Returns the electron density at each atom.
+    fn densities(&self, pairs: &[NeighborPair], indices: &[Option<usize>]) -> Vec<Float> {
+        let mut densities = vec![0 as Float; indices.len()];
+        for pair in pairs {
+            let [i, j] = pair.indices;
+            if let (Some(a), Some(b)) = (indices[i], indices[j]) {
+                densities[i] += self.density[b].energy(pair.r);
+                densities[j] += self.density[a].energy(pair.r);
+            }
+        }
+        densities
+    }
+}
+
+impl ManyBodyPotential for Eam {
+    fn energies(&self, system: &System, pairs: &[NeighborPair]) -> Vec<Float> {
+        let indices = self.species_indices(system);
+        let densities = self.densities(pairs, &indices);
+        let mut energies: Vec<Float> = indices
+            .iter()
+            .zip(densities.iter())
+            .map(|(index, &density)| match index {
+                Some(a) => self.embedding[*a].energy(density),
+                None => 0.0,
+            })
+            .collect();
+        for pair in pairs {
+            let [i, j] = pair.indices;
+            if let (Some(a), Some(b)) = (indices[i], indices[j]) {
+                let energy = self.pair_function(a, b).energy(pair.r);
+                energies[i] += 0.5 * energy;
+                energies[j] += 0.5 * energy;
+            }
+        }
+        energies
+    }
+
+    fn pair_forces(&self, system: &System, pairs: &[NeighborPair]) -> Vec<Vector3<Float>> {
+        let indices = self.species_indices(system);
+        let densities = self.densities(pairs, &indices);
+        // derivative of the embedding energy with respect to the density at each atom
+        let slopes: Vec<Float> = indices
+            .iter()
+            .zip(densities.iter())
+            .map(|(index, &density)| match index {
+                Some(a) => self.embedding[*a].force(density),
+                None => 0.0,
+            })
+            .collect();
+        pairs
+            .iter()
+            .map(|pair| {
+                let [i, j] = pair.indices;
+                match (indices[i], indices[j]) {
+                    (Some(a), Some(b)) => {
+                        let derivative = self.pair_function(a, b).force(pair.r)
+                            + slopes[i] * self.density[b].force(pair.r)
+                            + slopes[j] * self.density[a].force(pair.r);
+                        -derivative * pair.r_ij / pair.r
+                    }
+                    _ => Vector3::zeros(),
+                }
+            })
+            .collect()
+    }
+}
+
+type ManyBodySetupFn = fn(&System, ()) -> Vec<[usize; 2]>;
+
+type ManyBodyUpdateFn = fn(&System, &[[usize; 2]], Float) -> Vec<[usize; 2]>;
+
+type ManyBodySelection = Selection<ManyBodySetupFn, (), ManyBodyUpdateFn, Float, 2>;
+
+pub(crate) struct ManyBodyPotentialMeta {
+    pub potential: Box<dyn ManyBodyPotential>,
+    pub cutoff: Float,
+    pub thickness: Float,
+    pub selection: ManyBodySelection,
+}
+
+impl ManyBodyPotentialMeta {
+    pub fn new<T>(potential: T, cutoff: Float, thickness: Float) -> ManyBodyPotentialMeta
+    where
+        T: ManyBodyPotential + 'static,
+    {
+        let selection = Selection::new(
+            setup_all_pairs as ManyBodySetupFn,
+            update_pairs_by_cutoff_radius as ManyBodyUpdateFn,
+        );
+        ManyBodyPotentialMeta {
+            potential: Box::new(potential),
+            cutoff,
+            thickness,
+            selection,
+        }
+    }
+
+    pub fn setup(&mut self, system: &System) {
+        self.selection.setup(system, ())
+    }
+
+    pub fn update(&mut self, system: &System) {
+        self.selection.update(system, self.cutoff + self.thickness)
+    }
+
+    /// Returns every selected pair within the cutoff.
+    pub fn pairs(&self, system: &System) -> Vec<NeighborPair> {
+        self.selection
+            .indices()
+            .filter_map(|&[i, j]| {
+                let mut r_ij = system.positions[i] - system.positions[j];
+                system.cell.vector_image(&mut r_ij);
+                let r = r_ij.norm();
+                if r < self.cutoff {
+                    Some(NeighborPair {
+                        indices: [i, j],
+                        r_ij,
+                        r,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the virial tensor `sum(r_ij ⊗ f_ij)` of every selected pair within the cutoff.
+    pub fn virial(&self, system: &System) -> Matrix3<Float> {
+        let pairs = self.pairs(system);
+        let forces = self.potential.pair_forces(system, &pairs);
+        pairs
+            .iter()
+            .zip(forces.iter())
+            .fold(Matrix3::zeros(), |accumulator, (pair, f_ij)| {
+                accumulator + pair.r_ij * f_ij.transpose()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ManyBodyPotentialMeta;
+    use crate::internal::Float;
+    use crate::potentials::types::{Eam, Interpolation, Tabulated};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    fn table<F: Fn(Float) -> Float>(start: Float, stop: Float, function: F) -> Tabulated {
+        let count = 400;
+        let x: Vec<Float> = (0..count)
+            .map(|k| start + (stop - start) * k as Float / (count - 1) as Float)
+            .collect();
+        let y = x.iter().map(|&x| function(x)).collect();
+        Tabulated::from_columns(x, y, Interpolation::CubicSpline)
+    }
+
+    #[test]
+    fn eam_forces_match_energy_gradient() {
+        let copper = Species::from_element(Element::Cu);
+        let eam = Eam::new(
+            vec![copper],
+            vec![table(0.0, 5.0, |rho| -2.0 * Float::sqrt(rho + 0.01))],
+            vec![table(1.0, 6.0, |r| Float::exp(-(r - 2.5)))],
+            vec![table(1.0, 6.0, |r| 0.5 * Float::exp(-2.0 * (r - 2.5)))],
+        );
+        let mut system = System {
+            size: 3,
+            cell: Cell::cubic(20.0),
+            species: vec![copper; 3],
+            positions: vec![
+                Vector3::new(5.0, 5.0, 5.0),
+                Vector3::new(7.4, 5.3, 5.0),
+                Vector3::new(5.6, 7.2, 5.4),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut meta = ManyBodyPotentialMeta::new(eam, 5.0, 0.0);
+        meta.setup(&system);
+        meta.update(&system);
+        let energy = |system: &System| -> Float {
+            meta.potential
+                .energies(system, &meta.pairs(system))
+                .iter()
+                .sum()
+        };
+
+        // force on the first atom from a central difference of the total energy
+        let pairs = meta.pairs(&system);
+        let pair_forces = meta.potential.pair_forces(&system, &pairs);
+        let mut force = Vector3::zeros();
+        for (pair, f_ij) in pairs.iter().zip(pair_forces.iter()) {
+            if pair.indices[0] == 0 {
+                force += f_ij;
+            } else if pair.indices[1] == 0 {
+                force -= f_ij;
+            }
+        }
+        let h = 1e-3;
+        for k in 0..3 {
+            system.positions[0][k] += h;
+            let forward = energy(&system);
+            system.positions[0][k] -= 2.0 * h;
+            let backward = energy(&system);
+            system.positions[0][k] += h;
+            let gradient = (forward - backward) / (2.0 * h);
+            assert_relative_eq!(force[k], -gradient, epsilon = 1e-3);
+        }
+    }
+}
@@ -1,69 +1,347 @@
 //! Classical interatomic potentials.
 
 pub mod coulomb;
+pub mod external;
+pub mod many_body;
 pub mod pair;
 pub mod types;
 
+use nalgebra::Matrix3;
+
 use crate::internal::Float;
+use crate::neighbors::NeighborStats;
 use crate::potentials::coulomb::{CoulombPotential, CoulombPotentialMeta};
+use crate::potentials::external::{BondedTerm, ExternalPotential};
+use crate::potentials::many_body::{ManyBodyPotential, ManyBodyPotentialMeta};
 use crate::potentials::pair::{PairPotential, PairPotentialMeta};
+use crate::selection::{
+    setup_all_pairs, setup_all_pairs_with_self, update_pairs_by_cutoff_radius, Selection,
+};
+use crate::system::cell::Cell;
 use crate::system::species::Species;
 use crate::system::System;
 
 /// Base trait for all potentials.
 pub trait Potential: Send + Sync {}
 
+// Largest net charge of a system which periodic electrostatics treat as neutral.
+const NEUTRALITY_TOLERANCE: Float = 1e-4;
+
+type NeighborSetupFn = fn(&System, ()) -> Vec<[usize; 2]>;
+
+type NeighborUpdateFn = fn(&System, &[[usize; 2]], Float) -> Vec<[usize; 2]>;
+
+type NeighborSelection = Selection<NeighborSetupFn, (), NeighborUpdateFn, Float, 2>;
+
+/// Collection of all potentials applied to a system.
 pub struct Potentials {
     pub(crate) coulomb_meta: Option<CoulombPotentialMeta>,
+    pub(crate) external_potentials: Vec<Box<dyn ExternalPotential>>,
+    pub(crate) many_body_metas: Vec<ManyBodyPotentialMeta>,
     pub(crate) pair_metas: Vec<PairPotentialMeta>,
     pub(crate) update_frequency: usize,
+    // relative tolerance of the mixing rule check at setup
+    mixing_tolerance: Option<Float>,
+    // pair potentials interact with every periodic image within their cutoff
+    periodic_images: bool,
+    // master neighbor list from which each pair potential selects its species
+    neighbors: NeighborSelection,
 }
 
 impl Potentials {
-    pub fn setup(&mut self, system: &System) {
+    /// Prepares each potential to run on the given system.
+    ///
+    /// Returns an error if any cutoff is too large for the system's cell as reported by [`validate`](Potentials::validate),
+    /// if the system lacks topology required by a potential as reported by [`check_topology`](Potentials::check_topology),
+    /// if the system is charged as reported by [`check_neutrality`](Potentials::check_neutrality)
+    /// or if a cross term breaks the mixing rules as reported by [`check_mixing`](Potentials::check_mixing)
+    /// when a [mixing tolerance](PotentialsBuilder::mixing_tolerance) is set.
+    pub fn setup(&mut self, system: &System) -> Result<(), String> {
+        self.validate(&system.cell)?;
+        self.check_topology(system)?;
+        self.check_neutrality(system)?;
+        if let Some(tolerance) = self.mixing_tolerance {
+            self.check_mixing(tolerance)?;
+        }
         // setup coulomb potential if it exists
-        match &mut self.coulomb_meta {
-            Some(meta) => meta.setup(system),
-            None => {}
+        if let Some(meta) = &mut self.coulomb_meta {
+            meta.setup(system)
         }
-        // setup each pair potential
-        self.pair_metas
+        // setup each many-body potential
+        self.many_body_metas
             .iter_mut()
-            .for_each(|meta| meta.setup(system))
+            .for_each(|meta| meta.setup(system));
+        // setup the neighbor list shared by the pair potentials
+        if !self.pair_metas.is_empty() {
+            self.neighbors.setup(system, ())
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if no potentials were added.
+    pub fn is_empty(&self) -> bool {
+        self.coulomb_meta.is_none()
+            && self.external_potentials.is_empty()
+            && self.many_body_metas.is_empty()
+            && self.pair_metas.is_empty()
+    }
+
+    // Returns the largest search radius of any pair potential.
+    fn neighbor_radius(&self) -> Float {
+        self.pair_metas
+            .iter()
+            .map(|meta| meta.cutoff + meta.thickness)
+            .fold(0.0, Float::max)
+    }
+
+    /// Checks that every cutoff radius fits within the minimum image radius of a cell.
+    ///
+    /// A larger cutoff would need several periodic images of the same atom, which the minimum image
+    /// convention cannot provide, so interactions would be silently missed.
+    /// The neighbor list skin is not included because pairs are always selected by their minimum image distance.
+    /// Pair potentials are not limited when they include [periodic images](PotentialsBuilder::periodic_images).
+    pub fn validate(&self, cell: &Cell) -> Result<(), String> {
+        let radius = cell.minimum_image_radius();
+        let cutoffs = self
+            .coulomb_meta
+            .iter()
+            .filter(|meta| meta.potential.is_periodic())
+            .map(|meta| ("coulomb", meta.cutoff))
+            .chain(
+                self.many_body_metas
+                    .iter()
+                    .map(|meta| ("many-body", meta.cutoff)),
+            )
+            .chain(
+                self.pair_metas
+                    .iter()
+                    .filter(|_| !self.periodic_images)
+                    .map(|meta| ("pair", meta.cutoff)),
+            );
+        for (kind, cutoff) in cutoffs {
+            if cutoff > radius {
+                return Err(format!(
+                    "{} potential cutoff of {} exceeds half of the smallest cell width ({}) so the minimum image convention would miss interactions",
+                    kind, cutoff, radius
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the system has the bonds, angles or dihedrals which each external potential acts on.
+    ///
+    /// A potential which [requires topology](ExternalPotential::requires_topology) that the system lacks
+    /// would otherwise silently contribute nothing.
+    pub fn check_topology(&self, system: &System) -> Result<(), String> {
+        for (index, potential) in self.external_potentials.iter().enumerate() {
+            let (name, count) = match potential.requires_topology() {
+                Some(BondedTerm::Bond) => ("bonds", system.bonds.len()),
+                Some(BondedTerm::Angle) => ("angles", system.angles.len()),
+                Some(BondedTerm::Dihedral) => ("dihedrals", system.dihedrals.len()),
+                None => continue,
+            };
+            if count == 0 {
+                return Err(format!(
+                    "external potential {} acts on the {} of the system but it has none so the potential would do nothing",
+                    index, name
+                ));
+            }
+        }
+        Ok(())
     }
 
+    /// Checks that a system with a periodic Coulomb potential has no net charge.
+    ///
+    /// Periodic electrostatics assume a neutral cell, so the energy of a charged one is wrong unless
+    /// it is offset by a uniform neutralizing background.
+    pub fn check_neutrality(&self, system: &System) -> Result<(), String> {
+        let periodic = self
+            .coulomb_meta
+            .as_ref()
+            .is_some_and(|meta| meta.potential.is_periodic());
+        let net_charge = system.net_charge();
+        if periodic && net_charge.abs() > NEUTRALITY_TOLERANCE {
+            return Err(format!(
+                "system has a net charge of {} so periodic electrostatics need a neutralizing background",
+                net_charge
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the cross terms of Lennard-Jones and Mie pair potentials against the Lorentz-Berthelot mixing rules.
+    ///
+    /// A cross term is only checked if both of its species have a same-species potential with an `epsilon` and `sigma`.
+    /// Its parameters must be within a relative `tolerance` of `epsilon_ij = sqrt(epsilon_i * epsilon_j)`
+    /// and `sigma_ij = (sigma_i + sigma_j) / 2`.
+    pub fn check_mixing(&self, tolerance: Float) -> Result<(), String> {
+        let lennard_jones = |meta: &PairPotentialMeta| {
+            let parameters = meta.potential.parameters();
+            Some((*parameters.get("epsilon")?, *parameters.get("sigma")?))
+        };
+        let same_species: Vec<(Species, (Float, Float))> = self
+            .pair_metas
+            .iter()
+            .filter(|meta| meta.species.0 == meta.species.1)
+            .filter_map(|meta| Some((meta.species.0, lennard_jones(meta)?)))
+            .collect();
+        let find = |species: Species| {
+            same_species
+                .iter()
+                .find(|(other, _)| *other == species)
+                .map(|&(_, parameters)| parameters)
+        };
+        for (index, meta) in self.pair_metas.iter().enumerate() {
+            if meta.species.0 == meta.species.1 {
+                continue;
+            }
+            let parameters = (
+                lennard_jones(meta),
+                find(meta.species.0),
+                find(meta.species.1),
+            );
+            let ((epsilon, sigma), (epsilon_i, sigma_i), (epsilon_j, sigma_j)) = match parameters {
+                (Some(ij), Some(i), Some(j)) => (ij, i, j),
+                _ => continue,
+            };
+            let mixed = [
+                ("epsilon", epsilon, Float::sqrt(epsilon_i * epsilon_j)),
+                ("sigma", sigma, (sigma_i + sigma_j) / 2.0),
+            ];
+            for &(name, value, expected) in mixed.iter() {
+                if (value - expected).abs() > tolerance * expected.abs() {
+                    return Err(format!(
+                        "{} of {} in cross term pair potential {} deviates from the Lorentz-Berthelot value of {}",
+                        name, value, index, expected
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the selection of each potential if the update frequency is reached.
     pub fn update(&mut self, system: &System, iteration: usize) {
         // only update if the update frequency is reached
-        if iteration % self.update_frequency != 0 {
+        if !iteration.is_multiple_of(self.update_frequency) {
             return;
         }
         // update coulomb potential if it exists
-        match &mut self.coulomb_meta {
-            Some(meta) => meta.update(system),
-            None => {}
+        if let Some(meta) = &mut self.coulomb_meta {
+            meta.update(system)
         }
-        // update each pair potential
-        self.pair_metas
+        // update each many-body potential
+        self.many_body_metas
             .iter_mut()
-            .for_each(|meta| meta.update(system))
+            .for_each(|meta| meta.update(system));
+        // build the shared neighbor list once and let each pair potential draw from it
+        if !self.pair_metas.is_empty() {
+            self.neighbors.update(system, self.neighbor_radius());
+            let neighbors = self.neighbors.current_indices();
+            self.pair_metas
+                .iter_mut()
+                .for_each(|meta| meta.update(system, neighbors))
+        }
+    }
+
+    /// Returns the statistics of the neighbor list of each pair potential in the order they were added.
+    pub fn neighbor_stats(&self) -> Vec<NeighborStats> {
+        self.pair_metas
+            .iter()
+            .map(|meta| meta.neighbors.stats())
+            .collect()
+    }
+
+    /// Returns the energy of every pair and Coulomb interaction involving atom `index`.
+    ///
+    /// Only the neighbors of the atom are visited so the energy change of a single atom move,
+    /// as in Metropolis Monte Carlo, costs far less than the [`PotentialEnergy`](crate::properties::energy::PotentialEnergy)
+    /// of the whole system. Each interaction is counted in full, so the sum over all atoms is twice the pairwise energy.
+    /// Many-body and external potentials do not split into pairs so they do not contribute.
+    /// Moves larger than the neighbor list thickness require an [`update`](Potentials::update) first.
+    pub fn atom_energy(&self, system: &System, index: usize) -> Float {
+        let coulomb = match &self.coulomb_meta {
+            Some(meta) => meta.atom_energy(system, index),
+            None => 0.0,
+        };
+        coulomb
+            + self
+                .pair_metas
+                .iter()
+                .map(|meta| meta.atom_energy(system, index))
+                .sum::<Float>()
+    }
+
+    // Returns the thinnest neighbor list skin of any potential, or `None` if no potential keeps a neighbor list.
+    pub(crate) fn min_thickness(&self) -> Option<Float> {
+        let coulomb = self
+            .coulomb_meta
+            .iter()
+            .filter(|meta| meta.potential.is_periodic())
+            .map(|meta| meta.thickness);
+        let many_body = self.many_body_metas.iter().map(|meta| meta.thickness);
+        let pair = self.pair_metas.iter().map(|meta| meta.thickness);
+        coulomb.chain(many_body).chain(pair).reduce(Float::min)
+    }
+
+    /// Returns the virial tensor `sum(r_ij ⊗ f_ij)` contributed by every potential.
+    ///
+    /// External potentials contribute the [virial](ExternalPotential::virial) of their interactions between atoms.
+    pub fn virial(&self, system: &System) -> Matrix3<Float> {
+        let coulomb = match &self.coulomb_meta {
+            Some(meta) => meta.virial(system),
+            None => Matrix3::zeros(),
+        };
+        let many_body = self
+            .many_body_metas
+            .iter()
+            .fold(coulomb, |accumulator, meta| {
+                accumulator + meta.virial(system)
+            });
+        let external = self
+            .external_potentials
+            .iter()
+            .fold(many_body, |accumulator, potential| {
+                accumulator + potential.virial(system)
+            });
+        self.pair_metas.iter().fold(external, |accumulator, meta| {
+            accumulator + meta.virial(system)
+        })
     }
 }
 
+/// Constructor for the [`Potentials`] type.
 pub struct PotentialsBuilder {
     coulomb_meta: Option<CoulombPotentialMeta>,
+    external_potentials: Vec<Box<dyn ExternalPotential>>,
+    many_body_metas: Vec<ManyBodyPotentialMeta>,
     pair_metas: Vec<PairPotentialMeta>,
     update_frequency: usize,
+    default_cutoff: Option<Float>,
+    // indices of the pair potentials which use the default cutoff
+    defaulted_pairs: Vec<usize>,
+    mixing_tolerance: Option<Float>,
+    periodic_images: bool,
 }
 
 impl PotentialsBuilder {
+    /// Returns a new `PotentialsBuilder`.
     pub fn new() -> PotentialsBuilder {
         PotentialsBuilder {
             coulomb_meta: None,
+            external_potentials: Vec::new(),
+            many_body_metas: Vec::new(),
             pair_metas: Vec::new(),
             update_frequency: 1,
+            default_cutoff: None,
+            defaulted_pairs: Vec::new(),
+            mixing_tolerance: None,
+            periodic_images: false,
         }
     }
 
+    /// Adds a Coulombic potential to evaluate between all charged atoms.
     pub fn coulomb<T>(mut self, potential: T, cutoff: Float, thickness: Float) -> PotentialsBuilder
     where
         T: CoulombPotential + 'static,
@@ -72,6 +350,28 @@ impl PotentialsBuilder {
         self
     }
 
+    /// Adds an external potential to evaluate on each atom.
+    pub fn external<T: ExternalPotential + 'static>(mut self, potential: T) -> PotentialsBuilder {
+        self.external_potentials.push(Box::new(potential));
+        self
+    }
+
+    /// Adds a many-body potential to evaluate between all atoms.
+    pub fn many_body<T>(
+        mut self,
+        potential: T,
+        cutoff: Float,
+        thickness: Float,
+    ) -> PotentialsBuilder
+    where
+        T: ManyBodyPotential + 'static,
+    {
+        self.many_body_metas
+            .push(ManyBodyPotentialMeta::new(potential, cutoff, thickness));
+        self
+    }
+
+    /// Adds a pair potential to evaluate between atoms of the given species.
     pub fn pair<T>(
         mut self,
         potential: T,
@@ -83,24 +383,508 @@ impl PotentialsBuilder {
         T: PairPotential + 'static,
     {
         self.pair_metas.push(PairPotentialMeta::new(
-            potential,
-            species,
-            cutoff,
-            thickness,
+            potential, species, cutoff, thickness,
         ));
         self
     }
 
+    /// Adds a pair potential which uses the default cutoff radius.
+    pub fn pair_with_default_cutoff<T>(
+        mut self,
+        potential: T,
+        species: (Species, Species),
+        thickness: Float,
+    ) -> PotentialsBuilder
+    where
+        T: PairPotential + 'static,
+    {
+        // the cutoff is resolved when the potentials are built
+        self.defaulted_pairs.push(self.pair_metas.len());
+        self.pair(potential, species, 0.0, thickness)
+    }
+
+    /// Sets the cutoff radius of pair potentials added without an explicit cutoff.
+    pub fn default_cutoff(mut self, cutoff: Float) -> PotentialsBuilder {
+        self.default_cutoff = Some(cutoff);
+        self
+    }
+
+    /// Checks the cross terms against the Lorentz-Berthelot mixing rules when the potentials are set up.
+    ///
+    /// Cross terms which deviate by more than the relative `tolerance` make [`Potentials::setup`] return an error.
+    /// See [`Potentials::check_mixing`] for the terms which are checked.
+    pub fn mixing_tolerance(mut self, tolerance: Float) -> PotentialsBuilder {
+        self.mixing_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Evaluates the pair potentials between every periodic image within their cutoff, including the images of an atom itself.
+    ///
+    /// This lifts the limit of half of the cell width on the pair cutoffs for very small cells
+    /// at the cost of searching every image and of the SIMD force kernels.
+    /// Coulomb and many-body potentials still follow the minimum image convention.
+    pub fn periodic_images(mut self) -> PotentialsBuilder {
+        self.periodic_images = true;
+        self
+    }
+
+    /// Sets the number of iterations between selection updates.
     pub fn update_frequency(mut self, freq: usize) -> PotentialsBuilder {
         self.update_frequency = freq;
         self
     }
 
-    pub fn build(self) -> Potentials {
+    /// Returns an initialized [`Potentials`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pair potential was added without a cutoff and no default cutoff is set.
+    pub fn build(mut self) -> Potentials {
+        for &index in &self.defaulted_pairs {
+            self.pair_metas[index].cutoff = self
+                .default_cutoff
+                .expect("pair potential added without a cutoff requires a default cutoff");
+        }
+        for meta in &mut self.pair_metas {
+            meta.periodic_images = self.periodic_images;
+        }
+        let setup_pairs = if self.periodic_images {
+            setup_all_pairs_with_self
+        } else {
+            setup_all_pairs
+        };
         Potentials {
             coulomb_meta: self.coulomb_meta,
+            external_potentials: self.external_potentials,
+            many_body_metas: self.many_body_metas,
             pair_metas: self.pair_metas,
             update_frequency: self.update_frequency,
+            mixing_tolerance: self.mixing_tolerance,
+            periodic_images: self.periodic_images,
+            neighbors: Selection::new(
+                setup_pairs as NeighborSetupFn,
+                update_pairs_by_cutoff_radius as NeighborUpdateFn,
+            ),
+        }
+    }
+}
+
+impl Default for PotentialsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PotentialsBuilder;
+    use crate::internal::consts::PI;
+    use crate::internal::Float;
+    use crate::potentials::external::ExternalPotential;
+    use crate::potentials::types::{
+        DampedShiftedForce, DirectCoulomb, Harmonic, HarmonicBond, LennardJones,
+    };
+    use crate::properties::energy::{PairEnergy, PerAtomEnergy, PotentialEnergy};
+    use crate::properties::forces::Forces;
+    use crate::properties::Property;
+    use crate::selection::{setup_pairs_by_species, update_pairs_by_cutoff_radius, Selection};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn default_cutoff() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let lj = LennardJones::new(4.184, 3.4);
+        let potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .pair_with_default_cutoff(lj, (argon, xenon), 1.0)
+            .default_cutoff(10.0)
+            .build();
+        assert_relative_eq!(potentials.pair_metas[0].cutoff, 8.5);
+        assert_relative_eq!(potentials.pair_metas[1].cutoff, 10.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_default_cutoff() {
+        let argon = Species::from_element(Element::Ar);
+        let lj = LennardJones::new(4.184, 3.4);
+        PotentialsBuilder::new()
+            .pair_with_default_cutoff(lj, (argon, argon), 1.0)
+            .build();
+    }
+
+    #[test]
+    fn validate_cutoff() {
+        let argon = Species::from_element(Element::Ar);
+        let lj = LennardJones::new(4.184, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .build();
+        assert!(potentials.validate(&Cell::cubic(20.0)).is_ok());
+        let message = potentials.validate(&Cell::cubic(12.0)).unwrap_err();
+        assert!(message.contains("cutoff of 8.5"));
+
+        // setup reports the oversized cutoff instead of panicking
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(12.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(1.0, 1.0, 1.0), Vector3::new(5.0, 1.0, 1.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        assert_eq!(potentials.setup(&system).unwrap_err(), message);
+    }
+
+    #[test]
+    fn shared_neighbor_list() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        // scatter atoms of alternating species through the cell
+        let size = 200;
+        let positions = (0..size)
+            .map(|i| {
+                let i = i as Float;
+                Vector3::new(i * 3.7 % 25.0, i * 7.3 % 25.0, i * 11.9 % 25.0)
+            })
+            .collect();
+        let species = (0..size)
+            .map(|i| if i % 3 == 0 { xenon } else { argon })
+            .collect();
+        let system = System {
+            size,
+            cell: Cell::cubic(25.0),
+            species,
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let lj = LennardJones::new(4.184, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .pair(lj, (xenon, xenon), 10.0, 1.5)
+            .pair(lj, (xenon, argon), 9.0, 0.5)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // each potential selects exactly the pairs it would have found on its own
+        for meta in &potentials.pair_metas {
+            let mut selection =
+                Selection::new(setup_pairs_by_species, update_pairs_by_cutoff_radius);
+            selection.setup(&system, meta.species);
+            selection.update(&system, meta.cutoff + meta.thickness);
+            assert!(!selection.current_indices().is_empty());
+            // the neighbor list keeps each pair in the row of its first atom
+            let mut expected = selection.current_indices().to_vec();
+            expected.sort_unstable();
+            let mut pairs: Vec<[usize; 2]> = meta.neighbors.pairs().collect();
+            pairs.sort_unstable();
+            assert_eq!(pairs, expected);
         }
     }
+
+    #[test]
+    fn periodic_images() {
+        let argon = Species::from_element(Element::Ar);
+        let a = 5.26;
+        // a single unit cell is far narrower than twice the cutoff
+        let mut unit = System::fcc_lattice(argon, a, 1);
+        unit.positions[0] += Vector3::new(0.1, -0.05, 0.02);
+        // the same crystal replicated until the minimum image convention holds
+        let n = 4;
+        let mut positions = Vec::new();
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    let shift = a * Vector3::new(x as Float, y as Float, z as Float);
+                    positions.extend(unit.positions.iter().map(|position| position + shift));
+                }
+            }
+        }
+        let size = positions.len();
+        let supercell = System {
+            size,
+            cell: Cell::cubic(a * n as Float),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+
+        let lj = LennardJones::new(0.238, 3.4);
+        let mut minimum_image = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .build();
+        assert!(minimum_image.setup(&unit).is_err());
+        minimum_image.setup(&supercell).unwrap();
+        minimum_image.update(&supercell, 0);
+        let mut images = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .periodic_images()
+            .build();
+        images.setup(&unit).unwrap();
+        images.update(&unit, 0);
+
+        // the unit cell with its images behaves as one copy of the supercell
+        let copies = size as Float / unit.size as Float;
+        let energy = PotentialEnergy.calculate(&unit, &images);
+        let expected = PotentialEnergy.calculate(&supercell, &minimum_image) / copies;
+        assert_relative_eq!(energy, expected, max_relative = 1e-4);
+        let per_atom = PerAtomEnergy.calculate(&unit, &images);
+        assert_relative_eq!(per_atom.iter().sum::<Float>(), energy, max_relative = 1e-4);
+        let atom_energy = images.atom_energy(&unit, 0);
+        let expected = minimum_image.atom_energy(&supercell, 0);
+        assert_relative_eq!(atom_energy, expected, max_relative = 1e-4);
+        let forces = Forces.calculate(&unit, &images);
+        let expected = Forces.calculate(&supercell, &minimum_image);
+        for (force, expected) in forces.iter().zip(&expected) {
+            assert_relative_eq!(force, expected, epsilon = 1e-4);
+        }
+        let virial = images.virial(&unit);
+        let expected = minimum_image.virial(&supercell) / copies;
+        assert_relative_eq!(virial, expected, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn atom_energy() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let size = 200;
+        let positions = (0..size)
+            .map(|i| {
+                let i = i as Float;
+                Vector3::new(i * 3.7 % 25.0, i * 7.3 % 25.0, i * 11.9 % 25.0)
+            })
+            .collect();
+        let species = (0..size)
+            .map(|i| if i % 3 == 0 { xenon } else { argon })
+            .collect();
+        let system = System {
+            size,
+            cell: Cell::cubic(25.0),
+            species,
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let lj = LennardJones::new(0.238, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .pair(Harmonic::new(0.1, 5.0), (xenon, argon), 6.0, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // every pair is counted once by each of its atoms
+        let energies: Vec<Float> = (0..size)
+            .map(|i| potentials.atom_energy(&system, i))
+            .collect();
+        let total = PotentialEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(
+            energies.iter().sum::<Float>(),
+            2.0 * total,
+            max_relative = 1e-4
+        );
+        for (energy, half) in energies
+            .iter()
+            .zip(PerAtomEnergy.calculate(&system, &potentials))
+        {
+            assert_relative_eq!(*energy, 2.0 * half, epsilon = 1e-4, max_relative = 1e-4);
+        }
+    }
+
+    #[test]
+    fn neighbor_stats() {
+        let argon = Species::from_element(Element::Ar);
+        // simple cubic lattice with one atom per spacing cubed
+        let (spacing, n) = (3.0, 10);
+        let mut positions = Vec::new();
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    positions.push(Vector3::new(x as Float, y as Float, z as Float) * spacing);
+                }
+            }
+        }
+        let size = positions.len();
+        let system = System {
+            size,
+            cell: Cell::cubic(spacing * n as Float),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let (cutoff, thickness) = (7.5, 0.5);
+        let lj = LennardJones::new(0.238, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), cutoff, thickness)
+            .build();
+        potentials.setup(&system).unwrap();
+        assert_eq!(potentials.neighbor_stats()[0].rebuilds, 0);
+        for iteration in 0..3 {
+            potentials.update(&system, iteration);
+            assert_eq!(potentials.neighbor_stats()[0].rebuilds, iteration + 1);
+        }
+
+        // every atom sees the lattice sites within the search radius around it
+        let radius = (cutoff + thickness) / spacing;
+        let reach = radius as i32;
+        let mut sites = 0;
+        for x in -reach..=reach {
+            for y in -reach..=reach {
+                for z in -reach..=reach {
+                    let r = Vector3::new(x as Float, y as Float, z as Float).norm();
+                    if r > 0.0 && r < radius {
+                        sites += 1;
+                    }
+                }
+            }
+        }
+        let stats = potentials.neighbor_stats()[0];
+        assert_relative_eq!(stats.average_neighbors, sites as Float);
+        // which is close to the count expected from the number density
+        let ideal = 4.0 / 3.0 * PI * radius.powi(3);
+        assert_relative_eq!(stats.average_neighbors, ideal, max_relative = 0.05);
+    }
+
+    #[test]
+    fn mixing_rules() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let helium = Species::from_element(Element::He);
+        let build = |cross: LennardJones| {
+            PotentialsBuilder::new()
+                .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+                .pair(LennardJones::new(0.439, 4.1), (xenon, xenon), 8.5, 1.0)
+                .pair(cross, (argon, xenon), 8.5, 1.0)
+                // cross terms without both same-species terms are not checked
+                .pair(LennardJones::new(1.0, 1.0), (argon, helium), 8.5, 1.0)
+                .pair(Harmonic::new(0.1, 5.0), (xenon, argon), 8.5, 1.0)
+                .mixing_tolerance(1e-3)
+                .build()
+        };
+
+        let consistent = build(LennardJones::new(Float::sqrt(0.238 * 0.439), 3.75));
+        assert!(consistent.check_mixing(1e-3).is_ok());
+
+        let message = build(LennardJones::new(0.3, 3.75))
+            .check_mixing(1e-3)
+            .unwrap_err();
+        assert!(message.starts_with("epsilon"));
+        let message = build(LennardJones::new(Float::sqrt(0.238 * 0.439), 3.9))
+            .check_mixing(1e-3)
+            .unwrap_err();
+        assert!(message.starts_with("sigma"));
+        // a looser tolerance accepts the deviation
+        let loose = build(LennardJones::new(0.3, 3.75));
+        assert!(loose.check_mixing(0.5).is_ok());
+
+        // setup reports the inconsistent cross term at the tolerance of the builder
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(20.0),
+            species: vec![argon, xenon],
+            positions: vec![Vector3::new(1.0, 1.0, 1.0), Vector3::new(5.0, 1.0, 1.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut consistent = build(LennardJones::new(Float::sqrt(0.238 * 0.439), 3.75));
+        assert!(consistent.setup(&system).is_ok());
+        let mut inconsistent = build(LennardJones::new(0.3, 3.75));
+        let message = inconsistent.setup(&system).unwrap_err();
+        assert!(message.starts_with("epsilon"));
+    }
+
+    #[test]
+    fn missing_topology() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(1.0, 1.0, 1.0), Vector3::new(2.5, 1.0, 1.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 4.0, 0.5)
+            .external(HarmonicBond::new(100.0, 1.0))
+            .build();
+
+        // the bond potential would silently do nothing without bonds
+        let message = potentials.check_topology(&system).unwrap_err();
+        assert!(message.contains("external potential 0 acts on the bonds"));
+        assert_eq!(potentials.setup(&system).unwrap_err(), message);
+
+        system.bonds.push([0, 1]);
+        assert!(potentials.check_topology(&system).is_ok());
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let bond = PotentialEnergy.calculate(&system, &potentials)
+            - PairEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(bond, 100.0 * 0.25, epsilon = 1e-3);
+        // the stretched bond pulls its atoms together
+        let forces = HarmonicBond::new(100.0, 1.0).forces(&system);
+        assert!(forces[0][0] > 0.0 && forces[1][0] < 0.0);
+    }
+
+    #[test]
+    fn check_neutrality() {
+        let sodium = Species::from_element(Element::Na).with_charge(1.0);
+        let chlorine = Species::from_element(Element::Cl).with_charge(-1.0);
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(20.0),
+            species: vec![sodium, chlorine],
+            positions: vec![Vector3::new(1.0, 1.0, 1.0), Vector3::new(3.5, 1.0, 1.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .coulomb(DampedShiftedForce::new(0.2, 8.0), 8.0, 1.0)
+            .build();
+        assert_relative_eq!(system.net_charge(), 0.0);
+        assert!(potentials.check_neutrality(&system).is_ok());
+        assert!(potentials.setup(&system).is_ok());
+
+        // an extra sodium ion charges the cell
+        system.size += 1;
+        system.species.push(sodium);
+        system.positions.push(Vector3::new(6.0, 1.0, 1.0));
+        system.velocities.push(Vector3::zeros());
+        assert_relative_eq!(system.net_charge(), 1.0);
+        let message = potentials.check_neutrality(&system).unwrap_err();
+        assert!(message.contains("net charge of 1"));
+        assert_eq!(potentials.setup(&system).unwrap_err(), message);
+
+        // an isolated system may be charged
+        let isolated = PotentialsBuilder::new()
+            .coulomb(DirectCoulomb::new(1.0), 8.0, 0.0)
+            .build();
+        assert!(isolated.check_neutrality(&system).is_ok());
+    }
 }
@@ -1,18 +1,72 @@
 //! Potentials which describe pairwise nonbonded interactions..
 
+use std::collections::HashMap;
+
+use nalgebra::{Matrix3, Vector3};
+
 use crate::internal::Float;
-use crate::potentials::types::{Buckingham, Harmonic, LennardJones, Mie, Morse};
+#[cfg(feature = "simd")]
+use crate::internal::FloatLanes;
+use crate::neighbors::NeighborList;
+use crate::potentials::types::{
+    Buckingham, DpdConservative, Exp6, Harmonic, LennardJones, Mie, Morse, SoftCoreLennardJones,
+    Tabulated,
+};
 use crate::potentials::Potential;
-use crate::selection::{setup_pairs_by_species, update_pairs_by_cutoff_radius, Selection};
+use crate::selection::{image_shifts, update_pairs_by_species};
 use crate::system::species::Species;
 use crate::system::System;
 
+/// Number of pairs evaluated at once by [`force_lanes`](PairPotential::force_lanes).
+///
+/// One SIMD register holds 8 lanes of `f32` or 4 lanes of `f64`.
+#[cfg(feature = "simd")]
+pub const LANES: usize = std::mem::size_of::<FloatLanes>() / std::mem::size_of::<Float>();
+
 /// Shared behavior for pair potentials.
 pub trait PairPotential: Potential {
     /// Returns the potential energy of an atom in a pair separated by a distance `r`.
     fn energy(&self, r: Float) -> Float;
     /// Returns the magnitude of the force acting on an atom separated from another by a distance `r`.
+    ///
+    /// The value is the derivative of the energy `dE/dr`, which is the negative of the radial force `-dE/dr`.
+    /// The radial force follows the physical convention of being negative where the pair attracts,
+    /// so this value is positive where the pair attracts and negative where it repels.
     fn force(&self, r: Float) -> Float;
+    /// Returns the [`force`](PairPotential::force) at each of [`LANES`] distances.
+    ///
+    /// Potentials can override this with a branch free kernel over explicit SIMD lanes.
+    /// Lanes beyond the cutoff are evaluated and then discarded so the kernel must accept any positive distance.
+    #[cfg(feature = "simd")]
+    fn force_lanes(&self, r: &[Float; LANES]) -> [Float; LANES] {
+        let mut forces = [0.0; LANES];
+        for (force, &r) in forces.iter_mut().zip(r) {
+            *force = self.force(r);
+        }
+        forces
+    }
+    /// Returns the name and value of each parameter of the potential.
+    ///
+    /// Potentials defined by tables or composed of other potentials have no parameters.
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        HashMap::new()
+    }
+}
+
+/// Pair potentials which can be reconstructed from their [`parameters`](PairPotential::parameters).
+pub trait FromParameters: PairPotential + Sized {
+    /// Returns a new potential from a map of parameter names to values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a parameter is missing.
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Self;
+}
+
+fn parameter(parameters: &HashMap<&str, Float>, name: &str) -> Float {
+    *parameters
+        .get(name)
+        .unwrap_or_else(|| panic!("missing pair potential parameter `{}`", name))
 }
 
 impl PairPotential for Buckingham {
@@ -27,6 +81,117 @@ impl PairPotential for Buckingham {
         let term_b = (self.a * Float::exp(-r / self.rho)) / self.rho;
         term_a - term_b
     }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [("a", self.a), ("rho", self.rho), ("c", self.c)]
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromParameters for Buckingham {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Buckingham {
+        Buckingham::new(
+            parameter(parameters, "a"),
+            parameter(parameters, "rho"),
+            parameter(parameters, "c"),
+        )
+    }
+}
+
+impl PairPotential for DpdConservative {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        if r >= self.cutoff {
+            return 0.0;
+        }
+        0.5 * self.a * self.cutoff * (1.0 - r / self.cutoff).powi(2)
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        if r >= self.cutoff {
+            return 0.0;
+        }
+        -self.a * (1.0 - r / self.cutoff)
+    }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [("a", self.a), ("cutoff", self.cutoff)]
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromParameters for DpdConservative {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> DpdConservative {
+        DpdConservative::new(parameter(parameters, "a"), parameter(parameters, "cutoff"))
+    }
+}
+
+impl Exp6 {
+    #[inline]
+    fn energy_outer(&self, r: Float) -> Float {
+        self.a * Float::exp(-self.b * r) - (self.c / r.powi(6))
+    }
+
+    #[inline]
+    fn force_outer(&self, r: Float) -> Float {
+        (6.0 * self.c) / r.powi(7) - self.a * self.b * Float::exp(-self.b * r)
+    }
+
+    // curvature of the exponential repulsion at `r_min`
+    #[inline]
+    fn core_curvature(&self) -> Float {
+        0.5 * self.a * self.b.powi(2) * Float::exp(-self.b * self.r_min)
+    }
+}
+
+impl PairPotential for Exp6 {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        if r >= self.r_min {
+            return self.energy_outer(r);
+        }
+        let dr = r - self.r_min;
+        self.energy_outer(self.r_min)
+            + self.force_outer(self.r_min) * dr
+            + self.core_curvature() * dr * dr
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        if r >= self.r_min {
+            return self.force_outer(r);
+        }
+        let dr = r - self.r_min;
+        self.force_outer(self.r_min) + 2.0 * self.core_curvature() * dr
+    }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [
+            ("a", self.a),
+            ("b", self.b),
+            ("c", self.c),
+            ("r_min", self.r_min),
+        ]
+        .iter()
+        .cloned()
+        .collect()
+    }
+}
+
+impl FromParameters for Exp6 {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Exp6 {
+        Exp6::new(
+            parameter(parameters, "a"),
+            parameter(parameters, "b"),
+            parameter(parameters, "c"),
+            parameter(parameters, "r_min"),
+        )
+    }
 }
 
 impl PairPotential for Harmonic {
@@ -40,6 +205,16 @@ impl PairPotential for Harmonic {
     fn force(&self, r: Float) -> Float {
         2.0 * self.k * (r - self.x0)
     }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [("k", self.k), ("x0", self.x0)].iter().cloned().collect()
+    }
+}
+
+impl FromParameters for Harmonic {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Harmonic {
+        Harmonic::new(parameter(parameters, "k"), parameter(parameters, "x0"))
+    }
 }
 
 impl PairPotential for LennardJones {
@@ -55,6 +230,84 @@ impl PairPotential for LennardJones {
         let term_b = (48.0 * self.sigma.powi(12)) / r.powi(13);
         self.epsilon * (term_a - term_b)
     }
+
+    #[cfg(feature = "simd")]
+    #[inline]
+    fn force_lanes(&self, r: &[Float; LANES]) -> [Float; LANES] {
+        // (sigma / r)^6 is built from multiplications so every lane runs the same instructions
+        let inverse = FloatLanes::splat(1.0) / FloatLanes::new(*r);
+        let term_sq = FloatLanes::splat(self.sigma * self.sigma) * inverse * inverse;
+        let term = term_sq * term_sq * term_sq;
+        let forces = FloatLanes::splat(self.epsilon)
+            * inverse
+            * (FloatLanes::splat(24.0) * term - FloatLanes::splat(48.0) * term * term);
+        forces.to_array()
+    }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [("epsilon", self.epsilon), ("sigma", self.sigma)]
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromParameters for LennardJones {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> LennardJones {
+        LennardJones::new(
+            parameter(parameters, "epsilon"),
+            parameter(parameters, "sigma"),
+        )
+    }
+}
+
+impl SoftCoreLennardJones {
+    // Returns the softened distance `alpha * (1 - lambda)^2 + (r / sigma)^6`.
+    #[inline]
+    fn softened(&self, r: Float) -> Float {
+        let term = r / self.sigma;
+        let term_sq = term * term;
+        self.alpha * (1.0 - self.lambda) * (1.0 - self.lambda) + term_sq * term_sq * term_sq
+    }
+}
+
+impl PairPotential for SoftCoreLennardJones {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        let s = self.softened(r);
+        4.0 * self.lambda * self.epsilon * (1.0 / (s * s) - 1.0 / s)
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        let s = self.softened(r);
+        // derivative of the softened distance with respect to r
+        let ds = 6.0 * Float::powi(r / self.sigma, 5) / self.sigma;
+        4.0 * self.lambda * self.epsilon * (1.0 / (s * s) - 2.0 / (s * s * s)) * ds
+    }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [
+            ("epsilon", self.epsilon),
+            ("sigma", self.sigma),
+            ("lambda", self.lambda),
+            ("alpha", self.alpha),
+        ]
+        .iter()
+        .cloned()
+        .collect()
+    }
+}
+
+impl FromParameters for SoftCoreLennardJones {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> SoftCoreLennardJones {
+        SoftCoreLennardJones::new(
+            parameter(parameters, "epsilon"),
+            parameter(parameters, "sigma"),
+            parameter(parameters, "lambda"),
+            parameter(parameters, "alpha"),
+        )
+    }
 }
 
 impl PairPotential for Mie {
@@ -75,6 +328,29 @@ impl PairPotential for Mie {
         let term_b = (c * self.gamma_r * self.epsilon * (self.sigma / r).powf(self.gamma_r)) / r;
         term_a - term_b
     }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [
+            ("epsilon", self.epsilon),
+            ("sigma", self.sigma),
+            ("gamma_a", self.gamma_a),
+            ("gamma_r", self.gamma_r),
+        ]
+        .iter()
+        .cloned()
+        .collect()
+    }
+}
+
+impl FromParameters for Mie {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Mie {
+        Mie::new(
+            parameter(parameters, "epsilon"),
+            parameter(parameters, "sigma"),
+            parameter(parameters, "gamma_a"),
+            parameter(parameters, "gamma_r"),
+        )
+    }
 }
 
 impl PairPotential for Morse {
@@ -91,20 +367,148 @@ impl PairPotential for Morse {
         let term_b = Float::exp(-2.0 * self.a * (r - self.r_e));
         2.0 * self.a * self.d_e * (term_a - term_b)
     }
+
+    fn parameters(&self) -> HashMap<&'static str, Float> {
+        [("a", self.a), ("d_e", self.d_e), ("r_e", self.r_e)]
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+impl FromParameters for Morse {
+    fn from_parameters(parameters: &HashMap<&str, Float>) -> Morse {
+        Morse::new(
+            parameter(parameters, "a"),
+            parameter(parameters, "d_e"),
+            parameter(parameters, "r_e"),
+        )
+    }
 }
 
-type PairSetupFn = fn(&System, (Species, Species)) -> Vec<[usize; 2]>;
+/// Pair potential shifted so that its energy and force both vanish at the cutoff.
+///
+/// The shifted force form `E(r) - E(rc) - (r - rc) E'(rc)` removes the jumps in energy and force
+/// which occur as pairs cross a hard cutoff and would otherwise degrade energy conservation.
+#[derive(Clone, Copy, Debug)]
+pub struct ShiftedForce<T> {
+    /// Potential which is shifted.
+    pub potential: T,
+    /// Cutoff radius (angstrom).
+    pub cutoff: Float,
+}
 
-type PairUpdateFn = fn(&System, &[[usize; 2]], Float) -> Vec<[usize; 2]>;
+impl<T: PairPotential> ShiftedForce<T> {
+    /// Returns a new [`ShiftedForce`] potential which vanishes at `cutoff`.
+    ///
+    /// The cutoff should match the one the potential is added to [`Potentials`](crate::potentials::Potentials) with.
+    pub fn new(potential: T, cutoff: Float) -> ShiftedForce<T> {
+        ShiftedForce { potential, cutoff }
+    }
+}
 
-type PairSelection = Selection<PairSetupFn, (Species, Species), PairUpdateFn, Float, 2>;
+impl<T: PairPotential> Potential for ShiftedForce<T> {}
+
+impl<T: PairPotential> PairPotential for ShiftedForce<T> {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        if r >= self.cutoff {
+            return 0.0;
+        }
+        self.potential.energy(r)
+            - self.potential.energy(self.cutoff)
+            - (r - self.cutoff) * self.potential.force(self.cutoff)
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        if r >= self.cutoff {
+            return 0.0;
+        }
+        self.potential.force(r) - self.potential.force(self.cutoff)
+    }
+}
+
+/// Sum of several pair potentials acting on the same pair of species.
+///
+/// Composing potentials this way shares a single neighbor list between every term.
+pub struct SumPotential {
+    /// Potentials which are summed.
+    pub potentials: Vec<Box<dyn PairPotential>>,
+}
+
+impl SumPotential {
+    /// Returns a new [`SumPotential`] of the given potentials.
+    pub fn new(potentials: Vec<Box<dyn PairPotential>>) -> SumPotential {
+        SumPotential { potentials }
+    }
+}
+
+impl Potential for SumPotential {}
+
+impl PairPotential for SumPotential {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        self.potentials
+            .iter()
+            .map(|potential| potential.energy(r))
+            .sum()
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        self.potentials
+            .iter()
+            .map(|potential| potential.force(r))
+            .sum()
+    }
+}
+
+impl Tabulated {
+    // index of the interval containing `r` clamped to the ends of the table
+    #[inline]
+    fn interval(&self, r: Float) -> usize {
+        let upper = self.r.partition_point(|&x| x <= r);
+        upper.clamp(1, self.r.len() - 1) - 1
+    }
+}
+
+impl PairPotential for Tabulated {
+    #[inline]
+    fn energy(&self, r: Float) -> Float {
+        let k = self.interval(r);
+        let h = self.r[k + 1] - self.r[k];
+        let a = (self.r[k + 1] - r) / h;
+        let b = (r - self.r[k]) / h;
+        let linear = a * self.energy[k] + b * self.energy[k + 1];
+        let cubic = (a.powi(3) - a) * self.curvature[k] + (b.powi(3) - b) * self.curvature[k + 1];
+        linear + cubic * h * h / 6.0
+    }
+
+    #[inline]
+    fn force(&self, r: Float) -> Float {
+        let k = self.interval(r);
+        let h = self.r[k + 1] - self.r[k];
+        let a = (self.r[k + 1] - r) / h;
+        let b = (r - self.r[k]) / h;
+        let slope = (self.energy[k + 1] - self.energy[k]) / h;
+        let term_a = (3.0 * a * a - 1.0) * self.curvature[k];
+        let term_b = (3.0 * b * b - 1.0) * self.curvature[k + 1];
+        slope + (term_b - term_a) * h / 6.0
+    }
+}
 
 pub(crate) struct PairPotentialMeta {
     pub potential: Box<dyn PairPotential>,
     pub species: (Species, Species),
     pub cutoff: Float,
     pub thickness: Float,
-    pub selection: PairSelection,
+    // selected pairs in a row-contiguous layout
+    pub neighbors: NeighborList,
+    // interact with every periodic image within the cutoff rather than only the minimum image
+    pub periodic_images: bool,
+    // fractional shifts of the images which can be within the cutoff
+    shifts: Vec<Vector3<Float>>,
 }
 
 impl PairPotentialMeta {
@@ -117,31 +521,96 @@ impl PairPotentialMeta {
     where
         T: PairPotential + 'static,
     {
-        let selection = Selection::new(
-            setup_pairs_by_species as PairSetupFn,
-            update_pairs_by_cutoff_radius as PairUpdateFn,
-        );
         PairPotentialMeta {
             potential: Box::new(potential),
             species,
             cutoff,
             thickness,
-            selection,
+            neighbors: NeighborList::default(),
+            periodic_images: false,
+            shifts: Vec::new(),
         }
     }
 
-    pub fn setup(&mut self, system: &System) {
-        self.selection.setup(system, self.species)
+    /// Selects the pairs of this potential's species from a shared list of neighbors.
+    pub fn update(&mut self, system: &System, neighbors: &[[usize; 2]]) {
+        let args = (self.species, self.cutoff + self.thickness);
+        self.neighbors.rebuild_with(system.size, || {
+            update_pairs_by_species(system, neighbors, args)
+        });
+        if self.periodic_images {
+            self.shifts = image_shifts(&system.cell, self.cutoff + self.thickness);
+        }
+    }
+
+    /// Calls `f` with the vector from atom `i` to each image of atom `j` within the cutoff,
+    /// its length and the share of its interaction which belongs to the pair.
+    ///
+    /// Only the minimum image interacts unless the potential includes periodic images.
+    /// The images of an atom itself share their interaction equally with the atom since each
+    /// image at `+L` is matched by one at `-L`.
+    #[inline]
+    pub fn for_each_image<F>(&self, system: &System, i: usize, j: usize, mut f: F)
+    where
+        F: FnMut(Vector3<Float>, Float, Float),
+    {
+        let mut minimum = system.positions[j] - system.positions[i];
+        system.cell.vector_image(&mut minimum);
+        if !self.periodic_images {
+            let r = minimum.norm();
+            if r < self.cutoff {
+                f(minimum, r, 1.0);
+            }
+            return;
+        }
+        let share = if i == j { 0.5 } else { 1.0 };
+        for shift in &self.shifts {
+            if i == j && *shift == Vector3::zeros() {
+                continue;
+            }
+            let r_ij = minimum + system.cell.cartesian(shift);
+            let r = r_ij.norm();
+            if r < self.cutoff {
+                f(r_ij, r, share);
+            }
+        }
+    }
+
+    /// Returns the energy of every selected pair within the cutoff which involves atom `i`.
+    pub fn atom_energy(&self, system: &System, i: usize) -> Float {
+        let mut energy = 0.0;
+        // a pair of the atom with itself is listed in both its row and its transposed row
+        // so the shared interactions with its own images are counted in full
+        for j in self.neighbors.partners(i) {
+            self.for_each_image(system, i, j, |_, r, share| {
+                energy += share * self.potential.energy(r);
+            });
+        }
+        energy
     }
 
-    pub fn update(&mut self, system: &System) {
-        self.selection.update(system, self.cutoff + self.thickness)
+    /// Returns the virial tensor `sum(r_ij ⊗ f_ij)` of every selected pair within the cutoff.
+    pub fn virial(&self, system: &System) -> Matrix3<Float> {
+        let mut virial = Matrix3::zeros();
+        for [i, j] in self.neighbors.pairs() {
+            self.for_each_image(system, i, j, |r_ji, r, share| {
+                let r_ij = -r_ji;
+                let f_ij = -self.potential.force(r) * r_ij / r;
+                virial += share * r_ij * f_ij.transpose();
+            });
+        }
+        virial
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Buckingham, Harmonic, LennardJones, Mie, Morse, PairPotential};
+    use super::{
+        Buckingham, DpdConservative, Exp6, FromParameters, Harmonic, LennardJones, Mie, Morse,
+        PairPotential, ShiftedForce, SoftCoreLennardJones, SumPotential, Tabulated,
+    };
+    use crate::internal::Float;
+    use crate::potentials::types::Interpolation;
     use approx::*;
 
     #[test]
@@ -174,6 +643,59 @@ mod tests {
         assert_relative_eq!(r2_force, buckingham.force(r2), epsilon = 1e-5);
     }
 
+    #[cfg(feature = "simd")]
+    #[test]
+    fn lennard_jones_lanes() {
+        use super::LANES;
+        let lennard_jones = LennardJones::new(0.8, 2.0);
+        let mut r = [0.0; LANES];
+        for (k, r) in r.iter_mut().enumerate() {
+            *r = 1.8 + 0.35 * k as Float;
+        }
+        let forces = lennard_jones.force_lanes(&r);
+        for (&force, &r) in forces.iter().zip(&r) {
+            assert_relative_eq!(force, lennard_jones.force(r), max_relative = 1e-5);
+        }
+    }
+
+    #[test]
+    fn dpd_conservative() {
+        let dpd = DpdConservative::new(25.0, 2.0);
+        assert_relative_eq!(dpd.energy(0.0), 25.0, epsilon = 1e-5);
+        assert_relative_eq!(dpd.force(0.0), -25.0, epsilon = 1e-5);
+        assert_relative_eq!(dpd.energy(1.0), 6.25, epsilon = 1e-5);
+        assert_relative_eq!(dpd.force(1.0), -12.5, epsilon = 1e-5);
+        assert_relative_eq!(dpd.energy(2.5), 0.0);
+        assert_relative_eq!(dpd.force(2.5), 0.0);
+    }
+
+    #[test]
+    fn exp6() {
+        let a = 524960.604;
+        let rho = 0.149;
+        let c = 642.94068;
+        let r_min = 0.9;
+        let buckingham = Buckingham::new(a, rho, c);
+        let exp6 = Exp6::new(a, 1.0 / rho, c, r_min);
+
+        // identical to buckingham beyond r_min
+        for &r in &[r_min, 1.0, 1.5, 2.5] {
+            assert_relative_eq!(exp6.energy(r), buckingham.energy(r), max_relative = 1e-4);
+            assert_relative_eq!(exp6.force(r), buckingham.force(r), max_relative = 1e-4);
+        }
+
+        // monotonically repulsive below r_min
+        let mut previous = exp6.energy(r_min);
+        let mut r = r_min;
+        while r > 0.05 {
+            r -= 0.01;
+            let energy = exp6.energy(r);
+            assert!(energy > previous);
+            assert!(exp6.force(r) < 0.0);
+            previous = energy;
+        }
+    }
+
     #[test]
     fn harmonic() {
         // initialize the potantial
@@ -263,6 +785,86 @@ mod tests {
         assert_relative_eq!(r2_force, mie.force(r2), epsilon = 1e-5);
     }
 
+    #[test]
+    fn mie_matches_lennard_jones() {
+        let lj = LennardJones::new(0.8, 3.1);
+        let mie = Mie::new(0.8, 3.1, 6.0, 12.0);
+        for &r in &[2.6, 3.1, 3.48, 4.0, 5.5, 8.0] {
+            assert_relative_eq!(
+                mie.energy(r),
+                lj.energy(r),
+                epsilon = 1e-4,
+                max_relative = 1e-5
+            );
+            assert_relative_eq!(
+                mie.force(r),
+                lj.force(r),
+                epsilon = 1e-4,
+                max_relative = 1e-5
+            );
+        }
+        // both return dE/dr and agree on its sign on either side of the minimum
+        let h = 1e-3;
+        for &r in &[3.0, 4.0] {
+            let gradient = (lj.energy(r + h) - lj.energy(r - h)) / (2.0 * h);
+            assert_relative_eq!(lj.force(r), gradient, max_relative = 1e-2);
+        }
+        let r_min = Float::powf(2.0, 1.0 / 6.0) * 3.1;
+        assert!(mie.force(r_min - 0.1) < 0.0 && lj.force(r_min - 0.1) < 0.0);
+        assert!(mie.force(r_min + 0.1) > 0.0 && lj.force(r_min + 0.1) > 0.0);
+    }
+
+    #[test]
+    fn soft_core_lennard_jones() {
+        let lj = LennardJones::new(0.8, 3.1);
+        // fully coupled soft-core matches the standard potential
+        let coupled = SoftCoreLennardJones::new(0.8, 3.1, 1.0, 0.5);
+        for &r in &[2.6, 3.1, 3.48, 4.0, 5.5, 8.0] {
+            assert_relative_eq!(
+                coupled.energy(r),
+                lj.energy(r),
+                epsilon = 1e-4,
+                max_relative = 1e-5
+            );
+            assert_relative_eq!(
+                coupled.force(r),
+                lj.force(r),
+                epsilon = 1e-4,
+                max_relative = 1e-5
+            );
+        }
+
+        // partially coupled soft-core stays finite as the atoms overlap
+        let soft = SoftCoreLennardJones::new(0.8, 3.1, 0.5, 0.5);
+        let s = 0.5 * 0.5 * 0.5;
+        assert_relative_eq!(
+            soft.energy(0.0),
+            4.0 * 0.5 * 0.8 * (1.0 / (s * s) - 1.0 / s)
+        );
+        assert_eq!(soft.force(0.0), 0.0);
+        let mut r = 1.0;
+        while r > 1e-6 {
+            assert!(soft.energy(r).is_finite() && soft.force(r).is_finite());
+            assert!(soft.energy(r) <= soft.energy(0.0) + 1e-4);
+            r /= 10.0;
+        }
+        // the force is the derivative of the energy
+        for &r in &[0.5, 1.5, 2.8, 3.5, 5.0] {
+            let h = 1e-2;
+            let derivative = (soft.energy(r + h) - soft.energy(r - h)) / (2.0 * h);
+            assert_relative_eq!(
+                soft.force(r),
+                derivative,
+                epsilon = 1e-2,
+                max_relative = 1e-2
+            );
+        }
+        // decoupled atoms do not interact
+        let decoupled = SoftCoreLennardJones::new(0.8, 3.1, 0.0, 0.5);
+        assert_eq!(decoupled.energy(1.0), 0.0);
+        assert_eq!(decoupled.force(1.0), 0.0);
+    }
+
     #[test]
     fn morse() {
         let a = 1.5;
@@ -290,4 +892,104 @@ mod tests {
         assert_relative_eq!(r2_energy, morse.energy(r2), epsilon = 1e-5);
         assert_relative_eq!(r2_force, morse.force(r2), epsilon = 1e-5);
     }
+
+    #[test]
+    fn sum_potential() {
+        let lj = LennardJones::new(1.0, 2.5);
+        let harmonic = Harmonic::new(50.0, 2.0);
+        let sum = SumPotential::new(vec![Box::new(lj), Box::new(harmonic)]);
+        for &r in &[2.0, 2.5, 3.0, 4.5] {
+            assert_relative_eq!(
+                sum.energy(r),
+                lj.energy(r) + harmonic.energy(r),
+                epsilon = 1e-5
+            );
+            assert_relative_eq!(
+                sum.force(r),
+                lj.force(r) + harmonic.force(r),
+                epsilon = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn tabulated() {
+        // coarse table of a morse potential
+        let morse = Morse::new(1.5, 4.0, 2.0);
+        let r: Vec<Float> = (0..12).map(|i| 1.5 + 0.25 * i as Float).collect();
+        let energy: Vec<Float> = r.iter().map(|&x| morse.energy(x)).collect();
+        let linear = Tabulated::from_columns(r.clone(), energy.clone(), Interpolation::Linear);
+        let spline = Tabulated::from_columns(r.clone(), energy, Interpolation::CubicSpline);
+
+        // both schemes pass through the nodes
+        for &x in &r {
+            assert_relative_eq!(linear.energy(x), morse.energy(x), epsilon = 1e-4);
+            assert_relative_eq!(spline.energy(x), morse.energy(x), epsilon = 1e-4);
+        }
+
+        // the spline force is the derivative of the spline energy
+        let (x, dx) = (2.1, 1e-3);
+        let numeric = (spline.energy(x + dx) - spline.energy(x - dx)) / (2.0 * dx);
+        assert_relative_eq!(spline.force(x), numeric, epsilon = 1e-2);
+
+        // only the spline force is continuous across interior nodes
+        let delta = 1e-5;
+        for &x in &r[1..r.len() - 1] {
+            let linear_jump = (linear.force(x + delta) - linear.force(x - delta)).abs();
+            let spline_jump = (spline.force(x + delta) - spline.force(x - delta)).abs();
+            assert!(spline_jump < 1e-2);
+            if x < 2.5 {
+                assert!(linear_jump > 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn shifted_force() {
+        let lj = LennardJones::new(0.238, 3.4);
+        let cutoff = 8.5;
+        let shifted = ShiftedForce::new(lj, cutoff);
+        assert_relative_eq!(shifted.energy(cutoff - 1e-4), 0.0, epsilon = 1e-6);
+        assert_relative_eq!(shifted.force(cutoff - 1e-4), 0.0, epsilon = 1e-6);
+        assert_eq!(shifted.energy(cutoff + 1.0), 0.0);
+        assert_eq!(shifted.force(cutoff + 1.0), 0.0);
+
+        // the force is still the derivative of the energy
+        let h = 1e-3;
+        for &r in &[3.5, 4.0, 6.0] {
+            let derivative = (shifted.energy(r + h) - shifted.energy(r - h)) / (2.0 * h);
+            assert_relative_eq!(shifted.force(r), derivative, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn parameters_round_trip() {
+        let lj = LennardJones::new(1.0, 2.5);
+        let parameters = lj.parameters();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters["epsilon"], 1.0);
+        assert_eq!(parameters["sigma"], 2.5);
+
+        let copy = LennardJones::from_parameters(&parameters);
+        assert_eq!(copy.epsilon, lj.epsilon);
+        assert_eq!(copy.sigma, lj.sigma);
+        for &r in &[2.0, 2.8, 4.0] {
+            assert_eq!(copy.energy(r), lj.energy(r));
+            assert_eq!(copy.force(r), lj.force(r));
+        }
+
+        let mie = Mie::new(0.5, 3.0, 6.0, 14.0);
+        let copy = Mie::from_parameters(&mie.parameters());
+        assert_eq!(copy.gamma_r, 14.0);
+        let sum = SumPotential::new(vec![Box::new(lj), Box::new(mie)]);
+        assert!(sum.parameters().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "missing pair potential parameter `sigma`")]
+    fn parameters_missing() {
+        let mut parameters = LennardJones::new(1.0, 2.5).parameters();
+        parameters.remove("sigma");
+        LennardJones::from_parameters(&parameters);
+    }
 }
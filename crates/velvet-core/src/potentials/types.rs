@@ -1,7 +1,43 @@
 //! Interatomic potential functions.
 
+use nalgebra::Vector3;
+
+use crate::internal::consts::{boltzmann, coulomb, AVOGADRO, PI};
 use crate::internal::Float;
 use crate::potentials::Potential;
+use crate::system::species::Species;
+
+/// Harmonic bias on the angle between three atoms.
+///
+/// The energy `k (theta - theta0)^2` uses the minimum image angle (radians) at the vertex atom `j`.
+#[derive(Clone, Copy, Debug)]
+pub struct AngleRestraint {
+    /// Index of the first outer atom.
+    pub i: usize,
+    /// Index of the vertex atom.
+    pub j: usize,
+    /// Index of the second outer atom.
+    pub k_atom: usize,
+    /// Spring constant.
+    pub k: Float,
+    /// Target angle (radians).
+    pub theta0: Float,
+}
+
+impl AngleRestraint {
+    /// Returns a new [`AngleRestraint`] potential.
+    pub fn new(i: usize, j: usize, k_atom: usize, k: Float, theta0: Float) -> AngleRestraint {
+        AngleRestraint {
+            i,
+            j,
+            k_atom,
+            k,
+            theta0,
+        }
+    }
+}
+
+impl Potential for AngleRestraint {}
 
 /// [Buckingham](https://lammps.sandia.gov/doc/pair_buck.html#description) potential.
 #[derive(Clone, Copy, Debug)]
@@ -24,6 +60,12 @@ impl Buckingham {
 impl Potential for Buckingham {}
 
 /// [Damped Shifted Force](https://lammps.sandia.gov/doc/pair_coul.html#description) potential.
+///
+/// Pairwise alternative to Ewald summation whose energy and force both vanish at the cutoff.
+///
+/// # References
+///
+/// [1] Fennell, Christopher J., and J. Daniel Gezelter. "Is the Ewald summation still necessary? Pairwise alternatives to the accepted standard for long-range electrostatics." The Journal of chemical physics 124.23 (2006): 234104.
 #[derive(Clone, Copy, Debug)]
 pub struct DampedShiftedForce {
     /// Damping parameter.
@@ -35,13 +77,216 @@ pub struct DampedShiftedForce {
 impl DampedShiftedForce {
     /// Returns a new [`DampedShiftedForce`] potential.
     pub fn new(alpha: Float, cutoff: Float) -> DampedShiftedForce {
-        DampedShiftedForce {alpha, cutoff}
+        DampedShiftedForce { alpha, cutoff }
     }
 }
 
 impl Potential for DampedShiftedForce {}
 
+/// Harmonic bias on the distance between two atoms.
+///
+/// The energy `k (r - r0)^2` uses the minimum image distance between atoms `i` and `j`.
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceRestraint {
+    /// Index of the first atom.
+    pub i: usize,
+    /// Index of the second atom.
+    pub j: usize,
+    /// Spring constant.
+    pub k: Float,
+    /// Target distance.
+    pub r0: Float,
+}
+
+impl DistanceRestraint {
+    /// Returns a new [`DistanceRestraint`] potential.
+    pub fn new(i: usize, j: usize, k: Float, r0: Float) -> DistanceRestraint {
+        DistanceRestraint { i, j, k, r0 }
+    }
+}
+
+impl Potential for DistanceRestraint {}
+
+/// [Debye-Hückel](https://lammps.sandia.gov/doc/pair_coul.html#description) screened Coulombic potential.
+///
+/// The energy `qi qj l_B k_B T exp(-r / l_D) / r` describes ions in an implicit electrolyte.
+#[derive(Clone, Copy, Debug)]
+pub struct DebyeHuckel {
+    /// Bjerrum length (angstrom).
+    pub bjerrum_length: Float,
+    /// Debye screening length (angstrom).
+    pub debye_length: Float,
+    /// Temperature (Kelvin).
+    pub temperature: Float,
+}
+
+impl DebyeHuckel {
+    /// Returns a new [`DebyeHuckel`] potential.
+    pub fn new(bjerrum_length: Float, debye_length: Float, temperature: Float) -> DebyeHuckel {
+        DebyeHuckel {
+            bjerrum_length,
+            debye_length,
+            temperature,
+        }
+    }
+
+    /// Returns a new [`DebyeHuckel`] potential with screening lengths derived from the electrolyte.
+    ///
+    /// # Arguments
+    ///
+    /// * `ionic_strength` - Ionic strength of the electrolyte (mol/L).
+    /// * `dielectric` - Dielectric constant of the solvent (unitless).
+    /// * `temperature` - Temperature (Kelvin).
+    pub fn from_ionic_strength(
+        ionic_strength: Float,
+        dielectric: Float,
+        temperature: Float,
+    ) -> DebyeHuckel {
+        let bjerrum_length = coulomb() / (dielectric * boltzmann() * temperature);
+        // ions per cubic angstrom
+        let density = ionic_strength * AVOGADRO * 1e-27;
+        let debye_length = 1.0 / Float::sqrt(8.0 * PI * bjerrum_length * density);
+        DebyeHuckel::new(bjerrum_length, debye_length, temperature)
+    }
+}
+
+impl Potential for DebyeHuckel {}
+
+/// Unscreened Coulombic potential summed directly over every pair of an isolated system.
+///
+/// Pairs are separated by the difference of their positions without minimum images or a cutoff,
+/// so the system is treated as fully aperiodic regardless of its cell. This is exact for small
+/// gas-phase clusters where an Ewald sum is unnecessary and periodic images are unphysical.
+#[derive(Clone, Copy, Debug)]
+pub struct DirectCoulomb {
+    /// Dielectric constant (unitless).
+    pub dielectric: Float,
+}
+
+impl DirectCoulomb {
+    /// Returns a new [`DirectCoulomb`] potential.
+    pub fn new(dielectric: Float) -> DirectCoulomb {
+        DirectCoulomb { dielectric }
+    }
+}
+
+impl Potential for DirectCoulomb {}
+
+/// Soft conservative repulsion of [Dissipative Particle Dynamics](https://lammps.sandia.gov/doc/pair_dpd.html#description).
+///
+/// The force `a (1 - r / cutoff)` decays linearly to zero at the cutoff.
+///
+/// # References
+///
+/// [1] Groot, Robert D., and Patrick B. Warren. "Dissipative particle dynamics: Bridging the gap between atomistic and mesoscopic simulation." The Journal of chemical physics 107.11 (1997): 4423-4435.
+#[derive(Clone, Copy, Debug)]
+pub struct DpdConservative {
+    /// Maximum repulsion (Kcal/mole-angstrom).
+    pub a: Float,
+    /// Cutoff radius (angstrom).
+    pub cutoff: Float,
+}
+
+impl DpdConservative {
+    /// Returns a new [`DpdConservative`] potential.
+    pub fn new(a: Float, cutoff: Float) -> DpdConservative {
+        DpdConservative { a, cutoff }
+    }
+}
+
+impl Potential for DpdConservative {}
+
+/// [Embedded atom method](https://lammps.sandia.gov/doc/pair_eam.html#description) potential for metals.
+///
+/// The energy of atom `i` is `F_i(rho_i) + 1/2 sum_j phi_ij(r_ij)` where the electron density
+/// `rho_i = sum_j f_j(r_ij)` is contributed by each neighbor according to its species.
+/// Every function is tabulated with the distance or density as the first column of a [`Tabulated`].
+///
+/// # References
+///
+/// [1] Daw, Murray S., and Michael I. Baskes. "Embedded-atom method: Derivation and application to impurities, surfaces, and other defects in metals." Physical Review B 29.12 (1984): 6443.
+#[derive(Clone, Debug)]
+pub struct Eam {
+    /// Species described by the potential.
+    pub species: Vec<Species>,
+    /// Embedding energy `F(rho)` of each species.
+    pub embedding: Vec<Tabulated>,
+    /// Electron density `f(r)` contributed by each species.
+    pub density: Vec<Tabulated>,
+    /// Pair energy `phi(r)` between each pair of species stored as a lower triangle in row major order.
+    pub pair: Vec<Tabulated>,
+}
+
+impl Eam {
+    /// Returns a new [`Eam`] potential.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of tabulated functions does not match the number of species.
+    pub fn new(
+        species: Vec<Species>,
+        embedding: Vec<Tabulated>,
+        density: Vec<Tabulated>,
+        pair: Vec<Tabulated>,
+    ) -> Eam {
+        let n = species.len();
+        assert_eq!(
+            embedding.len(),
+            n,
+            "EAM requires one embedding function per species"
+        );
+        assert_eq!(
+            density.len(),
+            n,
+            "EAM requires one density function per species"
+        );
+        assert_eq!(
+            pair.len(),
+            n * (n + 1) / 2,
+            "EAM requires one pair function per pair of species"
+        );
+        Eam {
+            species,
+            embedding,
+            density,
+            pair,
+        }
+    }
+
+    /// Returns the pair energy function between the species at indices `a` and `b`.
+    pub fn pair_function(&self, a: usize, b: usize) -> &Tabulated {
+        let (row, column) = if a >= b { (a, b) } else { (b, a) };
+        &self.pair[row * (row + 1) / 2 + column]
+    }
+}
+
+impl Potential for Eam {}
+
+/// Exp-6 (modified [Buckingham](https://lammps.sandia.gov/doc/pair_buck.html#description)) potential with a repulsive core.
+///
+/// Below `r_min` the unphysical attractive region of the Buckingham form is replaced by a
+/// quadratic extrapolation which matches the energy and force at `r_min` and remains purely repulsive.
+/// `r_min` should be placed at or slightly beyond the spurious inner maximum of the Buckingham form.
+#[derive(Clone, Copy, Debug)]
+pub struct Exp6 {
+    /// Energy units.
+    pub a: Float,
+    /// Inverse distance units.
+    pub b: Float,
+    /// Energy units.
+    pub c: Float,
+    /// Distance below which the repulsive extrapolation is used.
+    pub r_min: Float,
+}
+
+impl Exp6 {
+    /// Returns a new [`Exp6`] potential.
+    pub fn new(a: Float, b: Float, c: Float, r_min: Float) -> Exp6 {
+        Exp6 { a, b, c, r_min }
+    }
+}
 
+impl Potential for Exp6 {}
 
 /// [Harmonic](https://lammps.sandia.gov/doc/bond_harmonic.html#description) oscillator potential.
 #[derive(Clone, Copy, Debug)]
@@ -61,6 +306,26 @@ impl Harmonic {
 
 impl Potential for Harmonic {}
 
+/// [Harmonic](https://lammps.sandia.gov/doc/bond_harmonic.html#description) bond potential.
+///
+/// The energy `k (r - r0)^2` of each bond in `System.bonds` uses the minimum image distance between its atoms.
+#[derive(Clone, Copy, Debug)]
+pub struct HarmonicBond {
+    /// Spring constant.
+    pub k: Float,
+    /// Equilibrium bond length.
+    pub r0: Float,
+}
+
+impl HarmonicBond {
+    /// Returns a new [`HarmonicBond`] potential.
+    pub fn new(k: Float, r0: Float) -> HarmonicBond {
+        HarmonicBond { k, r0 }
+    }
+}
+
+impl Potential for HarmonicBond {}
+
 /// [Lennard-Jones](https://lammps.sandia.gov/doc/pair_lj.html#description) 12/6 potential.
 #[derive(Clone, Copy, Debug)]
 pub struct LennardJones {
@@ -94,7 +359,15 @@ pub struct Mie {
 
 impl Mie {
     /// Returns a new [`Mie`] potential.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the repulsive exponent is not larger than the attractive exponent.
     pub fn new(epsilon: Float, sigma: Float, gamma_a: Float, gamma_r: Float) -> Mie {
+        assert!(
+            gamma_r > gamma_a && gamma_a > 0.0,
+            "Mie exponents must satisfy gamma_r > gamma_a > 0"
+        );
         Mie {
             epsilon,
             sigma,
@@ -106,6 +379,45 @@ impl Mie {
 
 impl Potential for Mie {}
 
+/// Soft-core [Lennard-Jones](https://doi.org/10.1016/0009-2614(94)00397-1) potential for alchemical free energy calculations.
+///
+/// The energy `4 * lambda * epsilon * (1 / s^2 - 1 / s)` with `s = alpha * (1 - lambda)^2 + (r / sigma)^6`
+/// stays finite as `r -> 0` for `lambda < 1` and reduces to the standard [`LennardJones`] potential at `lambda = 1`.
+#[derive(Clone, Copy, Debug)]
+pub struct SoftCoreLennardJones {
+    /// Depth of the potential well.
+    pub epsilon: Float,
+    /// Distance at which the pair potential energy is zero when fully coupled.
+    pub sigma: Float,
+    /// Coupling parameter from 0 (decoupled) to 1 (fully coupled).
+    pub lambda: Float,
+    /// Strength of the softening.
+    pub alpha: Float,
+}
+
+impl SoftCoreLennardJones {
+    /// Returns a new [`SoftCoreLennardJones`] potential.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lambda` is not within `[0, 1]` or `alpha` is negative.
+    pub fn new(epsilon: Float, sigma: Float, lambda: Float, alpha: Float) -> SoftCoreLennardJones {
+        assert!(
+            (0.0..=1.0).contains(&lambda),
+            "soft-core lambda must be within [0, 1]"
+        );
+        assert!(alpha >= 0.0, "soft-core alpha must not be negative");
+        SoftCoreLennardJones {
+            epsilon,
+            sigma,
+            lambda,
+            alpha,
+        }
+    }
+}
+
+impl Potential for SoftCoreLennardJones {}
+
 /// [Morse](https://lammps.sandia.gov/doc/pair_morse.html#description) potential.
 #[derive(Clone, Copy, Debug)]
 pub struct Morse {
@@ -126,6 +438,26 @@ impl Morse {
 
 impl Potential for Morse {}
 
+/// Harmonic restraint which tethers atoms to fixed reference positions.
+///
+/// The energy of each restrained atom is `k |r_i - ref_i|^2` using the minimum image displacement.
+#[derive(Clone, Debug)]
+pub struct PositionRestraint {
+    /// Spring constant.
+    pub k: Float,
+    /// Index and reference position of each restrained atom.
+    pub references: Vec<(usize, Vector3<Float>)>,
+}
+
+impl PositionRestraint {
+    /// Returns a new [`PositionRestraint`] potential.
+    pub fn new(k: Float, references: Vec<(usize, Vector3<Float>)>) -> PositionRestraint {
+        PositionRestraint { k, references }
+    }
+}
+
+impl Potential for PositionRestraint {}
+
 /// Standard [Coulombic](https://lammps.sandia.gov/doc/pair_coul.html#description) potential.
 #[derive(Clone, Copy, Debug)]
 pub struct StandardCoulombic {
@@ -141,3 +473,87 @@ impl StandardCoulombic {
 }
 
 impl Potential for StandardCoulombic {}
+
+/// Interpolation scheme between the nodes of a [`Tabulated`] potential.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Piecewise linear energy with a discontinuous force at each node.
+    Linear,
+    /// Natural cubic spline energy with a continuous force.
+    CubicSpline,
+}
+
+/// [Tabulated](https://lammps.sandia.gov/doc/pair_table.html#description) pair potential.
+///
+/// Distances outside of the table are extrapolated from the nearest interval.
+#[derive(Clone, Debug)]
+pub struct Tabulated {
+    /// Distance of each node in ascending order.
+    pub r: Vec<Float>,
+    /// Energy at each node.
+    pub energy: Vec<Float>,
+    /// Interpolation scheme between nodes.
+    pub interpolation: Interpolation,
+    // second derivative of the energy at each node
+    pub(crate) curvature: Vec<Float>,
+}
+
+impl Tabulated {
+    /// Returns a new [`Tabulated`] potential from columns of distances and energies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns differ in length, contain fewer than two nodes, or the distances are not strictly increasing.
+    pub fn from_columns(
+        r: Vec<Float>,
+        energy: Vec<Float>,
+        interpolation: Interpolation,
+    ) -> Tabulated {
+        assert_eq!(
+            r.len(),
+            energy.len(),
+            "tabulated columns must have equal length"
+        );
+        assert!(
+            r.len() >= 2,
+            "tabulated potential requires at least two nodes"
+        );
+        assert!(
+            r.windows(2).all(|w| w[1] > w[0]),
+            "tabulated distances must be strictly increasing"
+        );
+        let curvature = match interpolation {
+            Interpolation::Linear => vec![0.0; r.len()],
+            Interpolation::CubicSpline => natural_spline(&r, &energy),
+        };
+        Tabulated {
+            r,
+            energy,
+            interpolation,
+            curvature,
+        }
+    }
+}
+
+impl Potential for Tabulated {}
+
+// Returns the second derivatives of a natural cubic spline through `(x, y)`.
+fn natural_spline(x: &[Float], y: &[Float]) -> Vec<Float> {
+    let n = x.len();
+    let mut y2 = vec![0.0; n];
+    let mut u = vec![0.0; n];
+    // forward sweep of the tridiagonal system
+    for i in 1..n - 1 {
+        let sig = (x[i] - x[i - 1]) / (x[i + 1] - x[i - 1]);
+        let p = sig * y2[i - 1] + 2.0;
+        y2[i] = (sig - 1.0) / p;
+        let slope = (y[i + 1] - y[i]) / (x[i + 1] - x[i]) - (y[i] - y[i - 1]) / (x[i] - x[i - 1]);
+        u[i] = (6.0 * slope / (x[i + 1] - x[i - 1]) - sig * u[i - 1]) / p;
+    }
+    // back substitution with zero curvature at both ends
+    y2[n - 1] = 0.0;
+    for i in (0..n - 1).rev() {
+        y2[i] = y2[i] * y2[i + 1] + u[i];
+    }
+    y2
+}
@@ -1,21 +1,247 @@
 //! Algorithms to control the progress of a simulation.
 
+use nalgebra::Vector3;
+use rand::Rng;
+
+use crate::barostats::{Barostat, NullBarostat};
 use crate::integrators::Integrator;
+use crate::internal::consts::boltzmann;
+use crate::internal::Float;
 use crate::potentials::Potentials;
+use crate::properties::energy::{ExternalEnergy, ManyBodyEnergy};
+use crate::properties::temperature::Temperature;
+use crate::properties::{IntrinsicProperty, Property};
+use crate::rng::{self, VelvetRng};
 use crate::system::System;
 use crate::thermostats::Thermostat;
 
+/// Shared behavior for algorithms which advance the state of a system.
 pub trait Propagator: Send + Sync {
+    /// Prepares the propagator to run.
     fn setup(&mut self, _: &mut System, _: &Potentials) {}
+    /// Advances the system by one step.
     fn propagate(&mut self, _: &mut System, _: &Potentials) {}
+    /// Returns the internal state of each component which must be stored to resume the propagation.
+    fn state(&self) -> Vec<Vec<Float>> {
+        Vec::new()
+    }
+    /// Restores the internal state returned by [`state`](Propagator::state) after setup.
+    fn restore(&mut self, _: &[Vec<Float>]) {}
+}
+
+/// Periodically removes the net linear and optionally angular momentum of a system.
+///
+/// Numerical noise slowly accumulates net momentum over long runs which shows up as a drift
+/// of the whole system. Rotation is only conserved in non-periodic systems such as isolated clusters.
+#[derive(Clone, Copy, Debug)]
+pub struct ComFixer {
+    interval: usize,
+    remove_rotation: bool,
+    step: usize,
+}
+
+impl ComFixer {
+    /// Returns a new [`ComFixer`].
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Number of steps between corrections.
+    /// * `remove_rotation` - Whether to remove the net angular momentum as well.
+    pub fn new(interval: usize, remove_rotation: bool) -> ComFixer {
+        assert!(interval > 0, "the COM fixer interval must be positive");
+        ComFixer {
+            interval,
+            remove_rotation,
+            step: 0,
+        }
+    }
+
+    /// Counts a step and removes the net momentum if it completes an interval.
+    pub fn apply(&mut self, system: &mut System) {
+        self.step += 1;
+        if self.step.is_multiple_of(self.interval) {
+            system.remove_net_momentum();
+            if self.remove_rotation {
+                system.remove_net_angular_momentum();
+            }
+        }
+    }
+}
+
+/// Set of atoms controlled by a region-bound thermostat.
+#[derive(Clone, Debug)]
+pub enum Region {
+    /// Fixed set of atom indices.
+    Group(Vec<usize>),
+    /// Atoms whose wrapped fractional coordinate along `axis` (0, 1 or 2 for 'a', 'b' or 'c') lies in `[lower, upper)`.
+    ///
+    /// Membership is reevaluated every step as atoms move between slabs.
+    Slab {
+        /// Index of the cell vector normal to the slab.
+        axis: usize,
+        /// Inclusive lower bound as a fraction of the cell vector.
+        lower: Float,
+        /// Exclusive upper bound as a fraction of the cell vector.
+        upper: Float,
+    },
+}
+
+impl Region {
+    /// Returns the indices of the atoms in the region.
+    pub fn indices(&self, system: &System) -> Vec<usize> {
+        match self {
+            Region::Group(indices) => indices.clone(),
+            Region::Slab { axis, lower, upper } => system
+                .positions
+                .iter()
+                .enumerate()
+                .filter(|(_, position)| {
+                    let x = system.cell.fractional(position)[*axis];
+                    let x = x - Float::floor(x);
+                    *lower <= x && x < *upper
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        }
+    }
+}
+
+// Returns a copy of the atoms at `indices` without any bonded topology.
+fn subsystem(system: &System, indices: &[usize]) -> System {
+    System {
+        size: indices.len(),
+        cell: system.cell.clone(),
+        species: indices.iter().map(|&i| system.species[i]).collect(),
+        positions: indices.iter().map(|&i| system.positions[i]).collect(),
+        velocities: indices.iter().map(|&i| system.velocities[i]).collect(),
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    }
+}
+
+// Runs `apply` on the atoms at `indices` and copies their velocities back into the system.
+fn apply_to_region<F: FnOnce(&mut System)>(system: &mut System, indices: &[usize], apply: F) {
+    let mut region = subsystem(system, indices);
+    apply(&mut region);
+    for (&i, velocity) in indices.iter().zip(region.velocities) {
+        system.velocities[i] = velocity;
+    }
+}
+
+/// Periodically rescales the velocities in slabs along a cell vector to impose a linear temperature profile.
+///
+/// The cell is divided into `bins` slabs of equal width along `axis` and the target temperature of each slab
+/// is interpolated linearly from `lower` at a fractional coordinate of 0 to `upper` at 1 using the slab's center.
+/// Every `interval` steps the velocities of each slab are scaled so its temperature matches its target,
+/// which is useful to validate non-equilibrium molecular dynamics against a known profile.
+#[derive(Clone, Copy, Debug)]
+pub struct TemperatureGradient {
+    axis: usize,
+    bins: usize,
+    lower: Float,
+    upper: Float,
+    interval: usize,
+    step: usize,
+}
+
+impl TemperatureGradient {
+    /// Returns a new [`TemperatureGradient`].
+    ///
+    /// # Arguments
+    ///
+    /// * `axis` - Index of the cell vector along which the temperature varies (0, 1 or 2 for 'a', 'b' or 'c').
+    /// * `bins` - Number of slabs.
+    /// * `lower` - Target temperature at the origin of the cell vector.
+    /// * `upper` - Target temperature at the end of the cell vector.
+    /// * `interval` - Number of steps between rescalings.
+    pub fn new(
+        axis: usize,
+        bins: usize,
+        lower: Float,
+        upper: Float,
+        interval: usize,
+    ) -> TemperatureGradient {
+        assert!(axis < 3, "the temperature gradient axis must be 0, 1 or 2");
+        assert!(
+            bins > 0,
+            "the temperature gradient must have at least one bin"
+        );
+        assert!(
+            interval > 0,
+            "the temperature gradient interval must be positive"
+        );
+        TemperatureGradient {
+            axis,
+            bins,
+            lower,
+            upper,
+            interval,
+            step: 0,
+        }
+    }
+
+    /// Returns the region of each slab in order along the axis.
+    pub fn regions(&self) -> Vec<Region> {
+        let width = 1.0 / self.bins as Float;
+        (0..self.bins)
+            .map(|k| Region::Slab {
+                axis: self.axis,
+                lower: k as Float * width,
+                upper: (k + 1) as Float * width,
+            })
+            .collect()
+    }
+
+    /// Returns the target temperature of each slab in order along the axis.
+    pub fn targets(&self) -> Vec<Float> {
+        (0..self.bins)
+            .map(|k| {
+                let center = (k as Float + 0.5) / self.bins as Float;
+                self.lower + (self.upper - self.lower) * center
+            })
+            .collect()
+    }
+
+    /// Counts a step and rescales the velocities of each slab if it completes an interval.
+    ///
+    /// Slabs which are empty or have no kinetic energy are left untouched.
+    pub fn apply(&mut self, system: &mut System) {
+        self.step += 1;
+        if !self.step.is_multiple_of(self.interval) {
+            return;
+        }
+        for (region, target) in self.regions().iter().zip(self.targets()) {
+            let indices = region.indices(system);
+            if indices.is_empty() {
+                continue;
+            }
+            let temperature = Temperature.calculate_intrinsic(&subsystem(system, &indices));
+            if temperature > 0.0 {
+                let factor = Float::sqrt(target / temperature);
+                for &i in indices.iter() {
+                    system.velocities[i] *= factor;
+                }
+            }
+        }
+    }
 }
 
+/// Molecular dynamics propagation with an integrator, a thermostat and an optional barostat.
+///
+/// Additional thermostats can be bound to regions of the system, such as the hot and cold slabs
+/// of a thermal gradient, while atoms outside of every region evolve microcanonically.
 pub struct MolecularDynamics {
     integrator: Box<dyn Integrator>,
     thermostat: Box<dyn Thermostat>,
+    barostat: Box<dyn Barostat>,
+    com_fixer: Option<ComFixer>,
+    regions: Vec<(Region, Box<dyn Thermostat>)>,
+    temperature_gradient: Option<TemperatureGradient>,
 }
 
 impl MolecularDynamics {
+    /// Returns a new [`MolecularDynamics`] propagator.
     pub fn new<I, T>(integrator: I, thermostat: T) -> MolecularDynamics
     where
         I: Integrator + 'static,
@@ -24,19 +250,704 @@ impl MolecularDynamics {
         MolecularDynamics {
             integrator: Box::new(integrator),
             thermostat: Box::new(thermostat),
+            barostat: Box::new(NullBarostat),
+            com_fixer: None,
+            regions: Vec::new(),
+            temperature_gradient: None,
         }
     }
+
+    /// Controls the pressure with a barostat after each thermostatted integration step.
+    pub fn barostat<B: Barostat + 'static>(mut self, barostat: B) -> MolecularDynamics {
+        self.barostat = Box::new(barostat);
+        self
+    }
+
+    /// Removes the net momentum of the system with a [`ComFixer`] at the end of each step.
+    pub fn com_fixer(mut self, com_fixer: ComFixer) -> MolecularDynamics {
+        self.com_fixer = Some(com_fixer);
+        self
+    }
+
+    /// Imposes a linear temperature profile with a [`TemperatureGradient`] after the barostat in each step.
+    pub fn temperature_gradient(
+        mut self,
+        temperature_gradient: TemperatureGradient,
+    ) -> MolecularDynamics {
+        self.temperature_gradient = Some(temperature_gradient);
+        self
+    }
+
+    /// Controls the temperature of the atoms in `region` with an additional thermostat.
+    ///
+    /// The thermostat only sees the atoms in the region, so the main thermostat should be a
+    /// [`NullThermostat`](crate::thermostats::NullThermostat) unless it is meant to act on every atom.
+    pub fn region_thermostat<T: Thermostat + 'static>(
+        mut self,
+        region: Region,
+        thermostat: T,
+    ) -> MolecularDynamics {
+        self.regions.push((region, Box::new(thermostat)));
+        self
+    }
 }
 
 impl Propagator for MolecularDynamics {
     fn setup(&mut self, system: &mut System, potentials: &Potentials) {
         self.integrator.setup(system, potentials);
         self.thermostat.setup(system);
+        for (region, thermostat) in self.regions.iter_mut() {
+            let indices = region.indices(system);
+            thermostat.setup(&subsystem(system, &indices));
+        }
+        self.barostat.setup(system);
     }
 
     fn propagate(&mut self, system: &mut System, potentials: &Potentials) {
+        // membership is fixed for the whole step so each thermostat acts on the same atoms
+        let indices: Vec<Vec<usize>> = self
+            .regions
+            .iter()
+            .map(|(region, _)| region.indices(system))
+            .collect();
         self.thermostat.pre_integrate(system);
+        for ((_, thermostat), indices) in self.regions.iter_mut().zip(indices.iter()) {
+            apply_to_region(system, indices, |region| thermostat.pre_integrate(region));
+        }
         self.integrator.integrate(system, potentials);
+        // stochastic integrators may still displace the atoms of a planar system along z
+        if system.cell.is_planar() {
+            system.project_onto_plane();
+        }
         self.thermostat.post_integrate(system);
+        for ((_, thermostat), indices) in self.regions.iter_mut().zip(indices.iter()) {
+            apply_to_region(system, indices, |region| thermostat.post_integrate(region));
+        }
+        self.barostat.post_integrate(system, potentials);
+        if let Some(temperature_gradient) = &mut self.temperature_gradient {
+            temperature_gradient.apply(system);
+        }
+        if let Some(com_fixer) = &mut self.com_fixer {
+            com_fixer.apply(system);
+        }
+    }
+
+    fn state(&self) -> Vec<Vec<Float>> {
+        let mut state = vec![
+            self.integrator.state(),
+            self.thermostat.state(),
+            self.barostat.state(),
+            self.com_fixer
+                .map_or_else(Vec::new, |com_fixer| vec![com_fixer.step as Float]),
+        ];
+        state.extend(
+            self.regions
+                .iter()
+                .map(|(_, thermostat)| thermostat.state()),
+        );
+        if let Some(temperature_gradient) = &self.temperature_gradient {
+            state.push(vec![temperature_gradient.step as Float]);
+        }
+        state
+    }
+
+    fn restore(&mut self, state: &[Vec<Float>]) {
+        self.integrator.restore(&state[0]);
+        self.thermostat.restore(&state[1]);
+        // restarts written before barostats were supported have no barostat state
+        if let Some(state) = state.get(2) {
+            self.barostat.restore(state);
+        }
+        if let (Some(com_fixer), Some(state)) = (&mut self.com_fixer, state.get(3)) {
+            if let Some(&step) = state.first() {
+                com_fixer.step = step as usize;
+            }
+        }
+        for (k, (_, thermostat)) in self.regions.iter_mut().enumerate() {
+            if let Some(state) = state.get(4 + k) {
+                thermostat.restore(state);
+            }
+        }
+        let k = 4 + self.regions.len();
+        if let (Some(temperature_gradient), Some(state)) =
+            (&mut self.temperature_gradient, state.get(k))
+        {
+            if let Some(&step) = state.first() {
+                temperature_gradient.step = step as usize;
+            }
+        }
+    }
+}
+
+/// Metropolis Monte Carlo propagation by random displacements of single atoms.
+///
+/// Each step attempts as many moves as there are atoms. A move displaces a randomly chosen atom
+/// by up to `max_displacement` along each axis, within the plane of a planar cell, and is accepted
+/// with probability `min(1, exp(-dE / kT))` where `dE` is the change in the potential energy.
+/// Pair and Coulomb terms are found from the [`atom_energy`](Potentials::atom_energy) of the atom
+/// while many-body and external terms are recomputed for the whole system. Velocities are left untouched.
+///
+/// The neighbor lists are only refreshed between steps, so `max_displacement` is capped such that
+/// a single move spans at most half of the thinnest neighbor list thickness.
+pub struct MonteCarlo {
+    temperature: Float,
+    max_displacement: Float,
+    // target acceptance ratio and the number of attempts between adjustments of the step size
+    tuning: Option<(Float, usize)>,
+    accepted: usize,
+    attempted: usize,
+    window: (usize, usize),
+    seed: Option<u64>,
+    rng: VelvetRng,
+}
+
+impl MonteCarlo {
+    /// Returns a new [`MonteCarlo`] propagator.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature` - Temperature of the Metropolis criterion.
+    /// * `max_displacement` - Largest displacement of a move along each axis.
+    pub fn new(temperature: Float, max_displacement: Float) -> MonteCarlo {
+        MonteCarlo {
+            temperature,
+            max_displacement,
+            tuning: None,
+            accepted: 0,
+            attempted: 0,
+            window: (0, 0),
+            seed: None,
+            rng: rng::from_entropy(),
+        }
+    }
+
+    /// Seeds the random number generator to produce a reproducible trajectory.
+    ///
+    /// The generator is reseeded from `seed` and the number of attempted moves at every step
+    /// so a run resumed from a restart continues the same trajectory.
+    pub fn seed(mut self, seed: u64) -> MonteCarlo {
+        self.seed = Some(seed);
+        self.rng = rng::from_seed(seed);
+        self
+    }
+
+    /// Adjusts the maximum displacement every `interval` attempts to drive the acceptance ratio toward `target`.
+    ///
+    /// The displacement is scaled by the ratio of the acceptance over the last interval to the target,
+    /// limited to halving or doubling it at once. Tuning violates detailed balance so it belongs
+    /// to equilibration rather than production.
+    pub fn adaptive(mut self, target: Float, interval: usize) -> MonteCarlo {
+        assert!(interval > 0, "the tuning interval must be positive");
+        self.tuning = Some((target, interval));
+        self
+    }
+
+    /// Returns the current maximum displacement of a move.
+    pub fn max_displacement(&self) -> Float {
+        self.max_displacement
+    }
+
+    /// Returns the number of moves attempted so far.
+    pub fn attempted(&self) -> usize {
+        self.attempted
+    }
+
+    /// Returns the number of moves accepted so far.
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    /// Returns the fraction of all attempted moves which were accepted.
+    pub fn acceptance_ratio(&self) -> Float {
+        self.accepted as Float / self.attempted.max(1) as Float
+    }
+
+    // Returns every part of the potential energy which changes when atom `i` moves.
+    fn energy(system: &System, potentials: &Potentials, i: usize) -> Float {
+        let mut energy = potentials.atom_energy(system, i);
+        // many-body and external terms do not split over atoms so their totals are compared
+        if !potentials.many_body_metas.is_empty() {
+            energy += ManyBodyEnergy.calculate(system, potentials);
+        }
+        if !potentials.external_potentials.is_empty() {
+            energy += ExternalEnergy.calculate(system, potentials);
+        }
+        energy
+    }
+
+    // Attempts to displace atom `i` and returns `true` if the move is accepted.
+    fn attempt(&mut self, system: &mut System, potentials: &Potentials, i: usize) -> bool {
+        let d = self.max_displacement;
+        // an empty range cannot be sampled so the atom stays put
+        if d <= 0.0 {
+            return false;
+        }
+        let old_position = system.positions[i];
+        let old_energy = MonteCarlo::energy(system, potentials, i);
+        let mut displacement = Vector3::new(
+            self.rng.gen_range(-d, d),
+            self.rng.gen_range(-d, d),
+            self.rng.gen_range(-d, d),
+        );
+        if system.cell.is_planar() {
+            displacement[2] = 0.0;
+        }
+        system.positions[i] += displacement;
+        system.cell.wrap_vector(&mut system.positions[i]);
+        let delta = MonteCarlo::energy(system, potentials, i) - old_energy;
+        let accepted = delta <= 0.0
+            || self.rng.gen::<Float>() < Float::exp(-delta / (boltzmann() * self.temperature));
+        if !accepted {
+            system.positions[i] = old_position;
+        }
+        accepted
+    }
+
+    // Rescales the maximum displacement once a tuning interval is complete.
+    fn tune(&mut self, limit: Float) {
+        if let Some((target, interval)) = self.tuning {
+            let (accepted, attempted) = self.window;
+            if attempted < interval {
+                return;
+            }
+            let ratio = accepted as Float / attempted as Float;
+            self.max_displacement *= (ratio / target).clamp(0.5, 2.0);
+            self.max_displacement = self.max_displacement.min(limit);
+            self.window = (0, 0);
+        }
+    }
+}
+
+impl Propagator for MonteCarlo {
+    fn propagate(&mut self, system: &mut System, potentials: &Potentials) {
+        if let Some(seed) = self.seed {
+            self.rng = rng::from_seed(seed ^ self.attempted as u64);
+        }
+        // a move of length `d * sqrt(dimensions)` must stay within half of the neighbor list skin
+        let limit = match potentials.min_thickness() {
+            Some(thickness) => thickness / (2.0 * (system.cell.dimensions() as Float).sqrt()),
+            None => Float::INFINITY,
+        };
+        self.max_displacement = self.max_displacement.min(limit);
+        for _ in 0..system.size {
+            let i = self.rng.gen_range(0, system.size);
+            let accepted = self.attempt(system, potentials, i) as usize;
+            self.accepted += accepted;
+            self.attempted += 1;
+            self.window.0 += accepted;
+            self.window.1 += 1;
+            self.tune(limit);
+        }
+    }
+
+    fn state(&self) -> Vec<Vec<Float>> {
+        vec![vec![
+            self.max_displacement,
+            self.accepted as Float,
+            self.attempted as Float,
+            self.window.0 as Float,
+            self.window.1 as Float,
+        ]]
+    }
+
+    fn restore(&mut self, state: &[Vec<Float>]) {
+        if let [max_displacement, accepted, attempted, window_accepted, window_attempted] =
+            state[0][..]
+        {
+            self.max_displacement = max_displacement;
+            self.accepted = accepted as usize;
+            self.attempted = attempted as usize;
+            self.window = (window_accepted as usize, window_attempted as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        subsystem, ComFixer, MolecularDynamics, MonteCarlo, Propagator, Region, TemperatureGradient,
+    };
+    use crate::integrators::VelocityVerlet;
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::potentials::types::{DampedShiftedForce, HarmonicBond, LennardJones};
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::{KineticEnergy, PotentialEnergy};
+    use crate::properties::temperature::Temperature;
+    use crate::properties::{IntrinsicProperty, Property};
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use crate::thermostats::{Berendsen, NullThermostat};
+    use crate::velocity_distributions::{Boltzmann, VelocityDistribution};
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn com_fixer() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 3,
+            cell: Cell::cubic(100.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(50.0, 50.0, 50.0),
+                Vector3::new(53.8, 50.0, 50.0),
+                Vector3::new(50.0, 53.8, 50.0),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let drift = Vector3::new(0.01, -0.02, 0.005);
+        let mut fixer = ComFixer::new(3, true);
+        for step in 1..=6 {
+            // stand-in for numerical noise which pushes and spins the system
+            system.velocities[0] += drift;
+            system.velocities[1] += Vector3::new(0.0, 0.01, 0.0);
+            fixer.apply(&mut system);
+            let momentum = system.momentum().norm();
+            let angular_momentum = system.angular_momentum().norm();
+            if step % 3 == 0 {
+                assert_relative_eq!(momentum, 0.0, epsilon = 1e-5);
+                assert_relative_eq!(angular_momentum, 0.0, epsilon = 1e-4);
+            } else {
+                assert!(momentum > 0.1);
+                assert!(angular_momentum > 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn region_thermostats() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System::fcc_lattice(argon, 5.26, 3);
+        let size = system.size;
+        Boltzmann::new(40.0)
+            .remove_drift(true)
+            .seed(1234)
+            .apply(&mut system);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 0.5)
+            .build();
+
+        // each slab holds two lattice planes normal to x
+        let hot = Region::Slab {
+            axis: 0,
+            lower: 0.05,
+            upper: 0.4,
+        };
+        let cold = Region::Slab {
+            axis: 0,
+            lower: 0.55,
+            upper: 0.9,
+        };
+        let mut md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat)
+            .region_thermostat(hot.clone(), Berendsen::new(80.0, 1.0))
+            .region_thermostat(cold.clone(), Berendsen::new(20.0, 1.0));
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        md.setup(&mut system, &potentials);
+        let temperature = |system: &System, region: &Region| {
+            Temperature.calculate_intrinsic(&subsystem(system, &region.indices(system)))
+        };
+        // the atoms between the slabs are not thermostatted
+        let middle = Region::Group(
+            (0..size)
+                .filter(|i| !hot.indices(&system).contains(i) && !cold.indices(&system).contains(i))
+                .collect(),
+        );
+        // average over the second half of the run once the gradient has developed
+        let (mut hot_average, mut cold_average, mut middle_average) = (0.0, 0.0, 0.0);
+        for step in 0..200 {
+            md.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+            if step >= 100 {
+                hot_average += temperature(&system, &hot) / 100.0;
+                cold_average += temperature(&system, &cold) / 100.0;
+                middle_average += temperature(&system, &middle) / 100.0;
+            }
+        }
+
+        assert_eq!(hot.indices(&system).len(), 36);
+        assert_eq!(cold.indices(&system).len(), 36);
+        assert_relative_eq!(hot_average, 80.0, max_relative = 1e-2);
+        assert_relative_eq!(cold_average, 20.0, max_relative = 1e-2);
+        assert!(cold_average < middle_average && middle_average < hot_average);
+    }
+
+    #[test]
+    fn planar() {
+        let argon = Species::from_element(Element::Ar);
+        let (n, spacing) = (6, 3.8);
+        let mut positions = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                positions.push(Vector3::new(i as Float, j as Float, 1.0) * spacing);
+            }
+        }
+        let size = positions.len();
+        let width = n as Float * spacing;
+        // the cell is too thin along z for the cutoff unless it is planar
+        let mut system = System {
+            size,
+            cell: Cell::triclinic(width, width, 5.0, 90.0, 90.0, 90.0),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        system.make_planar();
+        assert_eq!(system.degrees_of_freedom(), 2 * size);
+        Boltzmann::new(40.0)
+            .remove_drift(true)
+            .seed(1234)
+            .apply(&mut system);
+        assert_relative_eq!(
+            Temperature.calculate_intrinsic(&system),
+            40.0,
+            max_relative = 1e-4
+        );
+
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 0.5)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let mut md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat);
+        md.setup(&mut system, &potentials);
+        for step in 1..=200 {
+            md.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+            assert!(system.positions.iter().all(|pos| pos[2] == 0.0));
+            assert!(system.velocities.iter().all(|vel| vel[2] == 0.0));
+        }
+        // the kinetic energy is shared by two degrees of freedom per atom
+        let kinetic_energy = KineticEnergy.calculate_intrinsic(&system);
+        let expected = 2.0 * kinetic_energy / (2.0 * size as Float * boltzmann());
+        assert!(kinetic_energy > 0.0);
+        assert_relative_eq!(
+            Temperature.calculate_intrinsic(&system),
+            expected,
+            max_relative = 1e-5
+        );
+    }
+
+    #[test]
+    fn temperature_gradient() {
+        let argon = Species::from_element(Element::Ar);
+        let a = 5.26;
+        let mut system = System::fcc_lattice(argon, a, 3);
+        // shifted by a quarter of the plane spacing so no lattice plane lies on a slab boundary
+        for position in system.positions.iter_mut() {
+            position[0] += 0.25 * a;
+        }
+        Boltzmann::new(50.0)
+            .remove_drift(true)
+            .seed(1234)
+            .apply(&mut system);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 0.5)
+            .build();
+
+        // each of the three slabs holds two lattice planes normal to x which exchange heat quickly
+        // so they are rescaled every other step
+        let gradient = TemperatureGradient::new(0, 3, 20.0, 80.0, 2);
+        let regions = gradient.regions();
+        let targets = gradient.targets();
+        assert_eq!(targets, vec![30.0, 50.0, 70.0]);
+        let mut md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat)
+            .temperature_gradient(gradient);
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        md.setup(&mut system, &potentials);
+        let mut averages = vec![0.0; regions.len()];
+        for step in 0..300 {
+            md.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+            if step >= 100 {
+                for (average, region) in averages.iter_mut().zip(regions.iter()) {
+                    let slab = subsystem(&system, &region.indices(&system));
+                    *average += Temperature.calculate_intrinsic(&slab) / 200.0;
+                }
+            }
+        }
+
+        for (region, (average, target)) in regions.iter().zip(averages.iter().zip(targets)) {
+            assert_eq!(region.indices(&system).len(), 36);
+            assert_relative_eq!(*average, target, max_relative = 0.1);
+        }
+        assert!(averages.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn monte_carlo_adaptive_step() {
+        let argon = Species::from_element(Element::Ar);
+        // liquid argon where long displacements almost always overlap a neighbor
+        let mut system = System::random_packing(Cell::cubic(17.5), argon, 100, 3.2, 1234).unwrap();
+        // a thick skin leaves room for long displacements between neighbor list updates
+        let thickness = 6.0;
+        let mut potentials = PotentialsBuilder::new()
+            .pair(
+                LennardJones::new(0.238, 3.4),
+                (argon, argon),
+                8.5,
+                thickness,
+            )
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let (target, initial) = (0.5, 4.0);
+        let mut mc = MonteCarlo::new(120.0, initial)
+            .seed(1234)
+            .adaptive(target, 200);
+        mc.setup(&mut system, &potentials);
+        mc.propagate(&mut system, &potentials);
+        potentials.update(&system, 1);
+        // the initial step is capped so a single move spans at most half of the skin
+        let limit = thickness / (2.0 * Float::sqrt(3.0));
+        assert_relative_eq!(mc.max_displacement(), limit);
+        assert!(mc.acceptance_ratio() < 0.2);
+
+        for step in 2..=50 {
+            mc.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+        }
+        assert!(mc.max_displacement() < 0.5 * limit);
+
+        // the tuned step size accepts close to the target fraction of moves
+        let (accepted, attempted) = (mc.accepted(), mc.attempted());
+        for step in 51..=100 {
+            mc.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+        }
+        let ratio = (mc.accepted() - accepted) as Float / (mc.attempted() - attempted) as Float;
+        assert_relative_eq!(ratio, target, epsilon = 0.05);
+        assert_eq!(mc.attempted(), 100 * system.size);
+        assert!(system.check_overlaps(2.5).is_empty());
+    }
+
+    #[test]
+    fn monte_carlo_energy_change() {
+        let cation = Species::new(39.9, 1.0);
+        let anion = Species::new(39.9, -1.0);
+        let mut system = System::random_packing(Cell::cubic(17.5), cation, 40, 3.2, 1234).unwrap();
+        system.species = (0..system.size)
+            .map(|i| if i % 2 == 0 { cation } else { anion })
+            .collect();
+        system.bonds = vec![[0, 1], [2, 3]];
+        let lj = LennardJones::new(0.238, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (cation, cation), 8.5, 1.0)
+            .pair(lj, (cation, anion), 8.5, 1.0)
+            .pair(lj, (anion, anion), 8.5, 1.0)
+            .coulomb(DampedShiftedForce::new(0.2, 8.0), 8.0, 1.0)
+            .external(HarmonicBond::new(10.0, 3.8))
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // the energy change of a single atom move includes every term of the potential energy
+        for &i in &[0, 1, 7] {
+            let old_total = PotentialEnergy.calculate(&system, &potentials);
+            let old_energy = MonteCarlo::energy(&system, &potentials, i);
+            system.positions[i] += Vector3::new(0.2, -0.1, 0.15);
+            let delta_total = PotentialEnergy.calculate(&system, &potentials) - old_total;
+            let delta = MonteCarlo::energy(&system, &potentials, i) - old_energy;
+            assert!(delta_total.abs() > 1e-3);
+            assert_relative_eq!(delta, delta_total, max_relative = 1e-3);
+        }
+    }
+
+    #[test]
+    fn monte_carlo_restart() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System::random_packing(Cell::cubic(17.5), argon, 50, 3.2, 1234).unwrap();
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let new_mc = || MonteCarlo::new(120.0, 0.4).seed(1234).adaptive(0.5, 30);
+        let mut mc = new_mc();
+        mc.setup(&mut system, &potentials);
+        for step in 1..=3 {
+            mc.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+        }
+
+        // a propagator restored from the state continues the same trajectory
+        let (mut resumed_system, state) = (system.clone(), mc.state());
+        let mut resumed = new_mc();
+        resumed.setup(&mut resumed_system, &potentials);
+        resumed.restore(&state);
+        let mut resumed_potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+            .build();
+        resumed_potentials.setup(&resumed_system).unwrap();
+        resumed_potentials.update(&resumed_system, 0);
+        for step in 4..=6 {
+            mc.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+            resumed.propagate(&mut resumed_system, &resumed_potentials);
+            resumed_potentials.update(&resumed_system, step);
+        }
+        assert_eq!(resumed_system.positions, system.positions);
+        assert_eq!(resumed.max_displacement(), mc.max_displacement());
+        assert_eq!(resumed.accepted(), mc.accepted());
+    }
+
+    #[test]
+    fn monte_carlo_planar() {
+        let argon = Species::from_element(Element::Ar);
+        let (n, spacing) = (6, 3.8);
+        let mut positions = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                positions.push(Vector3::new(i as Float, j as Float, 0.0) * spacing);
+            }
+        }
+        let size = positions.len();
+        let width = n as Float * spacing;
+        let mut system = System {
+            size,
+            cell: Cell::triclinic(width, width, 5.0, 90.0, 90.0, 90.0),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        system.make_planar();
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // moves stay in the plane and are capped at half of the skin over two dimensions
+        let mut mc = MonteCarlo::new(40.0, 2.0).seed(1234);
+        mc.setup(&mut system, &potentials);
+        for step in 1..=10 {
+            mc.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+        }
+        assert!(mc.accepted() > 0);
+        assert!(system.positions.iter().all(|pos| pos[2] == 0.0));
+        assert_relative_eq!(mc.max_displacement(), 1.0 / (2.0 * Float::sqrt(2.0)));
+
+        // a zero displacement rejects every move instead of sampling an empty range
+        let mut frozen = MonteCarlo::new(40.0, 0.0).seed(1234);
+        let positions = system.positions.clone();
+        frozen.propagate(&mut system, &potentials);
+        assert_eq!(frozen.accepted(), 0);
+        assert_eq!(frozen.attempted(), size);
+        assert_eq!(system.positions, positions);
     }
 }
@@ -0,0 +1,138 @@
+//! Time correlation functions of sampled observables.
+
+use std::collections::VecDeque;
+
+use nalgebra::{Matrix3, Vector3};
+
+use crate::internal::Float;
+
+/// Observable which can be correlated with a sample taken at another time.
+pub trait Correlate: Clone {
+    /// Returns the product of two samples which is averaged by the correlation function.
+    fn correlate(&self, other: &Self) -> Float;
+}
+
+impl Correlate for Float {
+    fn correlate(&self, other: &Self) -> Float {
+        self * other
+    }
+}
+
+impl Correlate for Vector3<Float> {
+    fn correlate(&self, other: &Self) -> Float {
+        self.dot(other)
+    }
+}
+
+impl Correlate for Matrix3<Float> {
+    fn correlate(&self, other: &Self) -> Float {
+        self.dot(other)
+    }
+}
+
+/// Buffers the history of an observable to evaluate its autocorrelation function.
+///
+/// The correlation at each lag is averaged over every time origin in the buffer.
+/// A window limits the buffer to the most recent samples.
+#[derive(Clone, Debug)]
+pub struct Autocorrelation<T: Correlate> {
+    max_lag: usize,
+    window: Option<usize>,
+    history: VecDeque<T>,
+}
+
+impl<T: Correlate> Autocorrelation<T> {
+    /// Returns a new [`Autocorrelation`] which spans lags from zero to `max_lag` samples.
+    pub fn new(max_lag: usize) -> Autocorrelation<T> {
+        Autocorrelation {
+            max_lag,
+            window: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Keeps only the most recent `window` samples.
+    pub fn window(mut self, window: usize) -> Autocorrelation<T> {
+        self.window = Some(window);
+        self
+    }
+
+    /// Records a sample.
+    pub fn push(&mut self, sample: T) {
+        if let Some(window) = self.window {
+            while self.history.len() >= window {
+                self.history.pop_front();
+            }
+        }
+        self.history.push_back(sample);
+    }
+
+    /// Returns the number of buffered samples.
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Returns true if no samples are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    /// Returns the autocorrelation function `<A(0)·A(t)>` for each lag.
+    pub fn calculate(&self) -> Vec<Float> {
+        let length = (self.max_lag + 1).min(self.history.len());
+        (0..length)
+            .map(|lag| {
+                let origins = self.history.len() - lag;
+                let sum: Float = (0..origins)
+                    .map(|t| self.history[t].correlate(&self.history[t + lag]))
+                    .sum();
+                sum / origins as Float
+            })
+            .collect()
+    }
+
+    /// Returns the autocorrelation function divided by its value at zero lag.
+    pub fn normalized(&self) -> Vec<Float> {
+        let acf = self.calculate();
+        match acf.first() {
+            Some(&zero) => acf.iter().map(|value| value / zero).collect(),
+            None => acf,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Autocorrelation;
+    use crate::internal::Float;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn sinusoid() {
+        let omega = 0.1;
+        let max_lag = 60;
+        let mut scalar = Autocorrelation::new(max_lag);
+        let mut vector = Autocorrelation::new(max_lag).window(500);
+        for t in 0..2000 {
+            let phase = omega * t as Float;
+            scalar.push(Float::cos(phase));
+            vector.push(Vector3::new(Float::cos(phase), Float::sin(phase), 0.0));
+        }
+        assert_eq!(scalar.len(), 2000);
+        assert_eq!(vector.len(), 500);
+
+        // a rotating vector correlates exactly as a cosine of the lag
+        let acf = vector.normalized();
+        assert_eq!(acf.len(), max_lag + 1);
+        for (lag, value) in acf.iter().enumerate() {
+            assert_relative_eq!(*value, Float::cos(omega * lag as Float), epsilon = 1e-3);
+        }
+
+        // a scalar cosine does so on average over many periods
+        let acf = scalar.normalized();
+        for (lag, value) in acf.iter().enumerate() {
+            assert_relative_eq!(*value, Float::cos(omega * lag as Float), epsilon = 2e-2);
+        }
+    }
+}
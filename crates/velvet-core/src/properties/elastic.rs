@@ -0,0 +1,129 @@
+//! Elastic response of the system to small deformations.
+
+use nalgebra::{Matrix3, Matrix6};
+
+use crate::internal::Float;
+use crate::potentials::Potentials;
+use crate::properties::pressure::StressTensor;
+use crate::properties::Property;
+use crate::system::System;
+
+// Pairs of cartesian indices of each Voigt component in the order xx, yy, zz, yz, xz, xy.
+const VOIGT: [(usize, usize); 6] = [(0, 0), (1, 1), (2, 2), (1, 2), (0, 2), (0, 1)];
+
+/// Returns the elastic stiffness matrix in Voigt notation from central differences of the stress.
+///
+/// Each independent Voigt component of strain is applied with a magnitude of `+strain` and `-strain`
+/// to a copy of the system and `C_ij` is the change in tensile stress component `i` per unit strain `j`.
+/// Shear components use engineering strain so `C_44` is the shear modulus of a cubic crystal.
+/// Atoms are deformed affinely without internal relaxation and the result is in units of energy per volume.
+///
+/// # Panics
+///
+/// Panics if the potentials can not be set up on a strained copy of the system.
+///
+/// # Examples
+///
+/// ```
+/// use velvet_core::prelude::*;
+/// use nalgebra::Vector3;
+///
+/// let argon = Species::from_element(Element::Ar);
+/// let system = System {
+///     size: 2,
+///     cell: Cell::cubic(10.0),
+///     species: vec![argon; 2],
+///     positions: vec![Vector3::new(2.0, 2.0, 2.0), Vector3::new(5.8, 2.0, 2.0)],
+///     velocities: vec![Vector3::zeros(); 2],
+///     bonds: Vec::new(),
+///     angles: Vec::new(),
+///     dihedrals: Vec::new(),
+/// };
+/// let mut potentials = PotentialsBuilder::new()
+///     .pair(LennardJones::new(0.238, 3.4), (argon, argon), 4.5, 0.0)
+///     .build();
+///
+/// // a dimer along x only resists strains with an x component
+/// let stiffness = elastic_constants(&system, &mut potentials, 1e-3);
+/// assert!(stiffness[(0, 0)] != 0.0);
+/// assert_eq!(stiffness[(1, 1)], 0.0);
+/// ```
+pub fn elastic_constants(
+    system: &System,
+    potentials: &mut Potentials,
+    strain: Float,
+) -> Matrix6<Float> {
+    let mut stiffness = Matrix6::zeros();
+    for (j, &(a, b)) in VOIGT.iter().enumerate() {
+        let mut tensor = Matrix3::zeros();
+        // engineering shear strain is split between both symmetric entries
+        let magnitude = if a == b { strain } else { strain / 2.0 };
+        tensor[(a, b)] = magnitude;
+        tensor[(b, a)] = magnitude;
+        let forward = strained_stress(system, potentials, tensor);
+        let backward = strained_stress(system, potentials, -tensor);
+        for (i, &(c, d)) in VOIGT.iter().enumerate() {
+            // the stress tensor is positive under compression
+            stiffness[(i, j)] = -(forward[(c, d)] - backward[(c, d)]) / (2.0 * strain);
+        }
+    }
+    // restore the neighbor lists of the undeformed system
+    potentials
+        .setup(system)
+        .unwrap_or_else(|message| panic!("{}", message));
+    potentials.update(system, 0);
+    stiffness
+}
+
+// Returns the stress tensor of a copy of the system deformed by `strain`.
+fn strained_stress(
+    system: &System,
+    potentials: &mut Potentials,
+    strain: Matrix3<Float>,
+) -> Matrix3<Float> {
+    let mut strained = system.clone();
+    strained.apply_strain(strain);
+    potentials
+        .setup(&strained)
+        .unwrap_or_else(|message| panic!("{}", message));
+    potentials.update(&strained, 0);
+    StressTensor.calculate(&strained, potentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::elastic_constants;
+    use crate::potentials::types::LennardJones;
+    use crate::potentials::PotentialsBuilder;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+
+    #[test]
+    fn cubic_symmetry() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System::fcc_lattice(argon, 5.26, 3);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.5, 0.0)
+            .build();
+        let c = elastic_constants(&system, &mut potentials, 1e-3);
+
+        let (c11, c12, c44) = (c[(0, 0)], c[(0, 1)], c[(3, 3)]);
+        assert!(c11 > c12 && c12 > 0.0 && c44 > 0.0);
+        let tolerance = 1e-3 * c11;
+        for i in 0..3 {
+            assert_relative_eq!(c[(i, i)], c11, epsilon = tolerance);
+            assert_relative_eq!(c[(i + 3, i + 3)], c44, epsilon = tolerance);
+            for j in 0..3 {
+                if i != j {
+                    assert_relative_eq!(c[(i, j)], c12, epsilon = tolerance);
+                    assert_relative_eq!(c[(i + 3, j + 3)], 0.0, epsilon = tolerance);
+                }
+                // normal and shear components are uncoupled
+                assert_relative_eq!(c[(i, j + 3)], 0.0, epsilon = tolerance);
+                assert_relative_eq!(c[(i + 3, j)], 0.0, epsilon = tolerance);
+            }
+        }
+    }
+}
@@ -4,9 +4,10 @@
 use rayon::prelude::*;
 
 use crate::internal::Float;
-use crate::potentials::Potentials;
 use crate::potentials::coulomb::CoulombPotentialMeta;
+use crate::potentials::external::BondedTerm;
 use crate::potentials::pair::PairPotentialMeta;
+use crate::potentials::Potentials;
 use crate::properties::{IntrinsicProperty, Property};
 use crate::system::System;
 
@@ -15,18 +16,30 @@ use crate::system::System;
 pub struct CoulombicEnergy;
 
 impl CoulombicEnergy {
-    fn calculate_inner(&self, meta: &CoulombPotentialMeta, system: &System, i: usize, j: usize) -> Float {
-        let pos_i = system.positions[i];
+    fn calculate_inner(
+        &self,
+        meta: &CoulombPotentialMeta,
+        system: &System,
+        i: usize,
+        j: usize,
+    ) -> Float {
         let qi = system.species[i].charge();
-        let pos_j = system.positions[j];
         let qj = system.species[j].charge();
-        let r = system.cell.distance(&pos_i, &pos_j);
-        if r < meta.cutoff {
+        let r = meta.separation(system, i, j).norm();
+        if meta.interacts(r) {
             meta.potential.energy(qi, qj, r)
         } else {
             0.0
         }
     }
+
+    fn self_energy(&self, meta: &CoulombPotentialMeta, system: &System) -> Float {
+        system
+            .species
+            .iter()
+            .map(|species| meta.potential.self_energy(species.charge()))
+            .sum()
+    }
 }
 
 impl Property for CoulombicEnergy {
@@ -36,12 +49,14 @@ impl Property for CoulombicEnergy {
     fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
         match &potentials.coulomb_meta {
             None => 0.0,
-            Some(meta) => meta
-                .selection
-                .indices()
-                .map(|&[i, j]| {
-                    self.calculate_inner(meta, system, i, j)
-                }).sum()
+            Some(meta) => {
+                let pair_energy: Float = meta
+                    .selection
+                    .indices()
+                    .map(|&[i, j]| self.calculate_inner(meta, system, i, j))
+                    .sum();
+                pair_energy + self.self_energy(meta, system)
+            }
         }
     }
 
@@ -49,12 +64,14 @@ impl Property for CoulombicEnergy {
     fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
         match &potentials.coulomb_meta {
             None => 0.0,
-            Some(meta) => meta
-                .selection
-                .par_indices()
-                .map(|&[i, j]| {
-                    self.calculate_inner(meta, system, i, j)
-                }).sum()
+            Some(meta) => {
+                let pair_energy: Float = meta
+                    .selection
+                    .par_indices()
+                    .map(|&[i, j]| self.calculate_inner(meta, system, i, j))
+                    .sum();
+                pair_energy + self.self_energy(meta, system)
+            }
         }
     }
 
@@ -63,20 +80,86 @@ impl Property for CoulombicEnergy {
     }
 }
 
+/// Potential energy due to external potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct ExternalEnergy;
+
+impl ExternalEnergy {
+    fn per_atom(&self, system: &System, potentials: &Potentials) -> Vec<Float> {
+        potentials.external_potentials.iter().fold(
+            vec![0 as Float; system.size],
+            |accumulator, potential| {
+                accumulator
+                    .iter()
+                    .zip(potential.energies(system).iter())
+                    .map(|(a, b)| a + b)
+                    .collect()
+            },
+        )
+    }
+}
+
+impl Property for ExternalEnergy {
+    type Res = Float;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        self.per_atom(system, potentials).iter().sum()
+    }
+
+    fn name(&self) -> String {
+        "external_energy".to_string()
+    }
+}
+
+/// Potential energy due to many-body potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct ManyBodyEnergy;
+
+impl ManyBodyEnergy {
+    fn per_atom(&self, system: &System, potentials: &Potentials) -> Vec<Float> {
+        potentials.many_body_metas.iter().fold(
+            vec![0 as Float; system.size],
+            |accumulator, meta| {
+                let energies = meta.potential.energies(system, &meta.pairs(system));
+                accumulator
+                    .iter()
+                    .zip(energies.iter())
+                    .map(|(a, b)| a + b)
+                    .collect()
+            },
+        )
+    }
+}
+
+impl Property for ManyBodyEnergy {
+    type Res = Float;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        self.per_atom(system, potentials).iter().sum()
+    }
+
+    fn name(&self) -> String {
+        "many_body_energy".to_string()
+    }
+}
+
 /// Potential energy due to pairwise potentials.
 #[derive(Clone, Copy, Debug)]
 pub struct PairEnergy;
 
 impl PairEnergy {
-    fn calculate_inner(&self, meta: &PairPotentialMeta, system: &System, i: usize, j: usize) -> Float {
-        let pos_i = system.positions[i];
-        let pos_j = system.positions[j];
-        let r = system.cell.distance(&pos_i, &pos_j);
-        if r < meta.cutoff {
-            meta.potential.energy(r)
-        } else {
-            0.0
-        }
+    fn calculate_inner(
+        &self,
+        meta: &PairPotentialMeta,
+        system: &System,
+        i: usize,
+        j: usize,
+    ) -> Float {
+        let mut energy = 0.0;
+        meta.for_each_image(system, i, j, |_, r, share| {
+            energy += share * meta.potential.energy(r);
+        });
+        energy
     }
 }
 
@@ -89,12 +172,12 @@ impl Property for PairEnergy {
             .pair_metas
             .iter()
             .map(|meta| -> Float {
-                meta.selection
-                    .indices()
-                    .map(|&[i, j]| -> Float {
-                        self.calculate_inner(meta, system, i, j)
-                    }).sum()
-            }).sum()
+                meta.neighbors
+                    .pairs()
+                    .map(|[i, j]| -> Float { self.calculate_inner(meta, system, i, j) })
+                    .sum()
+            })
+            .sum()
     }
 
     #[cfg(feature = "rayon")]
@@ -103,12 +186,16 @@ impl Property for PairEnergy {
             .pair_metas
             .iter()
             .map(|meta| -> Float {
-                meta.selection
-                    .par_indices()
-                    .map(|&[i, j]| -> Float {
-                        self.calculate_inner(meta, system, i, j)
-                    }).sum()
-            }).sum()
+                meta.neighbors
+                    .par_rows()
+                    .map(|(i, row)| -> Float {
+                        row.iter()
+                            .map(|&j| self.calculate_inner(meta, system, i, j))
+                            .sum()
+                    })
+                    .sum()
+            })
+            .sum()
     }
 
     fn name(&self) -> String {
@@ -125,8 +212,10 @@ impl Property for PotentialEnergy {
 
     fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
         let coulomb_energy = CoulombicEnergy.calculate(system, potentials);
+        let external_energy = ExternalEnergy.calculate(system, potentials);
+        let many_body_energy = ManyBodyEnergy.calculate(system, potentials);
         let pair_energy = PairEnergy.calculate(system, potentials);
-        coulomb_energy + pair_energy
+        coulomb_energy + external_energy + many_body_energy + pair_energy
     }
 
     fn name(&self) -> String {
@@ -134,6 +223,101 @@ impl Property for PotentialEnergy {
     }
 }
 
+/// Potential energy split into the thermodynamic keywords reported by LAMMPS.
+///
+/// Distance and angle restraints are reported as bonds and angles, matching the harmonic bond and angle
+/// styles of LAMMPS, while any other external potential is reported separately as LAMMPS would report a fix.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LammpsEnergies {
+    /// Van der Waals energy of the pair and many-body potentials.
+    pub evdwl: Float,
+    /// Coulombic energy including the self energy of each charge.
+    pub ecoul: Float,
+    /// Total energy of the pairwise interactions which is the sum of `evdwl`, `ecoul` and `etail`.
+    pub epair: Float,
+    /// Energy of the bonds.
+    pub ebond: Float,
+    /// Energy of the angles.
+    pub eangle: Float,
+    /// Energy of the dihedrals.
+    pub edihed: Float,
+    /// Long range tail correction which is always zero because pair potentials are truncated without one.
+    pub etail: Float,
+    /// Energy of the external potentials which are not bonded terms.
+    pub external: Float,
+}
+
+impl LammpsEnergies {
+    /// Returns the total potential energy of every term.
+    pub fn total(&self) -> Float {
+        self.epair + self.ebond + self.eangle + self.edihed + self.external
+    }
+}
+
+/// Returns the potential energy of the system split the way LAMMPS reports it for line by line validation.
+pub fn lammps_style_breakdown(system: &System, potentials: &Potentials) -> LammpsEnergies {
+    let mut energies = LammpsEnergies {
+        evdwl: PairEnergy.calculate(system, potentials)
+            + ManyBodyEnergy.calculate(system, potentials),
+        ecoul: CoulombicEnergy.calculate(system, potentials),
+        ..LammpsEnergies::default()
+    };
+    energies.epair = energies.evdwl + energies.ecoul + energies.etail;
+    for potential in &potentials.external_potentials {
+        let energy: Float = potential.energies(system).iter().sum();
+        match potential.bonded_term() {
+            Some(BondedTerm::Bond) => energies.ebond += energy,
+            Some(BondedTerm::Angle) => energies.eangle += energy,
+            Some(BondedTerm::Dihedral) => energies.edihed += energy,
+            None => energies.external += energy,
+        }
+    }
+    energies
+}
+
+/// Potential energy of each atom in the system.
+///
+/// Each atom is assigned half of the energy of every interaction it participates in
+/// such that the sum over all atoms is equal to the [`PotentialEnergy`].
+#[derive(Clone, Copy, Debug)]
+pub struct PerAtomEnergy;
+
+impl Property for PerAtomEnergy {
+    type Res = Vec<Float>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let mut energies = ManyBodyEnergy.per_atom(system, potentials);
+        for (energy, external) in energies
+            .iter_mut()
+            .zip(ExternalEnergy.per_atom(system, potentials))
+        {
+            *energy += external;
+        }
+        if let Some(meta) = &potentials.coulomb_meta {
+            system.species.iter().enumerate().for_each(|(i, species)| {
+                energies[i] += meta.potential.self_energy(species.charge());
+            });
+            meta.selection.indices().for_each(|&[i, j]| {
+                let energy = CoulombicEnergy.calculate_inner(meta, system, i, j);
+                energies[i] += 0.5 * energy;
+                energies[j] += 0.5 * energy;
+            });
+        }
+        potentials.pair_metas.iter().for_each(|meta| {
+            meta.neighbors.pairs().for_each(|[i, j]| {
+                let energy = PairEnergy.calculate_inner(meta, system, i, j);
+                energies[i] += 0.5 * energy;
+                energies[j] += 0.5 * energy;
+            })
+        });
+        energies
+    }
+
+    fn name(&self) -> String {
+        "per_atom_energy".to_string()
+    }
+}
+
 /// Kinetic energy of the whole system
 #[derive(Clone, Copy, Debug)]
 pub struct KineticEnergy;
@@ -146,9 +330,7 @@ impl IntrinsicProperty for KineticEnergy {
             .species
             .iter()
             .zip(system.velocities.iter())
-            .map(|(species, vel)| {
-                0.5 * species.mass() * vel.norm_squared()
-            })
+            .map(|(species, vel)| 0.5 * species.mass() * vel.norm_squared())
             .sum();
         kinetic_energy
     }
@@ -158,6 +340,54 @@ impl IntrinsicProperty for KineticEnergy {
     }
 }
 
+/// Kinetic energy of the active atoms in the system.
+///
+/// Inactive atoms, such as frozen or constrained ones, contribute nothing regardless of their velocity.
+#[derive(Clone, Debug)]
+pub struct MaskedKineticEnergy {
+    active: Vec<bool>,
+}
+
+impl MaskedKineticEnergy {
+    /// Returns a new [`MaskedKineticEnergy`] property.
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - Whether each atom in the system is active.
+    pub fn new(active: Vec<bool>) -> MaskedKineticEnergy {
+        MaskedKineticEnergy { active }
+    }
+
+    /// Returns the number of active atoms.
+    pub fn active_count(&self) -> usize {
+        self.active.iter().filter(|&&active| active).count()
+    }
+}
+
+impl IntrinsicProperty for MaskedKineticEnergy {
+    type Res = Float;
+
+    fn calculate_intrinsic(&self, system: &System) -> <Self as IntrinsicProperty>::Res {
+        assert_eq!(
+            self.active.len(),
+            system.size,
+            "mask must have one entry per atom"
+        );
+        system
+            .species
+            .iter()
+            .zip(system.velocities.iter())
+            .zip(self.active.iter())
+            .filter(|(_, &active)| active)
+            .map(|((species, vel), _)| 0.5 * species.mass() * vel.norm_squared())
+            .sum()
+    }
+
+    fn name(&self) -> String {
+        "masked_kinetic_energy".to_string()
+    }
+}
+
 /// Sum of potential and kinetic energy.
 #[derive(Clone, Copy, Debug)]
 pub struct TotalEnergy;
@@ -175,3 +405,146 @@ impl Property for TotalEnergy {
         "total_energy".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{lammps_style_breakdown, CoulombicEnergy, PerAtomEnergy, PotentialEnergy};
+    use crate::internal::consts::coulomb;
+    use crate::internal::Float;
+    use crate::potentials::types::{
+        AngleRestraint, DampedShiftedForce, DistanceRestraint, LennardJones, PositionRestraint,
+    };
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn per_atom_energy() {
+        // four argon atoms on the corners of a square
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 4,
+            cell: Cell::cubic(30.0),
+            species: vec![argon; 4],
+            positions: vec![
+                Vector3::new(10.0, 10.0, 10.0),
+                Vector3::new(13.8, 10.0, 10.0),
+                Vector3::new(13.8, 13.8, 10.0),
+                Vector3::new(10.0, 13.8, 10.0),
+            ],
+            velocities: vec![Vector3::zeros(); 4],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let lj = LennardJones::new(4.184, 3.4);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let energies = PerAtomEnergy.calculate(&system, &potentials);
+        assert_eq!(energies.len(), system.size);
+        for energy in &energies {
+            assert_relative_eq!(*energy, energies[0], epsilon = 1e-5);
+        }
+        let total = PotentialEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(energies.iter().sum::<Float>(), total, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn damped_shifted_force_madelung() {
+        // rock salt crystal of 5x5x5 conventional unit cells
+        let a = 5.64;
+        let n = 5;
+        let sodium = Species::new(22.99, 1.0);
+        let chlorine = Species::new(35.45, -1.0);
+        let mut species = Vec::new();
+        let mut positions = Vec::new();
+        for x in 0..2 * n {
+            for y in 0..2 * n {
+                for z in 0..2 * n {
+                    if (x + y + z) % 2 == 0 {
+                        species.push(sodium);
+                    } else {
+                        species.push(chlorine);
+                    }
+                    positions.push(Vector3::new(x as Float, y as Float, z as Float) * (a / 2.0));
+                }
+            }
+        }
+        let size = positions.len();
+        let system = System {
+            size,
+            cell: Cell::cubic(a * n as Float),
+            species,
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let cutoff = 12.0;
+        let dsf = DampedShiftedForce::new(0.2, cutoff);
+        let mut potentials = PotentialsBuilder::new().coulomb(dsf, cutoff, 1.0).build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // The Ewald sum of a rock salt lattice reduces to the Madelung energy. With
+        // alpha = 0.2 and a 12 angstrom cutoff DSF agrees with it to about 0.1% so a 0.5%
+        // tolerance leaves room for single precision round off.
+        let madelung = 1.747565;
+        let reference = -(size as Float) / 2.0 * madelung * coulomb() / (a / 2.0);
+        let energy = CoulombicEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(energy, reference, max_relative = 0.005);
+    }
+
+    #[test]
+    fn lammps_style_breakdown_sums_to_total() {
+        // bent argon trimer with a harmonic bond and angle and one tethered atom
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(30.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(10.0, 10.0, 10.0),
+                Vector3::new(13.9, 10.0, 10.0),
+                Vector3::new(15.2, 13.5, 10.0),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(4.184, 3.4), (argon, argon), 8.5, 1.0)
+            .external(DistanceRestraint::new(0, 1, 10.0, 3.8))
+            .external(AngleRestraint::new(0, 1, 2, 20.0, 2.0))
+            .external(PositionRestraint::new(
+                1.0,
+                vec![(2, Vector3::new(15.0, 13.5, 10.0))],
+            ))
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let energies = lammps_style_breakdown(&system, &potentials);
+        assert!(energies.evdwl != 0.0);
+        assert_eq!(energies.epair, energies.evdwl);
+        assert_relative_eq!(energies.ebond, 10.0 * 0.01, epsilon = 1e-4);
+        assert!(energies.eangle > 0.0);
+        assert_relative_eq!(energies.external, 0.04, epsilon = 1e-4);
+        assert_eq!(energies.ecoul, 0.0);
+        assert_eq!(energies.edihed, 0.0);
+        assert_eq!(energies.etail, 0.0);
+        let total = PotentialEnergy.calculate(&system, &potentials);
+        assert_relative_eq!(energies.total(), total, epsilon = 1e-5);
+    }
+}
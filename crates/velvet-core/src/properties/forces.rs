@@ -3,12 +3,14 @@
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
-use nalgebra::Vector3;
+use nalgebra::{Matrix3, Vector3};
 
 use crate::internal::Float;
-use crate::potentials::Potentials;
 use crate::potentials::coulomb::CoulombPotentialMeta;
 use crate::potentials::pair::PairPotentialMeta;
+#[cfg(feature = "simd")]
+use crate::potentials::pair::LANES;
+use crate::potentials::Potentials;
 use crate::properties::Property;
 use crate::system::System;
 
@@ -17,14 +19,20 @@ use crate::system::System;
 pub struct CoulombicForces;
 
 impl CoulombicForces {
-    fn calculate_inner(&self, mut accumulator: Vec<Vector3<Float>>, meta: &CoulombPotentialMeta, system: &System, i: usize, j: usize) -> Vec<Vector3<Float>> {
-        let pos_i = system.positions[i];
+    fn calculate_inner(
+        &self,
+        mut accumulator: Vec<Vector3<Float>>,
+        meta: &CoulombPotentialMeta,
+        system: &System,
+        i: usize,
+        j: usize,
+    ) -> Vec<Vector3<Float>> {
         let qi = system.species[i].charge();
-        let pos_j = system.positions[j];
         let qj = system.species[j].charge();
-        let r = system.cell.distance(&pos_i, &pos_j);
-        if r < meta.cutoff {
-            let dir = system.cell.direction(&pos_i, &pos_j);
+        let r_ij = meta.separation(system, i, j);
+        let r = r_ij.norm();
+        if meta.interacts(r) {
+            let dir = -r_ij / r;
             let force = meta.potential.force(qi, qj, r) * dir;
             accumulator[i] += force;
             accumulator[j] -= force;
@@ -41,10 +49,8 @@ impl Property for CoulombicForces {
             None => vec![Vector3::zeros(); system.size],
             Some(meta) => meta.selection.indices().fold(
                 vec![Vector3::zeros(); system.size],
-                |accumulator, &[i, j]| {
-                    self.calculate_inner(accumulator, meta, system, i, j)
-                }
-            )
+                |accumulator, &[i, j]| self.calculate_inner(accumulator, meta, system, i, j),
+            ),
         }
     }
 
@@ -53,44 +59,160 @@ impl Property for CoulombicForces {
     }
 }
 
+/// Force acting on each atom in the system due to external potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct ExternalForces;
+
+impl Property for ExternalForces {
+    type Res = Vec<Vector3<Float>>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let mut forces = vec![Vector3::zeros(); system.size];
+        potentials.external_potentials.iter().for_each(|potential| {
+            forces
+                .iter_mut()
+                .zip(potential.forces(system))
+                .for_each(|(force, external)| *force += external)
+        });
+        forces
+    }
+
+    fn name(&self) -> String {
+        "external_forces".to_string()
+    }
+}
+
+/// Force acting on each atom in the system due to many-body potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct ManyBodyForces;
+
+impl Property for ManyBodyForces {
+    type Res = Vec<Vector3<Float>>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let mut forces = vec![Vector3::zeros(); system.size];
+        potentials.many_body_metas.iter().for_each(|meta| {
+            let pairs = meta.pairs(system);
+            let pair_forces = meta.potential.pair_forces(system, &pairs);
+            pairs
+                .iter()
+                .zip(pair_forces.iter())
+                .for_each(|(pair, f_ij)| {
+                    let [i, j] = pair.indices;
+                    forces[i] += f_ij;
+                    forces[j] -= f_ij;
+                })
+        });
+        forces
+    }
+
+    fn name(&self) -> String {
+        "many_body_forces".to_string()
+    }
+}
+
 /// Force acting on each atom in the system due to pairwise potentials.
 #[derive(Clone, Copy, Debug)]
 pub struct PairForces;
 
 impl PairForces {
+    // Adds the forces between atom `i` and each interacting image of atom `j`.
+    #[inline]
+    fn accumulate_pair(
+        &self,
+        accumulator: &mut [Vector3<Float>],
+        meta: &PairPotentialMeta,
+        system: &System,
+        i: usize,
+        j: usize,
+    ) {
+        meta.for_each_image(system, i, j, |r_ij, r, _| {
+            let force = meta.potential.force(r) / r * r_ij;
+            accumulator[i] += force;
+            accumulator[j] -= force;
+        });
+    }
+
+    // Returns the forces of a single row of the neighbor list.
+    #[cfg(not(feature = "simd"))]
+    fn accumulate_row(
+        &self,
+        mut accumulator: Vec<Vector3<Float>>,
+        meta: &PairPotentialMeta,
+        system: &System,
+        i: usize,
+        row: &[usize],
+    ) -> Vec<Vector3<Float>> {
+        for &j in row {
+            self.accumulate_pair(&mut accumulator, meta, system, i, j);
+        }
+        accumulator
+    }
+
+    // Returns the forces of a single row of the neighbor list evaluated `LANES` neighbors at a time.
+    #[cfg(feature = "simd")]
+    fn accumulate_row(
+        &self,
+        mut accumulator: Vec<Vector3<Float>>,
+        meta: &PairPotentialMeta,
+        system: &System,
+        i: usize,
+        row: &[usize],
+    ) -> Vec<Vector3<Float>> {
+        // the lanes hold a single image of each neighbor
+        if meta.periodic_images {
+            for &j in row {
+                self.accumulate_pair(&mut accumulator, meta, system, i, j);
+            }
+            return accumulator;
+        }
+        let pos_i = system.positions[i];
+        for chunk in row.chunks(LANES) {
+            // gather the separations of the chunk into lanes and pad the remainder with a valid distance
+            let mut displacements = [Vector3::zeros(); LANES];
+            for (displacement, &j) in displacements.iter_mut().zip(chunk) {
+                *displacement = system.positions[j] - pos_i;
+                system.cell.vector_image(displacement);
+            }
+            let mut r = [0.0; LANES];
+            for (r, displacement) in r.iter_mut().zip(&displacements) {
+                *r = displacement.norm();
+            }
+            let first = r[0];
+            r[chunk.len()..].iter_mut().for_each(|r| *r = first);
+            let forces = meta.potential.force_lanes(&r);
+            // scatter the forces of the lanes within the cutoff
+            for (lane, &j) in chunk.iter().enumerate() {
+                if r[lane] < meta.cutoff {
+                    let force = forces[lane] / r[lane] * displacements[lane];
+                    accumulator[i] += force;
+                    accumulator[j] -= force;
+                }
+            }
+        }
+        accumulator
+    }
+
     #[cfg(not(feature = "rayon"))]
     fn calculate_inner(&self, meta: &PairPotentialMeta, system: &System) -> Vec<Vector3<Float>> {
-        meta.selection.indices().fold(vec![Vector3::zeros(); system.size], |mut accumulator, &[i, j]| {
-            let pos_i = system.positions[i];
-            let pos_j = system.positions[j];
-            let r = system.cell.distance(&pos_i, &pos_j);
-            if r < meta.cutoff {
-                let dir = system.cell.direction(&pos_i, &pos_j);
-                let force = meta.potential.force(r) * dir;
-                accumulator[i] += force;
-                accumulator[j] -= force;
-            }
-            accumulator
-        })
+        meta.neighbors.rows().fold(
+            vec![Vector3::zeros(); system.size],
+            |accumulator, (i, row)| self.accumulate_row(accumulator, meta, system, i, row),
+        )
     }
 
     #[cfg(feature = "rayon")]
-    fn calculate_inner(&self, meta: &PairPotentialMeta, system: &System) -> Vec<Vector3<Float>>{
-        meta.selection.par_indices().fold(|| vec![Vector3::zeros(); system.size], |mut accumulator, &[i, j]| {
-            let pos_i = system.positions[i];
-            let pos_j = system.positions[j];
-            let r = system.cell.distance(&pos_i, &pos_j);
-            if r < meta.cutoff {
-                let dir = system.cell.direction(&pos_i, &pos_j);
-                let force = meta.potential.force(r) * dir;
-                accumulator[i] += force;
-                accumulator[j] -= force;
-            }
-            accumulator
-        })
-        .reduce(|| vec![Vector3::zeros(); system.size], |a, b| {
-            a.iter().zip(b.iter()).map(|(_a, _b)| _a + _b).collect()
-        })
+    fn calculate_inner(&self, meta: &PairPotentialMeta, system: &System) -> Vec<Vector3<Float>> {
+        meta.neighbors
+            .par_rows()
+            .fold(
+                || vec![Vector3::zeros(); system.size],
+                |accumulator, (i, row)| self.accumulate_row(accumulator, meta, system, i, row),
+            )
+            .reduce(
+                || vec![Vector3::zeros(); system.size],
+                |a, b| a.iter().zip(b.iter()).map(|(_a, _b)| _a + _b).collect(),
+            )
     }
 }
 
@@ -124,11 +246,22 @@ impl Property for Forces {
 
     fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
         let coulomb_forces = CoulombicForces.calculate(system, potentials);
+        let external_forces = ExternalForces.calculate(system, potentials);
+        let many_body_forces = ManyBodyForces.calculate(system, potentials);
         let pair_forces = PairForces.calculate(system, potentials);
         coulomb_forces
             .iter()
+            .zip(external_forces.iter())
+            .zip(many_body_forces.iter())
             .zip(pair_forces.iter())
-            .map(|(coul, pair)| coul + pair)
+            .map(|(((coul, external), many_body), pair)| {
+                let mut force = coul + external + many_body + pair;
+                // atoms of a planar system cannot move along z
+                if system.cell.is_planar() {
+                    force[2] = 0.0;
+                }
+                force
+            })
             .collect()
     }
 
@@ -136,3 +269,147 @@ impl Property for Forces {
         "forces".to_string()
     }
 }
+
+/// Virial tensor of each atom in the system.
+///
+/// Each atom is assigned half of the outer product `r_ij ⊗ f_ij` of every interaction it
+/// participates in, where `r_ij` points from atom `j` to atom `i` and `f_ij` is the force on `i` due to `j`.
+#[derive(Clone, Copy, Debug)]
+pub struct PerAtomVirial;
+
+impl PerAtomVirial {
+    fn accumulate(
+        &self,
+        virials: &mut [Matrix3<Float>],
+        i: usize,
+        j: usize,
+        r_ij: Vector3<Float>,
+        force: Float,
+    ) {
+        let f_ij = -force * r_ij.normalize();
+        let virial = 0.5 * r_ij * f_ij.transpose();
+        virials[i] += virial;
+        virials[j] += virial;
+    }
+}
+
+impl Property for PerAtomVirial {
+    type Res = Vec<Matrix3<Float>>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let mut virials = vec![Matrix3::zeros(); system.size];
+        if let Some(meta) = &potentials.coulomb_meta {
+            meta.selection.indices().for_each(|&[i, j]| {
+                let qi = system.species[i].charge();
+                let qj = system.species[j].charge();
+                let r_ij = meta.separation(system, i, j);
+                let r = r_ij.norm();
+                if meta.interacts(r) {
+                    let force = meta.potential.force(qi, qj, r);
+                    self.accumulate(&mut virials, i, j, r_ij, force);
+                }
+            });
+        }
+        potentials.many_body_metas.iter().for_each(|meta| {
+            let pairs = meta.pairs(system);
+            let pair_forces = meta.potential.pair_forces(system, &pairs);
+            pairs
+                .iter()
+                .zip(pair_forces.iter())
+                .for_each(|(pair, f_ij)| {
+                    let [i, j] = pair.indices;
+                    let virial = 0.5 * pair.r_ij * f_ij.transpose();
+                    virials[i] += virial;
+                    virials[j] += virial;
+                })
+        });
+        potentials.pair_metas.iter().for_each(|meta| {
+            meta.neighbors.pairs().for_each(|[i, j]| {
+                meta.for_each_image(system, i, j, |r_ji, r, share| {
+                    let force = share * meta.potential.force(r);
+                    self.accumulate(&mut virials, i, j, -r_ji, force);
+                })
+            })
+        });
+        virials
+    }
+
+    fn name(&self) -> String {
+        "per_atom_virial".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PairForces;
+    use crate::internal::Float;
+    use crate::potentials::pair::PairPotential;
+    use crate::potentials::types::LennardJones;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn pair_forces_match_scalar_kernel() {
+        let argon = Species::from_element(Element::Ar);
+        let cell = Cell::cubic(20.0);
+        let mut rng = StdRng::seed_from_u64(1234);
+        // random gas without overlapping atoms
+        let mut positions: Vec<Vector3<Float>> = Vec::new();
+        while positions.len() < 80 {
+            let candidate = Vector3::new(rng.gen(), rng.gen(), rng.gen()) * 20.0;
+            if positions.iter().all(|p| cell.distance(p, &candidate) > 3.0) {
+                positions.push(candidate);
+            }
+        }
+        let size = positions.len();
+        let system = System {
+            size,
+            cell,
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let lennard_jones = LennardJones::new(0.238, 3.4);
+        let cutoff = 8.5;
+        let mut potentials = PotentialsBuilder::new()
+            .pair(lennard_jones, (argon, argon), cutoff, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let forces = PairForces.calculate(&system, &potentials);
+
+        // every pair evaluated by the scalar kernel
+        let mut expected = vec![Vector3::zeros(); size];
+        for i in 0..size {
+            for j in (i + 1)..size {
+                let r = system
+                    .cell
+                    .distance(&system.positions[i], &system.positions[j]);
+                if r < cutoff {
+                    let dir = system
+                        .cell
+                        .direction(&system.positions[i], &system.positions[j]);
+                    let force = lennard_jones.force(r) * dir;
+                    expected[i] += force;
+                    expected[j] -= force;
+                }
+            }
+        }
+        let scale = expected.iter().map(|f| f.norm()).fold(0.0, Float::max);
+        assert!(scale > 0.0);
+        for (force, expected) in forces.iter().zip(&expected) {
+            assert_relative_eq!((force - expected).norm(), 0.0, epsilon = 1e-4 * scale);
+        }
+    }
+}
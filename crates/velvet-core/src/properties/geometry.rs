@@ -0,0 +1,558 @@
+//! Geometric quantities and their distributions accumulated over a trajectory.
+
+use std::collections::HashMap;
+
+use crate::internal::consts::{boltzmann, PI};
+use crate::internal::Float;
+use crate::properties::IntrinsicProperty;
+use crate::system::species::Species;
+use crate::system::System;
+
+// Fixed width histogram over the closed range `[min, max]`.
+#[derive(Clone, Debug)]
+pub(crate) struct Histogram {
+    min: Float,
+    max: Float,
+    pub(crate) counts: Vec<usize>,
+    pub(crate) total: usize,
+}
+
+impl Histogram {
+    pub(crate) fn new(min: Float, max: Float, bins: usize) -> Histogram {
+        Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+            total: 0,
+        }
+    }
+
+    pub(crate) fn width(&self) -> Float {
+        (self.max - self.min) / self.counts.len() as Float
+    }
+
+    pub(crate) fn insert(&mut self, value: Float) {
+        if value < self.min || value > self.max {
+            return;
+        }
+        let bin = ((value - self.min) / self.width()) as usize;
+        let bin = bin.min(self.counts.len() - 1);
+        self.counts[bin] += 1;
+        self.total += 1;
+    }
+
+    pub(crate) fn centers(&self) -> Vec<Float> {
+        let width = self.width();
+        (0..self.counts.len())
+            .map(|i| self.min + (i as Float + 0.5) * width)
+            .collect()
+    }
+
+    pub(crate) fn density(&self) -> Vec<Float> {
+        let norm = self.total.max(1) as Float * self.width();
+        self.counts.iter().map(|&c| c as Float / norm).collect()
+    }
+}
+
+/// Probability density of the bond lengths in `System.bonds`.
+#[derive(Clone, Debug)]
+pub struct BondLengthDistribution {
+    histogram: Histogram,
+}
+
+impl BondLengthDistribution {
+    /// Returns a new [`BondLengthDistribution`] with `bins` bins spanning `min` to `max` angstroms.
+    pub fn new(min: Float, max: Float, bins: usize) -> BondLengthDistribution {
+        BondLengthDistribution {
+            histogram: Histogram::new(min, max, bins),
+        }
+    }
+
+    /// Bins the length of every bond in the system.
+    pub fn accumulate(&mut self, system: &System) {
+        for k in 0..system.bonds.len() {
+            self.histogram.insert(system.bond_length(k));
+        }
+    }
+
+    /// Returns the bond length at the center of each bin.
+    pub fn centers(&self) -> Vec<Float> {
+        self.histogram.centers()
+    }
+
+    /// Returns the probability density of each bin.
+    pub fn density(&self) -> Vec<Float> {
+        self.histogram.density()
+    }
+}
+
+/// Probability density of the angles (radians) in `System.angles`.
+#[derive(Clone, Debug)]
+pub struct AngleDistribution {
+    histogram: Histogram,
+}
+
+impl AngleDistribution {
+    /// Returns a new [`AngleDistribution`] with `bins` bins spanning zero to pi.
+    pub fn new(bins: usize) -> AngleDistribution {
+        AngleDistribution {
+            histogram: Histogram::new(0.0, PI, bins),
+        }
+    }
+
+    /// Bins every angle in the system.
+    pub fn accumulate(&mut self, system: &System) {
+        for k in 0..system.angles.len() {
+            self.histogram.insert(system.angle_value(k));
+        }
+    }
+
+    /// Returns the angle at the center of each bin.
+    pub fn centers(&self) -> Vec<Float> {
+        self.histogram.centers()
+    }
+
+    /// Returns the probability density of each bin.
+    pub fn density(&self) -> Vec<Float> {
+        self.histogram.density()
+    }
+}
+
+/// Radial distribution function `g(r)` of every pair of atoms using minimum image distances.
+///
+/// Pair counts are normalized by the number expected in each spherical shell for an ideal gas
+/// at the density of the frame they were counted in, so frames of different volume can be mixed.
+/// Partial distributions between pairs of species are accumulated in the same pass over the pairs.
+#[derive(Clone, Debug)]
+pub struct RadialDistributionFunction {
+    histogram: Histogram,
+    ideal: Vec<Float>,
+    partials: Vec<PartialRdf>,
+}
+
+// Distances between the atoms of two species and the number expected of an ideal gas.
+#[derive(Clone, Debug)]
+struct PartialRdf {
+    species: (Species, Species),
+    histogram: Histogram,
+    ideal: Vec<Float>,
+}
+
+impl PartialRdf {
+    fn matches(&self, a: Species, b: Species) -> bool {
+        (a, b) == self.species || (b, a) == self.species
+    }
+}
+
+impl RadialDistributionFunction {
+    /// Returns a new [`RadialDistributionFunction`] with `bins` bins spanning zero to `cutoff` angstroms.
+    pub fn new(cutoff: Float, bins: usize) -> RadialDistributionFunction {
+        RadialDistributionFunction {
+            histogram: Histogram::new(0.0, cutoff, bins),
+            ideal: vec![0.0; bins],
+            partials: Vec::new(),
+        }
+    }
+
+    /// Accumulates a partial distribution between each pair of species as well.
+    pub fn species_pairs(mut self, pairs: Vec<(Species, Species)>) -> RadialDistributionFunction {
+        let (cutoff, bins) = (self.histogram.max, self.ideal.len());
+        self.partials = pairs
+            .into_iter()
+            .map(|species| PartialRdf {
+                species,
+                histogram: Histogram::new(0.0, cutoff, bins),
+                ideal: vec![0.0; bins],
+            })
+            .collect();
+        self
+    }
+
+    /// Bins the distance between every pair of atoms in the system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cutoff is longer than the minimum image radius of the cell.
+    pub fn accumulate(&mut self, system: &System) {
+        let cutoff = self.histogram.max;
+        assert!(
+            cutoff <= system.cell.minimum_image_radius(),
+            "cutoff of {} exceeds the minimum image radius of the cell",
+            cutoff
+        );
+        for i in 0..system.size {
+            for j in (i + 1)..system.size {
+                let r = system
+                    .cell
+                    .distance(&system.positions[i], &system.positions[j]);
+                if r > cutoff {
+                    continue;
+                }
+                self.histogram.insert(r);
+                let (a, b) = (system.species[i], system.species[j]);
+                for partial in self.partials.iter_mut().filter(|p| p.matches(a, b)) {
+                    partial.histogram.insert(r);
+                }
+            }
+        }
+        // pairs expected in each shell if the atoms were uniformly distributed
+        let shells = self.shell_volumes();
+        let volume = system.cell.volume();
+        let pairs = (system.size * system.size.saturating_sub(1)) as Float / 2.0;
+        for (ideal, shell) in self.ideal.iter_mut().zip(shells.iter()) {
+            *ideal += pairs / volume * shell;
+        }
+        for partial in self.partials.iter_mut() {
+            let (a, b) = partial.species;
+            let count = |s: Species| system.species.iter().filter(|&&x| x == s).count() as Float;
+            let pairs = if a == b {
+                count(a) * (count(a) - 1.0).max(0.0) / 2.0
+            } else {
+                count(a) * count(b)
+            };
+            for (ideal, shell) in partial.ideal.iter_mut().zip(shells.iter()) {
+                *ideal += pairs / volume * shell;
+            }
+        }
+    }
+
+    // Returns the volume of the spherical shell of each bin.
+    fn shell_volumes(&self) -> Vec<Float> {
+        let width = self.histogram.width();
+        (0..self.ideal.len())
+            .map(|k| {
+                let (lo, hi) = (k as Float * width, (k + 1) as Float * width);
+                4.0 / 3.0 * PI * (hi.powi(3) - lo.powi(3))
+            })
+            .collect()
+    }
+
+    /// Returns the distance at the center of each bin.
+    pub fn centers(&self) -> Vec<Float> {
+        self.histogram.centers()
+    }
+
+    /// Returns the value of `g(r)` in each bin.
+    pub fn rdf(&self) -> Vec<Float> {
+        normalize(&self.histogram, &self.ideal)
+    }
+
+    /// Returns the partial `g(r)` of each pair of species given to [`species_pairs`](RadialDistributionFunction::species_pairs).
+    ///
+    /// The total distribution is the sum of the partials weighted by their share of all pairs of atoms.
+    pub fn partial_rdfs(&self) -> HashMap<(Species, Species), Vec<Float>> {
+        self.partials
+            .iter()
+            .map(|partial| {
+                (
+                    partial.species,
+                    normalize(&partial.histogram, &partial.ideal),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the potential of mean force `w(r) = -kT ln g(r)` in each bin at `temperature`.
+    ///
+    /// Bins which were never visited have an infinite potential of mean force.
+    pub fn potential_of_mean_force(&self, temperature: Float) -> Vec<Float> {
+        let kt = boltzmann() * temperature;
+        self.rdf()
+            .into_iter()
+            .map(|g| {
+                if g > 0.0 {
+                    -kt * g.ln()
+                } else {
+                    Float::INFINITY
+                }
+            })
+            .collect()
+    }
+}
+
+// Returns the ratio of the counts of each bin to the number expected of an ideal gas.
+fn normalize(histogram: &Histogram, ideal: &[Float]) -> Vec<Float> {
+    histogram
+        .counts
+        .iter()
+        .zip(ideal.iter())
+        .map(|(&count, &ideal)| {
+            if ideal > 0.0 {
+                count as Float / ideal
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Distance from each atom to its nearest neighbor using minimum image distances.
+///
+/// An isolated atom in a system of one has an infinite nearest neighbor distance.
+#[derive(Clone, Copy, Debug)]
+pub struct NearestNeighborDistance;
+
+impl NearestNeighborDistance {
+    /// Returns the shortest distance between any two atoms which is useful to detect overlaps.
+    pub fn minimum(&self, system: &System) -> Float {
+        self.calculate_intrinsic(system)
+            .into_iter()
+            .fold(Float::INFINITY, Float::min)
+    }
+}
+
+impl IntrinsicProperty for NearestNeighborDistance {
+    type Res = Vec<Float>;
+
+    fn calculate_intrinsic(&self, system: &System) -> Self::Res {
+        let mut distances = vec![Float::INFINITY; system.size];
+        for i in 0..system.size {
+            for j in (i + 1)..system.size {
+                let r = system
+                    .cell
+                    .distance(&system.positions[i], &system.positions[j]);
+                distances[i] = distances[i].min(r);
+                distances[j] = distances[j].min(r);
+            }
+        }
+        distances
+    }
+
+    fn name(&self) -> String {
+        "nearest_neighbor_distance".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AngleDistribution, BondLengthDistribution, NearestNeighborDistance,
+        RadialDistributionFunction,
+    };
+    use crate::integrators::{Brownian, Integrator};
+    use crate::internal::consts::{boltzmann, PI};
+    use crate::internal::Float;
+    use crate::potentials::types::Harmonic;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::IntrinsicProperty;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn bond_length_distribution() {
+        let argon = Species::from_element(Element::Ar);
+        let r0 = 1.5;
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(30.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(15.0, 15.0, 15.0),
+                Vector3::new(15.0 + r0, 15.0, 15.0),
+            ],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: vec![[0, 1]],
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let k = 100.0;
+        let mut potentials = PotentialsBuilder::new()
+            .pair(Harmonic::new(k, r0), (argon, argon), 10.0, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // overdamped dynamics sample the Boltzmann distribution of the bond
+        let temperature = 300.0;
+        let mut brownian = Brownian::new(0.01, 100.0, temperature).seed(1234);
+        brownian.setup(&system, &potentials);
+        let (min, max, bins) = (1.0, 2.0, 50);
+        let mut distribution = BondLengthDistribution::new(min, max, bins);
+        for step in 0..50000 {
+            brownian.integrate(&mut system, &potentials);
+            if step % 10 == 0 {
+                distribution.accumulate(&system);
+            }
+        }
+
+        let centers = distribution.centers();
+        let density = distribution.density();
+        let width = (max - min) / bins as Float;
+        assert_relative_eq!(density.iter().sum::<Float>() * width, 1.0, epsilon = 1e-4);
+
+        // the peak sits at r0
+        let peak = (0..bins)
+            .max_by(|&a, &b| density[a].partial_cmp(&density[b]).unwrap())
+            .unwrap();
+        assert!((centers[peak] - r0).abs() < 2.0 * width);
+
+        // E = k(r - r0)^2 gives a gaussian with variance kT/2k
+        let mean: Float = centers
+            .iter()
+            .zip(density.iter())
+            .map(|(r, p)| r * p * width)
+            .sum();
+        let variance: Float = centers
+            .iter()
+            .zip(density.iter())
+            .map(|(r, p)| (r - mean).powi(2) * p * width)
+            .sum();
+        let target = boltzmann() * temperature / (2.0 * k);
+        assert_relative_eq!(variance.sqrt(), target.sqrt(), max_relative = 0.15);
+    }
+
+    #[test]
+    fn angle_distribution() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(30.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(11.0, 10.0, 10.0),
+                Vector3::new(10.0, 10.0, 10.0),
+                Vector3::new(10.0, 11.0, 10.0),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: vec![[0, 1, 2]],
+            dihedrals: Vec::new(),
+        };
+        let bins = 36;
+        let mut distribution = AngleDistribution::new(bins);
+        distribution.accumulate(&system);
+        distribution.accumulate(&system);
+
+        let density = distribution.density();
+        let centers = distribution.centers();
+        let peak = (0..bins).find(|&i| density[i] > 0.0).unwrap();
+        assert_relative_eq!(centers[peak], PI / 2.0, epsilon = PI / bins as Float);
+        assert_relative_eq!(density[peak], bins as Float / PI, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn nearest_neighbor_distance_fcc() {
+        let a0 = 5.26;
+        let system = System::fcc_lattice(Species::from_element(Element::Ar), a0, 3);
+        let size = system.size;
+
+        let expected = a0 / Float::sqrt(2.0);
+        let distances = NearestNeighborDistance.calculate_intrinsic(&system);
+        assert_eq!(distances.len(), size);
+        for distance in distances {
+            assert_relative_eq!(distance, expected, epsilon = 1e-4);
+        }
+        assert_relative_eq!(
+            NearestNeighborDistance.minimum(&system),
+            expected,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn radial_distribution_potential_of_mean_force() {
+        let a0 = 5.26;
+        let mut system = System::fcc_lattice(Species::from_element(Element::Ar), a0, 3);
+        let lattice = system.positions.clone();
+
+        // thermal vibrations broaden the shells of the crystal into peaks
+        let (cutoff, bins) = (7.5, 75);
+        let mut rdf = RadialDistributionFunction::new(cutoff, bins);
+        let mut rng = StdRng::seed_from_u64(1234);
+        for _ in 0..20 {
+            for (position, site) in system.positions.iter_mut().zip(lattice.iter()) {
+                let jitter = Vector3::new(rng.gen(), rng.gen(), rng.gen()).add_scalar(-0.5);
+                *position = site + jitter * 0.3;
+            }
+            rdf.accumulate(&system);
+        }
+
+        let centers = rdf.centers();
+        let g = rdf.rdf();
+        let nearest = a0 / Float::sqrt(2.0);
+        let width = cutoff / bins as Float;
+        let peak = (0..bins)
+            .max_by(|&a, &b| g[a].partial_cmp(&g[b]).unwrap())
+            .unwrap();
+        assert!((centers[peak] - nearest).abs() < width);
+
+        // the potential of mean force is deepest at the first peak and unbounded where atoms never meet
+        let temperature = 300.0;
+        let pmf = rdf.potential_of_mean_force(temperature);
+        let minimum = (0..bins)
+            .min_by(|&a, &b| pmf[a].partial_cmp(&pmf[b]).unwrap())
+            .unwrap();
+        assert_eq!(minimum, peak);
+        assert_relative_eq!(pmf[peak], -boltzmann() * temperature * g[peak].ln());
+        assert!(pmf[..10].iter().all(|w| w.is_infinite() && *w > 0.0));
+    }
+
+    #[test]
+    fn partial_radial_distribution_functions() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let (n_argon, n_xenon) = (60, 40);
+        let size = n_argon + n_xenon;
+        let mut system = System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: (0..size)
+                .map(|i| if i < n_argon { argon } else { xenon })
+                .collect(),
+            positions: vec![Vector3::zeros(); size],
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+
+        // every partial distribution of an ideal gas is flat at one
+        let (cutoff, bins) = (10.0, 20);
+        let pairs = vec![(argon, argon), (argon, xenon), (xenon, xenon)];
+        let mut rdf = RadialDistributionFunction::new(cutoff, bins).species_pairs(pairs.clone());
+        let mut rng = StdRng::seed_from_u64(1234);
+        for _ in 0..200 {
+            for position in system.positions.iter_mut() {
+                *position = Vector3::new(rng.gen(), rng.gen(), rng.gen()) * 20.0;
+            }
+            rdf.accumulate(&system);
+        }
+        let partials = rdf.partial_rdfs();
+        assert_eq!(partials.len(), 3);
+        for pair in &pairs {
+            let g = &partials[pair];
+            let mean = g[bins / 2..].iter().sum::<Float>() / (bins - bins / 2) as Float;
+            assert_relative_eq!(mean, 1.0, epsilon = 0.02);
+        }
+        // the pair given in reverse order is the same partial
+        let mut swapped =
+            RadialDistributionFunction::new(cutoff, bins).species_pairs(vec![(xenon, argon)]);
+        swapped.accumulate(&system);
+        assert!(swapped.partial_rdfs()[&(xenon, argon)]
+            .iter()
+            .any(|&g| g > 0.0));
+
+        // the partials weighted by their share of the pairs add up to the total
+        let (na, nx) = (n_argon as Float, n_xenon as Float);
+        let total_pairs = (size * (size - 1)) as Float / 2.0;
+        let weights = [
+            na * (na - 1.0) / 2.0 / total_pairs,
+            na * nx / total_pairs,
+            nx * (nx - 1.0) / 2.0 / total_pairs,
+        ];
+        for (k, total) in rdf.rdf().into_iter().enumerate() {
+            let sum: Float = pairs
+                .iter()
+                .zip(weights.iter())
+                .map(|(pair, weight)| weight * partials[pair][k])
+                .sum();
+            assert_relative_eq!(sum, total, epsilon = 1e-4, max_relative = 1e-4);
+        }
+    }
+}
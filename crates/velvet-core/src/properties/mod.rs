@@ -1,8 +1,15 @@
 //! Physical properties of the simulated system.
 
+pub mod correlation;
+pub mod elastic;
 pub mod energy;
 pub mod forces;
+pub mod geometry;
+pub mod order;
+pub mod pressure;
+pub mod statistics;
 pub mod temperature;
+pub mod transport;
 
 use crate::potentials::Potentials;
 use crate::system::System;
@@ -35,7 +42,7 @@ impl<T: IntrinsicProperty> Property for T {
     type Res = T::Res;
 
     fn calculate(&self, system: &System, _: &Potentials) -> Self::Res {
-        <T as IntrinsicProperty>::calculate_intrinsic(&self, system)
+        <T as IntrinsicProperty>::calculate_intrinsic(self, system)
     }
 
     fn name(&self) -> String {
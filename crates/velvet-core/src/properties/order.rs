@@ -0,0 +1,320 @@
+//! Structural order parameters.
+
+use nalgebra::Vector3;
+
+use crate::internal::consts::PI;
+use crate::internal::Float;
+use crate::properties::IntrinsicProperty;
+use crate::system::System;
+
+/// Per-atom Steinhardt bond orientational order parameter `q_l`.
+///
+/// # References
+///
+/// [1] Steinhardt, Paul J., David R. Nelson, and Marco Ronchetti. "Bond-orientational order in liquids and glasses." Physical Review B 28.2 (1983): 784.
+#[derive(Clone, Copy, Debug)]
+pub struct Steinhardt {
+    /// Degree of the spherical harmonics.
+    pub l: usize,
+    /// Cutoff radius which defines the nearest neighbors of each atom.
+    pub cutoff: Float,
+}
+
+impl Steinhardt {
+    /// Returns a new [`Steinhardt`] order parameter.
+    pub fn new(l: usize, cutoff: Float) -> Steinhardt {
+        Steinhardt { l, cutoff }
+    }
+}
+
+impl IntrinsicProperty for Steinhardt {
+    type Res = Vec<Float>;
+
+    fn calculate_intrinsic(&self, system: &System) -> <Self as IntrinsicProperty>::Res {
+        let factor = 4.0 * PI / (2 * self.l + 1) as Float;
+        let neighbors = neighbors(system, self.cutoff);
+        bond_order(system, &neighbors, self.l)
+            .iter()
+            .map(|qlm| Float::sqrt(factor * norm_squared(qlm)))
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        format!("steinhardt_q{}", self.l)
+    }
+}
+
+/// Number of atoms in the largest cluster of solid-like atoms.
+///
+/// Two neighboring atoms are connected if the normalized dot product of their `q6` vectors
+/// exceeds `threshold` and an atom is solid-like if it has at least `min_connections` connections.
+/// Neighboring solid-like atoms belong to the same cluster.
+///
+/// # References
+///
+/// [1] ten Wolde, Pieter Rein, Maria J. Ruiz-Montero, and Daan Frenkel. "Numerical calculation of the rate of crystal nucleation in a Lennard-Jones system at moderate undercooling." The Journal of chemical physics 104.24 (1996): 9932-9947.
+#[derive(Clone, Copy, Debug)]
+pub struct LargestSolidCluster {
+    /// Cutoff radius which defines the nearest neighbors of each atom.
+    pub cutoff: Float,
+    /// Minimum `q6` dot product between connected neighbors (typically 0.7).
+    pub threshold: Float,
+    /// Minimum number of connections for an atom to be solid-like (typically 7).
+    pub min_connections: usize,
+}
+
+impl LargestSolidCluster {
+    /// Returns a new [`LargestSolidCluster`] order parameter.
+    pub fn new(cutoff: Float, threshold: Float, min_connections: usize) -> LargestSolidCluster {
+        LargestSolidCluster {
+            cutoff,
+            threshold,
+            min_connections,
+        }
+    }
+
+    /// Returns true for each atom in the system which is solid-like.
+    pub fn solid_like(&self, system: &System) -> Vec<bool> {
+        let neighbors = neighbors(system, self.cutoff);
+        self.solid_like_inner(system, &neighbors)
+    }
+
+    fn solid_like_inner(
+        &self,
+        system: &System,
+        neighbors: &[Vec<(usize, Vector3<Float>)>],
+    ) -> Vec<bool> {
+        let q6 = bond_order(system, neighbors, 6);
+        let norms: Vec<Float> = q6
+            .iter()
+            .map(|qlm| Float::sqrt(norm_squared(qlm)))
+            .collect();
+        neighbors
+            .iter()
+            .enumerate()
+            .map(|(i, bonds)| {
+                let connections = bonds
+                    .iter()
+                    .filter(|(j, _)| {
+                        let norm = norms[i] * norms[*j];
+                        norm > 0.0 && dot(&q6[i], &q6[*j]) / norm > self.threshold
+                    })
+                    .count();
+                connections >= self.min_connections
+            })
+            .collect()
+    }
+}
+
+impl IntrinsicProperty for LargestSolidCluster {
+    type Res = usize;
+
+    fn calculate_intrinsic(&self, system: &System) -> <Self as IntrinsicProperty>::Res {
+        let neighbors = neighbors(system, self.cutoff);
+        let solid = self.solid_like_inner(system, &neighbors);
+        let mut clusters = DisjointSet::new(system.size);
+        for (i, bonds) in neighbors.iter().enumerate() {
+            for (j, _) in bonds {
+                if solid[i] && solid[*j] {
+                    clusters.union(i, *j);
+                }
+            }
+        }
+        (0..system.size)
+            .filter(|&i| solid[i])
+            .map(|i| {
+                let root = clusters.find(i);
+                clusters.size[root]
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn name(&self) -> String {
+        "largest_solid_cluster".to_string()
+    }
+}
+
+// Disjoint set forest with path compression and union by size.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut i = i;
+        while self.parent[i] != root {
+            let next = self.parent[i];
+            self.parent[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    fn union(&mut self, i: usize, j: usize) {
+        let (mut a, mut b) = (self.find(i), self.find(j));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+// Returns the index and minimum image bond vector of every neighbor of each atom.
+fn neighbors(system: &System, cutoff: Float) -> Vec<Vec<(usize, Vector3<Float>)>> {
+    let mut neighbors = vec![Vec::new(); system.size];
+    for i in 0..system.size {
+        for j in (i + 1)..system.size {
+            let mut bond = system.positions[j] - system.positions[i];
+            system.cell.vector_image(&mut bond);
+            if bond.norm() < cutoff {
+                neighbors[i].push((j, bond));
+                neighbors[j].push((i, -bond));
+            }
+        }
+    }
+    neighbors
+}
+
+// Returns the complex components `q_lm` for `m = -l..=l` averaged over the bonds of each atom.
+fn bond_order(
+    system: &System,
+    neighbors: &[Vec<(usize, Vector3<Float>)>],
+    l: usize,
+) -> Vec<Vec<(Float, Float)>> {
+    (0..system.size)
+        .map(|i| {
+            let mut qlm = vec![(0.0, 0.0); 2 * l + 1];
+            if neighbors[i].is_empty() {
+                return qlm;
+            }
+            for (_, bond) in &neighbors[i] {
+                for (m, ylm) in spherical_harmonics(l, bond).iter().enumerate() {
+                    qlm[m].0 += ylm.0;
+                    qlm[m].1 += ylm.1;
+                }
+            }
+            let count = neighbors[i].len() as Float;
+            qlm.iter()
+                .map(|(re, im)| (re / count, im / count))
+                .collect()
+        })
+        .collect()
+}
+
+// Returns the spherical harmonics `Y_lm` for `m = -l..=l` in the direction of `v`.
+fn spherical_harmonics(l: usize, v: &Vector3<Float>) -> Vec<(Float, Float)> {
+    let cos_theta = v[2] / v.norm();
+    let phi = Float::atan2(v[1], v[0]);
+    let mut ylm = vec![(0.0, 0.0); 2 * l + 1];
+    for m in 0..=l {
+        let ratio: Float = ((l - m + 1)..=(l + m)).map(|k| 1.0 / k as Float).product();
+        let norm = Float::sqrt((2 * l + 1) as Float / (4.0 * PI) * ratio);
+        let magnitude = norm * legendre(l, m, cos_theta);
+        let angle = m as Float * phi;
+        let (re, im) = (magnitude * Float::cos(angle), magnitude * Float::sin(angle));
+        ylm[l + m] = (re, im);
+        // Y_l(-m) = (-1)^m conj(Y_lm)
+        let sign = if m % 2 == 0 { 1.0 } else { -1.0 };
+        ylm[l - m] = (sign * re, -sign * im);
+    }
+    ylm
+}
+
+// Associated Legendre polynomial `P_lm(x)` including the Condon-Shortley phase.
+fn legendre(l: usize, m: usize, x: Float) -> Float {
+    let mut pmm = 1.0;
+    if m > 0 {
+        let root = Float::sqrt((1.0 - x) * (1.0 + x));
+        let mut factor = 1.0;
+        for _ in 0..m {
+            pmm *= -factor * root;
+            factor += 2.0;
+        }
+    }
+    if l == m {
+        return pmm;
+    }
+    let mut pmmp1 = x * (2 * m + 1) as Float * pmm;
+    for ll in (m + 2)..=l {
+        let pll =
+            (x * (2 * ll - 1) as Float * pmmp1 - (ll + m - 1) as Float * pmm) / (ll - m) as Float;
+        pmm = pmmp1;
+        pmmp1 = pll;
+    }
+    pmmp1
+}
+
+fn norm_squared(qlm: &[(Float, Float)]) -> Float {
+    qlm.iter().map(|(re, im)| re * re + im * im).sum()
+}
+
+// Real part of the complex dot product between `a` and the conjugate of `b`.
+fn dot(a: &[(Float, Float)], b: &[(Float, Float)]) -> Float {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| x.0 * y.0 + x.1 * y.1)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LargestSolidCluster, Steinhardt};
+    use crate::internal::Float;
+    use crate::properties::IntrinsicProperty;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn steinhardt_fcc() {
+        let system = System::fcc_lattice(Species::from_element(Element::Ar), 5.26, 4);
+        let q4 = Steinhardt::new(4, 4.5).calculate_intrinsic(&system);
+        let q6 = Steinhardt::new(6, 4.5).calculate_intrinsic(&system);
+        for i in 0..system.size {
+            assert_relative_eq!(q4[i], 0.19094, epsilon = 1e-4);
+            assert_relative_eq!(q6[i], 0.57452, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn largest_solid_cluster() {
+        let cluster = LargestSolidCluster::new(4.5, 0.7, 7);
+
+        // every atom of a perfect crystal belongs to one cluster
+        let mut system = System::fcc_lattice(Species::from_element(Element::Ar), 5.26, 4);
+        assert_eq!(cluster.calculate_intrinsic(&system), system.size);
+
+        // randomly placed atoms at the same density form no large cluster
+        let mut rng = StdRng::seed_from_u64(1234);
+        let length = system.cell.a();
+        for position in system.positions.iter_mut() {
+            *position = Vector3::new(
+                rng.gen::<Float>() * length,
+                rng.gen::<Float>() * length,
+                rng.gen::<Float>() * length,
+            );
+        }
+        assert!(cluster.calculate_intrinsic(&system) < system.size / 20);
+    }
+}
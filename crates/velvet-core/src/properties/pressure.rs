@@ -0,0 +1,303 @@
+//! Pressure and stress of the system.
+
+use nalgebra::Matrix3;
+
+use crate::internal::Float;
+use crate::potentials::Potentials;
+use crate::properties::forces::PerAtomVirial;
+use crate::properties::Property;
+use crate::system::System;
+
+/// Pressure tensor of the system.
+///
+/// The tensor is the sum of the kinetic term `m_i v_i ⊗ v_i` of each atom and the virial
+/// `r_ij ⊗ f_ij` of every interaction divided by the cell volume, or by its area if the cell is planar.
+/// Interactions use minimum image vectors so the tensor is valid for triclinic cells.
+/// Positive values are compressive.
+#[derive(Clone, Copy, Debug)]
+pub struct StressTensor;
+
+impl Property for StressTensor {
+    type Res = Matrix3<Float>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let kinetic: Matrix3<Float> = system
+            .species
+            .iter()
+            .zip(system.velocities.iter())
+            .map(|(species, vel)| species.mass() * vel * vel.transpose())
+            .sum();
+        let measure = if system.cell.is_planar() {
+            system.cell.area()
+        } else {
+            system.cell.volume()
+        };
+        (kinetic + potentials.virial(system)) / measure
+    }
+
+    fn name(&self) -> String {
+        "stress_tensor".to_string()
+    }
+}
+
+/// Scalar pressure of the system.
+#[derive(Clone, Copy, Debug)]
+pub struct Pressure;
+
+impl Property for Pressure {
+    type Res = Float;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let dimensions = system.cell.dimensions() as Float;
+        StressTensor.calculate(system, potentials).trace() / dimensions
+    }
+
+    fn name(&self) -> String {
+        "pressure".to_string()
+    }
+}
+
+/// Stress tensor of each atom in the system.
+///
+/// Each atom is assigned its kinetic term `m_i v_i ⊗ v_i` and half of the virial of every
+/// interaction it participates in. The tensors are not divided by a volume so their sum equals
+/// the [`StressTensor`] times the cell volume, less the virial of any external potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct PerAtomStress;
+
+impl Property for PerAtomStress {
+    type Res = Vec<Matrix3<Float>>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        PerAtomVirial
+            .calculate(system, potentials)
+            .iter()
+            .zip(system.species.iter().zip(system.velocities.iter()))
+            .map(|(virial, (species, vel))| virial + species.mass() * vel * vel.transpose())
+            .collect()
+    }
+
+    fn name(&self) -> String {
+        "per_atom_stress".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PerAtomStress, Pressure, StressTensor};
+    use crate::internal::consts::PI;
+    use crate::internal::Float;
+    use crate::potentials::pair::PairPotential;
+    use crate::potentials::types::{
+        AngleRestraint, DistanceRestraint, Harmonic, HarmonicBond, LennardJones,
+    };
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::energy::PotentialEnergy;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::{Matrix3, Vector3};
+
+    #[test]
+    fn harmonic_bond_under_tension() {
+        let argon = Species::from_element(Element::Ar);
+        let (k, r0, r) = (100.0, 1.5, 1.7);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(10.0, 10.0, 10.0),
+                Vector3::new(10.0 + r, 10.0, 10.0),
+            ],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: vec![[0, 1]],
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let harmonic = Harmonic::new(k, r0);
+        let mut potentials = PotentialsBuilder::new()
+            .pair(harmonic, (argon, argon), 5.0, 1.0)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        // a stretched bond pulls the atoms together so r_ij · f_ij = -r f
+        let virial = potentials.virial(&system);
+        let expected = -r * harmonic.force(r);
+        assert!(expected < 0.0);
+        assert_relative_eq!(virial.trace(), expected, epsilon = 1e-3);
+        assert_relative_eq!(virial[(0, 0)], expected, epsilon = 1e-3);
+        assert_relative_eq!(virial[(1, 1)], 0.0, epsilon = 1e-5);
+
+        // tension gives a negative pressure at rest
+        let volume = system.cell.volume();
+        let stress = StressTensor.calculate(&system, &potentials);
+        assert_relative_eq!(stress[(0, 0)], expected / volume, epsilon = 1e-6);
+        let pressure = Pressure.calculate(&system, &potentials);
+        assert_relative_eq!(pressure, expected / (3.0 * volume), epsilon = 1e-6);
+
+        // the same bond as an external potential on the topology gives the same pressure
+        let mut bonded = PotentialsBuilder::new()
+            .external(HarmonicBond::new(k, r0))
+            .build();
+        bonded.setup(&system).unwrap();
+        bonded.update(&system, 0);
+        let virial = bonded.virial(&system);
+        assert_relative_eq!(virial[(0, 0)], expected, epsilon = 1e-3);
+        assert_relative_eq!(virial[(1, 1)], 0.0, epsilon = 1e-5);
+        let bonded_pressure = Pressure.calculate(&system, &bonded);
+        assert_relative_eq!(bonded_pressure, pressure, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn per_atom_stress_sum() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 4,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 4],
+            positions: vec![
+                Vector3::new(1.0, 1.0, 1.0),
+                Vector3::new(4.6, 1.3, 0.8),
+                Vector3::new(2.1, 4.2, 1.9),
+                Vector3::new(9.1, 8.8, 2.5),
+            ],
+            velocities: vec![
+                Vector3::new(0.01, -0.02, 0.0),
+                Vector3::new(0.0, 0.01, 0.03),
+                Vector3::new(-0.02, 0.0, 0.01),
+                Vector3::new(0.01, 0.01, -0.04),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .pair(Harmonic::new(10.0, 3.0), (argon, argon), 4.5, 0.5)
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let total: Matrix3<_> = PerAtomStress.calculate(&system, &potentials).iter().sum();
+        let expected = StressTensor.calculate(&system, &potentials) * system.cell.volume();
+        assert!(expected.norm() > 1.0);
+        for (a, b) in total.iter().zip(expected.iter()) {
+            assert_relative_eq!(a, b, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn sheared_cell() {
+        let argon = Species::from_element(Element::Ar);
+        let cell = Cell::triclinic(10.5, 10.5, 10.5, 90.0, 90.0, 60.0);
+        let mut positions = Vec::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                for k in 0..3 {
+                    let n = (9 * i + 3 * j + k) as Float;
+                    let fractional = Vector3::new(
+                        i as Float / 3.0 + 0.01 * Float::sin(n),
+                        j as Float / 3.0 + 0.01 * Float::cos(1.7 * n),
+                        k as Float / 3.0 + 0.01 * Float::sin(2.3 * n),
+                    );
+                    positions.push(cell.cartesian(&fractional));
+                }
+            }
+        }
+        let size = positions.len();
+        let system = System {
+            size,
+            cell,
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        assert!(system.cell.minimum_image_radius() > 4.4);
+        let build = || {
+            PotentialsBuilder::new()
+                .pair(LennardJones::new(0.238, 3.4), (argon, argon), 4.4, 0.0)
+                .build()
+        };
+        let energy = |factor: Float| -> (Float, Float) {
+            let mut system = system.clone();
+            system.cell.scale(factor);
+            system
+                .positions
+                .iter_mut()
+                .for_each(|position| *position *= factor);
+            let mut potentials = build();
+            potentials.setup(&system).unwrap();
+            potentials.update(&system, 0);
+            (
+                system.cell.volume(),
+                PotentialEnergy.calculate(&system, &potentials),
+            )
+        };
+
+        let mut potentials = build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let stress = StressTensor.calculate(&system, &potentials);
+        let pressure = Pressure.calculate(&system, &potentials);
+        assert_relative_eq!(stress.trace() / 3.0, pressure, epsilon = 1e-6);
+
+        // at rest the pressure is the derivative of the energy under isotropic dilation
+        let h = 1e-3;
+        let (v_plus, e_plus) = energy(1.0 + h);
+        let (v_minus, e_minus) = energy(1.0 - h);
+        let expected = -(e_plus - e_minus) / (v_plus - v_minus);
+        assert!(expected.abs() > 1e-4);
+        assert_relative_eq!(pressure, expected, max_relative = 1e-3);
+    }
+
+    #[test]
+    fn restraints_under_strain() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(9.0, 5.0, 5.0),
+                Vector3::new(0.5, 5.2, 5.0),
+                Vector3::new(1.2, 6.9, 5.3),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let mut potentials = PotentialsBuilder::new()
+            .external(DistanceRestraint::new(0, 1, 10.0, 2.0))
+            .external(AngleRestraint::new(0, 1, 2, 10.0, PI / 2.0))
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+        let stress = StressTensor.calculate(&system, &potentials);
+        assert!(stress.norm() > 1e-3);
+
+        // at rest each component of the stress is the derivative of the energy under that strain
+        let volume = system.cell.volume();
+        let h = 1e-3;
+        let energy = |a: usize, b: usize, magnitude: Float| {
+            let mut strain = Matrix3::zeros();
+            strain[(a, b)] = magnitude;
+            let mut strained = system.clone();
+            strained.apply_strain(strain);
+            PotentialEnergy.calculate(&strained, &potentials)
+        };
+        for a in 0..3 {
+            for b in 0..3 {
+                let expected = -(energy(a, b, h) - energy(a, b, -h)) / (2.0 * h * volume);
+                assert_relative_eq!(stress[(a, b)], expected, epsilon = 1e-5);
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! Statistical analysis of properties sampled over a trajectory.
+
+use crate::internal::consts::boltzmann;
+use crate::internal::Float;
+use crate::properties::geometry::Histogram;
+
+/// Detects the end of the equilibration transient in a series of scalar samples.
+///
+/// The equilibration point is the truncation index which minimizes the marginal standard error
+/// `sum((x_i - mean)^2) / (n - d)^2` of the samples remaining after discarding the first `d`.
+/// Only the first half of the series is considered as a truncation point.
+///
+/// # References
+///
+/// [1] White, K. Preston. "An effective truncation heuristic for bias reduction in simulation output." Simulation 69.6 (1997): 323-334.
+#[derive(Clone, Debug, Default)]
+pub struct EquilibrationDetector {
+    samples: Vec<Float>,
+}
+
+impl EquilibrationDetector {
+    /// Returns a new empty [`EquilibrationDetector`].
+    pub fn new() -> EquilibrationDetector {
+        EquilibrationDetector::default()
+    }
+
+    /// Records a sample.
+    pub fn push(&mut self, value: Float) {
+        self.samples.push(value);
+    }
+
+    /// Returns the recorded samples.
+    pub fn samples(&self) -> &[Float] {
+        &self.samples
+    }
+
+    /// Returns the index of the first sample after which the series is stationary.
+    pub fn equilibration_index(&self) -> usize {
+        let n = self.samples.len();
+        if n < 2 {
+            return 0;
+        }
+        // samples are shifted by their mean to limit cancellation in the sums of squares
+        let shift = self.samples.iter().sum::<Float>() / n as Float;
+        // suffix sums allow each truncation to be evaluated in constant time
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let mut best = (Float::INFINITY, 0);
+        for d in (0..n).rev() {
+            let x = self.samples[d] - shift;
+            sum += x;
+            sum_sq += x * x;
+            if d > n / 2 {
+                continue;
+            }
+            let count = (n - d) as Float;
+            let squares = Float::max(sum_sq - sum * sum / count, 0.0);
+            let error = squares / (count * count);
+            if error <= best.0 {
+                best = (error, d);
+            }
+        }
+        best.1
+    }
+
+    /// Returns the mean of the samples after the equilibration point.
+    pub fn equilibrated_mean(&self) -> Float {
+        let equilibrated = &self.samples[self.equilibration_index()..];
+        equilibrated.iter().sum::<Float>() / equilibrated.len() as Float
+    }
+}
+
+// Harmonic bias of a single umbrella sampling window and the histogram of its samples.
+#[derive(Clone, Debug)]
+struct Window {
+    k: Float,
+    center: Float,
+    histogram: Histogram,
+}
+
+impl Window {
+    // Returns the bias energy `k (x - center)^2` at `x`.
+    fn bias(&self, x: Float) -> Float {
+        self.k * (x - self.center).powi(2)
+    }
+}
+
+/// Unbiased potential of mean force from umbrella sampling windows using the weighted histogram analysis method.
+///
+/// Each window samples a collective variable under a harmonic bias `k (x - center)^2`
+/// such as a [`DistanceRestraint`](crate::potentials::types::DistanceRestraint).
+/// The unbiased probability of each bin and the free energy of each window are solved self-consistently
+/// by iterating until no window's free energy changes by more than the tolerance.
+/// Only samples within the range of the histogram contribute.
+///
+/// # References
+///
+/// [1] Kumar, Shankar, et al. "The weighted histogram analysis method for free-energy calculations on biomolecules. I. The method." Journal of Computational Chemistry 13.8 (1992): 1011-1021.
+#[derive(Clone, Debug)]
+pub struct Wham {
+    min: Float,
+    max: Float,
+    bins: usize,
+    temperature: Float,
+    tolerance: Float,
+    max_iterations: usize,
+    windows: Vec<Window>,
+}
+
+impl Wham {
+    /// Returns a new [`Wham`] analyzer with `bins` bins spanning `min` to `max` at the sampled temperature.
+    pub fn new(min: Float, max: Float, bins: usize, temperature: Float) -> Wham {
+        Wham {
+            min,
+            max,
+            bins,
+            temperature,
+            tolerance: 1e-5,
+            max_iterations: 10000,
+            windows: Vec::new(),
+        }
+    }
+
+    /// Sets the largest change in window free energy between iterations at convergence.
+    pub fn tolerance(mut self, tolerance: Float) -> Wham {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the maximum number of self-consistent iterations.
+    pub fn max_iterations(mut self, max_iterations: usize) -> Wham {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Records the samples of a window biased by `k (x - center)^2`.
+    pub fn window(&mut self, k: Float, center: Float, samples: &[Float]) {
+        let mut histogram = Histogram::new(self.min, self.max, self.bins);
+        for &x in samples {
+            histogram.insert(x);
+        }
+        self.windows.push(Window {
+            k,
+            center,
+            histogram,
+        });
+    }
+
+    /// Returns the value of the collective variable at the center of each bin.
+    pub fn centers(&self) -> Vec<Float> {
+        Histogram::new(self.min, self.max, self.bins).centers()
+    }
+
+    /// Returns the unbiased probability of each bin.
+    pub fn probabilities(&self) -> Vec<Float> {
+        let beta = 1.0 / (boltzmann() * self.temperature);
+        let centers = self.centers();
+        // boltzmann factors of each window's bias in each bin
+        let factors: Vec<Vec<Float>> = self
+            .windows
+            .iter()
+            .map(|w| {
+                centers
+                    .iter()
+                    .map(|&x| Float::exp(-beta * w.bias(x)))
+                    .collect()
+            })
+            .collect();
+        let counts: Vec<Float> = (0..self.bins)
+            .map(|b| {
+                self.windows
+                    .iter()
+                    .map(|w| w.histogram.counts[b] as Float)
+                    .sum()
+            })
+            .collect();
+
+        let mut free_energies = vec![0 as Float; self.windows.len()];
+        let mut probabilities = vec![0 as Float; self.bins];
+        for _ in 0..self.max_iterations {
+            for (b, probability) in probabilities.iter_mut().enumerate() {
+                let denominator: Float = self
+                    .windows
+                    .iter()
+                    .zip(&factors)
+                    .zip(&free_energies)
+                    .map(|((w, factor), f)| {
+                        w.histogram.total as Float * Float::exp(beta * f) * factor[b]
+                    })
+                    .sum();
+                *probability = if counts[b] > 0.0 {
+                    counts[b] / denominator
+                } else {
+                    0.0
+                };
+            }
+            let mut change: Float = 0.0;
+            for (f, factor) in free_energies.iter_mut().zip(&factors) {
+                let partition: Float = probabilities.iter().zip(factor).map(|(p, c)| p * c).sum();
+                let next = -Float::ln(partition) / beta;
+                change = change.max((next - *f).abs());
+                *f = next;
+            }
+            // free energies are only defined relative to the first window
+            let reference = free_energies[0];
+            free_energies.iter_mut().for_each(|f| *f -= reference);
+            if change < self.tolerance {
+                break;
+            }
+        }
+        let total: Float = probabilities.iter().sum();
+        probabilities.iter().map(|p| p / total).collect()
+    }
+
+    /// Returns the potential of mean force of each bin relative to its minimum.
+    ///
+    /// Bins which no window sampled have an infinite free energy.
+    pub fn free_energy(&self) -> Vec<Float> {
+        let kt = boltzmann() * self.temperature;
+        let pmf: Vec<Float> = self
+            .probabilities()
+            .iter()
+            .map(|&p| {
+                if p > 0.0 {
+                    -kt * Float::ln(p)
+                } else {
+                    Float::INFINITY
+                }
+            })
+            .collect();
+        let minimum = pmf.iter().cloned().fold(Float::INFINITY, Float::min);
+        pmf.iter().map(|f| f - minimum).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EquilibrationDetector, Wham};
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use approx::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Normal};
+
+    #[test]
+    fn relaxation_transient() {
+        let mut rng = StdRng::seed_from_u64(1234);
+        let noise = Normal::new(0.0, 1.0).unwrap();
+        let tau = 40.0;
+        let mut detector = EquilibrationDetector::new();
+        for t in 0..2000 {
+            let transient = 20.0 * Float::exp(-(t as Float) / tau);
+            detector.push(-100.0 + transient + noise.sample(&mut rng));
+        }
+        assert_eq!(detector.samples().len(), 2000);
+
+        // the transient falls below the noise after about 3 relaxation times
+        let index = detector.equilibration_index();
+        assert!(index as Float > 2.0 * tau, "index {} is too early", index);
+        assert!((index as Float) < 8.0 * tau, "index {} is too late", index);
+        assert_relative_eq!(detector.equilibrated_mean(), -100.0, epsilon = 0.1);
+    }
+
+    #[test]
+    fn wham_flat_profile() {
+        let mut rng = StdRng::seed_from_u64(1234);
+        let temperature = 300.0;
+        let kt = boltzmann() * temperature;
+        // the bias alone confines each window to a gaussian with a standard deviation of 0.5
+        let k = kt / (2.0 * 0.25);
+        let mut wham = Wham::new(1.0, 9.0, 16, temperature);
+        // windows of unequal length skew the raw histogram
+        for i in 0..21 {
+            let center = i as Float * 0.5;
+            let normal = Normal::new(center, 0.5).unwrap();
+            let samples: Vec<Float> = (0..1000 + 400 * i)
+                .map(|_| normal.sample(&mut rng))
+                .collect();
+            wham.window(k, center, &samples);
+        }
+        assert_eq!(wham.centers()[0], 1.25);
+
+        let probabilities = wham.probabilities();
+        assert_relative_eq!(probabilities.iter().sum::<Float>(), 1.0, epsilon = 1e-4);
+        let pmf = wham.free_energy();
+        assert_eq!(pmf.len(), 16);
+        for f in pmf {
+            assert!(f < 0.1 * kt, "free energy {} is not flat", f);
+        }
+    }
+}
@@ -1,8 +1,9 @@
-//! Instantaneous temperature of the system.
+//! Instantaneous temperature of the system and the distribution of atomic speeds.
 
-use crate::internal::consts::BOLTZMANN;
+use crate::internal::consts::{boltzmann, PI};
 use crate::internal::Float;
-use crate::properties::energy::KineticEnergy;
+use crate::properties::energy::{KineticEnergy, MaskedKineticEnergy};
+use crate::properties::geometry::Histogram;
 use crate::properties::IntrinsicProperty;
 use crate::system::System;
 
@@ -15,13 +16,195 @@ impl IntrinsicProperty for Temperature {
 
     fn calculate_intrinsic(&self, system: &System) -> <Self as IntrinsicProperty>::Res {
         let kinetic = KineticEnergy.calculate_intrinsic(system);
-        // NOTE: Calculating DOF this way is a potentially nasty bug if future
-        // support is added for degrees of freedom beyond just 3D particles.
-        let dof = (system.size * 3) as Float;
-        2.0 * kinetic / (dof * BOLTZMANN)
+        let dof = system.degrees_of_freedom() as Float;
+        2.0 * kinetic / (dof * boltzmann())
     }
 
     fn name(&self) -> String {
         "temperature".to_string()
     }
 }
+
+/// Instantaneous temperature of the active atoms in the system.
+///
+/// Only active atoms contribute degrees of freedom, so frozen or constrained atoms do not
+/// dilute the temperature of the moving ones. A mask without active atoms has zero temperature.
+#[derive(Clone, Debug)]
+pub struct MaskedTemperature {
+    kinetic_energy: MaskedKineticEnergy,
+}
+
+impl MaskedTemperature {
+    /// Returns a new [`MaskedTemperature`] property.
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - Whether each atom in the system is active.
+    pub fn new(active: Vec<bool>) -> MaskedTemperature {
+        MaskedTemperature {
+            kinetic_energy: MaskedKineticEnergy::new(active),
+        }
+    }
+}
+
+impl IntrinsicProperty for MaskedTemperature {
+    type Res = Float;
+
+    fn calculate_intrinsic(&self, system: &System) -> <Self as IntrinsicProperty>::Res {
+        let kinetic = self.kinetic_energy.calculate_intrinsic(system);
+        let dof = (self.kinetic_energy.active_count() * system.cell.dimensions()) as Float;
+        if dof == 0.0 {
+            return 0 as Float;
+        }
+        2.0 * kinetic / (dof * boltzmann())
+    }
+
+    fn name(&self) -> String {
+        "masked_temperature".to_string()
+    }
+}
+
+/// Probability density of the speeds of the atoms accumulated over a trajectory.
+///
+/// Comparing the density against the [`maxwell_boltzmann`](SpeedDistribution::maxwell_boltzmann)
+/// distribution at the target temperature verifies that a system has thermalized.
+#[derive(Clone, Debug)]
+pub struct SpeedDistribution {
+    histogram: Histogram,
+}
+
+impl SpeedDistribution {
+    /// Returns a new [`SpeedDistribution`] with `bins` bins spanning zero to `max` speed.
+    pub fn new(max: Float, bins: usize) -> SpeedDistribution {
+        SpeedDistribution {
+            histogram: Histogram::new(0.0, max, bins),
+        }
+    }
+
+    /// Bins the speed of every atom in the system.
+    pub fn accumulate(&mut self, system: &System) {
+        for velocity in &system.velocities {
+            self.histogram.insert(velocity.norm());
+        }
+    }
+
+    /// Returns the speed at the center of each bin.
+    pub fn centers(&self) -> Vec<Float> {
+        self.histogram.centers()
+    }
+
+    /// Returns the probability density of each bin.
+    pub fn density(&self) -> Vec<Float> {
+        self.histogram.density()
+    }
+
+    /// Returns the Maxwell-Boltzmann probability density of atoms of `mass` at `temperature` at the center of each bin.
+    pub fn maxwell_boltzmann(&self, temperature: Float, mass: Float) -> Vec<Float> {
+        let kt = boltzmann() * temperature;
+        let norm = 4.0 * PI * Float::powf(mass / (2.0 * PI * kt), 1.5);
+        self.centers()
+            .iter()
+            .map(|&v| norm * v * v * Float::exp(-mass * v * v / (2.0 * kt)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MaskedTemperature, SpeedDistribution, Temperature};
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::properties::energy::MaskedKineticEnergy;
+    use crate::properties::IntrinsicProperty;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use crate::velocity_distributions::{Boltzmann, VelocityDistribution};
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn half_frozen() {
+        let argon = Species::from_element(Element::Ar);
+        let size = 8;
+        let positions = (0..size)
+            .map(|i| Vector3::new(i as Float, 0.0, 0.0))
+            .collect();
+        let velocities = (0..size)
+            .map(|i| Vector3::new(0.01 * i as Float, -0.02, 0.005 * i as Float))
+            .collect();
+        let system = System {
+            size,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; size],
+            positions,
+            velocities,
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let active: Vec<bool> = (0..size).map(|i| i < size / 2).collect();
+        let masked = MaskedTemperature::new(active);
+
+        // the frozen half of the atoms still carry velocities which must be ignored
+        let mut moving = system.clone();
+        moving.size = size / 2;
+        moving.species.truncate(size / 2);
+        moving.positions.truncate(size / 2);
+        moving.velocities.truncate(size / 2);
+        let expected = Temperature.calculate_intrinsic(&moving);
+        assert_relative_eq!(
+            masked.calculate_intrinsic(&system),
+            expected,
+            max_relative = 1e-5
+        );
+        assert!(Temperature.calculate_intrinsic(&system) > expected);
+
+        // a fully frozen system has no kinetic energy or temperature rather than NaN
+        let frozen = vec![false; size];
+        let kinetic = MaskedKineticEnergy::new(frozen.clone()).calculate_intrinsic(&system);
+        assert_eq!(kinetic, 0.0);
+        let temperature = MaskedTemperature::new(frozen).calculate_intrinsic(&system);
+        assert_eq!(temperature, 0.0);
+    }
+
+    #[test]
+    fn speed_distribution() {
+        let argon = Species::from_element(Element::Ar);
+        let size = 5000;
+        let mut system = System {
+            size,
+            cell: Cell::cubic(100.0),
+            species: vec![argon; size],
+            positions: vec![Vector3::zeros(); size],
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let temperature = 300.0;
+        Boltzmann::new(temperature).seed(1234).apply(&mut system);
+
+        // the most probable speed is sqrt(2kT / m)
+        let mass = argon.mass();
+        let peak = Float::sqrt(2.0 * boltzmann() * temperature / mass);
+        let mut distribution = SpeedDistribution::new(3.0 * peak, 20);
+        distribution.accumulate(&system);
+        let observed = distribution.density();
+        let expected = distribution.maxwell_boltzmann(temperature, mass);
+
+        // chi-square of the counts against those expected with a degree of freedom per bin
+        let counts = size as Float * 3.0 * peak / 20.0;
+        let chi_square: Float = observed
+            .iter()
+            .zip(expected.iter())
+            .filter(|(_, &e)| e * counts >= 5.0)
+            .map(|(&o, &e)| counts * (o - e).powi(2) / e)
+            .sum();
+        assert!(chi_square < 40.0, "chi-square {} is too large", chi_square);
+        // nearly every atom is slower than three times the most probable speed
+        let total: Float = expected.iter().sum::<Float>() * 3.0 * peak / 20.0;
+        assert_relative_eq!(total, 1.0, epsilon = 1e-2);
+    }
+}
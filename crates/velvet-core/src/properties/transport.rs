@@ -0,0 +1,361 @@
+//! Transport properties of the system.
+
+use nalgebra::Vector3;
+
+use crate::internal::consts::boltzmann;
+use crate::internal::Float;
+use crate::potentials::Potentials;
+use crate::properties::correlation::Autocorrelation;
+use crate::properties::energy::PerAtomEnergy;
+use crate::properties::forces::PerAtomVirial;
+use crate::properties::Property;
+use crate::system::species::Species;
+use crate::system::System;
+
+/// Microscopic heat flux vector of the system.
+///
+/// The flux is the sum of the convective term `e_i v_i`, where `e_i` is the kinetic plus potential
+/// energy of each atom, and the virial work term `W_i v_i` divided by the cell volume.
+#[derive(Clone, Copy, Debug)]
+pub struct HeatFlux;
+
+impl Property for HeatFlux {
+    type Res = Vector3<Float>;
+
+    fn calculate(&self, system: &System, potentials: &Potentials) -> Self::Res {
+        let energies = PerAtomEnergy.calculate(system, potentials);
+        let virials = PerAtomVirial.calculate(system, potentials);
+        let flux: Vector3<Float> = (0..system.size)
+            .map(|i| {
+                let vel = system.velocities[i];
+                let kinetic = 0.5 * system.species[i].mass() * vel.norm_squared();
+                (kinetic + energies[i]) * vel + virials[i] * vel
+            })
+            .sum();
+        flux / system.cell.volume()
+    }
+
+    fn name(&self) -> String {
+        "heat_flux".to_string()
+    }
+}
+
+/// Accumulates the heat flux autocorrelation function to evaluate the Green-Kubo thermal conductivity.
+///
+/// # References
+///
+/// [1] Schelling, Patrick K., Simon R. Phillpot, and Pawel Keblinski. "Comparison of atomic-level simulation methods for computing thermal conductivity." Physical Review B 65.14 (2002): 144306.
+#[derive(Clone, Debug)]
+pub struct GreenKubo {
+    interval: Float,
+    fluxes: Autocorrelation<Vector3<Float>>,
+}
+
+impl GreenKubo {
+    /// Returns a new [`GreenKubo`] accumulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between consecutive heat flux samples (fs).
+    /// * `correlation_length` - Number of samples spanned by the autocorrelation function.
+    pub fn new(interval: Float, correlation_length: usize) -> GreenKubo {
+        GreenKubo {
+            interval,
+            fluxes: Autocorrelation::new(correlation_length.saturating_sub(1)),
+        }
+    }
+
+    /// Records a heat flux sample.
+    pub fn push(&mut self, flux: Vector3<Float>) {
+        self.fluxes.push(flux);
+    }
+
+    /// Returns the heat flux autocorrelation function `<J(0)·J(t)>` averaged over all time origins.
+    pub fn autocorrelation(&self) -> Vec<Float> {
+        self.fluxes.calculate()
+    }
+
+    /// Returns the thermal conductivity (kcal/mol/Å/fs/K) of a system with the given volume and temperature.
+    pub fn conductivity(&self, volume: Float, temperature: Float) -> Float {
+        let acf = self.autocorrelation();
+        // trapezoidal integration of the autocorrelation function
+        let integral: Float = acf
+            .windows(2)
+            .map(|w| 0.5 * (w[0] + w[1]) * self.interval)
+            .sum();
+        volume * integral / (3.0 * boltzmann() * temperature.powi(2))
+    }
+}
+
+/// Accumulates the mean squared displacement of the atoms to evaluate the self-diffusion coefficient.
+///
+/// Positions are unwrapped by adding the minimum image displacement between consecutive samples,
+/// so no atom may move more than half of the cell between samples.
+/// Displacements can optionally be grouped by [`Species`] to compare the mobility of each component of a mixture.
+#[derive(Clone, Debug)]
+pub struct MeanSquaredDisplacement {
+    interval: Float,
+    by_species: bool,
+    origins: Vec<Vector3<Float>>,
+    unwrapped: Vec<Vector3<Float>>,
+    previous: Vec<Vector3<Float>>,
+    msd: Vec<Float>,
+    species_msd: Vec<(Species, Vec<Float>)>,
+}
+
+impl MeanSquaredDisplacement {
+    /// Returns a new [`MeanSquaredDisplacement`] accumulator.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between consecutive samples (fs).
+    pub fn new(interval: Float) -> MeanSquaredDisplacement {
+        MeanSquaredDisplacement {
+            interval,
+            by_species: false,
+            origins: Vec::new(),
+            unwrapped: Vec::new(),
+            previous: Vec::new(),
+            msd: Vec::new(),
+            species_msd: Vec::new(),
+        }
+    }
+
+    /// Sets whether the displacements are also averaged over the atoms of each species.
+    pub fn by_species(mut self, by_species: bool) -> MeanSquaredDisplacement {
+        self.by_species = by_species;
+        self
+    }
+
+    /// Records the displacement of every atom since the first sample.
+    pub fn accumulate(&mut self, system: &System) {
+        if self.origins.is_empty() {
+            self.origins = system.positions.clone();
+            self.unwrapped = system.positions.clone();
+        } else {
+            for (i, position) in system.positions.iter().enumerate() {
+                let mut step = position - self.previous[i];
+                system.cell.vector_image(&mut step);
+                self.unwrapped[i] += step;
+            }
+        }
+        self.previous = system.positions.clone();
+
+        let squared: Vec<Float> = self
+            .unwrapped
+            .iter()
+            .zip(self.origins.iter())
+            .map(|(position, origin)| (position - origin).norm_squared())
+            .collect();
+        self.msd
+            .push(squared.iter().sum::<Float>() / system.size as Float);
+        if self.by_species {
+            let mut totals: Vec<(Species, Float, usize)> = Vec::new();
+            for (species, value) in system.species.iter().zip(squared.iter()) {
+                match totals.iter_mut().find(|(s, _, _)| s == species) {
+                    Some((_, total, count)) => {
+                        *total += value;
+                        *count += 1;
+                    }
+                    None => totals.push((*species, *value, 1)),
+                }
+            }
+            for (species, total, count) in totals {
+                let mean = total / count as Float;
+                match self.species_msd.iter_mut().find(|(s, _)| *s == species) {
+                    Some((_, msd)) => msd.push(mean),
+                    None => self.species_msd.push((species, vec![mean])),
+                }
+            }
+        }
+    }
+
+    /// Returns the mean squared displacement (Å^2) of every atom at each sample.
+    pub fn msd(&self) -> &[Float] {
+        &self.msd
+    }
+
+    /// Returns the mean squared displacement (Å^2) of the atoms of `species` at each sample.
+    ///
+    /// Returns `None` if displacements are not grouped by species or no atom is of `species`.
+    pub fn species_msd(&self, species: Species) -> Option<&[Float]> {
+        self.species_msd
+            .iter()
+            .find(|(s, _)| *s == species)
+            .map(|(_, msd)| msd.as_slice())
+    }
+
+    /// Returns the self-diffusion coefficient (Å^2/fs) of every atom from the Einstein relation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than two samples have been accumulated.
+    pub fn diffusion_coefficient(&self) -> Float {
+        self.einstein(&self.msd)
+    }
+
+    /// Returns the self-diffusion coefficient (Å^2/fs) of the atoms of `species` from the Einstein relation.
+    ///
+    /// Returns `None` if displacements are not grouped by species or no atom is of `species`.
+    pub fn species_diffusion_coefficient(&self, species: Species) -> Option<Float> {
+        self.species_msd(species).map(|msd| self.einstein(msd))
+    }
+
+    // Returns one sixth of the least squares slope of the mean squared displacement over time.
+    fn einstein(&self, msd: &[Float]) -> Float {
+        assert!(
+            msd.len() > 1,
+            "the diffusion coefficient requires at least two samples"
+        );
+        let n = msd.len() as Float;
+        let times: Vec<Float> = (0..msd.len()).map(|k| k as Float * self.interval).collect();
+        let mean_time = times.iter().sum::<Float>() / n;
+        let mean_msd = msd.iter().sum::<Float>() / n;
+        let (covariance, variance) = times.iter().zip(msd.iter()).fold(
+            (0 as Float, 0 as Float),
+            |(covariance, variance), (t, m)| {
+                (
+                    covariance + (t - mean_time) * (m - mean_msd),
+                    variance + (t - mean_time).powi(2),
+                )
+            },
+        );
+        covariance / variance / 6.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GreenKubo, HeatFlux, MeanSquaredDisplacement};
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::potentials::PotentialsBuilder;
+    use crate::properties::Property;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn heat_flux_ideal_gas() {
+        let argon = Species::from_element(Element::Ar);
+        let system = System {
+            size: 3,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(1.0, 2.0, 3.0),
+                Vector3::new(4.0, 5.0, 6.0),
+                Vector3::new(7.0, 8.0, 9.0),
+            ],
+            velocities: vec![
+                Vector3::new(0.01, 0.0, -0.02),
+                Vector3::new(-0.03, 0.01, 0.0),
+                Vector3::new(0.0, 0.02, 0.01),
+            ],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new().build();
+        let flux = HeatFlux.calculate(&system, &potentials);
+
+        // without interactions only the kinetic energy is convected
+        let expected: Vector3<Float> = system
+            .velocities
+            .iter()
+            .map(|vel| 0.5 * argon.mass() * vel.norm_squared() * vel)
+            .sum::<Vector3<Float>>()
+            / system.cell.volume();
+        for k in 0..3 {
+            assert_relative_eq!(flux[k], expected[k], epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn green_kubo_constant_flux() {
+        let mut green_kubo = GreenKubo::new(2.0, 11);
+        for _ in 0..20 {
+            green_kubo.push(Vector3::new(1.0, 0.0, 0.0));
+        }
+        let acf = green_kubo.autocorrelation();
+        assert_eq!(acf.len(), 11);
+        for value in acf {
+            assert_relative_eq!(value, 1.0);
+        }
+        // the integral spans 10 intervals of 2 fs
+        let conductivity = green_kubo.conductivity(1.0, 1.0);
+        assert_relative_eq!(
+            conductivity,
+            20.0 / (3.0 * boltzmann()),
+            max_relative = 1e-5
+        );
+    }
+
+    #[test]
+    fn species_diffusion() {
+        let argon = Species::from_element(Element::Ar);
+        let xenon = Species::from_element(Element::Xe);
+        let size = 20;
+        let speed = 0.05;
+        let mut system = System {
+            size,
+            cell: Cell::cubic(10.0),
+            species: (0..size)
+                .map(|i| if i % 2 == 0 { argon } else { xenon })
+                .collect(),
+            positions: (0..size)
+                .map(|i| Vector3::new(0.5 * i as Float, 0.3 * i as Float, 9.0))
+                .collect(),
+            // xenon atoms move twice as fast along scattered directions
+            velocities: (0..size)
+                .map(|i| {
+                    let angle = i as Float;
+                    let direction = Vector3::new(Float::cos(angle), Float::sin(angle), 0.5);
+                    let factor = if i % 2 == 0 { 1.0 } else { 2.0 };
+                    factor * speed * direction.normalize()
+                })
+                .collect(),
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let interval = 10.0;
+        let mut msd = MeanSquaredDisplacement::new(interval).by_species(true);
+        for _ in 0..20 {
+            msd.accumulate(&system);
+            // free flight with positions wrapped back into the cell
+            for (position, velocity) in system.positions.iter_mut().zip(system.velocities.iter()) {
+                *position += velocity * interval;
+                system.cell.wrap_vector(position);
+            }
+        }
+
+        // atoms cross the boundaries but the unwrapped displacement is ballistic
+        let time = 19.0 * interval;
+        let argon_msd = msd.species_msd(argon).unwrap();
+        let xenon_msd = msd.species_msd(xenon).unwrap();
+        assert_eq!(argon_msd.len(), 20);
+        assert_relative_eq!(argon_msd[19], (speed * time).powi(2), max_relative = 1e-4);
+        assert_relative_eq!(
+            xenon_msd[19],
+            (2.0 * speed * time).powi(2),
+            max_relative = 1e-4
+        );
+        assert_relative_eq!(
+            msd.msd()[19],
+            0.5 * (argon_msd[19] + xenon_msd[19]),
+            max_relative = 1e-4
+        );
+
+        let argon_slope = msd.species_diffusion_coefficient(argon).unwrap();
+        let xenon_slope = msd.species_diffusion_coefficient(xenon).unwrap();
+        assert!(argon_slope > 0.0);
+        assert_relative_eq!(xenon_slope, 4.0 * argon_slope, max_relative = 1e-3);
+        assert!(MeanSquaredDisplacement::new(interval)
+            .species_msd(argon)
+            .is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Restart files which capture the state of a simulation.
+
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use nalgebra::{Matrix3, Vector3};
+
+use crate::internal::Float;
+use crate::system::cell::Cell;
+use crate::system::species::Species;
+use crate::system::System;
+
+/// Snapshot of a [`System`] and the propagator state after a number of completed steps.
+#[derive(Clone, Debug)]
+pub struct Restart {
+    /// Number of completed steps.
+    pub step: usize,
+    /// System at the step.
+    pub system: System,
+    /// Internal state of each propagator component as returned by [`Propagator::state`](crate::propagators::Propagator::state).
+    pub state: Vec<Vec<Float>>,
+}
+
+impl Restart {
+    /// Returns a new [`Restart`].
+    pub fn new(step: usize, system: System, state: Vec<Vec<Float>>) -> Restart {
+        Restart {
+            step,
+            system,
+            state,
+        }
+    }
+
+    /// Parses a restart from a file.
+    pub fn parse_from_file<P: AsRef<Path>>(path: P) -> Restart {
+        let text = fs::read_to_string(path).unwrap();
+        Restart::parse_from_str(&text)
+    }
+
+    /// Parses a restart from a string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is not a valid restart.
+    pub fn parse_from_str(text: &str) -> Restart {
+        let mut lines = text.lines();
+        let step = section(lines.next(), "step");
+        let mut columns = [Vector3::zeros(); 3];
+        for column in columns.iter_mut() {
+            *column = vector(&numbers(lines.next(), 3));
+        }
+        let mut cell = Cell::from_matrix(Matrix3::from_columns(&columns));
+        cell.set_origin(vector(&labeled(lines.next(), "origin", 3)));
+        cell.set_planar(section(lines.next(), "planar") != 0);
+
+        let size = section(lines.next(), "atoms");
+        let mut species = Vec::with_capacity(size);
+        let mut positions = Vec::with_capacity(size);
+        let mut velocities = Vec::with_capacity(size);
+        for _ in 0..size {
+            let line = lines.next().expect("restart is missing atoms");
+            let (id, rest) = line
+                .split_once(' ')
+                .expect("restart atom is missing values");
+            let values = numbers(Some(rest), 8);
+            species.push(Species::from_parts(
+                id.parse().unwrap(),
+                values[0],
+                values[1],
+            ));
+            positions.push(vector(&values[2..5]));
+            velocities.push(vector(&values[5..8]));
+        }
+
+        let count = section(lines.next(), "bonds");
+        let bonds = (0..count).map(|_| indices(lines.next())).collect();
+        let count = section(lines.next(), "angles");
+        let angles = (0..count).map(|_| indices(lines.next())).collect();
+        let count = section(lines.next(), "dihedrals");
+        let dihedrals = (0..count).map(|_| indices(lines.next())).collect();
+        let count = section(lines.next(), "state");
+        let state = (0..count).map(|_| values(lines.next())).collect();
+
+        Restart {
+            step,
+            system: System {
+                size,
+                cell,
+                species,
+                positions,
+                velocities,
+                bonds,
+                angles,
+                dihedrals,
+            },
+            state,
+        }
+    }
+
+    /// Writes the restart to a file.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Writes the restart to a file while keeping the previous file at `<path>.prev`.
+    ///
+    /// The restart is written to a temporary file first so the last two restarts survive a crash mid-write.
+    pub fn write_to_file_rotating<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let temporary = with_suffix(path, "tmp");
+        self.write_to_file(&temporary)?;
+        if path.exists() {
+            fs::rename(path, with_suffix(path, "prev"))?;
+        }
+        fs::rename(&temporary, path)
+    }
+}
+
+impl std::fmt::Display for Restart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let system = &self.system;
+        let mut text = String::new();
+        writeln!(text, "step {}", self.step)?;
+        for vector in &[
+            system.cell.a_vector(),
+            system.cell.b_vector(),
+            system.cell.c_vector(),
+        ] {
+            writeln!(text, "{} {} {}", vector[0], vector[1], vector[2])?;
+        }
+        let origin = system.cell.origin();
+        writeln!(text, "origin {} {} {}", origin[0], origin[1], origin[2])?;
+        writeln!(text, "planar {}", system.cell.is_planar() as usize)?;
+        writeln!(text, "atoms {}", system.size)?;
+        for i in 0..system.size {
+            let species = system.species[i];
+            let pos = system.positions[i];
+            let vel = system.velocities[i];
+            writeln!(
+                text,
+                "{} {} {} {} {} {} {} {} {}",
+                species.id(),
+                species.mass(),
+                species.charge(),
+                pos[0],
+                pos[1],
+                pos[2],
+                vel[0],
+                vel[1],
+                vel[2]
+            )?;
+        }
+        writeln!(text, "bonds {}", system.bonds.len())?;
+        for bond in &system.bonds {
+            writeln!(text, "{} {}", bond[0], bond[1])?;
+        }
+        writeln!(text, "angles {}", system.angles.len())?;
+        for angle in &system.angles {
+            writeln!(text, "{} {} {}", angle[0], angle[1], angle[2])?;
+        }
+        writeln!(text, "dihedrals {}", system.dihedrals.len())?;
+        for dihedral in &system.dihedrals {
+            writeln!(
+                text,
+                "{} {} {} {}",
+                dihedral[0], dihedral[1], dihedral[2], dihedral[3]
+            )?;
+        }
+        writeln!(text, "state {}", self.state.len())?;
+        for values in &self.state {
+            let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            writeln!(text, "{}", values.join(" "))?;
+        }
+        f.write_str(&text)
+    }
+}
+
+// Appends a suffix to the file name of a path.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// Parses the count of a section header such as `atoms 2`.
+fn section(line: Option<&str>, name: &str) -> usize {
+    let line = line.unwrap_or_else(|| panic!("restart is missing the '{}' section", name));
+    match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [header, count] if *header == name => count.parse().unwrap(),
+        _ => panic!(
+            "expected the '{}' section in restart, found '{}'",
+            name, line
+        ),
+    }
+}
+
+// Parses the values of a labeled line such as `origin 0 0 0`.
+fn labeled(line: Option<&str>, name: &str, count: usize) -> Vec<Float> {
+    let line = line.unwrap_or_else(|| panic!("restart is missing the '{}' line", name));
+    match line.split_once(' ') {
+        Some((header, rest)) if header == name => numbers(Some(rest), count),
+        _ => panic!("expected the '{}' line in restart, found '{}'", name, line),
+    }
+}
+
+fn values(line: Option<&str>) -> Vec<Float> {
+    line.expect("restart ended unexpectedly")
+        .split_whitespace()
+        .map(|value| value.parse().unwrap())
+        .collect()
+}
+
+fn numbers(line: Option<&str>, count: usize) -> Vec<Float> {
+    let values = values(line);
+    assert_eq!(
+        values.len(),
+        count,
+        "restart line has the wrong number of values"
+    );
+    values
+}
+
+fn vector(values: &[Float]) -> Vector3<Float> {
+    Vector3::new(values[0], values[1], values[2])
+}
+
+fn indices<const N: usize>(line: Option<&str>) -> [usize; N] {
+    let mut indices = [0; N];
+    let mut values = line.expect("restart ended unexpectedly").split_whitespace();
+    for index in indices.iter_mut() {
+        *index = values
+            .next()
+            .expect("restart is missing an index")
+            .parse()
+            .unwrap();
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Restart;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn round_trip() {
+        let oxygen = Species::from_element(Element::O);
+        let hydrogen = Species::new(1.008, 0.41);
+        let system = System {
+            size: 3,
+            cell: Cell::triclinic(10.0, 11.0, 12.0, 90.0, 95.0, 100.0),
+            species: vec![oxygen.with_charge(-0.82), hydrogen, hydrogen],
+            positions: vec![
+                Vector3::new(5.0, 5.0, 5.0),
+                Vector3::new(5.957, 5.0, 5.0),
+                Vector3::new(4.76, 5.927, 5.0),
+            ],
+            velocities: vec![
+                Vector3::new(0.001, -0.002, 0.0003),
+                Vector3::new(0.01, 0.0, -0.02),
+                Vector3::new(-0.003, 0.004, 0.0),
+            ],
+            bonds: vec![[0, 1], [0, 2]],
+            angles: vec![[1, 0, 2]],
+            dihedrals: Vec::new(),
+        };
+        let state = vec![vec![0.5, -1.25e-7], Vec::new()];
+        let restart = Restart::new(42, system.clone(), state.clone());
+        let read = Restart::parse_from_str(&restart.to_string());
+
+        assert_eq!(read.step, 42);
+        assert_eq!(read.system.size, system.size);
+        assert_eq!(read.system.cell.b_vector(), system.cell.b_vector());
+        assert_eq!(read.system.species, system.species);
+        assert_eq!(read.system.species[0].charge(), -0.82);
+        assert_eq!(read.system.positions, system.positions);
+        assert_eq!(read.system.velocities, system.velocities);
+        assert_eq!(read.system.bonds, system.bonds);
+        assert_eq!(read.system.angles, system.angles);
+        assert_eq!(read.system.dihedrals, system.dihedrals);
+        assert_eq!(read.state, state);
+        assert!(!read.system.cell.is_planar());
+    }
+
+    #[test]
+    fn bounds() {
+        let argon = Species::from_element(Element::Ar);
+        let (lo, hi) = (Vector3::new(-5.0, 0.0, 2.5), Vector3::new(5.0, 12.0, 12.5));
+        let mut system = System {
+            size: 1,
+            cell: Cell::from_bounds(lo, hi),
+            species: vec![argon],
+            positions: vec![Vector3::new(1.0, 2.0, 3.0)],
+            velocities: vec![Vector3::zeros()],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // vacuum padding extends the upper bound only
+        system.add_vacuum(2, 5.0);
+        assert_eq!(system.cell.bounds(), (lo, Vector3::new(5.0, 12.0, 17.5)));
+
+        let restart = Restart::new(3, system.clone(), Vec::new());
+        let read = Restart::parse_from_str(&restart.to_string());
+        assert_eq!(read.system.cell.bounds(), system.cell.bounds());
+    }
+
+    #[test]
+    fn planar() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 2,
+            cell: Cell::triclinic(20.0, 20.0, 5.0, 90.0, 90.0, 90.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(5.0, 5.0, 0.0), Vector3::new(8.8, 5.0, 0.0)],
+            velocities: vec![Vector3::new(0.001, -0.002, 0.0); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        system.make_planar();
+        let restart = Restart::new(7, system, Vec::new());
+        let read = Restart::parse_from_str(&restart.to_string());
+        // a resumed planar run keeps two degrees of freedom per atom
+        assert!(read.system.cell.is_planar());
+        assert_eq!(read.system.degrees_of_freedom(), 4);
+    }
+
+    #[test]
+    fn sheared() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 1,
+            cell: Cell::cubic(10.0),
+            species: vec![argon],
+            positions: vec![Vector3::new(1.0, 2.0, 3.0)],
+            velocities: vec![Vector3::zeros()],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        system.cell.shear(2.5);
+        let restart = Restart::new(3, system.clone(), Vec::new());
+        let read = Restart::parse_from_str(&restart.to_string());
+        // the Lees-Edwards offset is the tilt of the cell so it resumes with the cell vectors
+        assert!(read.system.cell.shear_offset() > 2.4);
+        assert_eq!(read.system.cell.shear_offset(), system.cell.shear_offset());
+    }
+}
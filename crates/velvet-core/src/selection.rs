@@ -5,7 +5,10 @@ use rayon::prelude::*;
 
 use std::marker::PhantomData;
 
+use nalgebra::Vector3;
+
 use crate::internal::Float;
+use crate::system::cell::Cell;
 use crate::system::species::Species;
 use crate::system::System;
 
@@ -46,6 +49,18 @@ where
         self.current_indices = (self.update_func)(system, &self.possible_indices, args)
     }
 
+    /// Updates the selection by searching `indices` instead of the possible indices found during setup.
+    ///
+    /// This allows several selections to draw from a single shared selection.
+    pub fn update_from(&mut self, system: &System, indices: &[[usize; N]], args: UArgs) {
+        self.current_indices = (self.update_func)(system, indices, args)
+    }
+
+    /// Returns the selection's current indices as a slice.
+    pub fn current_indices(&self) -> &[[usize; N]] {
+        &self.current_indices
+    }
+
     /// Returns an iterator over the selection's current indices.
     pub fn indices(&self) -> impl Iterator<Item = &[usize; N]> {
         self.current_indices.iter()
@@ -60,10 +75,7 @@ where
 
 // This function should not be used in the public API but must be exported for integration testing purposes.
 #[doc(hidden)]
-pub fn setup_pairs_by_species(
-    system: &System,
-    species: (Species, Species),
-) -> Vec<[usize; 2]> {
+pub fn setup_pairs_by_species(system: &System, species: (Species, Species)) -> Vec<[usize; 2]> {
     let mut possible_indices: Vec<[usize; 2]> = Vec::with_capacity(system.size.pow(2));
     for i in 0..system.size {
         let species_i = system.species[i];
@@ -80,6 +92,27 @@ pub fn setup_pairs_by_species(
     possible_indices
 }
 
+// This function should not be used in the public API but must be exported for integration testing purposes.
+#[doc(hidden)]
+pub fn setup_all_pairs(system: &System, _: ()) -> Vec<[usize; 2]> {
+    let mut possible_indices: Vec<[usize; 2]> = Vec::with_capacity(system.size.pow(2));
+    for i in 0..system.size {
+        for j in (i + 1)..system.size {
+            possible_indices.push([i, j]);
+        }
+    }
+    possible_indices
+}
+
+// This function should not be used in the public API but must be exported for integration testing purposes.
+#[doc(hidden)]
+pub fn setup_all_pairs_with_self(system: &System, _: ()) -> Vec<[usize; 2]> {
+    let mut possible_indices = setup_all_pairs(system, ());
+    // each atom is paired with itself to interact with its own periodic images
+    possible_indices.extend((0..system.size).map(|i| [i, i]));
+    possible_indices
+}
+
 // This function should not be used in the public API but must be exported for integration testing purposes.
 #[doc(hidden)]
 pub fn setup_pairs_with_charge(system: &System, _: ()) -> Vec<[usize; 2]> {
@@ -88,7 +121,9 @@ pub fn setup_pairs_with_charge(system: &System, _: ()) -> Vec<[usize; 2]> {
         let species_i = system.species[i];
         for j in (i + 1)..system.size {
             let species_j = system.species[j];
-            if species_i.charge().abs() > Float::EPSILON || species_j.charge().abs() > Float::EPSILON {
+            if species_i.charge().abs() > Float::EPSILON
+                || species_j.charge().abs() > Float::EPSILON
+            {
                 possible_indices.push([i, j]);
             }
         }
@@ -115,3 +150,102 @@ pub fn update_pairs_by_cutoff_radius(
         .copied()
         .collect()
 }
+
+// This function should not be used in the public API but must be exported for integration testing purposes.
+#[doc(hidden)]
+pub fn update_pairs_by_species(
+    system: &System,
+    indices: &[[usize; 2]],
+    args: ((Species, Species), Float),
+) -> Vec<[usize; 2]> {
+    let (species, cutoff) = args;
+    indices
+        .iter()
+        .filter_map(|&[i, j]| {
+            // orient each pair in the same order as `setup_pairs_by_species`
+            let pair = if (system.species[i], system.species[j]) == species {
+                [i, j]
+            } else if (system.species[j], system.species[i]) == species {
+                [j, i]
+            } else {
+                return None;
+            };
+            let r = system
+                .cell
+                .distance(&system.positions[i], &system.positions[j]);
+            if r < cutoff {
+                Some(pair)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Neighbor of an atom at one of its periodic images.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageNeighbor {
+    /// Index of the neighboring atom.
+    pub index: usize,
+    /// Vector from the atom to this image of its neighbor.
+    pub displacement: Vector3<Float>,
+}
+
+/// Returns the neighbors of each atom within a cutoff radius including periodic images.
+///
+/// Unlike the minimum image convention, a neighbor is listed once for every image within the cutoff,
+/// and the images of an atom itself (other than the atom) are its neighbors too.
+/// This supports cutoffs larger than half of the cell width at the cost of searching every image,
+/// so it is intended for small cells.
+pub fn image_neighbors(system: &System, cutoff: Float) -> Vec<Vec<ImageNeighbor>> {
+    let shifts: Vec<Vector3<Float>> = image_shifts(&system.cell, cutoff)
+        .iter()
+        .map(|shift| system.cell.cartesian(shift))
+        .collect();
+    (0..system.size)
+        .map(|i| {
+            let mut neighbors = Vec::new();
+            for j in 0..system.size {
+                let mut minimum = system.positions[j] - system.positions[i];
+                system.cell.vector_image(&mut minimum);
+                for shift in &shifts {
+                    if i == j && *shift == Vector3::zeros() {
+                        continue;
+                    }
+                    let displacement = minimum + shift;
+                    if displacement.norm() < cutoff {
+                        neighbors.push(ImageNeighbor {
+                            index: j,
+                            displacement,
+                        });
+                    }
+                }
+            }
+            neighbors
+        })
+        .collect()
+}
+
+// Returns the fractional shifts of every periodic image which can hold a neighbor within `cutoff`
+// of the minimum image, including the zero shift.
+pub(crate) fn image_shifts(cell: &Cell, cutoff: Float) -> Vec<Vector3<Float>> {
+    let widths = cell.widths();
+    // a minimum image spans at most half of the cell along each fractional axis
+    let mut range: Vec<i32> = widths
+        .iter()
+        .map(|width| Float::ceil(cutoff / width + 0.5) as i32)
+        .collect();
+    // the layers of a planar cell are not periodic images
+    if cell.is_planar() {
+        range[2] = 0;
+    }
+    let mut shifts = Vec::new();
+    for x in -range[0]..=range[0] {
+        for y in -range[1]..=range[1] {
+            for z in -range[2]..=range[2] {
+                shifts.push(Vector3::new(x as Float, y as Float, z as Float));
+            }
+        }
+    }
+    shifts
+}
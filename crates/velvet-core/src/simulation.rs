@@ -5,9 +5,15 @@ use indicatif::ProgressDrawTarget;
 use indicatif::{ProgressBar, ProgressStyle};
 
 use crate::config::Configuration;
+use crate::internal::Float;
+use crate::outputs::OutputMeta;
 use crate::potentials::Potentials;
 use crate::propagators::Propagator;
+use crate::properties::energy::PotentialEnergy;
+use crate::properties::Property;
+use crate::restart::Restart;
 use crate::system::System;
+use crate::velocity_distributions::{Boltzmann, VelocityDistribution};
 
 /// High level abstraction for an atomistic simulation.
 pub struct Simulation {
@@ -15,9 +21,13 @@ pub struct Simulation {
     potentials: Potentials,
     propagator: Box<dyn Propagator>,
     config: Configuration,
+    // number of completed steps
+    step: usize,
+    // propagator state to restore before the next run
+    state: Option<Vec<Vec<Float>>>,
 }
 
-impl<'a> Simulation {
+impl Simulation {
     /// Returns a new [`Simulation`].
     pub fn new<P>(
         system: System,
@@ -33,17 +43,47 @@ impl<'a> Simulation {
             potentials,
             propagator: Box::new(propagator),
             config,
+            step: 0,
+            state: None,
         }
     }
 
+    /// Returns a [`Simulation`] which resumes from a [`Restart`].
+    ///
+    /// The propagator must be configured the same way as the one which wrote the restart.
+    pub fn from_restart<P>(
+        restart: Restart,
+        potentials: Potentials,
+        propagator: P,
+        config: Configuration,
+    ) -> Simulation
+    where
+        P: Propagator + 'static,
+    {
+        let mut simulation = Simulation::new(restart.system, potentials, propagator, config);
+        simulation.step = restart.step;
+        simulation.state = Some(restart.state);
+        simulation
+    }
+
     /// Runs the full iteration loop of the simulation.
-    pub fn run(&mut self, steps: usize) {
-        // setup potentials
-        self.potentials.setup(&self.system);
+    ///
+    /// Returns an error if the potentials can not be set up on the system as reported by
+    /// [`Potentials::setup`], if a raw output group fails to write to its destination, or if a
+    /// checkpoint can not be written.
+    pub fn run(&mut self, steps: usize) -> Result<(), String> {
+        // setup potentials and select the initial neighbors
+        self.potentials.setup(&self.system)?;
+        self.potentials.update(&self.system, 0);
 
         // setup propagation
         self.propagator.setup(&mut self.system, &self.potentials);
 
+        // resume from a restart
+        if let Some(state) = self.state.take() {
+            self.propagator.restore(&state);
+        }
+
         // setup progress bar
         let pb = ProgressBar::new(steps as u64);
         pb.set_style(
@@ -56,30 +96,48 @@ impl<'a> Simulation {
 
         // start iteration loop
         for i in 0..steps {
+            let step = self.step + i;
+
             // do one propagation step
             self.propagator
                 .propagate(&mut self.system, &self.potentials);
 
             // update the potentials
-            self.potentials.update(&self.system, i);
+            self.potentials.update(&self.system, step);
 
             // raw outputs
+            let meta = OutputMeta::new(step + 1, self.config.timestep());
             for group in self.config.raw_output_groups() {
-                let should_output = i % group.interval == 0 || i == steps - 1;
-                let destination = group.destination.as_mut();
+                let should_output = step.is_multiple_of(group.interval) || i == steps - 1;
+                let destination = &mut group.destination;
                 for output in group.outputs.iter() {
                     if should_output {
-                        output.output_raw(&self.system, &self.potentials, destination)
+                        output.output_raw_at(&meta, &self.system, &self.potentials, destination)
                     }
                 }
+                if should_output {
+                    destination
+                        .end_frame()
+                        .map_err(|error| format!("failed to write raw output: {}", error))?;
+                }
+            }
+
+            // CSV outputs
+            for group in self.config.csv_output_groups() {
+                if step.is_multiple_of(group.interval) || i == steps - 1 {
+                    group.write(&meta, &self.system, &self.potentials)
+                }
             }
 
             // HDF5 outputs
             #[cfg(feature = "hdf5-output")]
             {
                 for group in self.config.hdf5_output_groups() {
-                    let should_output = i % group.interval == 0 || i == steps - 1;
-                    let g = group.file_handle.create_group(&format!("{}", i)).unwrap();
+                    let should_output = step.is_multiple_of(group.interval) || i == steps - 1;
+                    let g = group
+                        .file_handle
+                        .create_group(&format!("{}", step))
+                        .unwrap();
                     for output in group.outputs.iter() {
                         if should_output {
                             output.output_hdf5(&self.system, &self.potentials, &g)
@@ -87,9 +145,33 @@ impl<'a> Simulation {
                     }
                 }
             }
+
+            // checkpoints
+            if let Some((path, interval)) = self.config.checkpoint() {
+                if (step + 1).is_multiple_of(interval) {
+                    let state = self.propagator.state();
+                    Restart::new(step + 1, self.system.clone(), state)
+                        .write_to_file_rotating(path)
+                        .map_err(|error| {
+                            format!("failed to write checkpoint {}: {}", path.display(), error)
+                        })?;
+                }
+            }
             pb.inc(1);
         }
         pb.finish();
+        self.step += steps;
+        Ok(())
+    }
+
+    /// Returns a reference to the simulated [`System`].
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    /// Returns a reference to the applied [`Potentials`].
+    pub fn potentials(&self) -> &Potentials {
+        &self.potentials
     }
 
     /// Consume the simulation and return its [`System`] and [`Potentials`].
@@ -97,3 +179,292 @@ impl<'a> Simulation {
         (self.system, self.potentials)
     }
 }
+
+/// Returns each temperature paired with the average of `property` over a production run at that temperature.
+///
+/// For each temperature a copy of `system` is given Maxwell-Boltzmann velocities, equilibrated for
+/// `equilibration_steps` and then sampled once per step for `production_steps`.
+/// `propagator` returns the thermostatted propagator which targets the given temperature.
+///
+/// # Panics
+///
+/// Panics if the potentials can not be set up on the system as reported by [`Potentials::setup`].
+pub fn temperature_sweep<T, F, P>(
+    system: &System,
+    potentials: &mut Potentials,
+    temperatures: &[Float],
+    equilibration_steps: usize,
+    production_steps: usize,
+    property: T,
+    propagator: F,
+) -> Vec<(Float, Float)>
+where
+    T: Property<Res = Float>,
+    F: Fn(Float) -> P,
+    P: Propagator,
+{
+    temperatures
+        .iter()
+        .map(|&temperature| {
+            let mut system = system.clone();
+            Boltzmann::new(temperature)
+                .remove_drift(true)
+                .apply(&mut system);
+            potentials
+                .setup(&system)
+                .unwrap_or_else(|message| panic!("{}", message));
+            potentials.update(&system, 0);
+            let mut propagator = propagator(temperature);
+            propagator.setup(&mut system, potentials);
+
+            let mut total = 0 as Float;
+            for step in 0..equilibration_steps + production_steps {
+                propagator.propagate(&mut system, potentials);
+                potentials.update(&system, step);
+                if step >= equilibration_steps {
+                    total += property.calculate(&system, potentials);
+                }
+            }
+            (temperature, total / production_steps as Float)
+        })
+        .collect()
+}
+
+// Coupling parameter step of the central difference which estimates `dU/dlambda`.
+const LAMBDA_STEP: Float = 1e-3;
+
+/// Quadrature rule over the coupling parameter of a [`thermodynamic_integration`].
+#[derive(Clone, Debug)]
+pub enum Quadrature {
+    /// Trapezoidal rule over increasing coupling parameters which need not be evenly spaced.
+    Trapezoid(Vec<Float>),
+    /// Gauss-Legendre rule with the given number of points over the interval `[0, 1]`.
+    GaussLegendre(usize),
+}
+
+impl Quadrature {
+    /// Returns each coupling parameter of the rule paired with its weight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a trapezoidal rule has fewer than two points or its points do not increase,
+    /// or if a Gauss-Legendre rule has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// let points = Quadrature::Trapezoid(vec![0.0, 0.5, 1.0]).points();
+    /// assert_eq!(points, vec![(0.0, 0.25), (0.5, 0.5), (1.0, 0.25)]);
+    ///
+    /// let total: f64 = Quadrature::GaussLegendre(4)
+    ///     .points()
+    ///     .iter()
+    ///     .map(|&(lambda, weight)| (weight * lambda * lambda) as f64)
+    ///     .sum();
+    /// assert!((total - 1.0 / 3.0).abs() < 1e-5);
+    /// ```
+    pub fn points(&self) -> Vec<(Float, Float)> {
+        match self {
+            Quadrature::Trapezoid(lambdas) => {
+                assert!(
+                    lambdas.len() >= 2,
+                    "the trapezoidal rule requires at least two points"
+                );
+                assert!(
+                    lambdas.windows(2).all(|w| w[0] < w[1]),
+                    "the points of the trapezoidal rule must increase"
+                );
+                let last = lambdas.len() - 1;
+                (0..=last)
+                    .map(|i| {
+                        let left = if i > 0 {
+                            lambdas[i] - lambdas[i - 1]
+                        } else {
+                            0.0
+                        };
+                        let right = if i < last {
+                            lambdas[i + 1] - lambdas[i]
+                        } else {
+                            0.0
+                        };
+                        (lambdas[i], 0.5 * (left + right))
+                    })
+                    .collect()
+            }
+            Quadrature::GaussLegendre(n) => {
+                assert!(
+                    *n > 0,
+                    "the Gauss-Legendre rule requires at least one point"
+                );
+                // Newton iteration for the roots of the Legendre polynomial in double precision
+                (0..*n)
+                    .rev()
+                    .map(|i| {
+                        let order = *n as f64;
+                        let mut x =
+                            f64::cos(std::f64::consts::PI * (i as f64 + 0.75) / (order + 0.5));
+                        let mut derivative = 1.0;
+                        for _ in 0..100 {
+                            let (mut p0, mut p1) = (1.0, x);
+                            for k in 2..=*n {
+                                let k = k as f64;
+                                let p2 = ((2.0 * k - 1.0) * x * p1 - (k - 1.0) * p0) / k;
+                                p0 = p1;
+                                p1 = p2;
+                            }
+                            derivative = order * (x * p1 - p0) / (x * x - 1.0);
+                            let dx = p1 / derivative;
+                            x -= dx;
+                            if dx.abs() < 1e-15 {
+                                break;
+                            }
+                        }
+                        let weight = 2.0 / ((1.0 - x * x) * derivative * derivative);
+                        // map from [-1, 1] onto [0, 1]
+                        ((0.5 * (x + 1.0)) as Float, (0.5 * weight) as Float)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // Returns the interval of coupling parameters covered by the rule.
+    fn bounds(&self) -> (Float, Float) {
+        match self {
+            Quadrature::Trapezoid(lambdas) => (lambdas[0], lambdas[lambdas.len() - 1]),
+            Quadrature::GaussLegendre(_) => (0.0, 1.0),
+        }
+    }
+}
+
+/// Returns the free energy difference across the coupling parameter and the average of `dU/dlambda` at each point.
+///
+/// For each point of the `quadrature` rule a copy of `system` is sampled by the propagator returned by
+/// `propagator` under the potentials returned by `potentials` at that coupling parameter.
+/// Each copy is equilibrated for `equilibration_steps` and then `dU/dlambda` is sampled once per step for
+/// `production_steps` by a central difference of the potential energy which is limited to the interval of the rule.
+/// The free energy difference is the weighted sum of the averages.
+///
+/// # Panics
+///
+/// Panics if the potentials can not be set up on the system as reported by [`Potentials::setup`].
+pub fn thermodynamic_integration<F, G, P>(
+    system: &System,
+    quadrature: &Quadrature,
+    equilibration_steps: usize,
+    production_steps: usize,
+    potentials: F,
+    propagator: G,
+) -> (Float, Vec<(Float, Float)>)
+where
+    F: Fn(Float) -> Potentials,
+    G: Fn(Float) -> P,
+    P: Propagator,
+{
+    let (lower, upper) = quadrature.bounds();
+    let mut free_energy = 0 as Float;
+    let averages = quadrature
+        .points()
+        .into_iter()
+        .map(|(lambda, weight)| {
+            let mut system = system.clone();
+            let below = Float::max(lambda - LAMBDA_STEP, lower);
+            let above = Float::min(lambda + LAMBDA_STEP, upper);
+            let mut current = potentials(lambda);
+            let mut backward = potentials(below);
+            let mut forward = potentials(above);
+            for potentials in [&mut current, &mut backward, &mut forward].iter_mut() {
+                potentials
+                    .setup(&system)
+                    .unwrap_or_else(|message| panic!("{}", message));
+                potentials.update(&system, 0);
+            }
+            let mut propagator = propagator(lambda);
+            propagator.setup(&mut system, &current);
+
+            let mut total = 0 as Float;
+            for step in 0..equilibration_steps + production_steps {
+                propagator.propagate(&mut system, &current);
+                for potentials in [&mut current, &mut backward, &mut forward].iter_mut() {
+                    potentials.update(&system, step);
+                }
+                if step >= equilibration_steps {
+                    let difference = PotentialEnergy.calculate(&system, &forward)
+                        - PotentialEnergy.calculate(&system, &backward);
+                    total += difference / (above - below);
+                }
+            }
+            let average = total / production_steps as Float;
+            free_energy += weight * average;
+            (lambda, average)
+        })
+        .collect();
+    (free_energy, averages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{thermodynamic_integration, Quadrature};
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::potentials::types::Harmonic;
+    use crate::potentials::PotentialsBuilder;
+    use crate::propagators::MonteCarlo;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn thermodynamic_integration_of_harmonic_spring() {
+        // stiffening a spring from k0 to k1 changes the free energy by 3/2 kT ln(k1 / k0)
+        let argon = Species::from_element(Element::Ar);
+        let (k0, k1, temperature) = (1.0, 4.0, 300.0);
+        let system = System {
+            size: 2,
+            cell: Cell::cubic(50.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(25.0, 25.0, 25.0),
+                Vector3::new(25.5, 25.0, 25.0),
+            ],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let expected = 1.5 * boltzmann() * temperature * Float::ln(k1 / k0);
+        for quadrature in [
+            Quadrature::GaussLegendre(5),
+            Quadrature::Trapezoid((0..=10).map(|i| i as Float / 10.0).collect()),
+        ]
+        .iter()
+        {
+            let (free_energy, averages) = thermodynamic_integration(
+                &system,
+                quadrature,
+                1000,
+                10000,
+                |lambda| {
+                    let k = k0 + lambda * (k1 - k0);
+                    PotentialsBuilder::new()
+                        .pair(Harmonic::new(k, 0.0), (argon, argon), 20.0, 1.0)
+                        .build()
+                },
+                |_| MonteCarlo::new(temperature, 1.0).seed(1234),
+            );
+            assert_eq!(averages.len(), quadrature.points().len());
+            // the spring softens the integrand as it stiffens
+            assert!(averages.windows(2).all(|w| w[0].1 > w[1].1));
+            assert!(
+                (free_energy - expected).abs() < 0.05 * expected,
+                "free energy difference {} is not close to {}",
+                free_energy,
+                expected
+            );
+        }
+    }
+}
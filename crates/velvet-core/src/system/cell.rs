@@ -9,6 +9,13 @@ use crate::internal::Float;
 pub struct Cell {
     matrix: Matrix3<Float>,
     inv_matrix: Matrix3<Float>,
+    orthogonal: bool,
+    // vectors shorter than this are their own minimum image
+    image_radius: Float,
+    // lower corner of the box in the frame of its source, see `Cell::bounds`
+    origin: Vector3<Float>,
+    // motion is confined to the xy-plane, see `Cell::set_planar`
+    planar: bool,
 }
 
 impl Cell {
@@ -33,9 +40,7 @@ impl Cell {
         beta: Float,
         gamma: Float,
     ) -> Cell {
-        let matrix = cell_matrix(a, b, c, alpha, beta, gamma);
-        let inv_matrix = matrix.try_inverse().unwrap();
-        Cell { matrix, inv_matrix }
+        Cell::from_matrix(cell_matrix(a, b, c, alpha, beta, gamma))
     }
 
     /// Constructs a [`Cell`] from cubic lattice parameters.
@@ -52,15 +57,98 @@ impl Cell {
     /// assert_eq!(cell.c(), a0);
     /// ```
     pub fn cubic(a: Float) -> Cell {
-        let matrix = cell_matrix(a, a, a, 90.0, 90.0, 90.0);
-        let inv_matrix = matrix.try_inverse().unwrap();
-        Cell { matrix, inv_matrix }
+        Cell::from_matrix(cell_matrix(a, a, a, 90.0, 90.0, 90.0))
     }
 
     /// Constructs a [`Cell`] from a 3x3 matrix.
     pub fn from_matrix(matrix: Matrix3<Float>) -> Cell {
         let inv_matrix = matrix.try_inverse().unwrap();
-        Cell { matrix, inv_matrix }
+        let orthogonal = is_orthogonal(&matrix);
+        let mut cell = Cell {
+            matrix,
+            inv_matrix,
+            orthogonal,
+            image_radius: 0.0,
+            origin: Vector3::zeros(),
+            planar: false,
+        };
+        cell.image_radius = cell.minimum_image_radius();
+        cell
+    }
+
+    /// Constructs an orthorhombic [`Cell`] from the lower and upper bounds of a box such as `xlo xhi ylo yhi zlo zhi` in LAMMPS.
+    ///
+    /// Positions within the cell are measured from its lower corner, so positions given in the frame
+    /// of the bounds must have `lo` subtracted. The lower corner is kept only to report the [`bounds`](Cell::bounds).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any upper bound is not greater than its lower bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use nalgebra::Vector3;
+    ///
+    /// let cell = Cell::from_bounds(Vector3::new(-1.0, 0.0, 2.0), Vector3::new(3.0, 5.0, 8.0));
+    /// assert!(cell.is_orthogonal());
+    /// assert_eq!(cell.lattice_parameters(), (4.0, 5.0, 6.0, 90.0, 90.0, 90.0));
+    /// ```
+    pub fn from_bounds(lo: Vector3<Float>, hi: Vector3<Float>) -> Cell {
+        let lengths = hi - lo;
+        assert!(
+            lengths.iter().all(|&length| length > 0.0),
+            "upper bounds {:?} must be greater than lower bounds {:?}",
+            hi,
+            lo
+        );
+        let mut cell = Cell::from_matrix(Matrix3::from_diagonal(&lengths));
+        cell.origin = lo;
+        cell
+    }
+
+    /// Returns the lower and upper bounds of an orthorhombic cell.
+    ///
+    /// The lower bounds are those the cell was constructed [`from_bounds`](Cell::from_bounds)
+    /// or the origin for any other cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell is not orthogonal.
+    pub fn bounds(&self) -> (Vector3<Float>, Vector3<Float>) {
+        assert!(
+            self.orthogonal,
+            "only orthogonal cells are bounded by a box"
+        );
+        (self.origin, self.origin + self.matrix.diagonal())
+    }
+
+    /// Returns the lower corner of the cell reported by [`bounds`](Cell::bounds).
+    pub fn origin(&self) -> Vector3<Float> {
+        self.origin
+    }
+
+    /// Sets the lower corner of the cell reported by [`bounds`](Cell::bounds) without moving the cell or any atoms.
+    pub fn set_origin(&mut self, origin: Vector3<Float>) {
+        self.origin = origin;
+    }
+
+    /// Returns true if the lattice vectors are mutually orthogonal and aligned with the axes.
+    ///
+    /// Periodic boundary conditions in orthogonal cells are applied per component
+    /// without converting to fractional coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// assert!(Cell::triclinic(3.0, 4.0, 5.0, 90.0, 90.0, 90.0).is_orthogonal());
+    /// assert!(!Cell::triclinic(3.0, 4.0, 5.0, 90.0, 90.0, 100.0).is_orthogonal());
+    /// ```
+    pub fn is_orthogonal(&self) -> bool {
+        self.orthogonal
     }
 
     /// Returns the magnitude of the 'a' vector.
@@ -99,6 +187,21 @@ impl Cell {
         a.angle(&b).to_degrees()
     }
 
+    /// Returns the lattice parameters `(a, b, c, alpha, beta, gamma)` with angles in degrees.
+    pub fn lattice_parameters(&self) -> (Float, Float, Float, Float, Float, Float) {
+        let a = self.a_vector();
+        let b = self.b_vector();
+        let c = self.c_vector();
+        (
+            a.norm(),
+            b.norm(),
+            c.norm(),
+            b.angle(&c).to_degrees(),
+            a.angle(&c).to_degrees(),
+            a.angle(&b).to_degrees(),
+        )
+    }
+
     /// Returns the 'a' vector.
     pub fn a_vector(&self) -> Vector3<Float> {
         Vector3::new(
@@ -183,6 +286,17 @@ impl Cell {
     /// assert_relative_eq!(vec[2], 1.0, epsilon=1e-6);
     /// ```
     pub fn wrap_vector(&self, vector: &mut Vector3<Float>) {
+        if self.orthogonal {
+            for k in 0..3 {
+                let length = self.matrix[(k, k)];
+                vector[k] -= length * Float::floor(vector[k] / length);
+            }
+        } else {
+            self.wrap_vector_general(vector);
+        }
+    }
+
+    fn wrap_vector_general(&self, vector: &mut Vector3<Float>) {
         let mut fractional = self.fractional(vector);
         fractional[0] -= Float::floor(fractional[0]);
         fractional[1] -= Float::floor(fractional[1]);
@@ -207,11 +321,41 @@ impl Cell {
     /// assert_relative_eq!(vec[2], 1.0, epsilon=1e-6);
     /// ```
     pub fn vector_image(&self, vector: &mut Vector3<Float>) {
+        if self.planar {
+            vector[2] = 0.0;
+        }
+        if self.orthogonal {
+            for k in 0..3 {
+                let length = self.matrix[(k, k)];
+                vector[k] -= length * Float::round(vector[k] / length);
+            }
+        } else {
+            self.vector_image_general(vector);
+        }
+    }
+
+    fn vector_image_general(&self, vector: &mut Vector3<Float>) {
         let mut fractional = self.fractional(vector);
         fractional[0] -= Float::round(fractional[0]);
         fractional[1] -= Float::round(fractional[1]);
         fractional[2] -= Float::round(fractional[2]);
         *vector = self.cartesian(&fractional);
+        // rounding the fractional coordinates can miss the nearest image of a skewed cell
+        if vector.norm() > self.image_radius {
+            let mut shortest = *vector;
+            for i in -1..=1 {
+                for j in -1..=1 {
+                    for k in -1..=1 {
+                        let shift = Vector3::new(i as Float, j as Float, k as Float);
+                        let image = *vector + self.matrix * shift;
+                        if image.norm() < shortest.norm() {
+                            shortest = image;
+                        }
+                    }
+                }
+            }
+            *vector = shortest;
+        }
     }
 
     /// Returns the unit vector path between `v1` and `v2` obeying periodic boundary conditions.
@@ -330,7 +474,195 @@ impl Cell {
     /// assert_relative_eq!(cell.volume(), 64.0);
     /// ```
     pub fn volume(&self) -> Float {
-        (self.a_vector().cross(&self.b_vector())).dot(&self.c_vector())
+        (self.a_vector().cross(&self.b_vector()))
+            .dot(&self.c_vector())
+            .abs()
+    }
+
+    /// Returns the largest cutoff radius for which the minimum image convention finds every interaction.
+    ///
+    /// This is half of the smallest distance between opposite faces of the cell,
+    /// ignoring the faces spanned by the 'a' and 'b' vectors in a [planar](Cell::set_planar) cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use approx::*;
+    ///
+    /// let cell = Cell::triclinic(10.0, 12.0, 14.0, 90.0, 90.0, 90.0);
+    /// assert_relative_eq!(cell.minimum_image_radius(), 5.0, epsilon = 1e-5);
+    /// ```
+    pub fn minimum_image_radius(&self) -> Float {
+        let widths = self.widths();
+        if self.planar {
+            widths[0].min(widths[1]) / 2.0
+        } else {
+            widths.min() / 2.0
+        }
+    }
+
+    /// Returns the area of the face spanned by the 'a' and 'b' vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use approx::*;
+    ///
+    /// let cell = Cell::triclinic(3.0, 4.0, 5.0, 90.0, 90.0, 90.0);
+    /// assert_relative_eq!(cell.area(), 12.0, epsilon = 1e-5);
+    /// ```
+    pub fn area(&self) -> Float {
+        self.a_vector().cross(&self.b_vector()).norm()
+    }
+
+    /// Returns the distances between opposite faces of the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use approx::*;
+    ///
+    /// let widths = Cell::triclinic(10.0, 12.0, 14.0, 90.0, 90.0, 90.0).widths();
+    /// assert_relative_eq!(widths[1], 12.0, epsilon = 1e-5);
+    /// ```
+    pub fn widths(&self) -> Vector3<Float> {
+        let a = self.a_vector();
+        let b = self.b_vector();
+        let c = self.c_vector();
+        let volume = self.volume();
+        Vector3::new(
+            volume / b.cross(&c).norm(),
+            volume / c.cross(&a).norm(),
+            volume / a.cross(&b).norm(),
+        )
+    }
+
+    /// Scales every cell vector by `factor`.
+    ///
+    /// Atoms are not moved, so their positions must be scaled separately to deform them with the cell.
+    pub fn scale(&mut self, factor: Float) {
+        self.reshape(self.matrix * factor);
+    }
+
+    /// Shears the cell by displacing the 'b' vector along x.
+    ///
+    /// The xy tilt is remapped into `[-a/2, a/2]` whenever it grows beyond half of the 'a' vector
+    /// which is equivalent to [Lees-Edwards](https://doi.org/10.1088/0022-3719/5/15/006) boundary conditions.
+    /// The 'a' vector is assumed to lie along x.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use approx::*;
+    ///
+    /// let mut cell = Cell::cubic(4.0);
+    /// cell.shear(1.5);
+    /// assert_relative_eq!(cell.b_vector()[0], 1.5);
+    /// cell.shear(1.5);
+    /// assert_relative_eq!(cell.b_vector()[0], -1.0);
+    /// ```
+    pub fn shear(&mut self, displacement: Float) {
+        let a = self.matrix[(0, 0)];
+        let mut tilt = self.matrix[(0, 1)] + displacement;
+        tilt -= a * Float::round(tilt / a);
+        self.matrix[(0, 1)] = tilt;
+        self.reshape(self.matrix);
+    }
+
+    /// Deforms the cell by the strain tensor `strain` so that each cell vector `v` becomes `(I + strain) * v`.
+    ///
+    /// Atoms are not moved, use [`System::apply_strain`](crate::system::System::apply_strain) to deform them with the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use nalgebra::Matrix3;
+    /// use approx::*;
+    ///
+    /// let mut cell = Cell::cubic(10.0);
+    /// cell.apply_strain(Matrix3::new(0.01, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0));
+    /// assert_relative_eq!(cell.a(), 10.1, epsilon = 1e-5);
+    /// assert_relative_eq!(cell.b(), 10.0, epsilon = 1e-5);
+    /// ```
+    pub fn apply_strain(&mut self, strain: Matrix3<Float>) {
+        self.reshape((Matrix3::identity() + strain) * self.matrix);
+    }
+
+    /// Returns the [Lees-Edwards](https://doi.org/10.1088/0022-3719/5/15/006) shear offset.
+    ///
+    /// The offset is the displacement along x of the periodic images across the boundaries of the 'b' vector,
+    /// which is the xy tilt accumulated by [`shear`](Cell::shear). An atom which leaves through the top
+    /// of the cell reenters through the bottom displaced by `-offset`, and a zero offset recovers
+    /// the standard periodic boundary conditions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use nalgebra::Vector3;
+    /// use approx::*;
+    ///
+    /// let mut cell = Cell::cubic(10.0);
+    /// cell.shear(2.0);
+    /// assert_relative_eq!(cell.shear_offset(), 2.0, epsilon = 1e-5);
+    /// let mut position = Vector3::new(5.0, 11.0, 5.0);
+    /// cell.wrap_vector(&mut position);
+    /// assert_relative_eq!(position, Vector3::new(3.0, 1.0, 5.0), epsilon = 1e-5);
+    /// ```
+    pub fn shear_offset(&self) -> Float {
+        self.matrix[(0, 1)]
+    }
+
+    /// Confines the simulation to the xy-plane if `planar` is true.
+    ///
+    /// Minimum images drop the z component of every vector, so the 'c' vector only separates the
+    /// periodic layers and no longer limits the cutoff radius. Use [`System::make_planar`](crate::system::System::make_planar)
+    /// to also confine the atoms. The 'a' and 'b' vectors are assumed to lie in the xy-plane.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use nalgebra::Vector3;
+    /// use approx::*;
+    ///
+    /// let mut cell = Cell::triclinic(10.0, 10.0, 4.0, 90.0, 90.0, 90.0);
+    /// cell.set_planar(true);
+    /// assert_eq!(cell.dimensions(), 2);
+    /// assert_relative_eq!(cell.minimum_image_radius(), 5.0, epsilon = 1e-5);
+    /// let distance = cell.distance(&Vector3::new(1.0, 1.0, 0.0), &Vector3::new(4.0, 5.0, 1.0));
+    /// assert_relative_eq!(distance, 5.0, epsilon = 1e-5);
+    /// ```
+    pub fn set_planar(&mut self, planar: bool) {
+        self.planar = planar;
+        self.image_radius = self.minimum_image_radius();
+    }
+
+    /// Returns true if the simulation is confined to the xy-plane.
+    pub fn is_planar(&self) -> bool {
+        self.planar
+    }
+
+    /// Returns the number of dimensions the atoms move in, which is 2 in a planar cell and 3 otherwise.
+    pub fn dimensions(&self) -> usize {
+        if self.planar {
+            2
+        } else {
+            3
+        }
+    }
+
+    // Replaces the cell matrix while keeping the origin and planar confinement.
+    pub(crate) fn reshape(&mut self, matrix: Matrix3<Float>) {
+        let (origin, planar) = (self.origin, self.planar);
+        *self = Cell::from_matrix(matrix);
+        self.origin = origin;
+        self.set_planar(planar);
     }
 }
 
@@ -356,6 +688,15 @@ fn cell_matrix(
     Matrix3::new(a, b_x, c_x, 0.0, b_y, c_y, 0.0, 0.0, c_z)
 }
 
+// Returns true if every off-diagonal element is negligible relative to the lattice lengths.
+fn is_orthogonal(matrix: &Matrix3<Float>) -> bool {
+    let scale = matrix.diagonal().abs().max();
+    (0..3)
+        .flat_map(|i| (0..3).map(move |j| (i, j)))
+        .filter(|(i, j)| i != j)
+        .all(|(i, j)| matrix[(i, j)].abs() <= 1e-6 * scale)
+}
+
 #[cfg(test)]
 mod tests {
     use super::Cell;
@@ -379,6 +720,39 @@ mod tests {
         assert_relative_eq!(cell.gamma(), 110.0);
     }
 
+    #[test]
+    fn lattice_parameters() {
+        let cell = Cell::triclinic(3.0, 4.0, 5.0, 80.0, 95.0, 110.0);
+        let (a, b, c, alpha, beta, gamma) = cell.lattice_parameters();
+        assert_eq!(a, cell.a());
+        assert_eq!(b, cell.b());
+        assert_eq!(c, cell.c());
+        assert_eq!(alpha, cell.alpha());
+        assert_eq!(beta, cell.beta());
+        assert_eq!(gamma, cell.gamma());
+    }
+
+    #[test]
+    fn bounds() {
+        let lo = Vector3::new(-2.5, 0.0, 1.0);
+        let hi = Vector3::new(7.5, 12.0, 4.0);
+        let cell = Cell::from_bounds(lo, hi);
+        assert_eq!(cell.bounds(), (lo, hi));
+        assert_relative_eq!(cell.a(), hi[0] - lo[0]);
+        assert_relative_eq!(cell.b(), hi[1] - lo[1]);
+        assert_relative_eq!(cell.c(), hi[2] - lo[2]);
+        assert_relative_eq!(cell.volume(), 360.0, epsilon = 1e-4);
+        // the lower corner survives scaling
+        let mut scaled = cell.clone();
+        scaled.scale(2.0);
+        assert_eq!(scaled.bounds(), (lo, lo + 2.0 * (hi - lo)));
+        // any other cell is bounded from the origin
+        assert_eq!(
+            Cell::cubic(3.0).bounds(),
+            (Vector3::zeros(), Vector3::new(3.0, 3.0, 3.0))
+        );
+    }
+
     #[test]
     fn cubic() {
         let a0 = 4.0;
@@ -401,7 +775,7 @@ mod tests {
 
         for test in &tests {
             let res = cell.cartesian(&cell.fractional(test));
-            assert_relative_eq!((test - &res).norm(), 0.0, epsilon = 1e-5);
+            assert_relative_eq!((test - res).norm(), 0.0, epsilon = 1e-5);
         }
     }
 
@@ -411,7 +785,7 @@ mod tests {
         let mut v = Vector3::new(1.0, 1.5, 6.0);
         cell.wrap_vector(&mut v);
         let res = Vector3::new(1.0, 1.5, 1.0);
-        assert_relative_eq!((v - &res).norm(), 0.0, epsilon = 1e-5);
+        assert_relative_eq!((v - res).norm(), 0.0, epsilon = 1e-5);
     }
 
     #[test]
@@ -420,7 +794,34 @@ mod tests {
         let mut v = Vector3::new(1.0, 1.5, 6.0);
         cell.vector_image(&mut v);
         let res = Vector3::new(1.0, 1.5, 1.0);
-        assert_relative_eq!((v - &res).norm(), 0.0, epsilon = 1e-5);
+        assert_relative_eq!((v - res).norm(), 0.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn vector_image_skewed() {
+        let cell = Cell::triclinic(10.0, 10.0, 10.0, 90.0, 90.0, 40.0);
+        for k in 0..50 {
+            let t = k as Float;
+            let v = Vector3::new(
+                10.0 * Float::sin(1.3 * t),
+                10.0 * Float::cos(0.7 * t),
+                3.0 * Float::sin(2.1 * t),
+            );
+            let mut image = v;
+            cell.vector_image(&mut image);
+            // compare against a search over many neighboring images
+            let mut shortest = Float::MAX;
+            for i in -3..=3 {
+                for j in -3..=3 {
+                    for l in -3..=3 {
+                        let shift = Vector3::new(i as Float, j as Float, l as Float);
+                        let candidate = v + cell.matrix * shift;
+                        shortest = shortest.min(candidate.norm());
+                    }
+                }
+            }
+            assert_relative_eq!(image.norm(), shortest, epsilon = 1e-4);
+        }
     }
 
     #[test]
@@ -480,4 +881,84 @@ mod tests {
         let volume = 60.0;
         assert_relative_eq!(cell.volume(), volume, epsilon = 1e-5);
     }
+
+    #[test]
+    fn orthogonal_fast_path() {
+        let cell = Cell::triclinic(7.0, 9.0, 11.0, 90.0, 90.0, 90.0);
+        assert!(cell.is_orthogonal());
+        assert!(!Cell::triclinic(7.0, 9.0, 11.0, 90.0, 90.0, 90.5).is_orthogonal());
+
+        let v1 = Vector3::new(0.5, 8.5, 1.0);
+        for i in -4..5 {
+            for j in -4..5 {
+                let shift = i as Float * 3.71 + j as Float * 0.93;
+                let v2 = Vector3::new(shift + 0.23, -shift * 1.3 + 0.41, 2.1 * shift + 30.37);
+
+                let mut fast = v2 - v1;
+                let mut general = fast;
+                cell.vector_image(&mut fast);
+                cell.vector_image_general(&mut general);
+                assert_relative_eq!(fast, general, epsilon = 1e-4);
+                let mut d = v2 - v1;
+                cell.vector_image_general(&mut d);
+                assert_relative_eq!(cell.distance(&v1, &v2), d.norm(), epsilon = 1e-4);
+
+                let mut fast = v2;
+                let mut general = v2;
+                cell.wrap_vector(&mut fast);
+                cell.wrap_vector_general(&mut general);
+                assert_relative_eq!(fast, general, epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn lees_edwards() {
+        let mut cell = Cell::triclinic(10.0, 12.0, 14.0, 90.0, 90.0, 90.0);
+        cell.shear(13.0);
+        // the offset is remapped into half of the 'a' vector
+        assert_relative_eq!(cell.shear_offset(), 3.0, epsilon = 1e-5);
+
+        // an atom crossing the top boundary reenters the bottom shifted against the offset
+        let mut top = Vector3::new(5.0, 12.5, 7.0);
+        cell.wrap_vector(&mut top);
+        assert_relative_eq!(top, Vector3::new(2.0, 0.5, 7.0), epsilon = 1e-5);
+        let mut bottom = Vector3::new(5.0, -0.5, 7.0);
+        cell.wrap_vector(&mut bottom);
+        assert_relative_eq!(bottom, Vector3::new(8.0, 11.5, 7.0), epsilon = 1e-5);
+        // the shifted image wraps along x as well
+        let mut corner = Vector3::new(9.0, 24.5, 7.0);
+        cell.wrap_vector(&mut corner);
+        assert_relative_eq!(corner, Vector3::new(3.0, 0.5, 7.0), epsilon = 1e-5);
+
+        // the nearest image of an atom across the boundary is displaced by the offset
+        let v1 = Vector3::new(5.0, 11.5, 7.0);
+        let v2 = Vector3::new(2.5, 0.5, 7.0);
+        let mut d = v2 - v1;
+        cell.vector_image(&mut d);
+        assert_relative_eq!(d, Vector3::new(0.5, 1.0, 0.0), epsilon = 1e-5);
+        assert_relative_eq!(cell.distance(&v1, &v2), d.norm(), epsilon = 1e-5);
+
+        // the same holds for the general path of a skewed cell
+        let mut skewed = Cell::triclinic(10.0, 12.0, 14.0, 90.0, 95.0, 90.0);
+        skewed.shear(3.0);
+        let mut d = v2 - v1;
+        skewed.vector_image(&mut d);
+        assert_relative_eq!(d, Vector3::new(0.5, 1.0, 0.0), epsilon = 1e-4);
+
+        // a zero offset reduces to the standard periodic boundary conditions
+        let standard = Cell::triclinic(10.0, 12.0, 14.0, 90.0, 90.0, 90.0);
+        cell.shear(-cell.shear_offset());
+        for i in -4..5 {
+            let v = Vector3::new(3.7 * i as Float, -5.3 * i as Float + 0.4, 9.1 * i as Float);
+            let (mut sheared, mut expected) = (v, v);
+            cell.vector_image(&mut sheared);
+            standard.vector_image(&mut expected);
+            assert_eq!(sheared, expected);
+            let (mut sheared, mut expected) = (v, v);
+            cell.wrap_vector(&mut sheared);
+            standard.wrap_vector(&mut expected);
+            assert_eq!(sheared, expected);
+        }
+    }
 }
@@ -3,7 +3,7 @@
 use crate::internal::Float;
 
 /// Every element on the periodic table.
-#[derive(Clone, Copy, Debug, PartialEq, EnumString, Hash, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, EnumString, Display, Hash, Eq)]
 pub enum Element {
     /// Hydrogen
     H,
@@ -25,6 +25,10 @@ pub enum Element {
     Cl,
     /// Argon
     Ar,
+    /// Copper
+    Cu,
+    /// Zirconium
+    Zr,
     /// Xenon
     Xe,
 }
@@ -43,6 +47,8 @@ impl Element {
             Element::Mg => 24.305,
             Element::Cl => 35.453,
             Element::Ar => 39.948,
+            Element::Cu => 63.546,
+            Element::Zr => 91.224,
             Element::Xe => 131.293,
         }
     }
@@ -60,6 +66,8 @@ impl Element {
             Element::Mg => 2.0,
             Element::Cl => -1.0,
             Element::Ar => 0.0,
+            Element::Cu => 0.0,
+            Element::Zr => 0.0,
             Element::Xe => 0.0,
         }
     }
@@ -77,9 +85,72 @@ impl Element {
             Element::Mg => 12,
             Element::Cl => 17,
             Element::Ar => 18,
+            Element::Cu => 29,
+            Element::Zr => 40,
             Element::Xe => 54,
         }
     }
+
+    /// Returns the van der Waals radius of the element in angstroms.
+    ///
+    /// Radii are those of [Bondi](https://doi.org/10.1021/j100785a001) except boron from
+    /// [Mantina et al.](https://doi.org/10.1021/jp8111556) and zirconium from [Alvarez](https://doi.org/10.1039/C3DT50599E).
+    pub const fn vdw_radius(&self) -> Float {
+        match self {
+            Element::H => 1.20,
+            Element::He => 1.40,
+            Element::B => 1.92,
+            Element::N => 1.55,
+            Element::O => 1.52,
+            Element::F => 1.47,
+            Element::Na => 2.27,
+            Element::Mg => 1.73,
+            Element::Cl => 1.75,
+            Element::Ar => 1.88,
+            Element::Cu => 1.40,
+            Element::Zr => 2.52,
+            Element::Xe => 2.16,
+        }
+    }
+
+    /// Returns the RGB color of the element in the Jmol variant of [CPK coloring](https://en.wikipedia.org/wiki/CPK_coloring).
+    pub const fn cpk_color(&self) -> [u8; 3] {
+        match self {
+            Element::H => [255, 255, 255],
+            Element::He => [217, 255, 255],
+            Element::B => [255, 181, 181],
+            Element::N => [48, 80, 248],
+            Element::O => [255, 13, 13],
+            Element::F => [144, 224, 80],
+            Element::Na => [171, 92, 242],
+            Element::Mg => [138, 255, 0],
+            Element::Cl => [31, 240, 31],
+            Element::Ar => [128, 209, 227],
+            Element::Cu => [200, 128, 51],
+            Element::Zr => [148, 224, 224],
+            Element::Xe => [66, 158, 176],
+        }
+    }
+
+    /// Returns the element with a given atomic number if it is supported.
+    pub const fn from_number(number: u8) -> Option<Element> {
+        match number {
+            1 => Some(Element::H),
+            2 => Some(Element::He),
+            5 => Some(Element::B),
+            7 => Some(Element::N),
+            8 => Some(Element::O),
+            9 => Some(Element::F),
+            11 => Some(Element::Na),
+            12 => Some(Element::Mg),
+            17 => Some(Element::Cl),
+            18 => Some(Element::Ar),
+            29 => Some(Element::Cu),
+            40 => Some(Element::Zr),
+            54 => Some(Element::Xe),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -98,4 +169,23 @@ mod tests {
     fn from_str_invalid() {
         let _ = Element::from_str("not a valid symbol").unwrap();
     }
+
+    #[test]
+    fn from_number() {
+        assert_eq!(
+            Element::from_number(Element::Cu.number()),
+            Some(Element::Cu)
+        );
+        assert_eq!(Element::from_number(3), None);
+        assert_eq!(Element::Cu.to_string(), "Cu");
+    }
+
+    #[test]
+    fn cpk_color() {
+        // oxygen is red and hydrogen is white
+        let [r, g, b] = Element::O.cpk_color();
+        assert!(r > 200 && g < 50 && b < 50);
+        assert!(Element::H.cpk_color().iter().all(|&c| c > 240));
+        assert!(Element::H.vdw_radius() < Element::O.vdw_radius());
+    }
 }
@@ -4,9 +4,11 @@ pub mod cell;
 pub mod elements;
 pub mod species;
 
-use nalgebra::Vector3;
+use nalgebra::{Matrix3, Vector3};
+use rand::Rng;
 
 use crate::internal::Float;
+use crate::rng;
 use crate::system::cell::Cell;
 use crate::system::species::Species;
 
@@ -23,4 +25,772 @@ pub struct System {
     pub positions: Vec<Vector3<Float>>,
     /// Velocity of each atom in the system.
     pub velocities: Vec<Vector3<Float>>,
+    /// Indices of each pair of bonded atoms.
+    pub bonds: Vec<[usize; 2]>,
+    /// Indices of each triplet of atoms forming an angle with the vertex in the middle.
+    pub angles: Vec<[usize; 3]>,
+    /// Indices of each quartet of atoms forming a dihedral about the bond between the middle two.
+    pub dihedrals: Vec<[usize; 4]>,
+}
+
+impl System {
+    /// Returns an empty system with no atoms or topology in `cell`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use nalgebra::Vector3;
+    ///
+    /// let mut system = System::new(Cell::cubic(10.0));
+    /// system.size = 1;
+    /// system.species.push(Species::from_element(Element::Ar));
+    /// system.positions.push(Vector3::new(5.0, 5.0, 5.0));
+    /// system.velocities.push(Vector3::zeros());
+    /// assert_eq!(system.center_of_mass(), Vector3::new(5.0, 5.0, 5.0));
+    /// ```
+    pub fn new(cell: Cell) -> System {
+        System {
+            size: 0,
+            cell,
+            species: Vec::new(),
+            positions: Vec::new(),
+            velocities: Vec::new(),
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+
+    /// Returns the electronic charge of each atom.
+    pub fn charges(&self) -> Vec<Float> {
+        self.species.iter().map(|s| s.charge()).collect()
+    }
+
+    /// Returns the total electronic charge of the system.
+    pub fn net_charge(&self) -> Float {
+        self.species.iter().map(|s| s.charge()).sum()
+    }
+
+    /// Returns the number of degrees of freedom of the atoms which is one per atom for each [dimension](Cell::dimensions).
+    pub fn degrees_of_freedom(&self) -> usize {
+        self.size * self.cell.dimensions()
+    }
+
+    /// Confines the system to the xy-plane for two dimensional dynamics.
+    ///
+    /// The cell becomes [planar](Cell::set_planar) and the z components of the positions and
+    /// velocities are zeroed. Forces along z are then ignored and molecular dynamics keeps the atoms in the plane.
+    pub fn make_planar(&mut self) {
+        self.cell.set_planar(true);
+        self.project_onto_plane();
+    }
+
+    // Zeroes the z component of every position and velocity.
+    pub(crate) fn project_onto_plane(&mut self) {
+        for (pos, vel) in self.positions.iter_mut().zip(self.velocities.iter_mut()) {
+            pos[2] = 0.0;
+            vel[2] = 0.0;
+        }
+    }
+
+    /// Returns the center of mass of the system.
+    ///
+    /// Positions are used as is so the result is only meaningful for
+    /// non-periodic systems such as isolated clusters.
+    pub fn center_of_mass(&self) -> Vector3<Float> {
+        let total_mass: Float = self.species.iter().map(|s| s.mass()).sum();
+        let weighted: Vector3<Float> = self
+            .species
+            .iter()
+            .zip(self.positions.iter())
+            .map(|(s, pos)| s.mass() * pos)
+            .sum();
+        weighted / total_mass
+    }
+
+    /// Returns the total linear momentum of the system.
+    pub fn momentum(&self) -> Vector3<Float> {
+        self.species
+            .iter()
+            .zip(self.velocities.iter())
+            .map(|(s, vel)| s.mass() * vel)
+            .sum()
+    }
+
+    /// Removes the center of mass velocity from the velocities.
+    pub fn remove_net_momentum(&mut self) {
+        let total_mass: Float = self.species.iter().map(|s| s.mass()).sum();
+        let drift = self.momentum() / total_mass;
+        for vel in self.velocities.iter_mut() {
+            *vel -= drift;
+        }
+    }
+
+    /// Returns the moment of inertia tensor about the center of mass.
+    pub fn inertia_tensor(&self) -> Matrix3<Float> {
+        let com = self.center_of_mass();
+        self.species
+            .iter()
+            .zip(self.positions.iter())
+            .map(|(s, pos)| {
+                let r = pos - com;
+                s.mass() * (Matrix3::identity() * r.norm_squared() - r * r.transpose())
+            })
+            .sum()
+    }
+
+    /// Returns the total angular momentum about the center of mass.
+    pub fn angular_momentum(&self) -> Vector3<Float> {
+        let com = self.center_of_mass();
+        self.species
+            .iter()
+            .zip(self.positions.iter().zip(self.velocities.iter()))
+            .map(|(s, (pos, vel))| s.mass() * (pos - com).cross(vel))
+            .sum()
+    }
+
+    /// Removes the rigid body rotation about the center of mass from the velocities.
+    ///
+    /// The angular velocity `ω = I⁻¹L` is solved from the inertia tensor and the angular
+    /// momentum and its velocity field `ω × r` is subtracted from each atom.
+    /// A pseudo-inverse is used so that linear molecules are handled as well.
+    pub fn remove_net_angular_momentum(&mut self) {
+        let com = self.center_of_mass();
+        let momentum = self.angular_momentum();
+        // the pseudo-inverse only fails for a negative epsilon
+        let inverse = self.inertia_tensor().pseudo_inverse(1e-6).unwrap();
+        let omega = inverse * momentum;
+        for (pos, vel) in self.positions.iter().zip(self.velocities.iter_mut()) {
+            *vel -= omega.cross(&(pos - com));
+        }
+    }
+
+    /// Extends the cell vector along `axis` (0, 1 or 2 for 'a', 'b' or 'c') by `thickness` without moving any atoms.
+    ///
+    /// The added space is left empty to create a slab with vacuum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` is greater than 2.
+    pub fn add_vacuum(&mut self, axis: usize, thickness: Float) {
+        let mut vectors = [
+            self.cell.a_vector(),
+            self.cell.b_vector(),
+            self.cell.c_vector(),
+        ];
+        let vector = vectors[axis];
+        vectors[axis] = vector + vector.normalize() * thickness;
+        self.cell.reshape(Matrix3::from_columns(&vectors));
+    }
+
+    /// Deforms the cell and every atom position affinely by the strain tensor `strain`.
+    ///
+    /// Positions become `(I + strain) * r` so fractional coordinates are unchanged.
+    pub fn apply_strain(&mut self, strain: Matrix3<Float>) {
+        self.cell.apply_strain(strain);
+        let deformation = Matrix3::identity() + strain;
+        for position in self.positions.iter_mut() {
+            *position = deformation * *position;
+        }
+    }
+
+    /// Returns the position of each atom in fractional coordinates of the cell.
+    pub fn fractional_positions(&self) -> Vec<Vector3<Float>> {
+        self.positions
+            .iter()
+            .map(|position| self.cell.fractional(position))
+            .collect()
+    }
+
+    /// Replaces the position of each atom with the cartesian coordinates of `fractional` in the cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of fractional positions does not match the size of the system.
+    pub fn set_fractional_positions(&mut self, fractional: &[Vector3<Float>]) {
+        assert_eq!(
+            fractional.len(),
+            self.size,
+            "expected {} fractional positions",
+            self.size
+        );
+        self.positions = fractional
+            .iter()
+            .map(|position| self.cell.cartesian(position))
+            .collect();
+    }
+
+    /// Returns every pair of atoms closer than `min_distance` using minimum image distances.
+    pub fn check_overlaps(&self, min_distance: Float) -> Vec<(usize, usize)> {
+        let mut overlaps = Vec::new();
+        for i in 0..self.size {
+            for j in (i + 1)..self.size {
+                let r = self.cell.distance(&self.positions[i], &self.positions[j]);
+                if r < min_distance {
+                    overlaps.push((i, j));
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// Returns a face centered cubic crystal of `species` in a cubic cell of `n` x `n` x `n` conventional unit cells.
+    ///
+    /// Each unit cell of lattice constant `a` holds four atoms. Velocities are zero and there is no topology.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    /// use approx::*;
+    ///
+    /// let argon = Species::from_element(Element::Ar);
+    /// let system = System::fcc_lattice(argon, 5.26, 3);
+    /// assert_eq!(system.size, 108);
+    /// assert_relative_eq!(system.cell.a(), 15.78, epsilon = 1e-4);
+    /// ```
+    pub fn fcc_lattice(species: Species, a: Float, n: usize) -> System {
+        let basis = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.5, 0.5, 0.0),
+            Vector3::new(0.5, 0.0, 0.5),
+            Vector3::new(0.0, 0.5, 0.5),
+        ];
+        let mut positions = Vec::with_capacity(4 * n * n * n);
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    let origin = Vector3::new(x as Float, y as Float, z as Float);
+                    positions.extend(basis.iter().map(|b| (origin + b) * a));
+                }
+            }
+        }
+        let size = positions.len();
+        System {
+            size,
+            cell: Cell::cubic(a * n as Float),
+            species: vec![species; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+
+    /// Returns a system of `n_atoms` atoms of `species` placed at random with none closer than `min_distance`.
+    ///
+    /// Atoms are inserted one at a time at uniformly random positions and any position within
+    /// `min_distance` of an existing atom (minimum image) is rejected. If no position is found
+    /// after many attempts the atom is placed where it overlaps least and the overlapping pairs
+    /// are pushed apart until the packing is free of overlaps again. This reaches densities beyond
+    /// the jamming limit of random sequential insertion, up to roughly random close packing.
+    /// Velocities are zero and there is no topology.
+    ///
+    /// Returns an error if the atoms cannot be packed without overlaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// let argon = Species::from_element(Element::Ar);
+    /// let system = System::random_packing(Cell::cubic(20.0), argon, 50, 3.0, 1234).unwrap();
+    /// assert_eq!(system.size, 50);
+    /// assert!(system.check_overlaps(3.0).is_empty());
+    /// ```
+    pub fn random_packing(
+        cell: Cell,
+        species: Species,
+        n_atoms: usize,
+        min_distance: Float,
+        seed: u64,
+    ) -> Result<System, String> {
+        let mut rng = rng::from_seed(seed);
+        let mut positions: Vec<Vector3<Float>> = Vec::with_capacity(n_atoms);
+        for placed in 0..n_atoms {
+            // keep the candidate farthest from its nearest neighbor in case every attempt overlaps
+            let mut best = (Float::NEG_INFINITY, Vector3::zeros());
+            for _ in 0..PACKING_ATTEMPTS {
+                let fractional = Vector3::new(rng.gen(), rng.gen(), rng.gen());
+                let candidate = cell.cartesian(&fractional);
+                let nearest = positions
+                    .iter()
+                    .map(|position| cell.distance(position, &candidate))
+                    .fold(Float::INFINITY, Float::min);
+                if nearest > best.0 {
+                    best = (nearest, candidate);
+                }
+                if nearest >= min_distance {
+                    break;
+                }
+            }
+            positions.push(best.1);
+            if best.0 < min_distance && !relax_overlaps(&cell, &mut positions, min_distance) {
+                return Err(format!(
+                    "packing stalled after {} of {} atoms with a minimum distance of {}",
+                    placed, n_atoms, min_distance
+                ));
+            }
+        }
+        Ok(System {
+            size: n_atoms,
+            cell,
+            species: vec![species; n_atoms],
+            positions,
+            velocities: vec![Vector3::zeros(); n_atoms],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        })
+    }
+
+    /// Shifts atoms by cell vectors so that every molecule connected by `bonds` is contiguous.
+    ///
+    /// Each molecule is traversed from its lowest indexed atom and every bonded neighbor is placed
+    /// at the minimum image of its bond so no bond spans the periodic boundary.
+    pub fn make_molecules_whole(&mut self) {
+        let neighbors = self.bonded_neighbors();
+        let mut visited = vec![false; self.size];
+        for root in 0..self.size {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![root];
+            while let Some(i) = stack.pop() {
+                for &j in &neighbors[i] {
+                    if visited[j] {
+                        continue;
+                    }
+                    visited[j] = true;
+                    let mut bond = self.positions[j] - self.positions[i];
+                    self.cell.vector_image(&mut bond);
+                    self.positions[j] = self.positions[i] + bond;
+                    stack.push(j);
+                }
+            }
+        }
+    }
+
+    /// Replaces `angles` and `dihedrals` with every one implied by `bonds`.
+    ///
+    /// Each atom with two or more bonds is the vertex of an angle between every pair of its neighbors
+    /// and each bonded path `i-j-k-l` of four distinct atoms forms a dihedral about the `j-k` bond.
+    pub fn perceive_angles_and_dihedrals(&mut self) {
+        let neighbors = self.bonded_neighbors();
+        self.angles.clear();
+        for (j, bonded) in neighbors.iter().enumerate() {
+            for (n, &i) in bonded.iter().enumerate() {
+                for &k in &bonded[(n + 1)..] {
+                    self.angles.push([i, j, k]);
+                }
+            }
+        }
+        self.dihedrals.clear();
+        for &[j, k] in &self.bonds {
+            for &i in neighbors[j].iter().filter(|&&i| i != k) {
+                // skip three membered rings where the outer atoms are the same
+                for &l in neighbors[k].iter().filter(|&&l| l != j && l != i) {
+                    self.dihedrals.push([i, j, k, l]);
+                }
+            }
+        }
+    }
+
+    /// Returns the minimum image length of bond `k` in `bonds`.
+    pub fn bond_length(&self, k: usize) -> Float {
+        let [i, j] = self.bonds[k];
+        self.cell.distance(&self.positions[i], &self.positions[j])
+    }
+
+    /// Returns the minimum image value (radians) of angle `k` in `angles`.
+    pub fn angle_value(&self, k: usize) -> Float {
+        let [i, j, l] = self.angles[k];
+        self.cell
+            .angle(&self.positions[i], &self.positions[j], &self.positions[l])
+    }
+
+    /// Returns the minimum image value (radians) of dihedral `k` in `dihedrals`.
+    pub fn dihedral_value(&self, k: usize) -> Float {
+        let [i, j, l, m] = self.dihedrals[k];
+        self.cell.dihedral(
+            &self.positions[i],
+            &self.positions[j],
+            &self.positions[l],
+            &self.positions[m],
+        )
+    }
+
+    // Returns the atoms bonded to each atom.
+    fn bonded_neighbors(&self) -> Vec<Vec<usize>> {
+        let mut neighbors = vec![Vec::new(); self.size];
+        for &[i, j] in &self.bonds {
+            neighbors[i].push(j);
+            neighbors[j].push(i);
+        }
+        neighbors
+    }
+}
+
+impl Default for System {
+    /// Returns an empty system in a unit cubic cell.
+    fn default() -> Self {
+        System::new(Cell::cubic(1.0))
+    }
+}
+
+// Random positions tried for each atom before the overlaps are relaxed instead.
+const PACKING_ATTEMPTS: usize = 1000;
+
+// Sweeps over every pair before the relaxation of a packing gives up.
+const RELAXATION_SWEEPS: usize = 1000;
+
+// Pushes overlapping atoms apart along their separation and returns `true` once none overlap.
+fn relax_overlaps(cell: &Cell, positions: &mut [Vector3<Float>], min_distance: Float) -> bool {
+    for _ in 0..RELAXATION_SWEEPS {
+        let mut overlapping = false;
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let mut separation = positions[j] - positions[i];
+                cell.vector_image(&mut separation);
+                let r = separation.norm();
+                if r >= min_distance {
+                    continue;
+                }
+                overlapping = true;
+                // coincident atoms are separated along an arbitrary axis
+                let direction = if r > 0.0 {
+                    separation / r
+                } else {
+                    Vector3::x()
+                };
+                // overshoot slightly so the pair is not left exactly at the threshold
+                let shift = direction * 0.5 * (1.01 * min_distance - r);
+                positions[i] -= shift;
+                positions[j] += shift;
+                cell.wrap_vector(&mut positions[i]);
+                cell.wrap_vector(&mut positions[j]);
+            }
+        }
+        if !overlapping {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::System;
+    use crate::internal::consts::PI;
+    use crate::internal::Float;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use approx::*;
+    use nalgebra::{Matrix3, Vector3};
+
+    #[test]
+    fn remove_net_angular_momentum() {
+        let positions = vec![
+            Vector3::new(10.0, 10.0, 10.0),
+            Vector3::new(12.0, 10.0, 10.0),
+            Vector3::new(10.0, 13.0, 10.0),
+            Vector3::new(10.0, 10.0, 14.0),
+        ];
+        let species = vec![
+            Species::from_element(Element::Ar),
+            Species::from_element(Element::He),
+            Species::from_element(Element::Xe),
+            Species::from_element(Element::Ar),
+        ];
+        let mut system = System {
+            size: 4,
+            cell: Cell::cubic(100.0),
+            species,
+            positions,
+            velocities: vec![Vector3::zeros(); 4],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+
+        // internal breathing motion away from the center of mass carries no angular momentum
+        let com = system.center_of_mass();
+        let internal: Vec<Vector3<Float>> = system
+            .positions
+            .iter()
+            .map(|pos| 0.001 * (pos - com))
+            .collect();
+        let omega = Vector3::new(0.002, -0.001, 0.003);
+        system.velocities = system
+            .positions
+            .iter()
+            .zip(internal.iter())
+            .map(|(pos, vel)| vel + omega.cross(&(pos - com)))
+            .collect();
+        assert!(system.angular_momentum().norm() > 1e-2);
+
+        system.remove_net_angular_momentum();
+        let momentum = system.angular_momentum();
+        for k in 0..3 {
+            assert_relative_eq!(momentum[k], 0.0, epsilon = 1e-4);
+        }
+        for (vel, expected) in system.velocities.iter().zip(internal.iter()) {
+            for k in 0..3 {
+                assert_relative_eq!(vel[k], expected[k], epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_strain() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 2,
+            cell: Cell::triclinic(5.0, 6.0, 7.0, 80.0, 95.0, 100.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 3.5, 0.5)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let fractional: Vec<Vector3<Float>> = system
+            .positions
+            .iter()
+            .map(|pos| system.cell.fractional(pos))
+            .collect();
+        let (a, b, c) = (system.cell.a(), system.cell.b(), system.cell.c());
+        let (ax, bx) = (system.cell.a_vector()[0], system.cell.b_vector()[0]);
+
+        // uniaxial strain along x only stretches the x component of each cell vector
+        let strain = Matrix3::new(0.02, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        system.apply_strain(strain);
+        assert_relative_eq!(system.cell.a_vector()[0], 1.02 * ax, epsilon = 1e-4);
+        assert_relative_eq!(system.cell.b_vector()[0], 1.02 * bx, epsilon = 1e-4);
+        assert!(system.cell.a() > a);
+        assert!(system.cell.b() > b);
+        assert!(system.cell.c() > c);
+        for (pos, expected) in system.positions.iter().zip(fractional.iter()) {
+            let actual = system.cell.fractional(pos);
+            for k in 0..3 {
+                assert_relative_eq!(actual[k], expected[k], epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn fractional_positions() {
+        let argon = Species::from_element(Element::Ar);
+        let positions = vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 3.5, 0.5)];
+        let mut system = System {
+            size: 2,
+            cell: Cell::triclinic(5.0, 6.0, 7.0, 80.0, 95.0, 100.0),
+            species: vec![argon; 2],
+            positions: positions.clone(),
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let fractional = system.fractional_positions();
+        assert_relative_eq!(
+            system.cell.cartesian(&fractional[1]),
+            positions[1],
+            epsilon = 1e-5
+        );
+
+        // the origin of the cell maps back to the origin
+        system.set_fractional_positions(&[Vector3::zeros(), fractional[1]]);
+        assert_relative_eq!(system.positions[0], Vector3::zeros(), epsilon = 1e-5);
+
+        system.set_fractional_positions(&fractional);
+        for (actual, expected) in system.positions.iter().zip(positions.iter()) {
+            assert_relative_eq!(actual, expected, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn add_vacuum() {
+        let argon = Species::from_element(Element::Ar);
+        let positions = vec![Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 3.5, 0.5)];
+        let mut system = System {
+            size: 2,
+            cell: Cell::triclinic(5.0, 6.0, 7.0, 80.0, 95.0, 100.0),
+            species: vec![argon; 2],
+            positions: positions.clone(),
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let thickness = 10.0;
+        system.add_vacuum(2, thickness);
+        assert_relative_eq!(system.cell.a(), 5.0, epsilon = 1e-4);
+        assert_relative_eq!(system.cell.b(), 6.0, epsilon = 1e-4);
+        assert_relative_eq!(system.cell.c(), 7.0 + thickness, epsilon = 1e-4);
+        assert_relative_eq!(system.cell.alpha(), 80.0, epsilon = 1e-3);
+        assert_relative_eq!(system.cell.beta(), 95.0, epsilon = 1e-3);
+        assert_relative_eq!(system.cell.gamma(), 100.0, epsilon = 1e-3);
+        assert_eq!(system.positions, positions);
+
+        // a planar cell stays planar
+        system.make_planar();
+        system.add_vacuum(2, thickness);
+        assert!(system.cell.is_planar());
+        assert_eq!(system.degrees_of_freedom(), 4);
+    }
+
+    #[test]
+    fn make_molecules_whole() {
+        let oxygen = Species::from_element(Element::O);
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(10.0),
+            species: vec![oxygen; 2],
+            positions: vec![Vector3::new(0.4, 5.0, 5.0), Vector3::new(9.2, 5.0, 5.0)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: vec![[0, 1]],
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        // the wrapped coordinates give a bond almost as long as the cell
+        assert_relative_eq!(
+            (system.positions[1] - system.positions[0]).norm(),
+            8.8,
+            epsilon = 1e-5
+        );
+
+        system.make_molecules_whole();
+        assert_relative_eq!(system.positions[0][0], 0.4, epsilon = 1e-5);
+        assert_relative_eq!(system.positions[1][0], -0.8, epsilon = 1e-5);
+        assert_relative_eq!(
+            (system.positions[1] - system.positions[0]).norm(),
+            1.2,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn check_overlaps() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 3,
+            cell: Cell::cubic(10.0),
+            species: vec![argon; 3],
+            positions: vec![
+                Vector3::new(1.0, 5.0, 5.0),
+                Vector3::new(5.0, 5.0, 5.0),
+                Vector3::new(9.0, 1.0, 1.0),
+            ],
+            velocities: vec![Vector3::zeros(); 3],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        assert!(system.check_overlaps(2.0).is_empty());
+
+        // the overlap is only visible through the periodic boundary
+        system.positions[2] = Vector3::new(9.5, 5.0, 5.0);
+        assert_eq!(system.check_overlaps(2.0), vec![(0, 2)]);
+    }
+
+    fn bonded(size: usize, bonds: Vec<[usize; 2]>) -> System {
+        let hydrogen = Species::from_element(Element::H);
+        System {
+            size,
+            cell: Cell::cubic(20.0),
+            species: vec![hydrogen; size],
+            positions: vec![Vector3::zeros(); size],
+            velocities: vec![Vector3::zeros(); size],
+            bonds,
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn perceive_chain() {
+        let mut system = bonded(4, vec![[0, 1], [1, 2], [2, 3]]);
+        system.perceive_angles_and_dihedrals();
+        assert_eq!(system.angles, vec![[0, 1, 2], [1, 2, 3]]);
+        assert_eq!(system.dihedrals, vec![[0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn perceive_methane() {
+        let mut system = bonded(5, vec![[0, 1], [0, 2], [0, 3], [0, 4]]);
+        system.perceive_angles_and_dihedrals();
+        // every pair of the four hydrogens forms an angle about the carbon
+        assert_eq!(system.angles.len(), 6);
+        assert!(system.angles.iter().all(|angle| angle[1] == 0));
+        assert!(system.dihedrals.is_empty());
+    }
+
+    #[test]
+    fn bonded_geometry() {
+        let hydrogen = Species::from_element(Element::H);
+        // a planar zigzag chain whose first bond crosses the periodic boundary
+        let system = System {
+            size: 4,
+            cell: Cell::cubic(10.0),
+            species: vec![hydrogen; 4],
+            positions: vec![
+                Vector3::new(9.5, 1.0, 5.0),
+                Vector3::new(0.5, 1.0, 5.0),
+                Vector3::new(0.5, 2.0, 5.0),
+                Vector3::new(1.5, 2.0, 5.0),
+            ],
+            velocities: vec![Vector3::zeros(); 4],
+            bonds: vec![[0, 1], [1, 2], [2, 3]],
+            angles: vec![[0, 1, 2], [1, 2, 3]],
+            dihedrals: vec![[0, 1, 2, 3]],
+        };
+        assert_relative_eq!(system.bond_length(0), 1.0, epsilon = 1e-5);
+        assert_relative_eq!(system.bond_length(2), 1.0, epsilon = 1e-5);
+        assert_relative_eq!(system.angle_value(0), PI / 2.0, epsilon = 1e-5);
+        assert_relative_eq!(system.angle_value(1), PI / 2.0, epsilon = 1e-5);
+        assert_relative_eq!(system.dihedral_value(0).abs(), PI, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn random_packing() {
+        let argon = Species::from_element(Element::Ar);
+        let min_distance: Float = 3.0;
+        let sphere = PI / 6.0 * min_distance.powi(3);
+
+        // a dilute gas only needs random insertion while a packing fraction of 0.4 is beyond
+        // the jamming limit of random insertion and has to be relaxed
+        for &fraction in &[0.1, 0.4] {
+            let volume = 100.0 * sphere / fraction;
+            let cell = Cell::cubic(Float::cbrt(volume));
+            let system = System::random_packing(cell, argon, 100, min_distance, 1234).unwrap();
+            assert_eq!(system.size, 100);
+            assert_eq!(system.positions.len(), 100);
+            assert!(system.check_overlaps(min_distance).is_empty());
+            for position in &system.positions {
+                let fractional = system.cell.fractional(position);
+                assert!(fractional.iter().all(|&f| (0.0..=1.0).contains(&f)));
+            }
+        }
+
+        // more spheres than fit in the cell
+        let result = System::random_packing(Cell::cubic(8.0), argon, 100, min_distance, 1234);
+        assert!(result.unwrap_err().contains("packing stalled"));
+    }
+
+    #[test]
+    fn empty() {
+        let system = System::new(Cell::cubic(10.0));
+        assert_eq!(system.size, 0);
+        assert!(system.species.is_empty());
+        assert!(system.positions.is_empty());
+        assert!(system.velocities.is_empty());
+        assert!(system.bonds.is_empty() && system.angles.is_empty() && system.dihedrals.is_empty());
+        assert_eq!(system.cell.a(), 10.0);
+        assert_eq!(System::default().size, 0);
+    }
 }
@@ -44,11 +44,71 @@ impl Species {
         }
     }
 
+    /// Constructs a [`Species`] from an [`Element`] distinguished by a label such as an isotope or atom type.
+    ///
+    /// Species compare equal if they share both element and label.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// let deuterium = Species::labeled(Element::H, "D").with_mass(2.014);
+    /// assert_eq!(deuterium, Species::labeled(Element::H, "D"));
+    /// assert_ne!(deuterium, Species::from_element(Element::H));
+    /// ```
+    pub fn labeled(element: Element, label: &str) -> Species {
+        Species {
+            id: (stable_hash(label) as u128) << 64 | element.number() as u128,
+            mass: element.mass(),
+            charge: element.charge(),
+        }
+    }
+
+    /// Constructs a [`Species`] which is not an element such as a coarse-grained bead.
+    ///
+    /// Species compare equal if they share a name regardless of their mass and charge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use velvet_core::prelude::*;
+    ///
+    /// let bead = Species::named("BeadA", 72.0, 0.0);
+    /// assert_eq!(bead, Species::named("BeadA", 72.0, 0.0));
+    /// assert_ne!(bead, Species::named("BeadB", 72.0, 0.0));
+    /// assert_eq!(bead.element(), None);
+    /// ```
+    pub fn named(name: &str, mass: Float, charge: Float) -> Species {
+        // a full lower half can not hold an atomic number so the species is never mistaken for an element
+        Species {
+            id: (stable_hash(name) as u128) << 64 | u64::MAX as u128,
+            mass,
+            charge,
+        }
+    }
+
+    // Rebuilds a species with a known ID such as one read from a restart file.
+    pub(crate) fn from_parts(id: u128, mass: Float, charge: Float) -> Species {
+        Species { id, mass, charge }
+    }
+
     /// Returns the species' unique ID.
     pub fn id(&self) -> u128 {
         self.id
     }
 
+    /// Returns the element the species was constructed from, if any.
+    pub fn element(&self) -> Option<Element> {
+        // element based IDs hold the atomic number in their lowest bits
+        // while the variant bits of a random ID keep its lowest 64 bits large
+        let low = self.id as u64;
+        if low > u8::MAX as u64 {
+            return None;
+        }
+        Element::from_number(low as u8)
+    }
+
     /// Returns the species' mass.
     pub fn mass(&self) -> Float {
         self.mass
@@ -58,6 +118,28 @@ impl Species {
     pub fn charge(&self) -> Float {
         self.charge
     }
+
+    /// Returns a copy of the species with a different mass.
+    ///
+    /// The copy keeps the same ID so it still compares equal to the original species.
+    pub fn with_mass(&self, mass: Float) -> Species {
+        Species {
+            id: self.id,
+            mass,
+            charge: self.charge,
+        }
+    }
+
+    /// Returns a copy of the species with a different electronic charge.
+    ///
+    /// The copy keeps the same ID so it still compares equal to the original species.
+    pub fn with_charge(&self, charge: Float) -> Species {
+        Species {
+            id: self.id,
+            mass: self.mass,
+            charge,
+        }
+    }
 }
 
 impl Hash for Species {
@@ -72,10 +154,20 @@ impl PartialEq for Species {
     }
 }
 
+impl Eq for Species {}
+
+// FNV-1a keeps IDs stable between runs so they can be stored in restart files.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::Species;
     use crate::system::elements::Element;
+    use std::collections::HashMap;
 
     #[test]
     fn from_element() {
@@ -86,6 +178,17 @@ mod tests {
         assert_eq!(species.id(), element.number() as u128);
     }
 
+    #[test]
+    fn element() {
+        assert_eq!(
+            Species::from_element(Element::Na).element(),
+            Some(Element::Na)
+        );
+        let deuterium = Species::labeled(Element::H, "D").with_mass(2.014);
+        assert_eq!(deuterium.element(), Some(Element::H));
+        assert_eq!(Species::new(1.0, 0.0).element(), None);
+    }
+
     #[test]
     fn compare_equivalent() {
         let hydrogen1 = Species::from_element(Element::H);
@@ -101,4 +204,47 @@ mod tests {
         let species = Species::new(hydrogen.mass(), hydrogen.charge());
         assert_ne!(species, hydrogen);
     }
+
+    #[test]
+    fn compare_labeled() {
+        let argon1 = Species::from_element(Element::Ar);
+        let argon2 = Species::from_element(Element::Ar).with_charge(0.5);
+        assert_eq!(argon1, argon2);
+
+        let hydrogen = Species::from_element(Element::H);
+        let deuterium = Species::labeled(Element::H, "D").with_mass(2.014);
+        assert_ne!(deuterium, hydrogen);
+        assert_eq!(deuterium, Species::labeled(Element::H, "D"));
+        assert_ne!(deuterium, Species::labeled(Element::He, "D"));
+        assert_ne!(deuterium, Species::labeled(Element::H, "T"));
+    }
+
+    #[test]
+    fn compare_named() {
+        let bead_a = Species::named("BeadA", 72.0, 0.0);
+        let bead_b = Species::named("BeadB", 44.0, -1.0);
+        assert_ne!(bead_a, bead_b);
+        assert_eq!(bead_a.mass(), 72.0);
+        assert_eq!(bead_a.charge(), 0.0);
+        assert_eq!(bead_b.mass(), 44.0);
+        assert_eq!(bead_b.charge(), -1.0);
+        assert_eq!(bead_a, Species::named("BeadA", 72.0, 0.0));
+        assert_eq!(bead_a.id(), Species::named("BeadA", 72.0, 0.0).id());
+        assert_eq!(bead_a.element(), None);
+        assert_ne!(
+            Species::named("H", 1.008, 0.0),
+            Species::from_element(Element::H)
+        );
+    }
+
+    #[test]
+    fn hash_map_key() {
+        let mut masses = HashMap::new();
+        masses.insert(Species::from_element(Element::H), 1.008);
+        masses.insert(Species::labeled(Element::H, "D").with_mass(2.014), 2.014);
+        masses.insert(Species::from_element(Element::H), 1.0);
+        assert_eq!(masses.len(), 2);
+        assert_eq!(masses[&Species::labeled(Element::H, "D")], 2.014);
+        assert_eq!(masses[&Species::from_element(Element::H)], 1.0);
+    }
 }
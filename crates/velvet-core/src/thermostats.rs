@@ -1,10 +1,13 @@
 //! Algorithms which control the temperature of a system.
 
 use nalgebra::Vector3;
+use rand_distr::{Distribution, Normal};
 
+use crate::internal::consts::boltzmann;
 use crate::internal::Float;
 use crate::properties::temperature::Temperature;
 use crate::properties::IntrinsicProperty;
+use crate::rng::{self, VelvetRng};
 use crate::system::System;
 
 /// Shared behavior for algorithms which control the temperature of a system.
@@ -15,6 +18,12 @@ pub trait Thermostat: Send + Sync {
     fn pre_integrate(&mut self, _: &mut System) {}
     /// Fires after the integration step.
     fn post_integrate(&mut self, _: &mut System) {}
+    /// Returns the internal state which must be stored to resume the thermostat.
+    fn state(&self) -> Vec<Float> {
+        Vec::new()
+    }
+    /// Restores the internal state returned by [`state`](Thermostat::state) after setup.
+    fn restore(&mut self, _: &[Float]) {}
 }
 
 /// Mock thermostat algorithm which applies no temperature controls.
@@ -34,6 +43,7 @@ impl Thermostat for NullThermostat {}
 pub struct Berendsen {
     target: Float,
     tau: Float,
+    axes: Option<[bool; 3]>,
 }
 
 impl Berendsen {
@@ -44,19 +54,59 @@ impl Berendsen {
     /// * `target` - Target temperature.
     /// * `tau` - Timestep of the thermostat expressed as a multiple of the integrator's timestep.
     pub fn new(target: Float, tau: Float) -> Berendsen {
-        Berendsen { target, tau }
+        Berendsen {
+            target,
+            tau,
+            axes: None,
+        }
+    }
+
+    /// Couples each selected Cartesian component of the velocities to the target independently.
+    ///
+    /// Every component carries one degree of freedom per atom, so the temperature along an axis
+    /// is `sum(m * v^2) / (N * kB)`. Components along unselected axes are left untouched.
+    pub fn anisotropic(mut self, axes: [bool; 3]) -> Berendsen {
+        self.axes = Some(axes);
+        self
+    }
+
+    // Returns the rescaling factor which brings `temperature` toward the target.
+    fn factor(&self, temperature: Float) -> Float {
+        Float::sqrt(1.0 + (self.target / temperature - 1.0) / self.tau)
     }
 }
 
 impl Thermostat for Berendsen {
     fn post_integrate(&mut self, system: &mut System) {
-        let temperature = Temperature.calculate_intrinsic(system);
-        let factor = Float::sqrt(1.0 + (self.target / temperature - 1.0) / self.tau);
-        system.velocities = system
-            .velocities
-            .iter()
-            .map(|&v| v * factor)
-            .collect::<Vec<Vector3<Float>>>();
+        let axes = match self.axes {
+            Some(axes) => axes,
+            None => {
+                let factor = self.factor(Temperature.calculate_intrinsic(system));
+                system.velocities = system
+                    .velocities
+                    .iter()
+                    .map(|&v| v * factor)
+                    .collect::<Vec<Vector3<Float>>>();
+                return;
+            }
+        };
+        for axis in (0..3).filter(|&axis| axes[axis]) {
+            let twice_kinetic: Float = system
+                .species
+                .iter()
+                .zip(system.velocities.iter())
+                .map(|(s, v)| s.mass() * v[axis].powi(2))
+                .sum();
+            let temperature = twice_kinetic / (system.size as Float * boltzmann());
+            // a component at rest has no kinetic energy to rescale
+            if temperature <= 0.0 {
+                continue;
+            }
+            let factor = self.factor(temperature);
+            for v in system.velocities.iter_mut() {
+                v[axis] *= factor;
+            }
+        }
     }
 }
 
@@ -120,4 +170,435 @@ impl Thermostat for NoseHoover {
         let psidot = self.freq.powi(2) * ((self.temperature / self.target) - 1.0);
         self.psi += psidot * (dt / 2.0);
     }
+
+    fn state(&self) -> Vec<Float> {
+        vec![self.psi, self.factor, self.temperature]
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.psi = state[0];
+        self.factor = state[1];
+        self.temperature = state[2];
+    }
+}
+
+/// Nose-Hoover-Langevin stochastic thermostat.
+///
+/// The thermostat variable `xi` of the Nose-Hoover thermostat is itself coupled to a Langevin bath
+/// through `friction`, which makes the dynamics ergodic for small or stiff systems where plain
+/// Nose-Hoover samples only part of the canonical distribution.
+/// The thermostat mass is `Q = N_f kB T tau^2` for `N_f` degrees of freedom.
+///
+/// # References
+///
+/// [1] Leimkuhler, Benedict, Emad Noorizadeh, and Florian Theil. "A gentle stochastic thermostat for molecular dynamics." Journal of Statistical Physics 135.2 (2009): 261-277.
+#[derive(Clone, Debug)]
+pub struct NoseHooverLangevin {
+    target: Float,
+    tau: Float,
+    friction: Float,
+    timestep: Float,
+    xi: Float,
+    distr: Normal<Float>,
+    rng: VelvetRng,
+    seed: Option<u64>,
+    steps: usize,
+}
+
+impl NoseHooverLangevin {
+    /// Returns a new [`NoseHooverLangevin`] thermostat.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Target temperature.
+    /// * `tau` - Relaxation time of the thermostat variable.
+    /// * `friction` - Friction coefficient of the thermostat variable (1 / time).
+    /// * `timestep` - Timestep of the integrator.
+    pub fn new(target: Float, tau: Float, friction: Float, timestep: Float) -> NoseHooverLangevin {
+        NoseHooverLangevin {
+            target,
+            tau,
+            friction,
+            timestep,
+            xi: 0 as Float,
+            distr: Normal::new(0.0, 1.0).unwrap(),
+            rng: rng::from_entropy(),
+            seed: None,
+            steps: 0,
+        }
+    }
+
+    /// Seeds the random number generator to produce a reproducible trajectory.
+    ///
+    /// The generator is reseeded from `seed` and the step count each step
+    /// so a run resumed from a restart continues the same trajectory.
+    pub fn seed(mut self, seed: u64) -> NoseHooverLangevin {
+        self.rng = rng::from_seed(seed);
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the current value of the thermostat variable `xi`.
+    pub fn xi(&self) -> Float {
+        self.xi
+    }
+
+    // Drives the thermostat variable by the temperature error over `h`.
+    fn drift(&mut self, system: &System, h: Float) {
+        let temperature = Temperature.calculate_intrinsic(system);
+        self.xi += (temperature / self.target - 1.0) / self.tau.powi(2) * h;
+    }
+
+    // Exact Ornstein-Uhlenbeck update of the thermostat variable over `h`.
+    fn fluctuate(&mut self, system: &System, h: Float) {
+        let dof = system.degrees_of_freedom() as Float;
+        let decay = Float::exp(-self.friction * h);
+        // kB T / Q is the equilibrium variance of xi
+        let variance = (1.0 - decay.powi(2)) / (dof * self.tau.powi(2));
+        self.xi = self.xi * decay + variance.sqrt() * self.distr.sample(&mut self.rng);
+    }
+
+    // Scales the velocities by the friction of the thermostat variable over `h`.
+    fn scale(&self, system: &mut System, h: Float) {
+        let factor = Float::exp(-self.xi * h);
+        for v in system.velocities.iter_mut() {
+            *v *= factor;
+        }
+    }
+}
+
+impl Thermostat for NoseHooverLangevin {
+    fn pre_integrate(&mut self, system: &mut System) {
+        if let Some(seed) = self.seed {
+            self.rng = rng::from_seed(seed ^ self.steps as u64);
+        }
+        self.steps += 1;
+        let h = self.timestep / 2.0;
+        self.fluctuate(system, h);
+        self.drift(system, h);
+        self.scale(system, h);
+    }
+
+    fn post_integrate(&mut self, system: &mut System) {
+        let h = self.timestep / 2.0;
+        self.scale(system, h);
+        self.drift(system, h);
+        self.fluctuate(system, h);
+    }
+
+    fn state(&self) -> Vec<Float> {
+        vec![self.xi, self.steps as Float]
+    }
+
+    fn restore(&mut self, state: &[Float]) {
+        self.xi = state[0];
+        // restarts written before the step count was stored resume from step zero
+        self.steps = state.get(1).map_or(0, |&steps| steps as usize);
+    }
+}
+
+/// Pairwise dissipative and random forces of Dissipative Particle Dynamics.
+///
+/// Each pair of atoms within the cutoff exchanges equal and opposite impulses so the total
+/// momentum is conserved, unlike Langevin style thermostats.
+/// Combine with a [`DpdConservative`](crate::potentials::types::DpdConservative) pair potential for the full DPD model.
+///
+/// # References
+///
+/// [1] Groot, Robert D., and Patrick B. Warren. "Dissipative particle dynamics: Bridging the gap between atomistic and mesoscopic simulation." The Journal of chemical physics 107.11 (1997): 4423-4435.
+#[derive(Clone, Debug)]
+pub struct Dpd {
+    friction: Float,
+    target: Float,
+    cutoff: Float,
+    timestep: Float,
+    distr: Normal<Float>,
+    rng: VelvetRng,
+}
+
+impl Dpd {
+    /// Returns a new [`Dpd`] thermostat.
+    ///
+    /// # Arguments
+    ///
+    /// * `friction` - Dissipative friction coefficient (mass / time).
+    /// * `target` - Target temperature.
+    /// * `cutoff` - Cutoff radius of the pairwise forces.
+    /// * `timestep` - Timestep of the integrator.
+    pub fn new(friction: Float, target: Float, cutoff: Float, timestep: Float) -> Dpd {
+        Dpd {
+            friction,
+            target,
+            cutoff,
+            timestep,
+            distr: Normal::new(0.0, 1.0).unwrap(),
+            rng: rng::from_entropy(),
+        }
+    }
+
+    /// Seeds the random number generator to produce a reproducible trajectory.
+    pub fn seed(mut self, seed: u64) -> Dpd {
+        self.rng = rng::from_seed(seed);
+        self
+    }
+}
+
+impl Thermostat for Dpd {
+    fn post_integrate(&mut self, system: &mut System) {
+        let dt = self.timestep;
+        // fluctuation-dissipation theorem
+        let sigma = Float::sqrt(2.0 * self.friction * boltzmann() * self.target);
+        for i in 0..system.size {
+            for j in (i + 1)..system.size {
+                let mut r_ij = system.positions[i] - system.positions[j];
+                system.cell.vector_image(&mut r_ij);
+                let r = r_ij.norm();
+                if r >= self.cutoff {
+                    continue;
+                }
+                let dir = r_ij / r;
+                let weight = 1.0 - r / self.cutoff;
+                let v_ij = system.velocities[i] - system.velocities[j];
+                let dissipative = -self.friction * weight * weight * dir.dot(&v_ij);
+                let random = sigma * weight * self.distr.sample(&mut self.rng) / Float::sqrt(dt);
+                let impulse = (dissipative + random) * dt * dir;
+                system.velocities[i] += impulse / system.species[i].mass();
+                system.velocities[j] -= impulse / system.species[j].mass();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Berendsen, Dpd, NoseHoover, NoseHooverLangevin, Thermostat};
+    use crate::integrators::VelocityVerlet;
+    use crate::internal::consts::boltzmann;
+    use crate::internal::Float;
+    use crate::potentials::types::{DpdConservative, LennardJones, PositionRestraint};
+    use crate::potentials::PotentialsBuilder;
+    use crate::propagators::{MolecularDynamics, Propagator};
+    use crate::properties::energy::KineticEnergy;
+    use crate::properties::temperature::Temperature;
+    use crate::properties::IntrinsicProperty;
+    use crate::system::cell::Cell;
+    use crate::system::elements::Element;
+    use crate::system::species::Species;
+    use crate::system::System;
+    use approx::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn dpd_momentum_and_temperature() {
+        let argon = Species::from_element(Element::Ar);
+        let n = 4;
+        let spacing = 4.0;
+        let mut positions = Vec::new();
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    positions.push(Vector3::new(x as Float, y as Float, z as Float) * spacing);
+                }
+            }
+        }
+        let size = positions.len();
+        let mut system = System {
+            size,
+            cell: Cell::cubic(spacing * n as Float),
+            species: vec![argon; size],
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let cutoff = 6.0;
+        let mut potentials = PotentialsBuilder::new()
+            .pair(
+                DpdConservative::new(0.5, cutoff),
+                (argon, argon),
+                cutoff,
+                1.0,
+            )
+            .build();
+        potentials.setup(&system).unwrap();
+        potentials.update(&system, 0);
+
+        let target = 300.0;
+        let timestep = 2.0;
+        let dpd = Dpd::new(2.0, target, cutoff, timestep).seed(1234);
+        let mut md = MolecularDynamics::new(VelocityVerlet::new(timestep), dpd);
+        md.setup(&mut system, &potentials);
+
+        let mut temperatures = Vec::new();
+        for step in 1..=400 {
+            md.propagate(&mut system, &potentials);
+            potentials.update(&system, step);
+            if step > 100 {
+                temperatures.push(Temperature.calculate_intrinsic(&system));
+            }
+            // the system starts at rest so the momentum stays zero
+            assert!(system.momentum().norm() < 1e-2);
+        }
+        let average = temperatures.iter().sum::<Float>() / temperatures.len() as Float;
+        assert_relative_eq!(average, target, max_relative = 0.1);
+    }
+
+    #[test]
+    fn berendsen_anisotropic() {
+        let argon = Species::from_element(Element::Ar);
+        let size = 20;
+        let positions = (0..size)
+            .map(|i| Vector3::new(i as Float, 0.0, 0.0) * 4.0)
+            .collect();
+        let velocities = (0..size)
+            .map(|i| {
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                Vector3::new(0.003, -0.002, 0.008) * sign * (1 + i % 3) as Float
+            })
+            .collect();
+        let mut system = System {
+            size,
+            cell: Cell::cubic(80.0),
+            species: vec![argon; size],
+            positions,
+            velocities,
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let component_temperature = |system: &System, axis: usize| {
+            let twice_kinetic: Float = system
+                .velocities
+                .iter()
+                .map(|v| argon.mass() * v[axis].powi(2))
+                .sum();
+            twice_kinetic / (size as Float * boltzmann())
+        };
+        let initial = KineticEnergy.calculate_intrinsic(&system);
+        let lateral: Vec<(Float, Float)> = system.velocities.iter().map(|v| (v[0], v[1])).collect();
+
+        // only the z component is coupled to the target
+        let target = 0.5 * component_temperature(&system, 2);
+        let mut berendsen = Berendsen::new(target, 2.0).anisotropic([false, false, true]);
+        for _ in 0..100 {
+            berendsen.post_integrate(&mut system);
+        }
+        assert_relative_eq!(
+            component_temperature(&system, 2),
+            target,
+            max_relative = 1e-4
+        );
+        for (v, &(x, y)) in system.velocities.iter().zip(lateral.iter()) {
+            assert_eq!((v[0], v[1]), (x, y));
+        }
+        assert!(KineticEnergy.calculate_intrinsic(&system) < initial);
+    }
+
+    // Returns the mean and variance of the temperature of a single harmonically tethered atom.
+    fn oscillator_temperatures<T>(thermostat: T, timestep: Float) -> (Float, Float)
+    where
+        T: Thermostat + 'static,
+    {
+        let argon = Species::from_element(Element::Ar);
+        let center = Vector3::new(25.0, 25.0, 25.0);
+        let mut system = System {
+            size: 1,
+            cell: Cell::cubic(50.0),
+            species: vec![argon],
+            positions: vec![center + Vector3::new(1.0, 0.0, 0.0)],
+            velocities: vec![Vector3::new(0.0, 0.01, 0.0)],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new()
+            .external(PositionRestraint::new(0.05, vec![(0, center)]))
+            .build();
+        let mut md = MolecularDynamics::new(VelocityVerlet::new(timestep), thermostat);
+        md.setup(&mut system, &potentials);
+        let mut temperatures = Vec::new();
+        let steps = 200_000;
+        for step in 0..steps {
+            md.propagate(&mut system, &potentials);
+            if step > steps / 10 {
+                temperatures.push(Temperature.calculate_intrinsic(&system));
+            }
+        }
+        let count = temperatures.len() as Float;
+        let mean = temperatures.iter().sum::<Float>() / count;
+        let variance = temperatures
+            .iter()
+            .map(|t| (t - mean).powi(2))
+            .sum::<Float>()
+            / count;
+        (mean, variance)
+    }
+
+    #[test]
+    fn nose_hoover_langevin_canonical() {
+        let target = 300.0;
+        let timestep = 1.0;
+        // the kinetic energy of 3 degrees of freedom is gamma distributed with a variance of 2/3 T^2
+        let canonical = 2.0 / 3.0 * Float::powi(target, 2);
+
+        let nhl = NoseHooverLangevin::new(target, 20.0, 0.05, timestep).seed(1234);
+        let (mean, variance) = oscillator_temperatures(nhl, timestep);
+        assert_relative_eq!(mean, target, max_relative = 0.05);
+        assert_relative_eq!(variance, canonical, max_relative = 0.15);
+
+        // plain Nose-Hoover holds the average but is trapped on a non-canonical orbit
+        let nh = NoseHoover::new(target, 0.05, timestep);
+        let (_, nh_variance) = oscillator_temperatures(nh, timestep);
+        assert!(nh_variance > 2.0 * canonical);
+    }
+
+    #[test]
+    fn nose_hoover_langevin_restart() {
+        let argon = Species::from_element(Element::Ar);
+        let mut system = System {
+            size: 2,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; 2],
+            positions: vec![
+                Vector3::new(8.0, 10.0, 10.0),
+                Vector3::new(12.0, 10.0, 10.0),
+            ],
+            velocities: vec![Vector3::new(0.001, 0.002, 0.0), Vector3::zeros()],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        };
+        let potentials = PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+            .build();
+        let md = || {
+            MolecularDynamics::new(
+                VelocityVerlet::new(1.0),
+                NoseHooverLangevin::new(300.0, 20.0, 0.05, 1.0).seed(7),
+            )
+        };
+
+        let mut uninterrupted = md();
+        let mut reference = system.clone();
+        uninterrupted.setup(&mut reference, &potentials);
+        for _ in 0..100 {
+            uninterrupted.propagate(&mut reference, &potentials);
+        }
+
+        let mut first = md();
+        first.setup(&mut system, &potentials);
+        for _ in 0..50 {
+            first.propagate(&mut system, &potentials);
+        }
+        let mut resumed = md();
+        resumed.setup(&mut system, &potentials);
+        resumed.restore(&first.state());
+        for _ in 0..50 {
+            resumed.propagate(&mut system, &potentials);
+        }
+
+        assert_eq!(system.positions, reference.positions);
+        assert_eq!(system.velocities, reference.velocities);
+    }
 }
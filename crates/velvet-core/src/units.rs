@@ -0,0 +1,46 @@
+//! Unit system selected at runtime.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static UNIT_SYSTEM: AtomicU8 = AtomicU8::new(0);
+
+/// System of units in which every quantity is expressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// LAMMPS [real](https://lammps.sandia.gov/doc/units.html) style units described in the crate documentation.
+    Real,
+    /// Lennard-Jones reduced units where epsilon, sigma, mass and the Boltzmann constant are 1.
+    ///
+    /// Charges are also reduced so the Coulomb constant is 1.
+    Reduced,
+}
+
+/// Sets the unit system used by every simulation in the process.
+///
+/// It should be set once before any component is constructed since some of them
+/// convert their parameters as they are built.
+///
+/// # Examples
+///
+/// ```
+/// use velvet_core::prelude::*;
+///
+/// set_unit_system(UnitSystem::Reduced);
+/// assert_eq!(unit_system(), UnitSystem::Reduced);
+/// set_unit_system(UnitSystem::Real);
+/// ```
+pub fn set_unit_system(units: UnitSystem) {
+    let value = match units {
+        UnitSystem::Real => 0,
+        UnitSystem::Reduced => 1,
+    };
+    UNIT_SYSTEM.store(value, Ordering::Relaxed)
+}
+
+/// Returns the active unit system which defaults to [`UnitSystem::Real`].
+pub fn unit_system() -> UnitSystem {
+    match UNIT_SYSTEM.load(Ordering::Relaxed) {
+        0 => UnitSystem::Real,
+        _ => UnitSystem::Reduced,
+    }
+}
@@ -3,10 +3,11 @@
 use nalgebra::Vector3;
 use rand_distr::{Distribution, Normal};
 
-use crate::internal::consts::BOLTZMANN;
+use crate::internal::consts::boltzmann;
 use crate::internal::Float;
 use crate::properties::temperature::Temperature;
 use crate::properties::IntrinsicProperty;
+use crate::rng;
 use crate::system::System;
 
 /// Shared behavior for algorithms which initialize the temperature of a system from a velocity distribution.
@@ -24,6 +25,9 @@ pub trait VelocityDistribution: Send + Sync {
 pub struct Boltzmann {
     target: Float,
     distr: Normal<Float>,
+    remove_drift: bool,
+    remove_rotation: bool,
+    seed: Option<u64>,
 }
 
 impl Boltzmann {
@@ -33,24 +37,65 @@ impl Boltzmann {
     ///
     /// * `target` - Target temperature.
     pub fn new(target: Float) -> Boltzmann {
-        let distr = Normal::new(0.0, Float::sqrt(BOLTZMANN * target)).unwrap();
-        Boltzmann { target, distr }
+        let distr = Normal::new(0.0, Float::sqrt(boltzmann() * target)).unwrap();
+        Boltzmann {
+            target,
+            distr,
+            remove_drift: false,
+            remove_rotation: false,
+            seed: None,
+        }
+    }
+
+    /// Removes the net momentum of the system after sampling velocities.
+    pub fn remove_drift(mut self, remove_drift: bool) -> Boltzmann {
+        self.remove_drift = remove_drift;
+        self
+    }
+
+    /// Removes the net angular momentum of the system after sampling velocities.
+    ///
+    /// Rotation is only conserved in non-periodic systems such as isolated clusters.
+    pub fn remove_rotation(mut self, remove_rotation: bool) -> Boltzmann {
+        self.remove_rotation = remove_rotation;
+        self
+    }
+
+    /// Seeds the random number generator so every application samples the same velocities.
+    pub fn seed(mut self, seed: u64) -> Boltzmann {
+        self.seed = Some(seed);
+        self
     }
 }
 
 impl VelocityDistribution for Boltzmann {
     fn apply(&self, system: &mut System) {
+        let mut rng = match self.seed {
+            Some(seed) => rng::from_seed(seed),
+            None => rng::from_entropy(),
+        };
+        let planar = system.cell.is_planar();
         system.velocities = system
             .species
             .iter()
             .map(|species| {
                 let inv_mass = 1.0 / species.mass();
-                let x = inv_mass.sqrt() * self.distr.sample(&mut rand::thread_rng());
-                let y = inv_mass.sqrt() * self.distr.sample(&mut rand::thread_rng());
-                let z = inv_mass.sqrt() * self.distr.sample(&mut rand::thread_rng());
+                let x = inv_mass.sqrt() * self.distr.sample(&mut rng);
+                let y = inv_mass.sqrt() * self.distr.sample(&mut rng);
+                let z = if planar {
+                    0.0
+                } else {
+                    inv_mass.sqrt() * self.distr.sample(&mut rng)
+                };
                 Vector3::new(x, y, z)
             })
             .collect::<Vec<Vector3<Float>>>();
+        if self.remove_drift {
+            system.remove_net_momentum();
+        }
+        if self.remove_rotation {
+            system.remove_net_angular_momentum();
+        }
         scale(system, self.target);
     }
 }
@@ -0,0 +1,160 @@
+use nalgebra::Vector3;
+
+use velvet_core::prelude::*;
+
+fn argon_dimer() -> (System, Potentials) {
+    let argon = Species::from_element(Element::Ar);
+    let system = System {
+        size: 2,
+        cell: Cell::cubic(20.0),
+        species: vec![argon; 2],
+        positions: vec![
+            Vector3::new(8.0, 10.0, 10.0),
+            Vector3::new(12.0, 10.0, 10.0),
+        ],
+        velocities: vec![
+            Vector3::new(0.001, 0.0, 0.0),
+            Vector3::new(-0.001, 0.0, 0.0),
+        ],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let potentials = PotentialsBuilder::new()
+        .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+        .build();
+    (system, potentials)
+}
+
+fn nvt() -> MolecularDynamics {
+    MolecularDynamics::new(VelocityVerlet::new(1.0), NoseHoover::new(300.0, 0.01, 1.0))
+}
+
+#[test]
+fn checkpoint_rotation() {
+    let (system, potentials) = argon_dimer();
+    let md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat);
+
+    let path = std::env::temp_dir().join("velvet-checkpoint.restart");
+    let previous = std::env::temp_dir().join("velvet-checkpoint.restart.prev");
+    let config = ConfigurationBuilder::new().checkpoint(&path, 4).build();
+    let mut simulation = Simulation::new(system, potentials, md, config);
+    simulation.run(10).unwrap();
+    let (system, _) = simulation.consume();
+
+    // checkpoints were written after steps 4 and 8
+    let latest = Restart::parse_from_file(&path);
+    let older = Restart::parse_from_file(&previous);
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&previous).unwrap();
+    assert_eq!(latest.step, 8);
+    assert_eq!(older.step, 4);
+    assert_eq!(latest.system.size, system.size);
+    assert_eq!(latest.system.species, system.species);
+    // the atoms kept moving after the last checkpoint
+    assert!(latest.system.positions[0] != system.positions[0]);
+    assert!(latest.system.positions[0] != older.system.positions[0]);
+}
+
+#[test]
+fn checkpoint_resume_matches_uninterrupted() {
+    let (system, potentials) = argon_dimer();
+    let config = ConfigurationBuilder::new().build();
+    let mut simulation = Simulation::new(system.clone(), potentials, nvt(), config);
+    simulation.run(20).unwrap();
+    let (uninterrupted, _) = simulation.consume();
+
+    let path = std::env::temp_dir().join("velvet-resume.restart");
+    let (_, potentials) = argon_dimer();
+    let config = ConfigurationBuilder::new().checkpoint(&path, 10).build();
+    let mut simulation = Simulation::new(system, potentials, nvt(), config);
+    simulation.run(10).unwrap();
+
+    // resume with fresh potentials and a fresh propagator
+    let restart = Restart::parse_from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(restart.step, 10);
+    let (_, potentials) = argon_dimer();
+    let config = ConfigurationBuilder::new().build();
+    let mut simulation = Simulation::from_restart(restart, potentials, nvt(), config);
+    simulation.run(10).unwrap();
+    let (resumed, _) = simulation.consume();
+
+    assert_eq!(resumed.positions, uninterrupted.positions);
+    assert_eq!(resumed.velocities, uninterrupted.velocities);
+}
+
+// Writer which rejects every write.
+struct Unwritable;
+
+impl std::io::Write for Unwritable {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("destination is closed"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn raw_output_error_is_reported() {
+    let (system, potentials) = argon_dimer();
+    let md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat);
+    let group = RawOutputGroupBuilder::new()
+        .destination(Unwritable)
+        .output(PotentialEnergy)
+        .build();
+    let config = ConfigurationBuilder::new().raw_output_group(group).build();
+    let mut simulation = Simulation::new(system, potentials, md, config);
+    let message = simulation.run(2).unwrap_err();
+    assert!(message.contains("destination is closed"));
+}
+
+#[test]
+fn checkpoint_error_is_reported() {
+    let (system, potentials) = argon_dimer();
+    let md = MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat);
+    let path = std::env::temp_dir()
+        .join("velvet-missing-directory")
+        .join("velvet.restart");
+    let config = ConfigurationBuilder::new().checkpoint(&path, 2).build();
+    let mut simulation = Simulation::new(system, potentials, md, config);
+    let message = simulation.run(4).unwrap_err();
+    assert!(message.contains("failed to write checkpoint"));
+}
+
+#[test]
+fn json_frames_report_the_step() {
+    let timesteps = |buffer: &SharedBuffer| -> Vec<usize> {
+        String::from_utf8(buffer.contents())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<JsonSnapshot>(line).unwrap().timestep)
+            .collect()
+    };
+    let config = |buffer: &SharedBuffer| {
+        let group = RawOutputGroupBuilder::new()
+            .destination(buffer.clone())
+            .interval(4)
+            .output(JsonFrame::new())
+            .build();
+        ConfigurationBuilder::new().raw_output_group(group).build()
+    };
+    let md = || MolecularDynamics::new(VelocityVerlet::new(1.0), NullThermostat);
+
+    // the final frame is written after the last step rather than on an interval
+    let (system, potentials) = argon_dimer();
+    let buffer = SharedBuffer::new();
+    let mut simulation = Simulation::new(system, potentials, md(), config(&buffer));
+    simulation.run(10).unwrap();
+    assert_eq!(timesteps(&buffer), vec![1, 5, 9, 10]);
+
+    // a resumed run continues counting from the restart
+    let (system, potentials) = simulation.consume();
+    let buffer = SharedBuffer::new();
+    let restart = Restart::new(10, system, md().state());
+    let mut simulation = Simulation::from_restart(restart, potentials, md(), config(&buffer));
+    simulation.run(3).unwrap();
+    assert_eq!(timesteps(&buffer), vec![13]);
+}
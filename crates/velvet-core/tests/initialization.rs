@@ -0,0 +1,46 @@
+use approx::*;
+use nalgebra::Vector3;
+
+use velvet_core::prelude::*;
+
+#[test]
+fn first_step_uses_initial_forces() {
+    let argon = Species::from_element(Element::Ar);
+    // the atoms start at rest in the attractive well so only the initial forces move them
+    let system = System {
+        size: 2,
+        cell: Cell::cubic(20.0),
+        species: vec![argon; 2],
+        positions: vec![
+            Vector3::new(8.0, 10.0, 10.0),
+            Vector3::new(12.0, 10.0, 10.0),
+        ],
+        velocities: vec![Vector3::zeros(); 2],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let build = || {
+        PotentialsBuilder::new()
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 8.5, 1.0)
+            .build()
+    };
+
+    // the neighbors are selected before the forces of the first step are needed
+    let mut potentials = build();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+    let forces = Forces.calculate(&system, &potentials);
+    assert!(forces[0][0] > 0.0);
+
+    let dt = 1.0;
+    let md = MolecularDynamics::new(VelocityVerlet::new(dt), NullThermostat);
+    let config = ConfigurationBuilder::new().build();
+    let mut simulation = Simulation::new(system.clone(), build(), md, config);
+    simulation.run(1).unwrap();
+    let positions = system.positions.iter().zip(forces.iter());
+    for (position, (initial, force)) in simulation.system().positions.iter().zip(positions) {
+        let expected = initial + 0.5 * force / argon.mass() * dt * dt;
+        assert_relative_eq!(*position, expected, epsilon = 1e-5);
+    }
+}
@@ -1,8 +1,14 @@
+use approx::*;
+use nalgebra::Vector3;
+
 use velvet_core::selection::{
-    setup_pairs_by_species, setup_pairs_with_charge, update_pairs_by_cutoff_radius, Selection,
+    image_neighbors, setup_pairs_by_species, setup_pairs_with_charge,
+    update_pairs_by_cutoff_radius, Selection,
 };
+use velvet_core::system::cell::Cell;
 use velvet_core::system::elements::Element;
 use velvet_core::system::species::Species;
+use velvet_core::system::System;
 use velvet_test_utils as test_utils;
 
 #[test]
@@ -37,3 +43,30 @@ fn setup_pairs_with_charge_update_pairs_by_cutoff_radius() {
     selection.update(&system, cutoff);
     assert_ne!(selection.indices().count(), 0);
 }
+
+#[test]
+fn image_neighbors_of_single_atom() {
+    let length = 3.0;
+    let system = System {
+        size: 1,
+        cell: Cell::cubic(length),
+        species: vec![Species::from_element(Element::Ar)],
+        positions: vec![Vector3::new(1.0, 2.0, 0.5)],
+        velocities: vec![Vector3::zeros()],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+
+    // the cutoff only reaches the nearest image along each axis
+    let neighbors = image_neighbors(&system, length + 0.5);
+    assert_eq!(neighbors.len(), 1);
+    assert_eq!(neighbors[0].len(), 6);
+    for neighbor in &neighbors[0] {
+        assert_eq!(neighbor.index, 0);
+        assert_relative_eq!(neighbor.displacement.norm(), length, epsilon = 1e-5);
+    }
+
+    // no images are within a cutoff shorter than the cell
+    assert!(image_neighbors(&system, length - 0.5)[0].is_empty());
+}
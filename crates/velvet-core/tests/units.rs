@@ -0,0 +1,47 @@
+// The unit system is global to the process so these tests live in their own binary.
+
+use approx::*;
+use nalgebra::Vector3;
+
+use velvet_core::properties::energy::KineticEnergy;
+use velvet_core::properties::temperature::Temperature;
+use velvet_core::properties::IntrinsicProperty;
+use velvet_core::system::cell::Cell;
+use velvet_core::system::species::Species;
+use velvet_core::system::System;
+use velvet_core::units::{set_unit_system, UnitSystem};
+use velvet_core::velocity_distributions::{Boltzmann, VelocityDistribution};
+
+#[cfg(feature = "f64")]
+type Float = f64;
+#[cfg(not(feature = "f64"))]
+type Float = f32;
+
+#[test]
+fn reduced_kinetic_energy_per_dof() {
+    set_unit_system(UnitSystem::Reduced);
+    let size = 64;
+    let particle = Species::new(1.0, 0.0);
+    let mut system = System {
+        size,
+        cell: Cell::cubic(4.0),
+        species: vec![particle; size],
+        positions: (0..size)
+            .map(|i| Vector3::new((i % 4) as Float, (i / 4 % 4) as Float, (i / 16) as Float))
+            .collect(),
+        velocities: vec![Vector3::zeros(); size],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    Boltzmann::new(1.0).seed(1234).apply(&mut system);
+
+    let dof = (3 * size) as Float;
+    let kinetic = KineticEnergy.calculate_intrinsic(&system);
+    assert_relative_eq!(kinetic / dof, 0.5, epsilon = 1e-5);
+    assert_relative_eq!(
+        Temperature.calculate_intrinsic(&system),
+        1.0,
+        epsilon = 1e-5
+    );
+}
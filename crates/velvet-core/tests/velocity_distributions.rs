@@ -1,10 +1,21 @@
 use approx::*;
+use nalgebra::Vector3;
 
 use velvet_core::properties::temperature::Temperature;
 use velvet_core::properties::IntrinsicProperty;
+use velvet_core::rng::SeedSequence;
+use velvet_core::system::cell::Cell;
+use velvet_core::system::elements::Element;
+use velvet_core::system::species::Species;
+use velvet_core::system::System;
 use velvet_core::velocity_distributions::{Boltzmann, VelocityDistribution};
 use velvet_test_utils as test_utils;
 
+#[cfg(feature = "f64")]
+type Float = f64;
+#[cfg(not(feature = "f64"))]
+type Float = f32;
+
 #[test]
 fn boltzmann() {
     let mut system = test_utils::argon_system();
@@ -17,3 +28,70 @@ fn boltzmann() {
         epsilon = 1e-3
     );
 }
+
+#[test]
+fn boltzmann_remove_drift_and_rotation() {
+    // small cluster of argon atoms on a cubic lattice
+    let coords = [0.0, 3.8, 7.6];
+    let mut positions = Vec::new();
+    for &x in coords.iter() {
+        for &y in coords.iter() {
+            for &z in coords.iter() {
+                positions.push(Vector3::new(x, y, z));
+            }
+        }
+    }
+    let size = positions.len();
+    let mut system = System {
+        size,
+        cell: Cell::cubic(100.0),
+        species: vec![Species::from_element(Element::Ar); size],
+        positions,
+        velocities: vec![Vector3::zeros(); size],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let target = 300.0;
+    let boltz = Boltzmann::new(target)
+        .remove_drift(true)
+        .remove_rotation(true);
+    boltz.apply(&mut system);
+    let momentum = system.momentum();
+    let angular_momentum = system.angular_momentum();
+    for k in 0..3 {
+        assert_relative_eq!(momentum[k], 0.0, epsilon = 1e-3);
+        assert_relative_eq!(angular_momentum[k], 0.0, epsilon = 1e-2);
+    }
+    assert_relative_eq!(
+        Temperature.calculate_intrinsic(&system),
+        target,
+        epsilon = 1e-3
+    );
+}
+
+#[test]
+fn boltzmann_seed() {
+    let size = 8;
+    let mut system = System {
+        size,
+        cell: Cell::cubic(100.0),
+        species: vec![Species::from_element(Element::Ar); size],
+        positions: (0..size)
+            .map(|i| Vector3::new(4.0 * i as Float, 0.0, 0.0))
+            .collect(),
+        velocities: vec![Vector3::zeros(); size],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let mut seeds = SeedSequence::new(1234);
+    let (first, second) = (seeds.next(), seeds.next());
+
+    Boltzmann::new(300.0).seed(first).apply(&mut system);
+    let velocities = system.velocities.clone();
+    Boltzmann::new(300.0).seed(first).apply(&mut system);
+    assert_eq!(system.velocities, velocities);
+    Boltzmann::new(300.0).seed(second).apply(&mut system);
+    assert_ne!(system.velocities, velocities);
+}
@@ -0,0 +1,238 @@
+use std::fs;
+use std::str::SplitWhitespace;
+
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+
+// EAM files use electron volts while Velvet uses kilocalories per mole.
+const KILOCALORIES_PER_ELECTRON_VOLT: Float = 23.060548;
+// Converts the squared effective charge of a funcfl file into `r * phi` in eV·Å as LAMMPS does.
+const HARTREE_BOHR: Float = 27.2 * 0.529;
+
+/// Element described by an EAM file.
+#[derive(Clone, Debug)]
+pub struct EamElement {
+    /// Chemical symbol.
+    pub symbol: String,
+    /// Atomic number.
+    pub number: usize,
+    /// Mass (grams/mole).
+    pub mass: Float,
+    /// Lattice constant (angstrom).
+    pub lattice_constant: Float,
+    /// Lattice structure such as `fcc`.
+    pub lattice: String,
+    /// Embedding energy (Kcal/mole) at each density node.
+    pub embedding: Vec<Float>,
+    /// Electron density at each distance node.
+    pub density: Vec<Float>,
+}
+
+/// Embedded atom method potential in the `funcfl` or `setfl` format of
+/// [DYNAMO](https://lammps.sandia.gov/doc/pair_eam.html#description).
+///
+/// Energies are converted to Velvet's internal units as they are parsed.
+/// Density nodes are spaced by `drho` and distance nodes by `dr`, both starting from zero.
+#[derive(Clone, Debug)]
+pub struct EamFile {
+    /// Elements in the order they are defined.
+    pub elements: Vec<EamElement>,
+    /// Spacing between density nodes.
+    pub drho: Float,
+    /// Spacing between distance nodes (angstrom).
+    pub dr: Float,
+    /// Cutoff radius (angstrom).
+    pub cutoff: Float,
+    /// Product `r * phi(r)` (Kcal/mole-angstrom) at each distance node for each pair of elements
+    /// stored as a lower triangle in row major order.
+    pub pair: Vec<Vec<Float>>,
+}
+
+impl EamFile {
+    /// Parses a single element `funcfl` file.
+    ///
+    /// The format does not name its element so the chemical symbol must be provided.
+    pub fn parse_funcfl_from_file<T: AsRef<str>>(filename: T, symbol: &str) -> EamFile {
+        let text = fs::read_to_string(filename.as_ref()).unwrap();
+        EamFile::parse_funcfl_from_str(&text, symbol)
+    }
+
+    /// Parses a single element `funcfl` string.
+    ///
+    /// The format does not name its element so the chemical symbol must be provided.
+    pub fn parse_funcfl_from_str(text: &str, symbol: &str) -> EamFile {
+        // the first line is a comment
+        let (_, body) = text.split_once('\n').expect("funcfl file is empty");
+        let mut tokens = body.split_whitespace();
+        let number = next(&mut tokens);
+        let mass = next(&mut tokens);
+        let lattice_constant = next(&mut tokens);
+        let lattice = tokens
+            .next()
+            .expect("funcfl file is missing the lattice")
+            .to_string();
+        let (nrho, drho, nr, dr, cutoff) = grid(&mut tokens);
+        let embedding = energies(&mut tokens, nrho);
+        let charge: Vec<Float> = values(&mut tokens, nr);
+        let density = values(&mut tokens, nr);
+        let pair = charge
+            .iter()
+            .map(|z| z * z * HARTREE_BOHR * KILOCALORIES_PER_ELECTRON_VOLT)
+            .collect();
+        EamFile {
+            elements: vec![EamElement {
+                symbol: symbol.to_string(),
+                number,
+                mass,
+                lattice_constant,
+                lattice,
+                embedding,
+                density,
+            }],
+            drho,
+            dr,
+            cutoff,
+            pair: vec![pair],
+        }
+    }
+
+    /// Parses a `setfl` file.
+    pub fn parse_setfl_from_file<T: AsRef<str>>(filename: T) -> EamFile {
+        let text = fs::read_to_string(filename.as_ref()).unwrap();
+        EamFile::parse_setfl_from_str(&text)
+    }
+
+    /// Parses a `setfl` string.
+    pub fn parse_setfl_from_str(text: &str) -> EamFile {
+        // the first three lines are comments
+        let body = text
+            .splitn(4, '\n')
+            .nth(3)
+            .expect("setfl file is missing its header");
+        let mut tokens = body.split_whitespace();
+        let count: usize = next(&mut tokens);
+        let symbols: Vec<String> = (0..count)
+            .map(|_| {
+                tokens
+                    .next()
+                    .expect("setfl file is missing an element")
+                    .to_string()
+            })
+            .collect();
+        let (nrho, drho, nr, dr, cutoff) = grid(&mut tokens);
+        let elements = symbols
+            .into_iter()
+            .map(|symbol| EamElement {
+                symbol,
+                number: next(&mut tokens),
+                mass: next(&mut tokens),
+                lattice_constant: next(&mut tokens),
+                lattice: tokens
+                    .next()
+                    .expect("setfl file is missing the lattice")
+                    .to_string(),
+                embedding: energies(&mut tokens, nrho),
+                density: values(&mut tokens, nr),
+            })
+            .collect();
+        let pair = (0..count * (count + 1) / 2)
+            .map(|_| energies(&mut tokens, nr))
+            .collect();
+        EamFile {
+            elements,
+            drho,
+            dr,
+            cutoff,
+            pair,
+        }
+    }
+
+    /// Returns an [`Eam`] potential with cubic spline interpolation.
+    ///
+    /// # Arguments
+    ///
+    /// * `types` - Chemical symbol in the file of each species.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a symbol is not defined in the file.
+    pub fn potential(&self, types: &[(Species, &str)]) -> Eam {
+        let indices: Vec<usize> = types
+            .iter()
+            .map(|(_, symbol)| {
+                self.elements
+                    .iter()
+                    .position(|element| element.symbol == *symbol)
+                    .unwrap_or_else(|| panic!("EAM file does not define {}", symbol))
+            })
+            .collect();
+        let rho: Vec<Float> = (0..self.elements[0].embedding.len())
+            .map(|k| k as Float * self.drho)
+            .collect();
+        // skip the node at zero distance where the pair energy is undefined
+        let r: Vec<Float> = (1..self.pair[0].len())
+            .map(|k| k as Float * self.dr)
+            .collect();
+        let embedding = indices
+            .iter()
+            .map(|&a| spline(rho.clone(), self.elements[a].embedding.clone()))
+            .collect();
+        let density = indices
+            .iter()
+            .map(|&a| spline(r.clone(), self.elements[a].density[1..].to_vec()))
+            .collect();
+        let mut pair = Vec::new();
+        for (row, &a) in indices.iter().enumerate() {
+            for &b in &indices[..=row] {
+                let (a, b) = if a >= b { (a, b) } else { (b, a) };
+                let product = &self.pair[a * (a + 1) / 2 + b];
+                let phi = r
+                    .iter()
+                    .zip(&product[1..])
+                    .map(|(r, product)| product / r)
+                    .collect();
+                pair.push(spline(r.clone(), phi));
+            }
+        }
+        let species = types.iter().map(|(species, _)| *species).collect();
+        Eam::new(species, embedding, density, pair)
+    }
+}
+
+fn spline(x: Vec<Float>, y: Vec<Float>) -> Tabulated {
+    Tabulated::from_columns(x, y, Interpolation::CubicSpline)
+}
+
+fn next<T: std::str::FromStr>(tokens: &mut SplitWhitespace) -> T
+where
+    T::Err: std::fmt::Debug,
+{
+    tokens
+        .next()
+        .expect("EAM file ended unexpectedly")
+        .parse()
+        .unwrap()
+}
+
+// Parses the `Nrho drho Nr dr cutoff` line.
+fn grid(tokens: &mut SplitWhitespace) -> (usize, Float, usize, Float, Float) {
+    (
+        next(tokens),
+        next(tokens),
+        next(tokens),
+        next(tokens),
+        next(tokens),
+    )
+}
+
+fn values(tokens: &mut SplitWhitespace, count: usize) -> Vec<Float> {
+    (0..count).map(|_| next(tokens)).collect()
+}
+
+fn energies(tokens: &mut SplitWhitespace, count: usize) -> Vec<Float> {
+    values(tokens, count)
+        .iter()
+        .map(|value| value * KILOCALORIES_PER_ELECTRON_VOLT)
+        .collect()
+}
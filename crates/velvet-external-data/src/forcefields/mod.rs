@@ -0,0 +1,4 @@
+//! Force field file formats.
+
+pub mod eam;
+pub mod openmm;
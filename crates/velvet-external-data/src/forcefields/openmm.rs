@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fs;
+
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+
+// OpenMM uses nanometers and kilojoules while Velvet uses angstroms and kilocalories.
+const ANGSTROMS_PER_NANOMETER: Float = 10.0;
+const KILOJOULES_PER_KILOCALORIE: Float = 4.184;
+
+/// Atom type defined in an OpenMM force field.
+#[derive(Clone, Debug)]
+pub struct AtomType {
+    /// Unique name of the type.
+    pub name: String,
+    /// Class shared by similar types.
+    pub class: String,
+    /// Chemical element symbol if one is given.
+    pub element: Option<String>,
+    /// Mass (grams/mole).
+    pub mass: Float,
+}
+
+/// Nonbonded parameters of an atom type.
+#[derive(Clone, Copy, Debug)]
+pub struct NonbondedParameters {
+    /// Electronic charge.
+    pub charge: Float,
+    /// Lennard-Jones sigma (angstrom).
+    pub sigma: Float,
+    /// Lennard-Jones epsilon (Kcal/mole).
+    pub epsilon: Float,
+}
+
+/// Harmonic bond between two atom types or classes.
+#[derive(Clone, Debug)]
+pub struct HarmonicBondParameters {
+    /// Atom type or class of each end of the bond.
+    pub types: [String; 2],
+    /// Equilibrium length (angstrom).
+    pub length: Float,
+    /// Spring constant (Kcal/mole-angstrom^2) of the energy `k (r - length)^2`.
+    pub k: Float,
+}
+
+/// Harmonic angle between three atom types or classes.
+#[derive(Clone, Debug)]
+pub struct HarmonicAngleParameters {
+    /// Atom type or class of each atom in the angle with the vertex in the middle.
+    pub types: [String; 3],
+    /// Equilibrium angle (radians).
+    pub angle: Float,
+    /// Spring constant (Kcal/mole-radian^2) of the energy `k (theta - angle)^2`.
+    pub k: Float,
+}
+
+/// Force field in OpenMM's XML format.
+///
+/// Parameters are converted to Velvet's internal units as they are parsed.
+///
+/// # Examples
+///
+/// Build the Lennard-Jones parameters of argon.
+/// ```
+/// use velvet_external_data::prelude::*;
+///
+/// let forcefield = OpenMMForceField::parse_from_str(r#"
+///     <ForceField>
+///       <AtomTypes>
+///         <Type name="Ar" class="Ar" element="Ar" mass="39.948"/>
+///       </AtomTypes>
+///       <NonbondedForce coulomb14scale="0.833333" lj14scale="0.5">
+///         <Atom type="Ar" charge="0.0" sigma="0.34" epsilon="0.996"/>
+///       </NonbondedForce>
+///     </ForceField>
+/// "#);
+///
+/// let lj = forcefield.lennard_jones("Ar", "Ar").unwrap();
+/// assert!((lj.sigma - 3.4).abs() < 1e-5);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OpenMMForceField {
+    /// Atom types in the order they are defined.
+    pub atom_types: Vec<AtomType>,
+    /// Nonbonded parameters keyed by atom type or class.
+    pub nonbonded: HashMap<String, NonbondedParameters>,
+    /// Harmonic bond parameters.
+    pub bonds: Vec<HarmonicBondParameters>,
+    /// Harmonic angle parameters.
+    pub angles: Vec<HarmonicAngleParameters>,
+    /// Tags of the force sections which are not supported, such as torsions.
+    pub unsupported: Vec<String>,
+}
+
+impl OpenMMForceField {
+    /// Parses a force field from an XML file.
+    pub fn parse_from_file<T: AsRef<str>>(filename: T) -> OpenMMForceField {
+        let text = fs::read_to_string(filename.as_ref()).unwrap();
+        OpenMMForceField::parse_from_str(&text)
+    }
+
+    /// Parses a force field from an XML string.
+    pub fn parse_from_str(text: &str) -> OpenMMForceField {
+        let document = roxmltree::Document::parse(text).unwrap();
+        let mut forcefield = OpenMMForceField::default();
+        for node in document
+            .root_element()
+            .children()
+            .filter(|n| n.is_element())
+        {
+            match node.tag_name().name() {
+                "AtomTypes" => {
+                    for child in elements(node, "Type") {
+                        forcefield.atom_types.push(AtomType {
+                            name: attribute(child, "name").to_string(),
+                            class: child.attribute("class").unwrap_or("").to_string(),
+                            element: child.attribute("element").map(|e| e.to_string()),
+                            mass: number(child, "mass"),
+                        });
+                    }
+                }
+                "NonbondedForce" => {
+                    for child in elements(node, "Atom") {
+                        let key = type_or_class(child, "");
+                        forcefield.nonbonded.insert(
+                            key,
+                            NonbondedParameters {
+                                charge: number(child, "charge"),
+                                sigma: number(child, "sigma") * ANGSTROMS_PER_NANOMETER,
+                                epsilon: number(child, "epsilon") / KILOJOULES_PER_KILOCALORIE,
+                            },
+                        );
+                    }
+                }
+                "HarmonicBondForce" => {
+                    for child in elements(node, "Bond") {
+                        // OpenMM defines the energy as 1/2 k (r - r0)^2
+                        let k = number(child, "k")
+                            / KILOJOULES_PER_KILOCALORIE
+                            / ANGSTROMS_PER_NANOMETER.powi(2);
+                        forcefield.bonds.push(HarmonicBondParameters {
+                            types: [type_or_class(child, "1"), type_or_class(child, "2")],
+                            length: number(child, "length") * ANGSTROMS_PER_NANOMETER,
+                            k: 0.5 * k,
+                        });
+                    }
+                }
+                "HarmonicAngleForce" => {
+                    for child in elements(node, "Angle") {
+                        let k = number(child, "k") / KILOJOULES_PER_KILOCALORIE;
+                        forcefield.angles.push(HarmonicAngleParameters {
+                            types: [
+                                type_or_class(child, "1"),
+                                type_or_class(child, "2"),
+                                type_or_class(child, "3"),
+                            ],
+                            angle: number(child, "angle"),
+                            k: 0.5 * k,
+                        });
+                    }
+                }
+                name if name.ends_with("Force") => forcefield.unsupported.push(name.to_string()),
+                _ => {}
+            }
+        }
+        forcefield
+    }
+
+    /// Returns the Lennard-Jones potential between two atom types using Lorentz-Berthelot mixing rules.
+    pub fn lennard_jones(&self, type_i: &str, type_j: &str) -> Option<LennardJones> {
+        let params_i = self.nonbonded_parameters(type_i)?;
+        let params_j = self.nonbonded_parameters(type_j)?;
+        let sigma = 0.5 * (params_i.sigma + params_j.sigma);
+        let epsilon = Float::sqrt(params_i.epsilon * params_j.epsilon);
+        Some(LennardJones::new(epsilon, sigma))
+    }
+
+    /// Returns the charge of an atom type.
+    pub fn charge(&self, type_name: &str) -> Option<Float> {
+        self.nonbonded_parameters(type_name)
+            .map(|params| params.charge)
+    }
+
+    /// Returns the harmonic bond potential between two atom types.
+    pub fn harmonic_bond(&self, type_i: &str, type_j: &str) -> Option<Harmonic> {
+        self.bond_parameters(type_i, type_j)
+            .map(|bond| Harmonic::new(bond.k, bond.length))
+    }
+
+    /// Returns the potentials of the system built from the force field.
+    ///
+    /// Lennard-Jones pair potentials act between every pair of species, a [`StandardCoulombic`] potential
+    /// acts between charged atoms if any atom type is charged, and a [`DistanceRestraint`] or [`AngleRestraint`]
+    /// with the parameters of its atom types acts on each bond and angle in the system.
+    /// Call [`apply_charges`](OpenMMForceField::apply_charges) first so the species carry their charges.
+    /// Nonbonded interactions between bonded atoms are not excluded.
+    ///
+    /// Returns an error if the force field defines forces which are not supported
+    /// or if parameters are missing for a pair of species, a bond or an angle.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - System whose species are matched to atom types.
+    /// * `types` - Atom type name of each species.
+    /// * `cutoff` - Cutoff radius of every pair and Coulomb potential.
+    /// * `thickness` - Neighbor list thickness of every pair and Coulomb potential.
+    pub fn potentials(
+        &self,
+        system: &System,
+        types: &[(Species, &str)],
+        cutoff: Float,
+        thickness: Float,
+    ) -> Result<Potentials, String> {
+        if !self.unsupported.is_empty() {
+            return Err(format!(
+                "unsupported forces in force field: {}",
+                self.unsupported.join(", ")
+            ));
+        }
+        let present: Vec<&(Species, &str)> = types
+            .iter()
+            .filter(|(species, _)| system.species.contains(species))
+            .collect();
+        let mut builder = PotentialsBuilder::new();
+        for (i, (species_i, type_i)) in present.iter().enumerate() {
+            for (species_j, type_j) in present.iter().skip(i) {
+                let lj = self.lennard_jones(type_i, type_j).ok_or_else(|| {
+                    format!("missing nonbonded parameters for {} and {}", type_i, type_j)
+                })?;
+                builder = builder.pair(lj, (*species_i, *species_j), cutoff, thickness);
+            }
+        }
+        let charged = present
+            .iter()
+            .any(|(_, type_name)| self.charge(type_name).unwrap_or(0.0).abs() > Float::EPSILON);
+        if charged {
+            builder = builder.coulomb(StandardCoulombic::new(1.0), cutoff, thickness);
+        }
+
+        // every atom in a bond or angle needs an atom type
+        let type_of = |index: usize| -> Result<&str, String> {
+            types
+                .iter()
+                .find(|(species, _)| *species == system.species[index])
+                .map(|(_, type_name)| *type_name)
+                .ok_or_else(|| format!("missing atom type for atom {}", index))
+        };
+        for &[i, j] in &system.bonds {
+            let (type_i, type_j) = (type_of(i)?, type_of(j)?);
+            let bond = self
+                .bond_parameters(type_i, type_j)
+                .ok_or_else(|| format!("missing bond parameters for {} and {}", type_i, type_j))?;
+            builder = builder.external(DistanceRestraint::new(i, j, bond.k, bond.length));
+        }
+        for &[i, j, k] in &system.angles {
+            let (type_i, type_j, type_k) = (type_of(i)?, type_of(j)?, type_of(k)?);
+            let angle = self
+                .angle_parameters(type_i, type_j, type_k)
+                .ok_or_else(|| {
+                    format!(
+                        "missing angle parameters for {}, {} and {}",
+                        type_i, type_j, type_k
+                    )
+                })?;
+            builder = builder.external(AngleRestraint::new(i, j, k, angle.k, angle.angle));
+        }
+        Ok(builder.build())
+    }
+
+    /// Assigns the charge of each atom type to the matching species in the system.
+    pub fn apply_charges(&self, system: &mut System, types: &[(Species, &str)]) {
+        for species in system.species.iter_mut() {
+            if let Some((_, type_name)) = types.iter().find(|(s, _)| s == species) {
+                if let Some(charge) = self.charge(type_name) {
+                    *species = species.with_charge(charge);
+                }
+            }
+        }
+    }
+
+    fn nonbonded_parameters(&self, type_name: &str) -> Option<&NonbondedParameters> {
+        self.nonbonded.get(type_name).or_else(|| {
+            let class = &self.atom_type(type_name)?.class;
+            self.nonbonded.get(class)
+        })
+    }
+
+    fn bond_parameters(&self, type_i: &str, type_j: &str) -> Option<&HarmonicBondParameters> {
+        self.bonds.iter().find(|bond| {
+            (self.matches(&bond.types[0], type_i) && self.matches(&bond.types[1], type_j))
+                || (self.matches(&bond.types[0], type_j) && self.matches(&bond.types[1], type_i))
+        })
+    }
+
+    // The vertex must match the middle type while the ends may be in either order.
+    fn angle_parameters(
+        &self,
+        type_i: &str,
+        type_j: &str,
+        type_k: &str,
+    ) -> Option<&HarmonicAngleParameters> {
+        self.angles.iter().find(|angle| {
+            self.matches(&angle.types[1], type_j)
+                && ((self.matches(&angle.types[0], type_i)
+                    && self.matches(&angle.types[2], type_k))
+                    || (self.matches(&angle.types[0], type_k)
+                        && self.matches(&angle.types[2], type_i)))
+        })
+    }
+
+    fn atom_type(&self, type_name: &str) -> Option<&AtomType> {
+        self.atom_types.iter().find(|t| t.name == type_name)
+    }
+
+    // Returns true if `key` is either the name or the class of the atom type.
+    fn matches(&self, key: &str, type_name: &str) -> bool {
+        key == type_name
+            || self
+                .atom_type(type_name)
+                .map(|t| t.class == key)
+                .unwrap_or(false)
+    }
+}
+
+fn elements<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |n| n.is_element() && n.tag_name().name() == tag)
+}
+
+fn attribute<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> &'a str {
+    node.attribute(name).unwrap_or_else(|| {
+        panic!(
+            "missing attribute '{}' in <{}>",
+            name,
+            node.tag_name().name()
+        )
+    })
+}
+
+fn number(node: roxmltree::Node, name: &str) -> Float {
+    attribute(node, name).parse().unwrap()
+}
+
+// Bonded and nonbonded entries refer to atoms by either type or class.
+fn type_or_class(node: roxmltree::Node, suffix: &str) -> String {
+    node.attribute(format!("type{}", suffix).as_str())
+        .or_else(|| node.attribute(format!("class{}", suffix).as_str()))
+        .unwrap_or_else(|| panic!("missing type or class in <{}>", node.tag_name().name()))
+        .to_string()
+}
@@ -1,9 +1,21 @@
 //! Utilities to import and export external data formats.
 
+// Casts between `Float` and the fixed width types of external formats are required for both `f32` and `f64` builds.
+#![allow(clippy::unnecessary_cast)]
+
+pub mod forcefields;
 mod internal;
 pub mod structures;
+pub mod trajectories;
 
 pub mod prelude {
+    pub use super::forcefields::eam::*;
+    pub use super::forcefields::openmm::*;
+    pub use super::structures::cif::*;
+    pub use super::structures::lammps_data::*;
     pub use super::structures::poscar::*;
+    pub use super::structures::xyz::*;
     pub use super::structures::*;
+    pub use super::trajectories::amber_netcdf::*;
+    pub use super::trajectories::*;
 }
@@ -0,0 +1,180 @@
+use std::io::Read;
+use std::str::FromStr;
+
+use nalgebra::Vector3;
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+use crate::structures::StructureFormat;
+
+/// Crystallographic information file format with P1 symmetry.
+///
+/// Systems are written with the lattice parameters of their cell and the fractional coordinates of every atom
+/// so no symmetry operations are needed to recover the structure.
+/// Parsing reads the same subset of the format and ignores any symmetry operations in the file.
+///
+/// # Examples
+///
+/// Construct a [`System`](velvet_core::system::System) from CIF formatted data.
+/// ```
+/// use velvet_external_data::prelude::*;
+///
+/// let system = Cif.parse_system_from_reader("\
+/// data_NaCl
+/// _cell_length_a 5.64
+/// _cell_length_b 5.64
+/// _cell_length_c 5.64
+/// _cell_angle_alpha 90.0
+/// _cell_angle_beta 90.0
+/// _cell_angle_gamma 90.0
+/// loop_
+/// _atom_site_label
+/// _atom_site_type_symbol
+/// _atom_site_fract_x
+/// _atom_site_fract_y
+/// _atom_site_fract_z
+/// Na1 Na 0.0 0.0 0.0
+/// Cl1 Cl 0.5 0.5 0.5
+/// ".as_bytes());
+///
+/// assert_eq!(system.size, 2);
+/// assert_eq!(Cif.write_str_from_system(&system).matches("Cl").count(), 2);
+/// ```
+pub struct Cif;
+
+impl StructureFormat for Cif {
+    /// Returns the system as a CIF with P1 symmetry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a species was not constructed from an element.
+    fn write_str_from_system(&self, system: &System) -> String {
+        let (a, b, c, alpha, beta, gamma) = system.cell.lattice_parameters();
+        let mut text = String::from("data_velvet\n");
+        text.push_str("_symmetry_space_group_name_H-M 'P 1'\n");
+        text.push_str("_symmetry_Int_Tables_number 1\n");
+        for (name, value) in [
+            ("length_a", a),
+            ("length_b", b),
+            ("length_c", c),
+            ("angle_alpha", alpha),
+            ("angle_beta", beta),
+            ("angle_gamma", gamma),
+        ]
+        .iter()
+        {
+            text.push_str(&format!("_cell_{} {:.6}\n", name, value));
+        }
+        text.push_str("loop_\n_symmetry_equiv_pos_as_xyz\n'x, y, z'\n");
+        text.push_str("loop_\n_atom_site_label\n_atom_site_type_symbol\n");
+        text.push_str("_atom_site_fract_x\n_atom_site_fract_y\n_atom_site_fract_z\n");
+        for (i, (species, position)) in system.species.iter().zip(&system.positions).enumerate() {
+            let symbol = species
+                .element()
+                .expect("CIF output requires species constructed from elements");
+            let fractional = system.cell.fractional(position);
+            text.push_str(&format!(
+                "{}{} {} {:.6} {:.6} {:.6}\n",
+                symbol,
+                i + 1,
+                symbol,
+                fractional[0],
+                fractional[1],
+                fractional[2]
+            ));
+        }
+        text
+    }
+
+    fn parse_system_from_reader<T: Read>(&self, mut reader: T) -> System {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+
+        let mut parameters = [None; 6];
+        let names = [
+            "_cell_length_a",
+            "_cell_length_b",
+            "_cell_length_c",
+            "_cell_angle_alpha",
+            "_cell_angle_beta",
+            "_cell_angle_gamma",
+        ];
+        let mut species = Vec::new();
+        let mut positions = Vec::new();
+        while let Some(line) = lines.next() {
+            if line == "loop_" {
+                let mut columns = Vec::new();
+                while let Some(column) = lines.peek().filter(|line| line.starts_with('_')) {
+                    columns.push(*column);
+                    lines.next();
+                }
+                let index = |name: &str| columns.iter().position(|&column| column == name);
+                let fractional = ["x", "y", "z"]
+                    .iter()
+                    .map(|axis| index(&format!("_atom_site_fract_{}", axis)))
+                    .collect::<Option<Vec<usize>>>();
+                let symbol = index("_atom_site_type_symbol").or_else(|| index("_atom_site_label"));
+                while let Some(row) = lines
+                    .peek()
+                    .filter(|line| !line.starts_with('_') && !line.starts_with("loop_"))
+                {
+                    let values: Vec<&str> = row.split_whitespace().collect();
+                    lines.next();
+                    if let (Some(fractional), Some(symbol)) = (&fractional, symbol) {
+                        // labels such as `O12` name the element followed by a site number
+                        let symbol = values[symbol].trim_end_matches(|c: char| !c.is_alphabetic());
+                        species.push(Species::from_element(Element::from_str(symbol).unwrap()));
+                        positions.push(Vector3::new(
+                            number(values[fractional[0]]),
+                            number(values[fractional[1]]),
+                            number(values[fractional[2]]),
+                        ));
+                    }
+                }
+            } else if let Some(i) = names.iter().position(|name| line.starts_with(name)) {
+                let value = line
+                    .split_whitespace()
+                    .nth(1)
+                    .expect("CIF cell parameter is missing its value");
+                parameters[i] = Some(number(value));
+            }
+        }
+
+        let parameters: Vec<Float> = parameters
+            .iter()
+            .zip(names.iter())
+            .map(|(value, name)| value.unwrap_or_else(|| panic!("CIF file is missing {}", name)))
+            .collect();
+        let cell = Cell::triclinic(
+            parameters[0],
+            parameters[1],
+            parameters[2],
+            parameters[3],
+            parameters[4],
+            parameters[5],
+        );
+        let positions: Vec<Vector3<Float>> = positions.iter().map(|x| cell.cartesian(x)).collect();
+        let size = positions.len();
+
+        System {
+            size,
+            cell,
+            species,
+            positions,
+            velocities: vec![Vector3::zeros(); size],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+}
+
+// Parses a CIF number which may be followed by its uncertainty in parentheses.
+fn number(value: &str) -> Float {
+    value.split('(').next().unwrap().parse().unwrap()
+}
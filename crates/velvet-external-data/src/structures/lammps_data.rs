@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use nalgebra::{Matrix3, Vector3};
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+use crate::structures::StructureFormat;
+
+/// LAMMPS' data file format.
+///
+/// Supports the `atomic`, `charge`, `bond`, `angle`, `molecular` and `full` atom styles.
+/// The style is read from the comment of the `Atoms` heading and otherwise inferred from the number of columns.
+/// Each atom type maps to the element named in the comment of its `Masses` line, if any.
+///
+/// # Examples
+///
+/// Construct a [`System`](velvet_core::system::System) with bonds from LAMMPS formatted data.
+/// ```
+/// use velvet_external_data::prelude::*;
+///
+/// let system = LammpsData.parse_system_from_reader("\
+/// Oxygen molecule
+///
+/// 2 atoms
+/// 1 bonds
+/// 1 atom types
+/// 1 bond types
+///
+/// 0.0 10.0 xlo xhi
+/// 0.0 10.0 ylo yhi
+/// 0.0 10.0 zlo zhi
+///
+/// Masses
+///
+/// 1 15.999 # O
+///
+/// Atoms # bond
+///
+/// 1 1 1 5.0 5.0 5.0
+/// 2 1 1 6.2 5.0 5.0
+///
+/// Bonds
+///
+/// 1 1 1 2
+/// ".as_bytes());
+///
+/// assert_eq!(system.size, 2);
+/// assert_eq!(system.bonds, vec![[0, 1]]);
+/// ```
+pub struct LammpsData;
+
+impl StructureFormat for LammpsData {
+    /// Returns the system as LAMMPS data in the `full` atom style.
+    ///
+    /// Each distinct species becomes an atom type named after its element, if any,
+    /// and every bond, angle and dihedral has the same type since the system does not distinguish them.
+    /// Species without an element load back with a new ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the 'a' vector of the cell is not along x or the 'b' vector is not in the xy-plane.
+    fn write_str_from_system(&self, system: &System) -> String {
+        let (a, b, c) = (
+            system.cell.a_vector(),
+            system.cell.b_vector(),
+            system.cell.c_vector(),
+        );
+        assert!(
+            a[1] == 0.0 && a[2] == 0.0 && b[2] == 0.0,
+            "LAMMPS data output requires the 'a' vector along x and the 'b' vector in the xy-plane"
+        );
+        // atom types are numbered in order of first appearance
+        let mut types: Vec<Species> = Vec::new();
+        for species in &system.species {
+            if !types.contains(species) {
+                types.push(*species);
+            }
+        }
+        // topology sections which have rows
+        let topology: Vec<(&str, &str, Vec<Vec<usize>>)> = vec![
+            (
+                "bond",
+                "Bonds",
+                system
+                    .bonds
+                    .iter()
+                    .map(|bond| bond.to_vec())
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "angle",
+                "Angles",
+                system
+                    .angles
+                    .iter()
+                    .map(|angle| angle.to_vec())
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "dihedral",
+                "Dihedrals",
+                system
+                    .dihedrals
+                    .iter()
+                    .map(|dihedral| dihedral.to_vec())
+                    .collect::<Vec<_>>(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, _, rows)| !rows.is_empty())
+        .collect();
+
+        let mut text = String::from("Written by Velvet\n\n");
+        text.push_str(&format!("{} atoms\n", system.size));
+        for (name, _, rows) in &topology {
+            text.push_str(&format!("{} {}s\n", rows.len(), name));
+        }
+        text.push_str(&format!("{} atom types\n", types.len()));
+        for (name, _, _) in &topology {
+            text.push_str(&format!("1 {} types\n", name));
+        }
+
+        let origin = system.cell.origin();
+        text.push('\n');
+        for (k, axis) in ["x", "y", "z"].iter().enumerate() {
+            let length = [a[0], b[1], c[2]][k];
+            text.push_str(&format!(
+                "{} {} {}lo {}hi\n",
+                origin[k],
+                origin[k] + length,
+                axis,
+                axis
+            ));
+        }
+        if !system.cell.is_orthogonal() {
+            text.push_str(&format!("{} {} {} xy xz yz\n", b[0], c[0], c[1]));
+        }
+
+        text.push_str("\nMasses\n\n");
+        for (index, species) in types.iter().enumerate() {
+            match species.element() {
+                Some(element) => {
+                    text.push_str(&format!("{} {} # {}\n", index + 1, species.mass(), element))
+                }
+                None => text.push_str(&format!("{} {}\n", index + 1, species.mass())),
+            }
+        }
+
+        // every atom belongs to the same molecule
+        text.push_str("\nAtoms # full\n\n");
+        for (index, (species, position)) in system.species.iter().zip(&system.positions).enumerate()
+        {
+            let kind = types.iter().position(|t| t == species).unwrap() + 1;
+            let position = position + origin;
+            text.push_str(&format!(
+                "{} 1 {} {} {} {} {}\n",
+                index + 1,
+                kind,
+                species.charge(),
+                position[0],
+                position[1],
+                position[2]
+            ));
+        }
+
+        text.push_str("\nVelocities\n\n");
+        for (index, velocity) in system.velocities.iter().enumerate() {
+            text.push_str(&format!(
+                "{} {} {} {}\n",
+                index + 1,
+                velocity[0],
+                velocity[1],
+                velocity[2]
+            ));
+        }
+
+        for (_, heading, rows) in &topology {
+            text.push_str(&format!("\n{}\n\n", heading));
+            for (index, row) in rows.iter().enumerate() {
+                let ids: Vec<String> = row.iter().map(|i| (i + 1).to_string()).collect();
+                text.push_str(&format!("{} 1 {}\n", index + 1, ids.join(" ")));
+            }
+        }
+        text
+    }
+
+    fn parse_system_from_reader<T: Read>(&self, reader: T) -> System {
+        self.parse_system_with_atom_types_from_reader(reader, &HashMap::new())
+    }
+}
+
+impl LammpsData {
+    /// Returns the system described by a LAMMPS data file with the mass and charge of some atom types overridden.
+    pub fn parse_system_with_atom_types_from_file<T: AsRef<str>>(
+        &self,
+        filename: T,
+        atom_types: &HashMap<usize, (Float, Float)>,
+    ) -> System {
+        let file = File::open(filename.as_ref()).unwrap();
+        self.parse_system_with_atom_types_from_reader(file, atom_types)
+    }
+
+    /// Returns the system described by LAMMPS formatted data with the mass and charge of some atom types overridden.
+    ///
+    /// `atom_types` maps a numeric atom type to its `(mass, charge)` which take precedence over
+    /// the `Masses` section and the charge column of the `Atoms` section. Styles without a charge
+    /// column otherwise load every atom as neutral. An atom type missing from the `Masses` section
+    /// is loaded entirely from its override.
+    pub fn parse_system_with_atom_types_from_reader<T: Read>(
+        &self,
+        mut reader: T,
+        atom_types: &HashMap<usize, (Float, Float)>,
+    ) -> System {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        // the first line is a title
+        let mut lines = text.lines().skip(1).peekable();
+
+        let mut bounds = [[0 as Float; 2]; 3];
+        let mut tilt = [0 as Float; 3];
+        let mut sections: HashMap<&str, (&str, Vec<Row>)> = HashMap::new();
+        while let Some(line) = lines.next() {
+            let row = Row::from(line);
+            if row.tokens.is_empty() {
+                continue;
+            }
+            if row.tokens[0].parse::<Float>().is_ok() {
+                // header lines start with values and end with a keyword
+                let values: Vec<Float> = row.tokens.iter().filter_map(|t| t.parse().ok()).collect();
+                match &row.tokens[values.len()..] {
+                    ["xlo", "xhi"] => bounds[0] = [values[0], values[1]],
+                    ["ylo", "yhi"] => bounds[1] = [values[0], values[1]],
+                    ["zlo", "zhi"] => bounds[2] = [values[0], values[1]],
+                    ["xy", "xz", "yz"] => tilt = [values[0], values[1], values[2]],
+                    // counts are implied by the length of each section
+                    _ => {}
+                }
+                continue;
+            }
+            // a section runs until the next line which does not start with a value
+            let heading = line.split('#').next().unwrap().trim();
+            let mut rows = Vec::new();
+            while let Some(next) = lines.peek().map(|&next| Row::from(next)) {
+                if let Some(first) = next.tokens.first() {
+                    if first.parse::<Float>().is_err() {
+                        break;
+                    }
+                    rows.push(next);
+                }
+                lines.next();
+            }
+            sections.insert(heading, (row.comment, rows));
+        }
+
+        let origin = Vector3::new(bounds[0][0], bounds[1][0], bounds[2][0]);
+        let cell = if tilt.iter().all(|&t| t == 0.0) {
+            Cell::from_bounds(
+                origin,
+                Vector3::new(bounds[0][1], bounds[1][1], bounds[2][1]),
+            )
+        } else {
+            // the box is spanned by a = (lx, 0, 0), b = (xy, ly, 0) and c = (xz, yz, lz)
+            let lengths: Vec<Float> = bounds.iter().map(|[lo, hi]| hi - lo).collect();
+            Cell::from_matrix(Matrix3::new(
+                lengths[0], tilt[0], tilt[1], 0.0, lengths[1], tilt[2], 0.0, 0.0, lengths[2],
+            ))
+        };
+
+        // each type maps to the element named in the comment of its mass
+        let mut types = HashMap::new();
+        if let Some((_, rows)) = sections.get("Masses") {
+            for row in rows {
+                let mass: Float = row.tokens[1].parse().unwrap();
+                let species = match Element::from_str(row.comment) {
+                    Ok(element) => Species::from_element(element).with_mass(mass),
+                    Err(_) => Species::new(mass, 0.0),
+                };
+                types.insert(row.tokens[0].parse::<usize>().unwrap(), species);
+            }
+        }
+        // overridden types keep the ID of their element so they still compare equal to it
+        for (&kind, &(mass, charge)) in atom_types {
+            let species = match types.get(&kind) {
+                Some(species) => species.with_mass(mass).with_charge(charge),
+                None => Species::new(mass, charge),
+            };
+            types.insert(kind, species);
+        }
+
+        let (style, rows) = sections
+            .get("Atoms")
+            .expect("LAMMPS data file is missing its Atoms section");
+        let style = match (*style, rows.first().map_or(5, |row| row.tokens.len())) {
+            ("", 5) | ("", 8) => "atomic",
+            ("", 7) | ("", 10) => "full",
+            ("", _) => panic!("LAMMPS data file must name the atom style of its Atoms section"),
+            (style, _) => style,
+        };
+        // columns of the type, charge and first coordinate
+        let (kind, charge, x) = match style {
+            "atomic" => (1, None, 2),
+            "charge" => (1, Some(2), 3),
+            "bond" | "angle" | "molecular" => (2, None, 3),
+            "full" => (2, Some(3), 4),
+            _ => panic!("unsupported LAMMPS atom style `{}`", style),
+        };
+        let mut atoms: Vec<&[&str]> = rows.iter().map(|row| &row.tokens[..]).collect();
+        atoms.sort_by_key(|row| row[0].parse::<usize>().unwrap());
+        let indices: HashMap<&str, usize> = atoms
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (row[0], index))
+            .collect();
+
+        let size = atoms.len();
+        let mut species = Vec::with_capacity(size);
+        let mut positions = Vec::with_capacity(size);
+        for row in &atoms {
+            let atom_type: usize = row[kind].parse().unwrap();
+            let base = *types
+                .get(&atom_type)
+                .unwrap_or_else(|| panic!("LAMMPS atom type {} has no mass", atom_type));
+            species.push(match charge {
+                Some(column) if !atom_types.contains_key(&atom_type) => {
+                    base.with_charge(row[column].parse().unwrap())
+                }
+                _ => base,
+            });
+            let position: Vec<Float> = row[x..x + 3].iter().map(|v| v.parse().unwrap()).collect();
+            positions.push(Vector3::new(position[0], position[1], position[2]) - origin);
+        }
+
+        let mut velocities = vec![Vector3::zeros(); size];
+        if let Some((_, rows)) = sections.get("Velocities") {
+            for row in rows.iter().map(|row| &row.tokens) {
+                let values: Vec<Float> = row[1..4].iter().map(|v| v.parse().unwrap()).collect();
+                velocities[indices[row[0]]] = Vector3::new(values[0], values[1], values[2]);
+            }
+        }
+
+        // topology rows list an ID and a type followed by the IDs of the atoms
+        let topology = |name: &str| -> Vec<Vec<usize>> {
+            sections.get(name).map_or(Vec::new(), |(_, rows)| {
+                rows.iter()
+                    .map(|row| {
+                        row.tokens[2..]
+                            .iter()
+                            .map(|id| {
+                                *indices.get(id).unwrap_or_else(|| {
+                                    panic!("LAMMPS {} refer to missing atom {}", name, id)
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+        };
+        let bonds = topology("Bonds").iter().map(|b| [b[0], b[1]]).collect();
+        let angles = topology("Angles")
+            .iter()
+            .map(|a| [a[0], a[1], a[2]])
+            .collect();
+        let dihedrals = topology("Dihedrals")
+            .iter()
+            .map(|d| [d[0], d[1], d[2], d[3]])
+            .collect();
+
+        System {
+            size,
+            cell,
+            species,
+            positions,
+            velocities,
+            bonds,
+            angles,
+            dihedrals,
+        }
+    }
+}
+
+// Whitespace separated values of a line and its trailing comment.
+struct Row<'a> {
+    tokens: Vec<&'a str>,
+    comment: &'a str,
+}
+
+impl<'a> From<&'a str> for Row<'a> {
+    fn from(line: &'a str) -> Row<'a> {
+        let (content, comment) = line.split_once('#').unwrap_or((line, ""));
+        Row {
+            tokens: content.split_whitespace().collect(),
+            comment: comment.trim(),
+        }
+    }
+}
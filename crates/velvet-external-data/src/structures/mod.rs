@@ -1,4 +1,7 @@
+pub mod cif;
+pub mod lammps_data;
 pub mod poscar;
+pub mod xyz;
 
 use std::fs::File;
 use std::io::Write;
@@ -19,5 +22,77 @@ pub trait StructureFormat {
         file.write_all(s.as_bytes()).unwrap()
     }
 
-    fn write_str_from_system(&self, system: &System) -> &str;
+    fn write_str_from_system(&self, system: &System) -> String;
+}
+
+/// Extends a [`System`] with velocities read from a separate structure file.
+pub trait LoadVelocities {
+    /// Overwrites the velocities with those of a structure file listing the same atoms in the same order.
+    ///
+    /// Returns an error without modifying the velocities if the number of atoms does not match.
+    fn load_velocities_from_file<F: StructureFormat, T: AsRef<str>>(
+        &mut self,
+        filename: T,
+        format: F,
+    ) -> Result<(), String>;
+}
+
+impl LoadVelocities for System {
+    fn load_velocities_from_file<F: StructureFormat, T: AsRef<str>>(
+        &mut self,
+        filename: T,
+        format: F,
+    ) -> Result<(), String> {
+        let source = format.parse_system_from_file(filename.as_ref());
+        if source.size != self.size {
+            return Err(format!(
+                "{} has {} atoms but the system has {}",
+                filename.as_ref(),
+                source.size,
+                self.size
+            ));
+        }
+        self.velocities = source.velocities;
+        Ok(())
+    }
+}
+
+/// Extends a [`System`] with connectivity read from a topology-bearing structure file.
+pub trait LoadTopology {
+    /// Overwrites the bonds, angles and dihedrals with those of a structure file listing the same atoms in the same order.
+    ///
+    /// Angles and dihedrals missing from the file are perceived from its bonds.
+    /// Returns an error without modifying the topology if the number of atoms does not match.
+    fn with_topology_from<F: StructureFormat, T: AsRef<str>>(
+        &mut self,
+        filename: T,
+        format: F,
+    ) -> Result<(), String>;
+}
+
+impl LoadTopology for System {
+    fn with_topology_from<F: StructureFormat, T: AsRef<str>>(
+        &mut self,
+        filename: T,
+        format: F,
+    ) -> Result<(), String> {
+        let source = format.parse_system_from_file(filename.as_ref());
+        if source.size != self.size {
+            return Err(format!(
+                "{} has {} atoms but the system has {}",
+                filename.as_ref(),
+                source.size,
+                self.size
+            ));
+        }
+        self.bonds = source.bonds;
+        self.perceive_angles_and_dihedrals();
+        if !source.angles.is_empty() {
+            self.angles = source.angles;
+        }
+        if !source.dihedrals.is_empty() {
+            self.dihedrals = source.dihedrals;
+        }
+        Ok(())
+    }
 }
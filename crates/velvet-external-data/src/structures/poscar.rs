@@ -32,7 +32,7 @@ use crate::structures::StructureFormat;
 pub struct Poscar;
 
 impl StructureFormat for Poscar {
-    fn write_str_from_system(&self, system: &System) -> &str {
+    fn write_str_from_system(&self, _: &System) -> String {
         unimplemented!()
     }
 
@@ -89,6 +89,9 @@ impl StructureFormat for Poscar {
             species,
             positions,
             velocities,
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
         }
     }
 }
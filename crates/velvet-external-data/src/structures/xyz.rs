@@ -0,0 +1,169 @@
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use nalgebra::{Matrix3, Vector3};
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+use crate::structures::StructureFormat;
+
+/// Extended XYZ structure format.
+///
+/// The comment line must define the cell as `Lattice="ax ay az bx by bz cx cy cz"`.
+/// Each atom line holds a chemical symbol and a position optionally followed by a velocity.
+///
+/// # Examples
+///
+/// Construct a [`System`](velvet_core::system::System) from XYZ formatted data.
+/// ```
+/// use velvet_external_data::prelude::*;
+///
+/// let system = Xyz.parse_system_from_reader("\
+/// 2
+/// Lattice=\"10.0 0.0 0.0 0.0 10.0 0.0 0.0 0.0 10.0\"
+/// Ar 0.0 0.0 0.0
+/// Ar 3.4 0.0 0.0 0.001 0.0 0.0
+/// ".as_bytes());
+///
+/// assert_eq!(system.size, 2);
+/// assert_eq!(system.velocities[1][0], 0.001);
+/// ```
+pub struct Xyz;
+
+impl StructureFormat for Xyz {
+    /// Returns the system as a single extended XYZ frame with velocities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a species was not constructed from an element.
+    fn write_str_from_system(&self, system: &System) -> String {
+        let lattice: Vec<String> = [
+            system.cell.a_vector(),
+            system.cell.b_vector(),
+            system.cell.c_vector(),
+        ]
+        .iter()
+        .flat_map(|vector| vector.iter().map(|value| value.to_string()))
+        .collect();
+        let mut text = format!(
+            "{}\nLattice=\"{}\" Properties=species:S:1:pos:R:3:velo:R:3\n",
+            system.size,
+            lattice.join(" ")
+        );
+        for ((species, position), velocity) in system
+            .species
+            .iter()
+            .zip(&system.positions)
+            .zip(&system.velocities)
+        {
+            let symbol = species
+                .element()
+                .expect("XYZ output requires species constructed from elements");
+            text.push_str(&format!(
+                "{} {} {} {} {} {} {}\n",
+                symbol,
+                position[0],
+                position[1],
+                position[2],
+                velocity[0],
+                velocity[1],
+                velocity[2]
+            ));
+        }
+        text
+    }
+
+    fn parse_system_from_reader<T: Read>(&self, reader: T) -> System {
+        self.parse_system_at_step_from_reader(reader, 0)
+    }
+}
+
+impl Xyz {
+    /// Returns the system described by frame `step` of a multi-frame XYZ file.
+    pub fn parse_system_at_step_from_file<T: AsRef<str>>(
+        &self,
+        filename: T,
+        step: usize,
+    ) -> System {
+        let file = File::open(filename.as_ref()).unwrap();
+        self.parse_system_at_step_from_reader(file, step)
+    }
+
+    /// Returns the system described by frame `step` of multi-frame XYZ data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data has fewer than `step + 1` frames.
+    pub fn parse_system_at_step_from_reader<T: Read>(&self, mut reader: T, step: usize) -> System {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        let mut lines = text.lines();
+
+        // each frame is a count line and a comment line followed by one line per atom
+        let mut frame = 0;
+        let size = loop {
+            let size: usize = lines
+                .next()
+                .unwrap_or_else(|| {
+                    panic!(
+                        "XYZ file has {} frames but step {} was requested",
+                        frame, step
+                    )
+                })
+                .trim()
+                .parse()
+                .unwrap();
+            if frame == step {
+                break size;
+            }
+            for _ in 0..size + 1 {
+                lines.next().expect("XYZ file ended unexpectedly");
+            }
+            frame += 1;
+        };
+
+        let comment = lines.next().expect("XYZ file is missing its comment line");
+        let (_, lattice) = comment
+            .split_once("Lattice=\"")
+            .expect("XYZ comment line is missing the lattice");
+        let (lattice, _) = lattice.split_once('"').expect("XYZ lattice is not quoted");
+        let lattice: Vec<Float> = lattice
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        assert_eq!(lattice.len(), 9, "XYZ lattice must have 9 values");
+        // the lattice lists the vectors in order while the cell stores them as columns
+        let cell = Cell::from_matrix(Matrix3::from_column_slice(&lattice));
+
+        let mut species = Vec::with_capacity(size);
+        let mut positions = Vec::with_capacity(size);
+        let mut velocities = Vec::with_capacity(size);
+        for _ in 0..size {
+            let mut tokens = lines
+                .next()
+                .expect("XYZ file ended unexpectedly")
+                .split_whitespace();
+            let symbol = tokens.next().expect("XYZ atom is missing its symbol");
+            species.push(Species::from_element(Element::from_str(symbol).unwrap()));
+            let values: Vec<Float> = tokens.map(|value| value.parse().unwrap()).collect();
+            match values.len() {
+                3 => velocities.push(Vector3::zeros()),
+                6 => velocities.push(Vector3::new(values[3], values[4], values[5])),
+                _ => panic!("XYZ atom must have a position and an optional velocity"),
+            }
+            positions.push(Vector3::new(values[0], values[1], values[2]));
+        }
+
+        System {
+            size,
+            cell,
+            species,
+            positions,
+            velocities,
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+}
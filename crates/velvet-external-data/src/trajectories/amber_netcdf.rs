@@ -0,0 +1,191 @@
+use nalgebra::Vector3;
+use netcdf3::{DataSet, FileReader, FileWriter, Version};
+use velvet_core::prelude::*;
+
+use crate::internal::Float;
+use crate::trajectories::{Frame, TrajectoryFormat};
+
+// Velvet's internal time unit is the femtosecond while AMBER uses picoseconds.
+const FEMTOSECONDS_PER_PICOSECOND: Float = 1000.0;
+
+/// AMBER's [NetCDF](https://ambermd.org/netcdf/nctraj.xhtml) trajectory format.
+///
+/// Times are stored in picoseconds and velocities in angstrom/picosecond.
+/// Both are converted to and from Velvet's internal femtosecond time unit.
+pub struct AmberNetcdf;
+
+impl TrajectoryFormat for AmberNetcdf {
+    fn read_trajectory_from_file<T: AsRef<str>>(
+        &self,
+        filename: T,
+        topology: &System,
+    ) -> Vec<Frame> {
+        let mut reader = FileReader::open(filename.as_ref()).unwrap();
+        let data_set = reader.data_set();
+        let n_frames = data_set.num_records().unwrap_or(0);
+        let n_atoms = data_set.dim_size("atom").unwrap();
+        assert_eq!(
+            n_atoms, topology.size,
+            "Number of atoms does not match the topology."
+        );
+
+        let has_velocities = data_set.has_var("velocities");
+        let has_cell = data_set.has_var("cell_lengths") && data_set.has_var("cell_angles");
+        // AMBER writes velocities in internal units with a scale factor to convert them to angstrom/picosecond
+        let velocity_scale = data_set
+            .get_var_attr_f32("velocities", "scale_factor")
+            .map(|x| x[0] as Float)
+            .or_else(|| {
+                data_set
+                    .get_var_attr_f64("velocities", "scale_factor")
+                    .map(|x| x[0] as Float)
+            })
+            .unwrap_or(1.0);
+
+        (0..n_frames)
+            .map(|index| {
+                let mut system = topology.clone();
+                let time = reader.read_record_f32("time", index).unwrap()[0] as Float;
+                system.positions =
+                    to_vectors(&reader.read_record_f32("coordinates", index).unwrap());
+                if has_velocities {
+                    let scale = velocity_scale / FEMTOSECONDS_PER_PICOSECOND;
+                    system.velocities =
+                        to_vectors(&reader.read_record_f32("velocities", index).unwrap())
+                            .iter()
+                            .map(|v| v * scale)
+                            .collect();
+                }
+                if has_cell {
+                    let lengths = reader.read_record_f64("cell_lengths", index).unwrap();
+                    let angles = reader.read_record_f64("cell_angles", index).unwrap();
+                    system.cell = Cell::triclinic(
+                        lengths[0] as Float,
+                        lengths[1] as Float,
+                        lengths[2] as Float,
+                        angles[0] as Float,
+                        angles[1] as Float,
+                        angles[2] as Float,
+                    );
+                }
+                Frame {
+                    time: time * FEMTOSECONDS_PER_PICOSECOND,
+                    system,
+                }
+            })
+            .collect()
+    }
+
+    fn write_file_from_trajectory<T: AsRef<str>>(&self, frames: &[Frame], filename: T) {
+        let n_atoms = frames.first().map(|frame| frame.system.size).unwrap_or(0);
+
+        let mut data_set = DataSet::new();
+        data_set.set_unlimited_dim("frame", frames.len()).unwrap();
+        data_set.add_fixed_dim("spatial", 3).unwrap();
+        data_set.add_fixed_dim("atom", n_atoms).unwrap();
+        data_set.add_fixed_dim("cell_spatial", 3).unwrap();
+        data_set.add_fixed_dim("label", 5).unwrap();
+        data_set.add_fixed_dim("cell_angular", 3).unwrap();
+
+        data_set
+            .add_global_attr_string("Conventions", "AMBER")
+            .unwrap();
+        data_set
+            .add_global_attr_string("ConventionVersion", "1.0")
+            .unwrap();
+        data_set
+            .add_global_attr_string("program", "velvet")
+            .unwrap();
+        data_set
+            .add_global_attr_string("programVersion", env!("CARGO_PKG_VERSION"))
+            .unwrap();
+
+        data_set.add_var_u8("spatial", &["spatial"]).unwrap();
+        data_set
+            .add_var_u8("cell_spatial", &["cell_spatial"])
+            .unwrap();
+        data_set
+            .add_var_u8("cell_angular", &["cell_angular", "label"])
+            .unwrap();
+        data_set.add_var_f32("time", &["frame"]).unwrap();
+        data_set
+            .add_var_attr_string("time", "units", "picosecond")
+            .unwrap();
+        data_set
+            .add_var_f32("coordinates", &["frame", "atom", "spatial"])
+            .unwrap();
+        data_set
+            .add_var_attr_string("coordinates", "units", "angstrom")
+            .unwrap();
+        data_set
+            .add_var_f64("cell_lengths", &["frame", "cell_spatial"])
+            .unwrap();
+        data_set
+            .add_var_attr_string("cell_lengths", "units", "angstrom")
+            .unwrap();
+        data_set
+            .add_var_f64("cell_angles", &["frame", "cell_angular"])
+            .unwrap();
+        data_set
+            .add_var_attr_string("cell_angles", "units", "degree")
+            .unwrap();
+        data_set
+            .add_var_f32("velocities", &["frame", "atom", "spatial"])
+            .unwrap();
+        data_set
+            .add_var_attr_string("velocities", "units", "angstrom/picosecond")
+            .unwrap();
+
+        let mut writer = FileWriter::create_new(filename.as_ref()).unwrap();
+        writer.set_def(&data_set, Version::Offset64Bit, 0).unwrap();
+        writer.write_var_u8("spatial", b"xyz").unwrap();
+        writer.write_var_u8("cell_spatial", b"abc").unwrap();
+        writer
+            .write_var_u8("cell_angular", b"alphabeta gamma")
+            .unwrap();
+
+        for (index, frame) in frames.iter().enumerate() {
+            let system = &frame.system;
+            assert_eq!(
+                system.size, n_atoms,
+                "Every frame must contain the same number of atoms."
+            );
+            let time = (frame.time / FEMTOSECONDS_PER_PICOSECOND) as f32;
+            writer.write_record_f32("time", index, &[time]).unwrap();
+            writer
+                .write_record_f32("coordinates", index, &from_vectors(&system.positions, 1.0))
+                .unwrap();
+            writer
+                .write_record_f32(
+                    "velocities",
+                    index,
+                    &from_vectors(&system.velocities, FEMTOSECONDS_PER_PICOSECOND),
+                )
+                .unwrap();
+            let cell = &system.cell;
+            let lengths = [cell.a() as f64, cell.b() as f64, cell.c() as f64];
+            let angles = [cell.alpha() as f64, cell.beta() as f64, cell.gamma() as f64];
+            writer
+                .write_record_f64("cell_lengths", index, &lengths)
+                .unwrap();
+            writer
+                .write_record_f64("cell_angles", index, &angles)
+                .unwrap();
+        }
+        writer.close().unwrap();
+    }
+}
+
+fn to_vectors(values: &[f32]) -> Vec<Vector3<Float>> {
+    values
+        .chunks(3)
+        .map(|x| Vector3::new(x[0] as Float, x[1] as Float, x[2] as Float))
+        .collect()
+}
+
+fn from_vectors(vectors: &[Vector3<Float>], scale: Float) -> Vec<f32> {
+    vectors
+        .iter()
+        .flat_map(|v| (v * scale).iter().map(|&x| x as f32).collect::<Vec<f32>>())
+        .collect()
+}
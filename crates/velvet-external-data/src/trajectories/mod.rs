@@ -0,0 +1,68 @@
+//! Trajectory file formats.
+
+pub mod amber_netcdf;
+
+use velvet_core::potentials::Potentials;
+use velvet_core::properties::Property;
+use velvet_core::system::System;
+
+use crate::internal::Float;
+
+/// A single snapshot of a trajectory.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// Simulation time of the snapshot in femtoseconds.
+    pub time: Float,
+    /// State of the system at the time of the snapshot.
+    pub system: System,
+}
+
+/// Shared behavior for formats which store a sequence of frames.
+pub trait TrajectoryFormat {
+    /// Reads every frame of a trajectory file.
+    ///
+    /// Trajectory formats generally do not store chemical species so they are taken from `topology`.
+    fn read_trajectory_from_file<T: AsRef<str>>(
+        &self,
+        filename: T,
+        topology: &System,
+    ) -> Vec<Frame>;
+
+    /// Writes every frame to a trajectory file.
+    fn write_file_from_trajectory<T: AsRef<str>>(&self, frames: &[Frame], filename: T);
+}
+
+/// Evaluates `property` on every frame of a trajectory file without rerunning the dynamics.
+///
+/// Species are taken from `topology` as in [`read_trajectory_from_file`](TrajectoryFormat::read_trajectory_from_file)
+/// and the potentials are set up and updated on each frame before the property is calculated.
+/// Returns one result per frame in the order they were stored.
+///
+/// # Panics
+///
+/// Panics if the number of atoms in the trajectory does not match the topology
+/// or if the potentials can not be set up on a frame.
+pub fn analyze_trajectory<F, P, T>(
+    filename: T,
+    format: F,
+    topology: &System,
+    potentials: &mut Potentials,
+    property: &P,
+) -> Vec<P::Res>
+where
+    F: TrajectoryFormat,
+    P: Property,
+    T: AsRef<str>,
+{
+    format
+        .read_trajectory_from_file(filename, topology)
+        .iter()
+        .map(|frame| {
+            potentials
+                .setup(&frame.system)
+                .unwrap_or_else(|message| panic!("{}", message));
+            potentials.update(&frame.system, 0);
+            property.calculate(&frame.system, potentials)
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+use nalgebra::Vector3;
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+fn frame(index: usize) -> Frame {
+    let offset = if index == 0 { 0.0 } else { 0.5 };
+    let argon = Species::from_element(Element::Ar);
+    let system = System {
+        size: 2,
+        cell: Cell::triclinic(10.0, 11.0, 12.0, 90.0, 95.0, 100.0),
+        species: vec![argon; 2],
+        positions: vec![
+            Vector3::new(1.0 + offset, 2.0, 3.0),
+            Vector3::new(4.0, 5.0 + offset, 6.0),
+        ],
+        velocities: vec![
+            Vector3::new(0.01, -0.02, 0.03),
+            Vector3::new(-0.01, 0.0, 0.005),
+        ],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    Frame {
+        time: if index == 0 { 0.0 } else { 2000.0 },
+        system,
+    }
+}
+
+#[test]
+fn round_trip() {
+    let frames = vec![frame(0), frame(1)];
+    let path = std::env::temp_dir().join("velvet-round-trip.nc");
+    let filename = path.to_str().unwrap();
+    AmberNetcdf.write_file_from_trajectory(&frames, filename);
+
+    let topology = frames[0].system.clone();
+    let read = AmberNetcdf.read_trajectory_from_file(filename, &topology);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(read.len(), frames.len());
+    for (expected, actual) in frames.iter().zip(read.iter()) {
+        assert!((expected.time - actual.time).abs() < 1e-3);
+        assert!((expected.system.cell.a() - actual.system.cell.a()).abs() < 1e-4);
+        assert!((expected.system.cell.beta() - actual.system.cell.beta()).abs() < 1e-3);
+        for (p0, p1) in expected
+            .system
+            .positions
+            .iter()
+            .zip(actual.system.positions.iter())
+        {
+            assert!((p0 - p1).norm() < 1e-5);
+        }
+        for (v0, v1) in expected
+            .system
+            .velocities
+            .iter()
+            .zip(actual.system.velocities.iter())
+        {
+            assert!((v0 - v1).norm() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn analyze_temperature() {
+    let mut frames = vec![frame(0), frame(1)];
+    // the second frame is four times as hot
+    for velocity in frames[1].system.velocities.iter_mut() {
+        *velocity *= 2.0;
+    }
+    let path = std::env::temp_dir().join("velvet-analyze.nc");
+    let filename = path.to_str().unwrap();
+    AmberNetcdf.write_file_from_trajectory(&frames, filename);
+
+    let mut potentials = PotentialsBuilder::new().build();
+    let temperatures = analyze_trajectory(
+        filename,
+        AmberNetcdf,
+        &frames[0].system,
+        &mut potentials,
+        &Temperature,
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(temperatures.len(), 2);
+    for (frame, temperature) in frames.iter().zip(temperatures.iter()) {
+        let expected = Temperature.calculate_intrinsic(&frame.system);
+        assert!((temperature - expected).abs() < 1e-4 * expected);
+    }
+    assert!((temperatures[1] / temperatures[0] - 4.0).abs() < 1e-4);
+}
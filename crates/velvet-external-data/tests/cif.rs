@@ -0,0 +1,94 @@
+use nalgebra::Vector3;
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+// monoclinic zirconia (baddeleyite) expanded from its asymmetric unit into P1
+fn zirconia_system() -> System {
+    let zirconium = Species::from_element(Element::Zr);
+    let oxygen = Species::from_element(Element::O);
+    let cell = Cell::triclinic(5.1505, 5.2116, 5.3173, 90.0, 99.23, 90.0);
+    let sites = [
+        (zirconium, Vector3::new(0.2754, 0.0395, 0.2083)),
+        (oxygen, Vector3::new(0.0700, 0.3317, 0.3447)),
+        (oxygen, Vector3::new(0.4496, 0.7569, 0.4792)),
+    ];
+    let mut species = Vec::new();
+    let mut positions = Vec::new();
+    for &(kind, site) in sites.iter() {
+        // equivalent positions of the P2_1/c space group
+        let images = [
+            site,
+            Vector3::new(-site[0], site[1] + 0.5, 0.5 - site[2]),
+            -site,
+            Vector3::new(site[0], 0.5 - site[1], site[2] + 0.5),
+        ];
+        for image in images.iter() {
+            species.push(kind);
+            positions.push(cell.cartesian(image));
+        }
+    }
+    let size = positions.len();
+    System {
+        size,
+        cell,
+        species,
+        positions,
+        velocities: vec![Vector3::zeros(); size],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    }
+}
+
+#[test]
+fn write_and_parse_zirconia() {
+    let system = zirconia_system();
+    let path = std::env::temp_dir().join("velvet-zirconia.cif");
+    let filename = path.to_str().unwrap();
+    Cif.write_file_from_system(&system, filename);
+    let parsed = Cif.parse_system_from_file(filename);
+    std::fs::remove_file(filename).unwrap();
+
+    assert_eq!(parsed.size, 12);
+    assert_eq!(parsed.species, system.species);
+    let (a, b, c, alpha, beta, gamma) = parsed.cell.lattice_parameters();
+    assert!((a - 5.1505).abs() < 1e-4);
+    assert!((b - 5.2116).abs() < 1e-4);
+    assert!((c - 5.3173).abs() < 1e-4);
+    assert!((alpha - 90.0).abs() < 1e-3);
+    assert!((beta - 99.23).abs() < 1e-3);
+    assert!((gamma - 90.0).abs() < 1e-3);
+    for (original, parsed) in system.positions.iter().zip(&parsed.positions) {
+        assert!((original - parsed).norm() < 1e-4);
+    }
+}
+
+#[test]
+fn parse_uncertainties_and_labels() {
+    let system = Cif.parse_system_from_reader(
+        "\
+data_MgO
+# cell parameters with their uncertainty
+_cell_length_a 4.212(1)
+_cell_length_b 4.212(1)
+_cell_length_c 4.212(1)
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+loop_
+_atom_site_label
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+Mg1 0.0 0.0 0.0
+O12 0.5 0.5 0.5
+"
+        .as_bytes(),
+    );
+
+    assert_eq!(system.size, 2);
+    assert_eq!(system.cell.a(), 4.212);
+    assert_eq!(system.species[0], Species::from_element(Element::Mg));
+    assert_eq!(system.species[1], Species::from_element(Element::O));
+    assert!((system.positions[1][2] - 2.106).abs() < 1e-5);
+}
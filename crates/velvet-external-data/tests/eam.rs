@@ -0,0 +1,145 @@
+use approx::*;
+
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+use velvet_test_utils as test_utils;
+
+#[cfg(feature = "f64")]
+type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+type Float = f32;
+
+// Johnson's nearest neighbor EAM for copper reproduces the cohesive energy by construction.
+// Johnson, R. A. "Analytic nearest-neighbor model for fcc metals." Physical Review B 37.8 (1988): 3924.
+const LATTICE_CONSTANT: f64 = 3.615;
+const COHESIVE_ENERGY: f64 = 3.54;
+const ALPHA: f64 = 5.09;
+const BETA: f64 = 5.85;
+const GAMMA: f64 = 8.00;
+const PHI_E: f64 = 0.59;
+
+const NRHO: usize = 500;
+const DRHO: f64 = 0.05;
+const NR: usize = 301;
+const DR: f64 = 0.01;
+const CUTOFF: f64 = 3.0;
+
+fn r_e() -> f64 {
+    LATTICE_CONSTANT / f64::sqrt(2.0)
+}
+
+fn embedding(rho: f64) -> f64 {
+    if rho <= 0.0 {
+        return 0.0;
+    }
+    let x = rho / 12.0;
+    let term_a = -COHESIVE_ENERGY * (1.0 - ALPHA / BETA * x.ln()) * x.powf(ALPHA / BETA);
+    let term_b = -6.0 * PHI_E * x.powf(GAMMA / BETA);
+    term_a + term_b
+}
+
+fn density(r: f64) -> f64 {
+    f64::exp(-BETA * (r / r_e() - 1.0))
+}
+
+fn pair(r: f64) -> f64 {
+    PHI_E * f64::exp(-GAMMA * (r / r_e() - 1.0))
+}
+
+fn block(values: impl Iterator<Item = f64>) -> String {
+    let values: Vec<String> = values.map(|value| format!("{:.10e}", value)).collect();
+    values
+        .chunks(5)
+        .map(|chunk| chunk.join(" "))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn rho_nodes() -> impl Iterator<Item = f64> {
+    (0..NRHO).map(|k| k as f64 * DRHO)
+}
+
+fn r_nodes() -> impl Iterator<Item = f64> {
+    (0..NR).map(|k| k as f64 * DR)
+}
+
+fn funcfl() -> String {
+    let charge = r_nodes().map(|r| f64::sqrt(r * pair(r) / (27.2 * 0.529)));
+    format!(
+        "Johnson Cu\n29 63.546 {} fcc\n{} {} {} {} {}\n{}\n{}\n{}\n",
+        LATTICE_CONSTANT,
+        NRHO,
+        DRHO,
+        NR,
+        DR,
+        CUTOFF,
+        block(rho_nodes().map(embedding)),
+        block(charge),
+        block(r_nodes().map(density)),
+    )
+}
+
+fn setfl() -> String {
+    format!(
+        "Johnson Cu\n\n\n1 Cu\n{} {} {} {} {}\n29 63.546 {} fcc\n{}\n{}\n{}\n",
+        NRHO,
+        DRHO,
+        NR,
+        DR,
+        CUTOFF,
+        LATTICE_CONSTANT,
+        block(rho_nodes().map(embedding)),
+        block(r_nodes().map(density)),
+        block(r_nodes().map(|r| r * pair(r))),
+    )
+}
+
+fn copper_system(copper: Species) -> System {
+    test_utils::fcc_lattice(copper, LATTICE_CONSTANT as Float, 3)
+}
+
+fn cohesive_energy(file: &EamFile, copper: Species) -> Float {
+    let system = copper_system(copper);
+    let eam = file.potential(&[(copper, "Cu")]);
+    let mut potentials = PotentialsBuilder::new()
+        .many_body(eam, file.cutoff, 0.5)
+        .build();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+    PotentialEnergy.calculate(&system, &potentials) / system.size as Float
+}
+
+#[test]
+fn eam_copper_cohesive_energy() {
+    let copper = Species::from_element(Element::Cu);
+    let expected = -COHESIVE_ENERGY as Float * 23.060548;
+
+    let funcfl = EamFile::parse_funcfl_from_str(&funcfl(), "Cu");
+    assert_eq!(funcfl.elements[0].number, 29);
+    assert_relative_eq!(funcfl.cutoff, CUTOFF as Float);
+    assert_relative_eq!(
+        cohesive_energy(&funcfl, copper),
+        expected,
+        max_relative = 1e-4
+    );
+
+    let setfl = EamFile::parse_setfl_from_str(&setfl());
+    assert_eq!(setfl.elements[0].symbol, "Cu");
+    assert_relative_eq!(
+        cohesive_energy(&setfl, copper),
+        expected,
+        max_relative = 1e-4
+    );
+
+    // the perfect lattice is in mechanical equilibrium
+    let system = copper_system(copper);
+    let mut potentials = PotentialsBuilder::new()
+        .many_body(setfl.potential(&[(copper, "Cu")]), setfl.cutoff, 0.5)
+        .build();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+    for force in Forces.calculate(&system, &potentials) {
+        assert!(force.norm() < 1e-3);
+    }
+}
@@ -0,0 +1,176 @@
+use nalgebra::Vector3;
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+// Butane united atom chain with bonds but no angles or dihedrals.
+static BUTANE: &str = "\
+Butane
+
+4 atoms
+3 bonds
+1 atom types
+1 bond types
+
+-10.0 10.0 xlo xhi
+-10.0 10.0 ylo yhi
+-10.0 10.0 zlo zhi
+
+Masses
+
+1 15.035
+
+Atoms # full
+
+1 1 1 0.0 0.000 0.000 0.000
+3 1 1 0.0 2.036 1.267 0.000
+2 1 1 0.0 1.540 0.000 0.000
+4 1 1 0.0 3.576 1.267 0.000
+
+Velocities
+
+1 0.001 0.0 0.0
+2 0.0 0.0 0.0
+3 0.0 0.0 0.0
+4 0.0 0.0 -0.002
+
+Bonds
+
+1 1 1 2
+2 1 2 3
+3 1 3 4
+";
+
+// Water molecule with an explicit angle.
+static WATER: &str = "\
+Water
+
+3 atoms
+2 bonds
+1 angles
+2 atom types
+
+0.0 12.0 xlo xhi
+0.0 12.0 ylo yhi
+0.0 12.0 zlo zhi
+1.0 0.0 0.0 xy xz yz
+
+Masses
+
+1 15.999 # O
+2 1.008 # H
+
+Atoms # full
+
+1 1 1 -0.834 6.000 6.000 6.000
+2 1 2 0.417 6.957 6.000 6.000
+3 1 2 0.417 5.760 6.927 6.000
+
+Bonds
+
+1 1 1 2
+2 1 1 3
+
+Angles
+
+1 1 2 1 3
+";
+
+fn write(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn parse_system() {
+    let system = LammpsData.parse_system_from_reader(WATER.as_bytes());
+    assert_eq!(system.size, 3);
+    assert_eq!(system.cell.a(), 12.0);
+    assert!(!system.cell.is_orthogonal());
+    assert_eq!(system.species[0], Species::from_element(Element::O));
+    assert_eq!(system.species[1], Species::from_element(Element::H));
+    assert_eq!(system.species[0].charge(), -0.834);
+    assert_eq!(system.positions[1][0], 6.957);
+    assert_eq!(system.bonds, vec![[0, 1], [0, 2]]);
+    assert_eq!(system.angles, vec![[1, 0, 2]]);
+}
+
+#[test]
+fn with_topology_from() {
+    let path = write("velvet-butane.lmp", BUTANE);
+    let mut system = LammpsData.parse_system_from_file(&path);
+    system.bonds.clear();
+    system.with_topology_from(&path, LammpsData).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // atoms are ordered by ID and the origin moves to the corner of the box
+    assert_eq!(system.positions[1][0], 11.540);
+    assert_eq!(system.velocities[3][2], -0.002);
+    // while the cell keeps the bounds of the box
+    let (lo, hi) = system.cell.bounds();
+    assert_eq!(lo, Vector3::new(-10.0, -10.0, -10.0));
+    assert_eq!(hi, Vector3::new(10.0, 10.0, 10.0));
+    // the bonds match the file while angles and dihedrals are perceived from them
+    assert_eq!(system.bonds.len(), 3);
+    assert_eq!(system.angles.len(), 2);
+    assert_eq!(system.dihedrals, vec![[0, 1, 2, 3]]);
+}
+
+#[test]
+fn with_topology_from_wrong_size() {
+    let path = write("velvet-water.lmp", WATER);
+    let mut system = LammpsData.parse_system_from_reader(BUTANE.as_bytes());
+    let result = system.with_topology_from(&path, LammpsData);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(result.is_err());
+    assert_eq!(system.bonds.len(), 3);
+}
+
+#[test]
+fn parse_system_with_atom_types() {
+    let path = write("velvet-butane-types.lmp", BUTANE);
+    let system = LammpsData.parse_system_from_file(&path);
+    assert_eq!(system.charges(), vec![0.0; 4]);
+    let atom_types = [(1, (15.035, 0.25))].iter().cloned().collect();
+    let system = LammpsData.parse_system_with_atom_types_from_file(&path, &atom_types);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(system.charges(), vec![0.25; 4]);
+    assert!(system.species.iter().all(|s| *s == system.species[0]));
+
+    // types without an override keep the charges of the file
+    let atom_types = [(1, (16.0, -0.8))].iter().cloned().collect();
+    let system = LammpsData.parse_system_with_atom_types_from_reader(WATER.as_bytes(), &atom_types);
+    assert_eq!(system.charges(), vec![-0.8, 0.417, 0.417]);
+    assert_eq!(system.species[0].mass(), 16.0);
+    assert_eq!(system.species[0], Species::from_element(Element::O));
+}
+
+#[test]
+fn write_and_parse() {
+    for text in [BUTANE, WATER].iter() {
+        let system = LammpsData.parse_system_from_reader(text.as_bytes());
+        let path = std::env::temp_dir().join("velvet-written.lmp");
+        let filename = path.to_str().unwrap();
+        LammpsData.write_file_from_system(&system, filename);
+        let parsed = LammpsData.parse_system_from_file(filename);
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(parsed.size, system.size);
+        for (parsed, species) in parsed.species.iter().zip(&system.species) {
+            // species without an element are loaded with a new ID
+            assert_eq!(parsed.element(), species.element());
+            assert_eq!(parsed.mass(), species.mass());
+            assert_eq!(parsed.charge(), species.charge());
+        }
+        assert_eq!(parsed.positions, system.positions);
+        assert_eq!(parsed.velocities, system.velocities);
+        assert_eq!(parsed.cell.a_vector(), system.cell.a_vector());
+        assert_eq!(parsed.cell.b_vector(), system.cell.b_vector());
+        assert_eq!(parsed.cell.c_vector(), system.cell.c_vector());
+        assert_eq!(parsed.cell.origin(), system.cell.origin());
+        assert_eq!(parsed.bonds, system.bonds);
+        assert_eq!(parsed.angles, system.angles);
+        assert_eq!(parsed.dihedrals, system.dihedrals);
+    }
+}
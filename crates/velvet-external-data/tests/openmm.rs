@@ -0,0 +1,151 @@
+use approx::*;
+use nalgebra::Vector3;
+
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+#[cfg(feature = "f64")]
+type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+type Float = f32;
+
+static FORCEFIELD: &str = r#"
+<ForceField>
+  <AtomTypes>
+    <Type name="Ar" class="Ar" element="Ar" mass="39.948"/>
+  </AtomTypes>
+  <NonbondedForce coulomb14scale="0.833333" lj14scale="0.5">
+    <Atom type="Ar" charge="0.0" sigma="0.34" epsilon="0.996"/>
+  </NonbondedForce>
+</ForceField>
+"#;
+
+#[test]
+fn openmm_lennard_jones() {
+    let forcefield = OpenMMForceField::parse_from_str(FORCEFIELD);
+    assert_eq!(forcefield.atom_types.len(), 1);
+    assert_eq!(forcefield.atom_types[0].element.as_deref(), Some("Ar"));
+    assert_relative_eq!(forcefield.atom_types[0].mass, 39.948, epsilon = 1e-5);
+
+    // parameters are converted from nm and kJ/mol
+    let lj = forcefield.lennard_jones("Ar", "Ar").unwrap();
+    assert_relative_eq!(lj.sigma, 3.4, epsilon = 1e-5);
+    assert_relative_eq!(lj.epsilon, 0.996 / 4.184, epsilon = 1e-5);
+
+    // the built potentials match the equivalent Lennard-Jones pair
+    let argon = Species::from_element(Element::Ar);
+    let r = 3.8;
+    let system = System {
+        size: 2,
+        cell: Cell::cubic(30.0),
+        species: vec![argon; 2],
+        positions: vec![
+            Vector3::new(10.0, 10.0, 10.0),
+            Vector3::new(10.0 + r, 10.0, 10.0),
+        ],
+        velocities: vec![Vector3::zeros(); 2],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let mut potentials = forcefield
+        .potentials(&system, &[(argon, "Ar")], 8.5, 1.0)
+        .unwrap();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+    let energy = PairEnergy.calculate(&system, &potentials);
+    assert_relative_eq!(energy, lj.energy(r), epsilon = 1e-5);
+}
+
+static WATER: &str = r#"
+<ForceField>
+  <AtomTypes>
+    <Type name="OW" class="OW" element="O" mass="15.99943"/>
+    <Type name="HW" class="HW" element="H" mass="1.007947"/>
+  </AtomTypes>
+  <HarmonicBondForce>
+    <Bond class1="OW" class2="HW" length="0.09572" k="462750.4"/>
+  </HarmonicBondForce>
+  <HarmonicAngleForce>
+    <Angle class1="HW" class2="OW" class3="HW" angle="1.82421813418" k="836.8"/>
+  </HarmonicAngleForce>
+  <NonbondedForce coulomb14scale="0.833333" lj14scale="0.5">
+    <Atom type="OW" charge="-0.834" sigma="0.315061" epsilon="0.636386"/>
+    <Atom type="HW" charge="0.417" sigma="1" epsilon="0"/>
+  </NonbondedForce>
+</ForceField>
+"#;
+
+#[test]
+fn openmm_bonded_and_coulomb() {
+    let forcefield = OpenMMForceField::parse_from_str(WATER);
+    let oxygen = Species::from_element(Element::O);
+    let hydrogen = Species::from_element(Element::H);
+    let types = [(oxygen, "OW"), (hydrogen, "HW")];
+    // stretch one bond and open the angle away from equilibrium
+    let (r0, r1, theta): (Float, Float, Float) = (1.0, 0.9572, 1.9);
+    let mut system = System {
+        size: 3,
+        cell: Cell::cubic(30.0),
+        species: vec![oxygen, hydrogen, hydrogen],
+        positions: vec![
+            Vector3::new(10.0, 10.0, 10.0),
+            Vector3::new(10.0 + r0, 10.0, 10.0),
+            Vector3::new(10.0 + r1 * theta.cos(), 10.0 + r1 * theta.sin(), 10.0),
+        ],
+        velocities: vec![Vector3::zeros(); 3],
+        bonds: vec![[0, 1], [0, 2]],
+        angles: vec![[1, 0, 2]],
+        dihedrals: Vec::new(),
+    };
+    forcefield.apply_charges(&mut system, &types);
+    let mut potentials = forcefield.potentials(&system, &types, 8.5, 1.0).unwrap();
+    potentials.setup(&system).unwrap();
+    potentials.update(&system, 0);
+
+    // OpenMM's energies are 1/2 k (x - x0)^2 in kJ/mol and nm
+    let energies = lammps_style_breakdown(&system, &potentials);
+    let bond = 0.5 * 462750.4 / 4.184 / 100.0 * (r0 - 0.9572).powi(2);
+    assert_relative_eq!(energies.ebond, bond, max_relative = 1e-4);
+    let angle = 0.5 * 836.8 / 4.184 * (theta - 1.824_218_2).powi(2);
+    assert_relative_eq!(energies.eangle, angle, max_relative = 1e-4);
+    // the partial charges of the molecule interact
+    assert!(energies.ecoul.abs() > 0.0);
+
+    // a bond between atom types without parameters is an error
+    system.bonds.push([1, 2]);
+    let message = forcefield
+        .potentials(&system, &types, 8.5, 1.0)
+        .err()
+        .unwrap();
+    assert!(message.contains("missing bond parameters for HW and HW"));
+}
+
+#[test]
+fn openmm_unsupported_forces() {
+    let text = FORCEFIELD.replace(
+        "</ForceField>",
+        "<PeriodicTorsionForce/><RBTorsionForce/></ForceField>",
+    );
+    let forcefield = OpenMMForceField::parse_from_str(&text);
+    let argon = Species::from_element(Element::Ar);
+    let system = System {
+        size: 1,
+        cell: Cell::cubic(30.0),
+        species: vec![argon],
+        positions: vec![Vector3::new(10.0, 10.0, 10.0)],
+        velocities: vec![Vector3::zeros()],
+        bonds: Vec::new(),
+        angles: Vec::new(),
+        dihedrals: Vec::new(),
+    };
+    let message = forcefield
+        .potentials(&system, &[(argon, "Ar")], 8.5, 1.0)
+        .err()
+        .unwrap();
+    assert_eq!(
+        message,
+        "unsupported forces in force field: PeriodicTorsionForce, RBTorsionForce"
+    );
+}
@@ -0,0 +1,118 @@
+use velvet_core::prelude::*;
+use velvet_external_data::prelude::*;
+
+static POSITIONS: &str = "\
+3
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\" Properties=species:S:1:pos:R:3
+Ar 1.0 2.0 3.0
+Ar 4.5 2.0 3.0
+Xe 8.0 8.0 8.0
+";
+
+static VELOCITIES: &str = "\
+3
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\" Properties=species:S:1:pos:R:3:velo:R:3
+Ar 1.1 2.0 3.0 0.001 -0.002 0.003
+Ar 4.4 2.1 3.0 -0.004 0.005 0.0
+Xe 8.0 7.9 8.2 0.0 0.0 -0.001
+";
+
+fn write(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn load_velocities() {
+    let positions = write("velvet-positions.xyz", POSITIONS);
+    let velocities = write("velvet-velocities.xyz", VELOCITIES);
+    let mut system = Xyz.parse_system_from_file(&positions);
+    system.load_velocities_from_file(&velocities, Xyz).unwrap();
+    std::fs::remove_file(&positions).unwrap();
+    std::fs::remove_file(&velocities).unwrap();
+
+    assert_eq!(system.size, 3);
+    assert_eq!(system.cell.a(), 12.0);
+    assert_eq!(system.species[2], Species::from_element(Element::Xe));
+    // positions come from the first file
+    assert_eq!(system.positions[1][0], 4.5);
+    assert_eq!(system.positions[2][1], 8.0);
+    // velocities come from the second file
+    assert_eq!(system.velocities[0][1], -0.002);
+    assert_eq!(system.velocities[1][0], -0.004);
+    assert_eq!(system.velocities[2][2], -0.001);
+}
+
+#[test]
+fn load_velocities_count_mismatch() {
+    let velocities = write("velvet-mismatched-velocities.xyz", VELOCITIES);
+    let mut system = Xyz.parse_system_from_reader(
+        "\
+1
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\"
+Ar 1.0 2.0 3.0
+"
+        .as_bytes(),
+    );
+    let result = system.load_velocities_from_file(&velocities, Xyz);
+    std::fs::remove_file(&velocities).unwrap();
+
+    assert!(result.unwrap_err().contains("3 atoms"));
+    assert_eq!(system.velocities[0], nalgebra::Vector3::zeros());
+}
+
+static TRAJECTORY: &str = "\
+2
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\" Time=0.0
+Ar 1.0 2.0 3.0
+Ar 4.5 2.0 3.0
+2
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\" Time=1.0
+Ar 1.2 2.0 3.0
+Ar 4.3 2.1 3.0
+2
+Lattice=\"12.0 0.0 0.0 0.0 12.0 0.0 0.0 0.0 12.0\" Time=2.0
+Ar 1.4 2.1 3.0
+Ar 4.1 2.2 2.9 0.001 0.0 0.0
+";
+
+#[test]
+fn parse_system_at_step() {
+    let trajectory = write("velvet-trajectory.xyz", TRAJECTORY);
+    let first = Xyz.parse_system_at_step_from_file(&trajectory, 0);
+    let last = Xyz.parse_system_at_step_from_file(&trajectory, 2);
+    std::fs::remove_file(&trajectory).unwrap();
+
+    assert_eq!(first.size, 2);
+    assert_eq!(last.size, 2);
+    assert_eq!(first.positions[0][0], 1.0);
+    assert_eq!(last.positions[0][0], 1.4);
+    assert_ne!(first.positions, last.positions);
+    assert_eq!(last.velocities[1][0], 0.001);
+}
+
+#[test]
+#[should_panic(expected = "XYZ file has 3 frames but step 3 was requested")]
+fn parse_system_past_last_step() {
+    Xyz.parse_system_at_step_from_reader(TRAJECTORY.as_bytes(), 3);
+}
+
+#[test]
+fn write_and_parse() {
+    let mut system = Xyz.parse_system_from_reader(VELOCITIES.as_bytes());
+    system.cell = Cell::triclinic(12.0, 13.0, 14.0, 80.0, 95.0, 105.0);
+    let path = std::env::temp_dir().join("velvet-written.xyz");
+    let filename = path.to_str().unwrap();
+    Xyz.write_file_from_system(&system, filename);
+    let parsed = Xyz.parse_system_from_file(filename);
+    std::fs::remove_file(filename).unwrap();
+
+    assert_eq!(parsed.size, system.size);
+    assert_eq!(parsed.species, system.species);
+    assert_eq!(parsed.positions, system.positions);
+    assert_eq!(parsed.velocities, system.velocities);
+    assert_eq!(parsed.cell.a_vector(), system.cell.a_vector());
+    assert_eq!(parsed.cell.b_vector(), system.cell.b_vector());
+    assert_eq!(parsed.cell.c_vector(), system.cell.c_vector());
+}
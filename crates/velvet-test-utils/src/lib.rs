@@ -1,6 +1,12 @@
 use velvet_core::prelude::*;
 use velvet_external_data::prelude::*;
 
+#[cfg(feature = "f64")]
+type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+type Float = f32;
+
 static UPDATE_FREQUENCY: usize = 5;
 
 pub fn argon_system() -> System {
@@ -83,3 +89,252 @@ pub fn nvt_simulation(mut system: System, potentials: Potentials) -> Simulation
     let config = ConfigurationBuilder::new().build();
     Simulation::new(system, potentials, md, config)
 }
+
+/// Returns a face centered cubic crystal of `n` x `n` x `n` unit cells as built by [`System::fcc_lattice`].
+pub fn fcc_lattice(species: Species, a: Float, n: usize) -> System {
+    System::fcc_lattice(species, a, n)
+}
+
+/// Runs the simulation one step at a time and returns the largest relative deviation of the total energy from its initial value.
+pub fn energy_drift(simulation: &mut Simulation, steps: usize) -> Float {
+    // set up the potentials before measuring the initial energy
+    simulation.run(0).unwrap();
+    let initial = TotalEnergy.calculate(simulation.system(), simulation.potentials());
+    let mut drift: Float = 0.0;
+    for _ in 0..steps {
+        simulation.run(1).unwrap();
+        let energy = TotalEnergy.calculate(simulation.system(), simulation.potentials());
+        drift = drift.max(Float::abs((energy - initial) / initial));
+    }
+    drift
+}
+
+/// Runs the simulation and panics if the relative drift of the total energy exceeds `tolerance`.
+pub fn assert_energy_conserved(simulation: &mut Simulation, steps: usize, tolerance: Float) {
+    let drift = energy_drift(simulation, steps);
+    assert!(
+        drift <= tolerance,
+        "total energy drifted by {} > {}",
+        drift,
+        tolerance
+    );
+}
+
+/// Runs the simulation and panics if the total momentum changes by more than `tolerance`.
+pub fn assert_momentum_conserved(simulation: &mut Simulation, steps: usize, tolerance: Float) {
+    let initial = simulation.system().momentum();
+    simulation.run(steps).unwrap();
+    let last = simulation.system().momentum();
+    let change = (last - initial).norm();
+    assert!(
+        change <= tolerance,
+        "total momentum changed from {:?} to {:?} ({} > {})",
+        initial,
+        last,
+        change,
+        tolerance
+    );
+}
+
+/// Mismatch between an analytic force component and its finite difference estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FDError {
+    /// Index of the atom.
+    pub atom: usize,
+    /// Cartesian component of the force.
+    pub axis: usize,
+    /// Force evaluated by the potentials.
+    pub analytic: Float,
+    /// Negative central difference of the potential energy.
+    pub numerical: Float,
+}
+
+impl std::fmt::Display for FDError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "force component {} of atom {} is {} but the energy gradient gives {}",
+            self.axis, self.atom, self.analytic, self.numerical
+        )
+    }
+}
+
+/// Compares the force on every atom against the negative central difference `-(E(+h) - E(-h)) / 2h`
+/// of the potential energy.
+///
+/// Returns the first component which differs by more than `tolerance`.
+/// The neighbor lists are selected once, so `h` should be smaller than the thickness of every potential.
+pub fn check_forces(
+    system: &System,
+    potentials: &mut Potentials,
+    h: Float,
+    tolerance: Float,
+) -> Result<(), FDError> {
+    potentials.setup(system).unwrap();
+    potentials.update(system, 0);
+    let forces = Forces.calculate(system, potentials);
+    let mut perturbed = system.clone();
+    for (atom, force) in forces.iter().enumerate() {
+        for (axis, &analytic) in force.iter().enumerate() {
+            perturbed.positions[atom][axis] += h;
+            let forward = PotentialEnergy.calculate(&perturbed, potentials);
+            perturbed.positions[atom][axis] -= 2.0 * h;
+            let backward = PotentialEnergy.calculate(&perturbed, potentials);
+            perturbed.positions[atom][axis] = system.positions[atom][axis];
+            let numerical = -(forward - backward) / (2.0 * h);
+            if Float::abs(analytic - numerical) > tolerance {
+                return Err(FDError {
+                    atom,
+                    axis,
+                    analytic,
+                    numerical,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    // Lennard-Jones potential which pushes atoms the wrong way.
+    struct ReversedLennardJones(LennardJones);
+
+    impl Potential for ReversedLennardJones {}
+
+    impl PairPotential for ReversedLennardJones {
+        fn energy(&self, r: Float) -> Float {
+            self.0.energy(r)
+        }
+
+        fn force(&self, r: Float) -> Float {
+            -self.0.force(r)
+        }
+    }
+
+    // 108 argon atoms in an fcc lattice at 90 K.
+    fn argon_crystal() -> System {
+        let mut system = fcc_lattice(Species::from_element(Element::Ar), 5.26, 3);
+        Boltzmann::new(90.0)
+            .remove_drift(true)
+            .seed(1234)
+            .apply(&mut system);
+        system
+    }
+
+    fn nve<T: PairPotential + 'static>(lj: T) -> Simulation {
+        // the truncated energy jumps as pairs cross the cutoff, so it lies between the third
+        // and fourth neighbor shells of the lattice
+        nve_with_cutoff(lj, 7.0)
+    }
+
+    fn nve_with_cutoff<T: PairPotential + 'static>(lj: T, cutoff: Float) -> Simulation {
+        let argon = Species::from_element(Element::Ar);
+        let potentials = PotentialsBuilder::new()
+            .update_frequency(UPDATE_FREQUENCY)
+            .pair(lj, (argon, argon), cutoff, 0.5)
+            .build();
+        let md = MolecularDynamics::new(VelocityVerlet::new(0.1), NullThermostat);
+        let config = ConfigurationBuilder::new().build();
+        Simulation::new(argon_crystal(), potentials, md, config)
+    }
+
+    #[test]
+    fn argon_nve_conserves_energy_and_momentum() {
+        let lj = LennardJones::new(0.238, 3.4);
+        assert_energy_conserved(&mut nve(lj), 200, 1e-3);
+        assert_momentum_conserved(&mut nve(lj), 200, 1e-2);
+    }
+
+    #[test]
+    #[should_panic(expected = "total energy drifted")]
+    fn reversed_force_breaks_energy_conservation() {
+        let lj = ReversedLennardJones(LennardJones::new(0.238, 3.4));
+        assert_energy_conserved(&mut nve(lj), 200, 1e-3);
+    }
+
+    #[test]
+    fn shifted_force_reduces_energy_drift() {
+        // a cutoff on the fourth neighbor shell is the worst case for a hard cutoff
+        let cutoff = 5.26 * Float::sqrt(2.0);
+        let lj = LennardJones::new(0.238, 3.4);
+        let hard = energy_drift(&mut nve_with_cutoff(lj, cutoff), 100);
+        let shifted = energy_drift(
+            &mut nve_with_cutoff(ShiftedForce::new(lj, cutoff), cutoff),
+            100,
+        );
+        assert!(
+            shifted < hard,
+            "shifted force drift {} is not less than hard cutoff drift {}",
+            shifted,
+            hard
+        );
+    }
+
+    #[test]
+    fn temperature_sweep_orders_kinetic_energy() {
+        let argon = Species::from_element(Element::Ar);
+        let mut potentials = PotentialsBuilder::new()
+            .update_frequency(UPDATE_FREQUENCY)
+            .pair(LennardJones::new(0.238, 3.4), (argon, argon), 7.0, 0.5)
+            .build();
+        let averages = temperature_sweep(
+            &argon_crystal(),
+            &mut potentials,
+            &[30.0, 60.0],
+            100,
+            100,
+            KineticEnergy,
+            |target| MolecularDynamics::new(VelocityVerlet::new(0.1), Berendsen::new(target, 10.0)),
+        );
+        assert_eq!(averages.len(), 2);
+        assert_eq!(averages[0].0, 30.0);
+        assert_eq!(averages[1].0, 60.0);
+        assert!(
+            averages[1].1 > 1.5 * averages[0].1,
+            "kinetic energy {} at 60 K is not well above {} at 30 K",
+            averages[1].1,
+            averages[0].1
+        );
+    }
+
+    fn argon_dimer() -> System {
+        let argon = Species::from_element(Element::Ar);
+        System {
+            size: 2,
+            cell: Cell::cubic(20.0),
+            species: vec![argon; 2],
+            positions: vec![Vector3::new(5.0, 5.0, 5.0), Vector3::new(8.2, 6.1, 4.6)],
+            velocities: vec![Vector3::zeros(); 2],
+            bonds: Vec::new(),
+            angles: Vec::new(),
+            dihedrals: Vec::new(),
+        }
+    }
+
+    fn dimer_potentials<T: PairPotential + 'static>(lj: T) -> Potentials {
+        let argon = Species::from_element(Element::Ar);
+        PotentialsBuilder::new()
+            .pair(lj, (argon, argon), 8.5, 1.0)
+            .build()
+    }
+
+    #[test]
+    fn argon_dimer_forces() {
+        let mut potentials = dimer_potentials(LennardJones::new(0.238, 3.4));
+        check_forces(&argon_dimer(), &mut potentials, 1e-3, 1e-3).unwrap();
+    }
+
+    #[test]
+    fn reversed_force_fails_check() {
+        let lj = ReversedLennardJones(LennardJones::new(0.238, 3.4));
+        let error =
+            check_forces(&argon_dimer(), &mut dimer_potentials(lj), 1e-3, 1e-3).unwrap_err();
+        assert_eq!(error.atom, 0);
+        assert_eq!(error.axis, 0);
+        assert!(Float::abs(error.analytic + error.numerical) < 1e-3);
+    }
+}
@@ -43,5 +43,5 @@ fn main() {
 
     // Run the simulation.
     let mut sim = Simulation::new(system, potentials, md, config);
-    sim.run(250_000);
+    sim.run(250_000).unwrap();
 }
@@ -1,5 +1,10 @@
 // Molecular dynamics simulation of MgO in the NVT ensemble.
 
+// Parameters are given at full precision for the `f64` feature.
+#![allow(clippy::excessive_precision)]
+// The Buckingham terms are defined for reference but only electrostatics are applied.
+#![allow(unused_variables)]
+
 use velvet::prelude::*;
 
 fn main() {
@@ -52,5 +57,5 @@ fn main() {
 
     // Run the simulation.
     let mut sim = Simulation::new(system, potentials, md, config);
-    sim.run(50_000);
+    sim.run(50_000).unwrap();
 }
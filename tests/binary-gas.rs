@@ -13,26 +13,26 @@ fn nve() {
     let potentials = test_utils::binary_gas_potentials();
     let mut sim = test_utils::nve_simulation(system, potentials);
 
-    sim.run(ITERATIONS);
-    let (mut system, potentials) = sim.consume();
+    sim.run(ITERATIONS).unwrap();
+    let (system, potentials) = sim.consume();
 
     let pe_target = -4550.0;
     assert_relative_eq!(
-        PotentialEnergy.calculate(&mut system, &potentials),
+        PotentialEnergy.calculate(&system, &potentials),
         pe_target,
         epsilon = 150.0
     );
 
     let ke_target = 425.0;
     assert_relative_eq!(
-        KineticEnergy.calculate(&mut system, &potentials),
+        KineticEnergy.calculate(&system, &potentials),
         ke_target,
         epsilon = 100.0
     );
 
     let temp_target = 1300.0;
     assert_relative_eq!(
-        Temperature.calculate(&mut system, &potentials),
+        Temperature.calculate(&system, &potentials),
         temp_target,
         epsilon = 250.0
     );
@@ -45,19 +45,19 @@ fn nvt() {
     let potentials = test_utils::binary_gas_potentials();
     let mut sim = test_utils::nvt_simulation(system, potentials);
 
-    sim.run(ITERATIONS);
-    let (mut system, potentials) = sim.consume();
+    sim.run(ITERATIONS).unwrap();
+    let (system, potentials) = sim.consume();
 
     let pe_target = -4850.0;
     assert_relative_eq!(
-        PotentialEnergy.calculate(&mut system, &potentials),
+        PotentialEnergy.calculate(&system, &potentials),
         pe_target,
         epsilon = 100.0
     );
 
     let ke_target = 100.0;
     assert_relative_eq!(
-        KineticEnergy.calculate(&mut system, &potentials),
+        KineticEnergy.calculate(&system, &potentials),
         ke_target,
         epsilon = 25.0
     );